@@ -0,0 +1,122 @@
+//! Benchmarks of parsing and rendering.
+//!
+//! Each corpus is rendered at several sizes, so superlinear behavior shows up
+//! as a falling throughput.
+//!
+//! ```sh
+//! cargo bench --bench parse
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use note_mark::prelude::*;
+
+/// A name and a function to make an input of about `size` lines.
+type Corpus = (&'static str, fn(usize) -> String);
+
+const CORPORA: &[Corpus] = &[
+    ("prose", |size| {
+        "Some *long* prose with **bold** text and a [link](https://example.com).\n".repeat(size)
+    }),
+    ("deep-list", |size| {
+        (0..size)
+            .map(|index| format!("{}- item\n", "  ".repeat(index % 32)))
+            .collect()
+    }),
+    ("stars", |size| "*".repeat(size * 8)),
+    ("unclosed-emphasis", |size| "**a *b ".repeat(size)),
+    ("nested-emphasis", |size| {
+        "*a ".repeat(size) + &"b* ".repeat(size)
+    }),
+    ("brackets", |size| "[a](".repeat(size * 2)),
+    ("nested-links", |size| {
+        "[".repeat(size) + "a" + &"](b)".repeat(size)
+    }),
+    ("attribute-blocks", |size| "[a]{".repeat(size * 2)),
+    ("headlines", |size| "# Title\n\ntext\n".repeat(size)),
+    ("blockquotes", |size| "> > quote\n".repeat(size)),
+];
+
+fn execute(c: &mut Criterion) {
+    let markdown = Markdown::default();
+
+    for (name, corpus) in CORPORA {
+        let mut group = c.benchmark_group(*name);
+
+        for size in [250, 1000, 4000] {
+            let input = corpus(size);
+
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+                b.iter(|| markdown.execute(input))
+            });
+        }
+
+        group.finish();
+    }
+}
+
+/// Lex the corpora alone, whose cost is mostly the runs of plain text.
+fn lexer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+
+    for (name, corpus) in CORPORA {
+        let input = corpus(1000);
+
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &input, |b, input| {
+            b.iter(|| lex(input).count())
+        });
+    }
+
+    group.finish();
+}
+
+/// Render with classes and attributes added to every element, which makes
+/// many small strings.
+fn attributes(c: &mut Criterion) {
+    let mut transformer = Transformer::new()
+        .heading_ids(true)
+        .heading_anchor(AnchorPosition::Before)
+        .external_rel("nofollow")
+        .quote_depth_class(true);
+
+    for tag in [
+        ElementTag::P,
+        ElementTag::Li,
+        ElementTag::A,
+        ElementTag::Td,
+        ElementTag::Blockquote,
+    ] {
+        transformer = transformer
+            .class_for(tag, "content")
+            .attr_for(tag, "data-kind", "text");
+    }
+
+    let markdown = Markdown::default()
+        .parser(Parser::new().tables(Tables::Piped))
+        .transformer(transformer);
+
+    let input = (0..1000)
+        .map(|index| {
+            format!(
+                concat![
+                    "# Title {}\n\n",
+                    "Text with a [link](https://example.com \"title\") and *emphasis*.\n\n",
+                    "3. item\n4. item {{.extra}}\n\n",
+                    "| a | b |\n| :-- | --: |\n| c | d |\n\n",
+                    "> quote\n\n",
+                ],
+                index
+            )
+        })
+        .collect::<String>();
+
+    let mut group = c.benchmark_group("attributes");
+
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("1000", |b| b.iter(|| markdown.execute(&input)));
+    group.finish();
+}
+
+criterion_group!(benches, execute, lexer, attributes);
+criterion_main!(benches);
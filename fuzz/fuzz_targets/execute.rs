@@ -0,0 +1,45 @@
+//! Render arbitrary input with several configurations, which must not panic.
+//!
+//! ```sh
+//! cargo +nightly fuzz run execute
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use note_mark::prelude::*;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+
+    let markdowns = [
+        Markdown::default(),
+        Markdown::untrusted(),
+        Markdown::untrusted().parser(
+            Parser::new()
+                .dialect(Dialect::CommonMark)
+                .list_indent_style(IndentStyle::Both)
+                .front_matter(true)
+                .max_nesting(Some(32)),
+        ),
+        Markdown::untrusted()
+            .parser(
+                Parser::new()
+                    .list_indent_style(IndentStyle::Space(0))
+                    .max_nesting(Some(32))
+                    .wiki_links(true)
+                    .fancy_lists(true)
+                    .comments(Comments::Strip),
+            )
+            .transformer(Transformer::new().section(true))
+            .stringifier(Stringifier::new().format(true)),
+    ];
+
+    for markdown in &markdowns {
+        markdown.execute(&input);
+        markdown.execute_text(&input);
+        markdown.execute_with_toc(&input);
+        markdown.execute_with_diagnostics(&input);
+        markdown.reformat(&input);
+    }
+});
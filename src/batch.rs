@@ -0,0 +1,381 @@
+//! Rendering of many documents which link to each other.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::{layer::lexer::lex, layer::transformer::WikiResolver, Markdown};
+
+/// A set of documents which are rendered together, such as the pages of a
+/// static site or the notes of a digital garden.
+///
+/// Wiki links such as `[[Page]]` between the documents are resolved to the
+/// URLs of the documents, and the links are collected into a [`LinkGraph`].
+/// Wiki links are parsed even if
+/// [`Parser::wiki_links`](crate::layer::parser::Parser::wiki_links) is not
+/// enabled.
+///
+/// The target of a wiki link matches a document if it is the name of the
+/// document or its file stem, which is the last segment of the name without
+/// the extension. Names are compared case-insensitively. A fragment such as
+/// `#usage` in the target is kept in the URL. A target which matches no
+/// document is passed to
+/// [`Transformer::wiki_resolver`](crate::layer::transformer::Transformer::wiki_resolver)
+/// if it is set, and is unresolved otherwise.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let output = Markdown::default()
+///     .batch()
+///     .document("notes/Home.md", "See [[Usage#install]] and [[Missing]].")
+///     .document("notes/Usage.md", "Back to [[home|the start]].")
+///     .render();
+///
+/// assert_eq!(
+///     output.documents[0].html,
+///     "<p>See <a class=\"wiki-link\" href=\"notes/Usage.html#install\">Usage#install</a> and Missing.</p>"
+/// );
+/// assert_eq!(
+///     output.documents[1].html,
+///     "<p>Back to <a class=\"wiki-link\" href=\"notes/Home.html\">the start</a>.</p>"
+/// );
+/// assert_eq!(output.graph.links, vec![vec![1], vec![0]]);
+/// assert_eq!(output.graph.unresolved, vec![vec!["Missing".to_string()], vec![]]);
+/// ```
+#[derive(Clone)]
+pub struct MarkdownBatch {
+    /// Markdown configuration.
+    markdown: Markdown,
+    /// The names and the inputs of the documents.
+    documents: Vec<(String, String)>,
+    /// The function to make the URL of a document from its name.
+    url: Arc<dyn Fn(&str) -> String + Send + Sync>,
+}
+
+impl fmt::Debug for MarkdownBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MarkdownBatch")
+            .field("markdown", &self.markdown)
+            .field("documents", &self.documents)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of rendering a [`MarkdownBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOutput {
+    /// The documents in the order they were added.
+    pub documents: Vec<BatchDocument>,
+    /// The wiki links between the documents.
+    pub graph: LinkGraph,
+}
+
+/// A rendered document of a [`MarkdownBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchDocument {
+    /// The name of the document.
+    pub name: String,
+    /// The URL of the document.
+    pub url: String,
+    /// The rendered HTML.
+    pub html: String,
+}
+
+/// The wiki links between the documents of a [`MarkdownBatch`].
+///
+/// Documents are given by their indices in [`BatchOutput::documents`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkGraph {
+    /// The documents which each document links to, in the order of the first
+    /// link to each of them.
+    pub links: Vec<Vec<usize>>,
+    /// The targets of the wiki links of each document which are not
+    /// resolved, without duplicates.
+    pub unresolved: Vec<Vec<String>>,
+}
+
+/// A wiki link found while rendering a document.
+enum Found {
+    Document(usize),
+    Unresolved(String),
+}
+
+/// The resolver of wiki links in a document, which records the links.
+struct BatchResolver {
+    /// The documents by their lowercase names and file stems.
+    names: Arc<HashMap<String, usize>>,
+    /// The URLs of the documents.
+    urls: Arc<Vec<String>>,
+    /// The resolver of the transformer, for targets which are not documents.
+    fallback: Option<Arc<dyn WikiResolver>>,
+    found: Mutex<Vec<Found>>,
+}
+
+impl WikiResolver for BatchResolver {
+    fn resolve(&self, target: &str) -> Option<String> {
+        let (page, fragment) = match target.find('#') {
+            Some(index) => target.split_at(index),
+            None => (target, ""),
+        };
+
+        let mut found = self.found.lock().unwrap();
+
+        if let Some(&index) = self.names.get(&page.trim().to_lowercase()) {
+            found.push(Found::Document(index));
+
+            return Some(format!("{}{fragment}", self.urls[index]));
+        }
+
+        let url = self
+            .fallback
+            .as_ref()
+            .and_then(|resolver| resolver.resolve(target));
+
+        if url.is_none() {
+            found.push(Found::Unresolved(target.to_string()));
+        }
+
+        url
+    }
+}
+
+impl MarkdownBatch {
+    /// Create a new batch without documents.
+    pub fn new(mut markdown: Markdown) -> Self {
+        markdown.parser.wiki_links = true;
+
+        Self {
+            markdown,
+            documents: vec![],
+            url: Arc::new(default_url),
+        }
+    }
+
+    /// Add a document.
+    pub fn document(mut self, name: impl Into<String>, input: impl Into<String>) -> Self {
+        self.documents.push((name.into(), input.into()));
+        self
+    }
+
+    /// Set the function to make the URL of a document from its name.
+    ///
+    /// Default is the name with its `.md` extension replaced with `.html`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let output = Markdown::default()
+    ///     .batch()
+    ///     .url(|name: &str| format!("/wiki/{}", name.to_lowercase()))
+    ///     .document("Home", "[[Home]]")
+    ///     .render();
+    ///
+    /// assert_eq!(
+    ///     output.documents[0].html,
+    ///     "<p><a class=\"wiki-link\" href=\"/wiki/home\">Home</a></p>"
+    /// );
+    /// ```
+    pub fn url(mut self, url: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        self.url = Arc::new(url);
+        self
+    }
+
+    /// Render the documents.
+    pub fn render(&self) -> BatchOutput {
+        let resolvers = self.resolvers();
+
+        let documents = self
+            .documents
+            .iter()
+            .zip(&resolvers)
+            .map(|((_, input), resolver)| self.render_document(input, resolver))
+            .collect();
+
+        self.output(documents, resolvers)
+    }
+
+    /// Render the documents in parallel.
+    ///
+    /// The output is the same as [`render`](Self::render).
+    #[cfg(feature = "parallel")]
+    pub fn render_parallel(&self) -> BatchOutput {
+        use rayon::prelude::*;
+
+        let resolvers = self.resolvers();
+
+        let documents = self
+            .documents
+            .par_iter()
+            .zip(&resolvers)
+            .map(|((_, input), resolver)| self.render_document(input, resolver))
+            .collect();
+
+        self.output(documents, resolvers)
+    }
+
+    /// Make a resolver for each document.
+    fn resolvers(&self) -> Vec<Arc<BatchResolver>> {
+        let urls = Arc::new(
+            self.documents
+                .iter()
+                .map(|(name, _)| (self.url)(name))
+                .collect::<Vec<_>>(),
+        );
+
+        // The full names are inserted first, so they take precedence over
+        // the file stems of other documents. The first document of a name
+        // takes precedence over the later ones.
+        let mut names = HashMap::new();
+
+        for (index, (name, _)) in self.documents.iter().enumerate() {
+            names.entry(name.to_lowercase()).or_insert(index);
+        }
+
+        for (index, (name, _)) in self.documents.iter().enumerate() {
+            names.entry(file_stem(name).to_lowercase()).or_insert(index);
+        }
+
+        let names = Arc::new(names);
+
+        self.documents
+            .iter()
+            .map(|_| {
+                Arc::new(BatchResolver {
+                    names: names.clone(),
+                    urls: urls.clone(),
+                    fallback: self.markdown.transformer.wiki_resolver.clone(),
+                    found: Mutex::new(vec![]),
+                })
+            })
+            .collect()
+    }
+
+    fn render_document(&self, input: &str, resolver: &Arc<BatchResolver>) -> String {
+        let markdown = self.markdown.for_document(input);
+
+        let mut transformer = markdown.transformer.clone();
+        transformer.wiki_resolver = Some(resolver.clone());
+
+        let (tree, _) = markdown.parse_for_html(input, lex(input));
+        let document = transformer.transform(tree);
+
+        markdown.stringifier.stringify(document)
+    }
+
+    fn output(&self, htmls: Vec<String>, resolvers: Vec<Arc<BatchResolver>>) -> BatchOutput {
+        let mut documents = vec![];
+        let mut graph = LinkGraph::default();
+
+        for (index, (((name, _), html), resolver)) in
+            self.documents.iter().zip(htmls).zip(resolvers).enumerate()
+        {
+            let mut links = vec![];
+            let mut unresolved = vec![];
+
+            for found in resolver.found.lock().unwrap().drain(..) {
+                match found {
+                    Found::Document(index) if !links.contains(&index) => links.push(index),
+                    Found::Unresolved(target) if !unresolved.contains(&target) => {
+                        unresolved.push(target)
+                    }
+                    _ => {}
+                }
+            }
+
+            documents.push(BatchDocument {
+                name: name.clone(),
+                url: resolver.urls[index].clone(),
+                html,
+            });
+            graph.links.push(links);
+            graph.unresolved.push(unresolved);
+        }
+
+        BatchOutput { documents, graph }
+    }
+}
+
+/// Replace the `.md` extension of the name with `.html`.
+fn default_url(name: &str) -> String {
+    format!("{}.html", name.strip_suffix(".md").unwrap_or(name))
+}
+
+/// Get the last segment of the name without the extension.
+fn file_stem(name: &str) -> &str {
+    let name = name.rsplit('/').next().unwrap_or(name);
+
+    match name.rfind('.') {
+        Some(index) if index > 0 => &name[..index],
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_batch() {
+        let markdown =
+            Markdown::default().transformer(Transformer::new().wiki_resolver(|target: &str| {
+                (target == "External").then(|| "https://example.com".to_string())
+            }));
+
+        let batch = markdown
+            .batch()
+            .document("a.md", "[[B]] [[b]] [[dir/b.md]] [[c]] [[External]] [[C]]")
+            .document("dir/b.md", "[[a.md|A]]\n\n- [[B]]")
+            .document("b", "[[b]]");
+
+        let output = batch.render();
+
+        assert_eq!(
+            output.documents[0].html,
+            concat![
+                "<p><a class=\"wiki-link\" href=\"b.html\">B</a> ",
+                "<a class=\"wiki-link\" href=\"b.html\">b</a> ",
+                "<a class=\"wiki-link\" href=\"dir/b.html\">dir/b.md</a> c ",
+                "<a class=\"wiki-link\" href=\"https://example.com\">External</a> C</p>",
+            ]
+        );
+        assert_eq!(output.documents[1].url, "dir/b.html");
+        assert_eq!(
+            output.graph,
+            LinkGraph {
+                links: vec![vec![2, 1], vec![0, 2], vec![2]],
+                unresolved: vec![vec!["c".into(), "C".into()], vec![], vec![]],
+            }
+        );
+
+        assert_eq!(
+            Markdown::default().batch().render(),
+            BatchOutput {
+                documents: vec![],
+                graph: LinkGraph::default(),
+            }
+        );
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(batch.render_parallel(), output);
+    }
+
+    #[test]
+    fn test_file_stem() {
+        for (name, stem) in [
+            ("a.md", "a"),
+            ("dir/a.b.md", "a.b"),
+            (".md", ".md"),
+            ("dir/a", "a"),
+        ] {
+            assert_eq!(file_stem(name), stem);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Convert markdown files or the standard input to HTML.
+//!
+//! This binary is built with the `cli` feature. HTML in the input is escaped
+//! and only `http`, `https` and `mailto` links are kept, as with
+//! [`Markdown::untrusted`], unless `--unsafe-html` is given.
+
+use std::{
+    env,
+    fs::File,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use note_mark::{options::*, prelude::*};
+
+const USAGE: &str = "\
+Usage: note-mark [OPTIONS] [FILE]...
+
+Convert markdown to HTML. The standard input is read if no file is given or
+a file is `-`.
+
+Options:
+      --toc             Write the table of contents before the document
+      --format          Write the HTML with line breaks and indents
+      --section <ID>    Write only the section under the headline with the id
+      --unsafe-html     Let HTML, scripts and any link through
+      --profile <NAME>  Use the settings of a profile: comments, notes or docs
+  -h, --help            Print this help
+  -V, --version         Print the version
+";
+
+/// The options of a conversion.
+#[derive(Debug, Default)]
+struct Args {
+    toc: bool,
+    format: bool,
+    section: Option<String>,
+    unsafe_html: bool,
+    profile: Option<Profile>,
+    files: Vec<String>,
+}
+
+/// What the command line asks for.
+enum Command {
+    Help,
+    Version,
+    Convert(Args),
+}
+
+fn parse_args(mut iter: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = Args::default();
+
+    while let Some(arg) = iter.next() {
+        // The value of an option is the next argument or after `=`.
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+
+        let value = || {
+            value
+                .or_else(|| iter.next())
+                .ok_or_else(|| format!("`{name}` needs a value"))
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--toc" => args.toc = true,
+            "--format" => args.format = true,
+            "--section" => args.section = Some(value()?),
+            "--unsafe-html" => args.unsafe_html = true,
+            "--profile" => {
+                let profile = value()?;
+
+                args.profile = Some(profile.parse().map_err(|error| format!("{error}"))?);
+            }
+            "--" => {
+                args.files.extend(iter.by_ref());
+                break;
+            }
+            _ if name.starts_with('-') && name != "-" => {
+                return Err(format!("unknown option `{name}`"));
+            }
+            _ => args.files.push(arg),
+        }
+    }
+
+    if args.toc && args.section.is_some() {
+        return Err("`--toc` and `--section` cannot be used together".to_string());
+    }
+
+    Ok(Command::Convert(args))
+}
+
+fn markdown(args: &Args) -> Markdown {
+    let mut stringifier = StringifierOptions {
+        format: args.format.then_some(true),
+        escape: Some(!args.unsafe_html),
+        sanitize: Some(!args.unsafe_html),
+        any_url_scheme: Some(args.unsafe_html),
+        ..Default::default()
+    };
+
+    if !args.unsafe_html {
+        stringifier.url_schemes = Some(vec![
+            "http".to_string(),
+            "https".to_string(),
+            "mailto".to_string(),
+        ]);
+    }
+
+    Markdown::from_options(&MarkdownOptions {
+        profile: args.profile,
+        stringifier,
+        ..Default::default()
+    })
+}
+
+/// Convert the input and write the HTML with a line break at the end.
+fn convert(
+    markdown: &Markdown,
+    args: &Args,
+    mut reader: impl Read,
+    writer: impl Write,
+) -> Result<(), String> {
+    // The input is read by the renderer unless only a part of it is written.
+    if args.section.is_none() && !args.toc {
+        let html = markdown
+            .execute_reader(reader)
+            .map_err(|error| error.to_string())?;
+
+        return write(writer, &html);
+    }
+
+    let mut input = String::new();
+
+    reader
+        .read_to_string(&mut input)
+        .map_err(|error| format!("cannot read the input: {error}"))?;
+
+    let html = match &args.section {
+        Some(id) => markdown
+            .section(&input, id)
+            .ok_or_else(|| format!("no headline has the id `{id}`"))?,
+        None => {
+            let (html, toc) = markdown.execute_with_toc(&input);
+
+            format!("{toc}\n{html}")
+        }
+    };
+
+    write(writer, &html)
+}
+
+fn write(mut writer: impl Write, html: &str) -> Result<(), String> {
+    writeln!(writer, "{html}").map_err(|error| format!("cannot write the output: {error}"))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let markdown = markdown(args);
+    let stdout = io::stdout();
+
+    if args.files.is_empty() {
+        return convert(&markdown, args, io::stdin().lock(), stdout.lock());
+    }
+
+    for file in &args.files {
+        let result = match file.as_str() {
+            "-" => convert(&markdown, args, io::stdin().lock(), stdout.lock()),
+            _ => File::open(file)
+                .map_err(|error| format!("cannot open the file: {error}"))
+                .and_then(|reader| convert(&markdown, args, reader, stdout.lock())),
+        };
+
+        result.map_err(|error| format!("{file}: {error}"))?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Version) => {
+            println!("note-mark {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Convert(args)) => args,
+        Err(error) => {
+            eprintln!("note-mark: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("note-mark: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
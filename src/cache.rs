@@ -0,0 +1,284 @@
+//! Caching of rendered documents.
+
+use std::collections::HashMap;
+
+use crate::{incremental::content_hash, Markdown};
+
+/// A cache of rendered documents, which drops the least recently used one
+/// when it is full.
+///
+/// Preview panes render the same documents again and again, e.g. when tabs
+/// are switched. A render is keyed by the hash of the input and a
+/// fingerprint of the options, so one cache can serve several [`Markdown`]
+/// configurations. The input is compared too, so a hash collision is a miss.
+///
+/// The fingerprint is made from the [`Debug`] output of the options, which
+/// shows every option in a stable order, since maps of options are sorted.
+/// Resolvers are shown only by whether they are set, and
+/// counter styles by how they format 1, so clear the cache when a resolver
+/// or a custom counter style changes its results.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let markdown = Markdown::default();
+/// let mut cache = RenderCache::new(16);
+///
+/// assert_eq!(cache.execute(&markdown, "# Hi"), "<h1>Hi</h1>");
+/// assert_eq!(cache.execute(&markdown, "# Hi"), "<h1>Hi</h1>");
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    /// The maximum number of renders kept.
+    capacity: usize,
+    /// The renders by their keys.
+    entries: HashMap<Key, Entry>,
+    /// The count of lookups, which orders the uses of entries.
+    tick: u64,
+}
+
+/// The key of a render in a [`RenderCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    render: Render,
+    /// The hash of the input.
+    input: u64,
+    /// The hash of the options.
+    options: u64,
+}
+
+/// The enum to represent what is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Render {
+    Html,
+    HtmlWithToc,
+}
+
+/// A render in a [`RenderCache`].
+#[derive(Debug, Clone)]
+struct Entry {
+    input: String,
+    html: String,
+    /// The table of contents, which is empty for [`Render::Html`].
+    toc: String,
+    /// The tick of the last use.
+    used: u64,
+}
+
+impl RenderCache {
+    /// Create a cache which keeps up to `capacity` renders. A cache with no
+    /// capacity keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Convert Markdown to HTML like [`Markdown::execute`], or return the
+    /// cached HTML.
+    pub fn execute(&mut self, markdown: &Markdown, input: &str) -> String {
+        let (html, _) = self.render(Render::Html, markdown, input, |markdown, input| {
+            (markdown.execute(input), String::new())
+        });
+
+        html
+    }
+
+    /// Convert Markdown to HTML with a table of contents like
+    /// [`Markdown::execute_with_toc`], or return the cached ones.
+    pub fn execute_with_toc(&mut self, markdown: &Markdown, input: &str) -> (String, String) {
+        self.render(
+            Render::HtmlWithToc,
+            markdown,
+            input,
+            Markdown::execute_with_toc,
+        )
+    }
+
+    /// Get the number of cached renders.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache has no renders.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all cached renders.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn render(
+        &mut self,
+        render: Render,
+        markdown: &Markdown,
+        input: &str,
+        f: impl FnOnce(&Markdown, &str) -> (String, String),
+    ) -> (String, String) {
+        let key = Key {
+            render,
+            input: content_hash(input),
+            options: content_hash(&format!("{markdown:?}")),
+        };
+
+        self.tick += 1;
+
+        if let Some(entry) = self
+            .entries
+            .get_mut(&key)
+            .filter(|entry| entry.input == input)
+        {
+            entry.used = self.tick;
+
+            return (entry.html.clone(), entry.toc.clone());
+        }
+
+        let (html, toc) = f(markdown, input);
+
+        if self.capacity == 0 {
+            return (html, toc);
+        }
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| *key);
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(
+            key,
+            Entry {
+                input: input.to_string(),
+                html: html.clone(),
+                toc: toc.clone(),
+                used: self.tick,
+            },
+        );
+
+        (html, toc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layer::{stringifier::Stringifier, transformer::Transformer},
+        model::html::ElementTag,
+    };
+
+    #[test]
+    fn test_render_cache() {
+        let markdown = Markdown::default();
+        let with_ids = Markdown::default().transformer(Transformer::new().heading_ids(true));
+
+        let mut cache = RenderCache::new(2);
+
+        assert_eq!(cache.execute(&markdown, "# A"), "<h1>A</h1>");
+        assert_eq!(cache.execute(&with_ids, "# A"), "<h1 id=\"a\">A</h1>");
+        assert_eq!(cache.len(), 2);
+
+        // `# A` of the default options is used again, so the other one is
+        // dropped.
+        assert_eq!(cache.execute(&markdown, "# A"), "<h1>A</h1>");
+        assert_eq!(cache.execute(&markdown, "# B"), "<h1>B</h1>");
+        assert_eq!(cache.len(), 2);
+        assert!(!cache
+            .entries
+            .values()
+            .any(|entry| entry.html.contains("id")));
+
+        assert_eq!(
+            cache.execute_with_toc(&markdown, "# A"),
+            markdown.execute_with_toc("# A")
+        );
+        assert_eq!(
+            cache.execute_with_toc(&markdown, "# A"),
+            markdown.execute_with_toc("# A")
+        );
+
+        cache.clear();
+
+        assert!(cache.is_empty());
+
+        let mut cache = RenderCache::new(0);
+
+        assert_eq!(cache.execute(&markdown, "a"), "<p>a</p>");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_render_cache_options() {
+        use std::sync::Arc;
+
+        use crate::model::counter::{Counter, Numbering};
+
+        let roman = Numbering {
+            styles: vec![Arc::new(Counter::UpperRoman)],
+            ..Default::default()
+        };
+
+        // Each pair differs only in an option which is not a plain value.
+        let pairs = [
+            (
+                Transformer::new().heading_numbering(Numbering::default()),
+                Transformer::new().heading_numbering(roman),
+            ),
+            (
+                Transformer::new().heading_ids(true),
+                Transformer::new()
+                    .heading_ids(true)
+                    .slugifier(|text| format!("x-{text}")),
+            ),
+        ];
+
+        let mut cache = RenderCache::new(8);
+
+        for (first, second) in pairs {
+            for transformer in [first, second] {
+                let markdown = Markdown::default().transformer(transformer);
+
+                assert_eq!(cache.execute(&markdown, "# Y"), markdown.execute("# Y"));
+            }
+        }
+
+        assert_eq!(cache.len(), 4);
+
+        // Options built the same way share renders.
+        let build = || {
+            let stringifier = (0..16).fold(Stringifier::new(), |stringifier, index| {
+                stringifier.variable(&format!("k{index}"), "v")
+            });
+
+            let transformer = [ElementTag::P, ElementTag::H1, ElementTag::Li, ElementTag::A]
+                .into_iter()
+                .fold(Transformer::new(), |transformer, tag| {
+                    transformer.class_for(tag, "x").attr_for(tag, "k", "v")
+                });
+
+            Markdown::default()
+                .stringifier(stringifier)
+                .transformer(transformer)
+        };
+
+        let mut cache = RenderCache::new(8);
+
+        cache.execute(&build(), "{{k1}}");
+        cache.execute(&build(), "{{k1}}");
+
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,281 @@
+//! Error and warning types.
+
+use std::{fmt, io, ops::Range};
+
+use crate::model::token::{LineIndex, Position};
+
+/// The error returned when a configuration value cannot be parsed from a
+/// string.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let error = "soft".parse::<HeadlineEnding>().unwrap_err();
+///
+/// assert_eq!(error.to_string(), "invalid value `soft` for HeadlineEnding");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConfigError {
+    /// The name of the configuration type.
+    pub name: &'static str,
+    /// The value which cannot be parsed.
+    pub value: String,
+}
+
+impl ParseConfigError {
+    /// Create a new error.
+    pub fn new(name: &'static str, value: &str) -> Self {
+        Self {
+            name,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value `{}` for {}", self.value, self.name)
+    }
+}
+
+impl std::error::Error for ParseConfigError {}
+
+/// The error returned by the fallible APIs such as
+/// [`Markdown::try_execute`](crate::Markdown::try_execute), instead of
+/// rendering with a degraded result.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{prelude::*, Error};
+///
+/// let markdown = Markdown::default().parser(Parser::default().max_input_len(Some(4)));
+///
+/// let error = markdown.try_execute("# Hello").unwrap_err();
+///
+/// assert!(matches!(error, Error::InputTooLong { len: 7, max: 4 }));
+/// assert_eq!(error.to_string(), "the input of 7 bytes is longer than the limit of 4 bytes");
+/// ```
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration is invalid. This is a warning for which
+    /// [`ConfigWarning::is_invalid`] is true.
+    Config(ConfigWarning),
+    /// The input is longer than
+    /// [`Parser::max_input_len`](crate::layer::parser::Parser::max_input_len),
+    /// so the rest would be ignored.
+    InputTooLong { len: usize, max: usize },
+    /// The input has more tokens than
+    /// [`Parser::max_tokens`](crate::layer::parser::Parser::max_tokens), so
+    /// the rest would be ignored.
+    TooManyTokens { max: usize },
+    /// An include is not resolved, includes itself or is nested too deeply.
+    Include(Diagnostic),
+    /// The output cannot be written.
+    Io(io::Error),
+    /// The input cannot be read, or it is not UTF-8.
+    Read(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(warning) => write!(f, "invalid configuration: {warning}"),
+            Self::InputTooLong { len, max } => {
+                write!(
+                    f,
+                    "the input of {len} bytes is longer than the limit of {max} bytes"
+                )
+            }
+            Self::TooManyTokens { max } => {
+                write!(f, "the input has more tokens than the limit of {max}")
+            }
+            Self::Include(diagnostic) => write!(f, "{diagnostic}"),
+            Self::Io(error) => write!(f, "cannot write the output: {error}"),
+            Self::Read(error) => write!(f, "cannot read the input: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) | Self::Read(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// The warning about a conflicting or likely misconfigured setting.
+///
+/// Warnings are returned by [`Markdown::validate`](crate::Markdown::validate)
+/// and [`Markdown::validate_input`](crate::Markdown::validate_input).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigWarning {
+    /// The indent style is `IndentStyle::Space(0)`, or `IndentStyle::Both`
+    /// with a tab stop of 0, which cannot count indents.
+    ZeroIndentWidth,
+    /// The minimum level of the table of contents is greater than the maximum
+    /// level, or the levels are out of `1..=6`.
+    TocLevelRange { min: u8, max: u8 },
+    /// URL schemes are filtered or attributes are sanitized, but text is not
+    /// escaped, so HTML in the input is rendered as is.
+    SanitizeWithoutEscape,
+    /// The title chain is empty, so no title is found.
+    EmptyTitleChain,
+    /// A list item in the input is indented with characters which the indent
+    /// style does not count. The line starts from 1.
+    IndentStyleMismatch { line: usize },
+    /// Sections are enabled but headlines end with a hard break, so the line
+    /// after a headline is put into the headline instead of its section.
+    SectionWithHardBreakHeadline,
+    /// A value in the front matter cannot be parsed as the option of its key,
+    /// so the option is not overridden.
+    InvalidFrontMatterOption {
+        key: String,
+        error: ParseConfigError,
+    },
+}
+
+impl ConfigWarning {
+    /// Whether the setting cannot work at all, rather than likely being a
+    /// mistake. Such warnings are errors of
+    /// [`Markdown::try_execute`](crate::Markdown::try_execute).
+    pub fn is_invalid(&self) -> bool {
+        matches!(
+            self,
+            Self::ZeroIndentWidth
+                | Self::TocLevelRange { .. }
+                | Self::InvalidFrontMatterOption { .. }
+        )
+    }
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroIndentWidth => f.write_str("an indent width of 0 cannot count indents"),
+            Self::TocLevelRange { min, max } => {
+                write!(
+                    f,
+                    "table of contents levels {min}..={max} are out of 1..=6 or empty"
+                )
+            }
+            Self::SanitizeWithoutEscape => {
+                f.write_str("links and attributes are sanitized but HTML in text is not escaped")
+            }
+            Self::EmptyTitleChain => f.write_str("the title chain is empty"),
+            Self::IndentStyleMismatch { line } => {
+                write!(
+                    f,
+                    "line {line} is indented with characters the indent style does not count"
+                )
+            }
+            Self::SectionWithHardBreakHeadline => {
+                f.write_str("sections are enabled but headlines end with a hard break")
+            }
+            Self::InvalidFrontMatterOption { key, error } => {
+                write!(f, "front matter option `{key}` is ignored: {error}")
+            }
+        }
+    }
+}
+
+/// A problem in the input, which is rendered as text instead of the markup.
+///
+/// Diagnostics are returned by
+/// [`Markdown::execute_with_diagnostics`](crate::Markdown::execute_with_diagnostics).
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let input = "Some\n**bold";
+///
+/// let (_, diagnostics) = Markdown::default().execute_with_diagnostics(input);
+///
+/// let position = diagnostics[0].position(&LineIndex::new(input));
+///
+/// assert_eq!(
+///     format!("{}:{}: {}", position.line, position.column, diagnostics[0]),
+///     "2:1: unclosed `**`"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The kind of the problem.
+    pub kind: DiagnosticKind,
+    /// The byte range of the problem in the input.
+    pub span: Range<usize>,
+}
+
+/// The kind of a [`Diagnostic`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// `*` or `**` which is not closed.
+    UnclosedEmphasis { delimiter: String },
+    /// `[text](` whose destination is not closed in the line.
+    MalformedLink,
+    /// An include of a target which is already being included.
+    IncludeCycle { target: String },
+    /// An include nested deeper than
+    /// [`Parser::max_include_depth`](crate::layer::parser::Parser::max_include_depth).
+    IncludeTooDeep { target: String },
+    /// An include whose target the resolver does not find.
+    UnresolvedInclude { target: String },
+    /// Syntax nested deeper than the limits of the parser, such as
+    /// [`Parser::max_nesting`](crate::layer::parser::Parser::max_nesting).
+    /// Only the first one in a document is reported.
+    NestingTooDeep,
+    /// The input which is ignored because of
+    /// [`Parser::max_input_len`](crate::layer::parser::Parser::max_input_len)
+    /// or [`Parser::max_tokens`](crate::layer::parser::Parser::max_tokens).
+    InputTruncated,
+}
+
+impl DiagnosticKind {
+    /// Whether this is a problem of an include.
+    pub fn is_include(&self) -> bool {
+        matches!(
+            self,
+            Self::IncludeCycle { .. }
+                | Self::IncludeTooDeep { .. }
+                | Self::UnresolvedInclude { .. }
+        )
+    }
+}
+
+impl Diagnostic {
+    /// Get the position of the start of the span.
+    pub fn position(&self, index: &LineIndex) -> Position {
+        index.position(self.span.start)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DiagnosticKind::UnclosedEmphasis { delimiter } => write!(f, "unclosed `{delimiter}`"),
+            DiagnosticKind::MalformedLink => f.write_str("link destination is not closed"),
+            DiagnosticKind::IncludeCycle { target } => write!(f, "`{target}` includes itself"),
+            DiagnosticKind::IncludeTooDeep { target } => {
+                write!(f, "`{target}` is included too deeply")
+            }
+            DiagnosticKind::UnresolvedInclude { target } => write!(f, "`{target}` is not found"),
+            DiagnosticKind::NestingTooDeep => f.write_str("nested too deeply"),
+            DiagnosticKind::InputTruncated => f.write_str("the input is cut here"),
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Incremental parsing for documents which are edited repeatedly.
+
+use std::{ops::Range, sync::OnceLock};
+
+use crate::{
+    layer::lexer::lex,
+    model::tree::{BlockItem, BlockTree, MarkdownTree},
+    options::MarkdownOptions,
+    Markdown,
+};
+
+/// A markdown document which reparses only the blocks touched by an edit.
+///
+/// The source is kept as chunks of top level blocks (see
+/// [`Parser::split_blocks`](crate::layer::parser::Parser::split_blocks)).
+/// When a range is replaced, only the chunks around the edit are split and
+/// parsed again, and the blocks of the other chunks are reused.
+///
+/// The options in the front matter are applied as by
+/// [`Markdown::for_document`], and an edit which changes them parses the
+/// whole source again.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let markdown = Markdown::default();
+///
+/// let mut document = markdown.incremental("# Title\n\nHello\n");
+///
+/// document.replace_range(9..14, "Hello, **world**");
+///
+/// assert_eq!(document.source(), "# Title\n\nHello, **world**\n");
+/// assert_eq!(
+///     document.render(),
+///     "<h1>Title</h1><p>Hello, <strong>world</strong></p>"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalDocument {
+    /// Markdown configuration.
+    markdown: Markdown,
+    /// The configuration of the current source, with the options of its
+    /// front matter applied.
+    document: Markdown,
+    /// The options read from the front matter of the current source.
+    options: Option<MarkdownOptions>,
+    /// The current source.
+    source: String,
+    /// Chunks of the source and their parsed blocks.
+    blocks: Vec<Block>,
+}
+
+/// A chunk of the source and its parsed blocks.
+#[derive(Debug, Clone)]
+struct Block {
+    /// The byte range of the chunk in the source.
+    range: Range<usize>,
+    /// The number of the first line of the chunk, counted from 1.
+    line: usize,
+    /// The parsed blocks of the chunk. Their source positions are counted
+    /// from the start of the chunk, so they can be reused wherever the chunk
+    /// moves.
+    items: Vec<BlockItem<'static>>,
+    /// The hash of the source of the chunk.
+    source_hash: u64,
+    /// The hash of the rendered HTML of the chunk, computed when it is
+    /// first asked for.
+    html_hash: OnceLock<u64>,
+}
+
+/// The content hashes of a chunk of top level blocks.
+///
+/// Hashes are computed with [`content_hash`], so they are stable across
+/// processes and can be used as cache keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHash {
+    /// The byte range of the chunk in the source.
+    pub range: Range<usize>,
+    /// The hash of the source of the chunk.
+    pub source: u64,
+    /// The hash of the HTML of the chunk rendered on its own.
+    pub html: u64,
+}
+
+impl IncrementalDocument {
+    /// Create a new document and parse the whole input.
+    pub fn new(markdown: Markdown, input: &str) -> Self {
+        let document = markdown.for_document(input).into_owned();
+
+        let mut line = 1;
+        let mut end = 0;
+
+        let blocks = document
+            .parser
+            .split_blocks(input)
+            .into_iter()
+            .map(|range| {
+                line += input[end..range.start].matches('\n').count();
+                end = range.start;
+
+                Block::new(&document, input, range, line)
+            })
+            .collect();
+
+        Self {
+            options: markdown.document_options(input),
+            markdown,
+            document,
+            source: input.to_string(),
+            blocks,
+        }
+    }
+
+    /// Get the current source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Replace a byte range of the source with the text, and reparse only the
+    /// affected blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of the source or does not lie on char
+    /// boundaries, like [`String::replace_range`].
+    pub fn replace_range(&mut self, range: Range<usize>, text: &str) {
+        assert!(
+            range.start <= range.end && range.end <= self.source.len(),
+            "range {:?} is out of the source of length {}",
+            range,
+            self.source.len()
+        );
+
+        // The chunks touching the range and one neighbour on each side, since
+        // an edit can join or split the chunks next to it.
+        let first = self
+            .blocks
+            .iter()
+            .position(|block| block.range.end >= range.start)
+            .unwrap_or(0)
+            .saturating_sub(1);
+        let last = self
+            .blocks
+            .iter()
+            .rposition(|block| block.range.start <= range.end)
+            .map_or(self.blocks.len() - 1, |index| index + 1)
+            .min(self.blocks.len() - 1);
+
+        let start = self.blocks[first].range.start;
+        let mut line = self.blocks[first].line;
+
+        let old_texts = self.blocks[first..=last]
+            .iter()
+            .map(|block| self.source[block.range.clone()].to_string())
+            .collect::<Vec<_>>();
+
+        let line_shift = text.matches('\n').count() as isize
+            - self.source[range.clone()].matches('\n').count() as isize;
+
+        self.source.replace_range(range.clone(), text);
+
+        if self.markdown.front_matter_options {
+            let options = self.markdown.document_options(&self.source);
+
+            if options != self.options {
+                let source = std::mem::take(&mut self.source);
+
+                *self = Self::new(self.markdown.clone(), &source);
+                return;
+            }
+        }
+
+        let shift = text.len() as isize - range.len() as isize;
+        let edit_end = range.start + text.len();
+
+        // The chunks are split again until a boundary after the edit is the
+        // start of an old chunk, from which the chunks are the same as before.
+        // An edit which opens or closes a code block can change all the
+        // chunks after it. Front matter is parsed only at the start, so an
+        // old chunk is the same only where it is still at the start or not.
+        let mut chunks = vec![];
+        let mut end = self.blocks.len();
+
+        self.document
+            .parser
+            .for_chunk(start)
+            .each_block(&self.source[start..], |chunk| {
+                let chunk = chunk.start + start..chunk.end + start;
+                let old_start = (chunk.start as isize - shift) as usize;
+
+                if chunk.start >= edit_end && (old_start == 0) == (chunk.start == 0) {
+                    if let Ok(index) = self
+                        .blocks
+                        .binary_search_by_key(&old_start, |block| block.range.start)
+                    {
+                        end = index;
+                        return false;
+                    }
+                }
+
+                chunks.push(chunk);
+                true
+            });
+
+        let mut old_blocks = self
+            .blocks
+            .drain(first..end)
+            .enumerate()
+            .map(|(index, block)| {
+                let text = match old_texts.get(index) {
+                    Some(text) => text.clone(),
+                    None => {
+                        let start = (block.range.start as isize + shift) as usize;
+                        let end = (block.range.end as isize + shift) as usize;
+
+                        self.source[start..end].to_string()
+                    }
+                };
+
+                (text, block)
+            })
+            .collect::<Vec<_>>();
+
+        let mut line_end = start;
+
+        let new_blocks = chunks
+            .into_iter()
+            .map(|range| {
+                let chunk_text = &self.source[range.clone()];
+
+                line += self.source[line_end..range.start].matches('\n').count();
+                line_end = range.start;
+
+                // A chunk at the start is parsed with front matter, so it is
+                // reused only where it is still at the start or not.
+                let reused = old_blocks.iter().position(|(text, block)| {
+                    text == chunk_text && (block.range.start == 0) == (range.start == 0)
+                });
+
+                match reused {
+                    Some(index) => Block {
+                        range,
+                        line,
+                        ..old_blocks.swap_remove(index).1
+                    },
+                    None => Block::new(&self.document, &self.source, range, line),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for block in &mut self.blocks[first..] {
+            block.range = (block.range.start as isize + shift) as usize
+                ..(block.range.end as isize + shift) as usize;
+            block.line = (block.line as isize + line_shift) as usize;
+        }
+
+        self.blocks.splice(first..first, new_blocks);
+    }
+
+    /// Get the markdown tree of the current source.
+    pub fn tree(&self) -> MarkdownTree<'static> {
+        let positions = self.document.stringifier.source_positions;
+
+        MarkdownTree {
+            root: BlockTree {
+                root: self
+                    .blocks
+                    .iter()
+                    .flat_map(|block| block.items(positions))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Get the content hashes of the chunks of top level blocks.
+    ///
+    /// Hashes of unchanged chunks are kept across edits, so they can be used
+    /// to reuse cached renders of the chunks. HTML hashes are computed on the
+    /// first call and cached per chunk.
+    ///
+    /// The HTML hash is of the chunk rendered on its own. Transforms across
+    /// blocks, such as [`Transformer::section`](crate::layer::transformer::Transformer::section),
+    /// make the output of [`render`](Self::render) differ from the joined
+    /// chunks, so the HTML hashes are not valid as cache keys of the output
+    /// with them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let mut document = Markdown::default().incremental("# Title\n\nHello\n");
+    ///
+    /// let before = document.block_hashes();
+    ///
+    /// document.replace_range(9..14, "World");
+    ///
+    /// let after = document.block_hashes();
+    ///
+    /// assert_eq!(before[0], after[0]);
+    /// assert_ne!(before[1].source, after[1].source);
+    /// assert_eq!(after[1].html, content_hash("<p>World</p>"));
+    /// ```
+    pub fn block_hashes(&self) -> Vec<BlockHash> {
+        self.blocks
+            .iter()
+            .map(|block| BlockHash {
+                range: block.range.clone(),
+                source: block.source_hash,
+                html: block.html_hash(&self.document),
+            })
+            .collect()
+    }
+
+    /// Render the current source to HTML.
+    pub fn render(&self) -> String {
+        let document = self.document.transformer.transform(self.tree());
+
+        self.document.stringifier.stringify(document)
+    }
+}
+
+impl Block {
+    /// Parse a chunk of the source into blocks which do not borrow it. The
+    /// chunk starts at the line `line`.
+    fn new(markdown: &Markdown, source: &str, range: Range<usize>, line: usize) -> Self {
+        let input = &source[range.clone()];
+
+        let parser = markdown.parser.for_chunk(range.start);
+
+        let tree = match markdown.stringifier.source_positions {
+            true => parser.parse_with_positions(input, lex(input), 1).0,
+            false => parser.parse(input, lex(input)),
+        }
+        .into_owned();
+
+        Self {
+            range,
+            line,
+            items: tree.root.into_root(),
+            source_hash: content_hash(input),
+            html_hash: OnceLock::new(),
+        }
+    }
+
+    /// Get the parsed blocks, with their source positions moved to the line
+    /// of the chunk if `positions` is set.
+    fn items(&self, positions: bool) -> Vec<BlockItem<'static>> {
+        let mut items = self.items.clone();
+
+        if positions && self.line != 1 {
+            shift_lines(&mut items, self.line - 1);
+        }
+
+        items
+    }
+
+    /// Get the hash of the HTML of the chunk, rendering it if needed.
+    fn html_hash(&self, markdown: &Markdown) -> u64 {
+        *self.html_hash.get_or_init(|| {
+            let tree = MarkdownTree {
+                root: BlockTree {
+                    root: self.items.clone(),
+                },
+            };
+
+            let html = markdown
+                .stringifier
+                .stringify(markdown.transformer.transform(tree));
+
+            content_hash(&html)
+        })
+    }
+}
+
+/// Add `lines` to the lines of the `data-sourcepos` attributes of the items
+/// and their descendants.
+fn shift_lines(items: &mut [BlockItem], lines: usize) {
+    let mut stack = items.iter_mut().collect::<Vec<_>>();
+
+    while let Some(item) = stack.pop() {
+        match item {
+            BlockItem::Attributed(attributes, item) => {
+                for (key, value) in &mut attributes.attrs {
+                    if key == "data-sourcepos" {
+                        *value = shift_position(value, lines);
+                    }
+                }
+
+                stack.push(item);
+            }
+            BlockItem::BulletList(tree) | BlockItem::OrderedList(_, _, tree) => stack.extend(
+                tree.root
+                    .iter_mut()
+                    .flat_map(|item| item.children.iter_mut()),
+            ),
+            BlockItem::BlockQuote(tree) | BlockItem::Container(_, tree) => {
+                stack.extend(tree.root.iter_mut())
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Add `lines` to the lines of a position such as `3:1-4:10`.
+fn shift_position(position: &str, lines: usize) -> String {
+    position
+        .split('-')
+        .map(|part| match part.split_once(':') {
+            Some((line, column)) => match line.parse::<usize>() {
+                Ok(line) => format!("{}:{column}", line + lines),
+                Err(_) => part.to_string(),
+            },
+            None => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Hash the text with 64-bit FNV-1a.
+///
+/// The hash does not depend on the process or the platform, unlike
+/// [`DefaultHasher`](std::collections::hash_map::DefaultHasher).
+pub fn content_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply the edits one by one and check the output with a full parse.
+    fn check(input: &str, edits: &[(Range<usize>, &str)]) {
+        check_with(&Markdown::default(), input, edits);
+    }
+
+    /// Apply the edits one by one with the configuration and check the
+    /// output with a full parse.
+    fn check_with(markdown: &Markdown, input: &str, edits: &[(Range<usize>, &str)]) {
+        let mut document = markdown.incremental(input);
+
+        assert_eq!(document.render(), markdown.execute(input));
+
+        for (range, text) in edits {
+            document.replace_range(range.clone(), text);
+
+            let expected = markdown.execute(document.source());
+
+            assert_eq!(
+                document.render(),
+                expected,
+                "source: {:?}",
+                document.source()
+            );
+        }
+    }
+
+    #[test]
+    fn test_replace_in_block() {
+        check(
+            "# Title\n\nFirst\n\nSecond\n\nThird\n",
+            &[(11, "x"), (17, "**bold**"), (0, "## ")].map(|(at, text)| (at..at, text)),
+        );
+    }
+
+    #[test]
+    fn test_join_and_split_blocks() {
+        let input = "First\n\nSecond\n\nThird\n";
+
+        check(input, &[(5..7, " "), (5..6, "\n\n")]);
+        check(input, &[(13..15, ""), (0..0, "- a\n\n")]);
+    }
+
+    #[test]
+    fn test_lists() {
+        check(
+            "- a\n\n- b\n\nEnd\n",
+            &[(5..5, "- c\n\n"), (0..1, "1."), (6..6, "  - d\n")],
+        );
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let input = "A\n\n# B\n\nC\n";
+
+        check(input, &[(0..0, "```\n\n"), (0..5, ""), (4..4, "~~~\n")]);
+        check("```\nA\n\n# B\n```\n\nC\n", &[(11..15, ""), (0..3, "")]);
+    }
+
+    #[test]
+    fn test_source_positions() {
+        let markdown = Markdown::default()
+            .stringifier(crate::layer::stringifier::Stringifier::new().source_positions(true));
+
+        let input = "A\n\n> B\n>\n> - c\n\n  D\n\nE\n";
+
+        assert!(markdown
+            .incremental(input)
+            .render()
+            .contains("<p data-sourcepos=\"9:1-9:1\">E</p>"));
+
+        check_with(
+            &markdown,
+            input,
+            &[(0..0, "X\n\n"), (3..3, "\n\n\n"), (0..6, ""), (8..8, "\n")],
+        );
+    }
+
+    #[test]
+    fn test_front_matter_options() {
+        let markdown = Markdown::default()
+            .parser(crate::layer::parser::Parser::new().front_matter(true))
+            .front_matter_options(true);
+
+        let input = "---\ntoc: true\n---\n[TOC]\n\n# A\n\nB\n";
+
+        assert!(markdown
+            .incremental(input)
+            .render()
+            .starts_with("<ul><li><a href=\"#a\">A</a></li></ul>"));
+
+        check_with(
+            &markdown,
+            input,
+            &[
+                (9..13, "false"),
+                (24..24, "# C\n\n"),
+                (4..14, "section: true"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_blank_and_whole_source() {
+        check("", &[(0..0, "# A\n\nB"), (0..6, ""), (0..0, "\n\n\nC\n")]);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(content_hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_block_hashes() {
+        let markdown = Markdown::default();
+
+        let mut document = markdown.incremental("A\n\nB\n\nC\n");
+
+        document.replace_range(3..4, "X");
+        document.replace_range(3..4, "B");
+
+        let hashes = document.block_hashes();
+
+        assert_eq!(
+            hashes,
+            markdown.incremental(document.source()).block_hashes()
+        );
+        assert_eq!(
+            hashes.iter().map(|hash| hash.html).collect::<Vec<_>>(),
+            ["<p>A</p>", "<p>B</p>", "<p>C</p>"].map(content_hash)
+        );
+    }
+
+    #[test]
+    fn test_reuse_blocks() {
+        let markdown = Markdown::default();
+
+        let mut document = markdown.incremental("A\n\nB\n\nC\n\nD\n\nE\n");
+
+        document.replace_range(6..7, "X");
+
+        assert_eq!(document.source(), "A\n\nB\n\nX\n\nD\n\nE\n");
+        assert_eq!(
+            document
+                .blocks
+                .iter()
+                .map(|b| b.range.clone())
+                .collect::<Vec<_>>(),
+            vec![0..3, 3..6, 6..9, 9..12, 12..14]
+        );
+        assert_eq!(
+            document.tree(),
+            markdown
+                .parser
+                .parse(document.source(), lex(document.source()))
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! List of figures.
+//!
+//! Figures are `<figure>` elements with a `<figcaption>`, such as blockquotes
+//! with a citation made by
+//! [`Transformer::quote_citation`](crate::layer::transformer::Transformer::quote_citation)
+//! and code blocks with a `caption` in their info string.
+
+use std::collections::HashSet;
+
+use crate::{
+    layer::toc::{config::ListType, TocMaker, UsedIds},
+    model::{html::*, messages::Messages},
+};
+
+/// The struct to make a list of figures.
+#[derive(Debug, Clone)]
+pub struct FigureListMaker {
+    /// The prefix of ids given to figures which do not have one, followed by
+    /// the number of the figure. Default is `figure-`.
+    pub id_prefix: String,
+    /// The type of the list. Default is
+    /// [`ListType::Ordered`](crate::layer::toc::config::ListType).
+    pub list_type: ListType,
+    /// Start the items with [`Messages::figure_label`] and the number of the
+    /// figure, such as `Figure 1: A`. Default is `false`.
+    pub numbered: bool,
+    /// The messages for generated text. Messages set with
+    /// [`Markdown::messages`](crate::Markdown::messages) are used instead of
+    /// these.
+    pub messages: Messages,
+}
+
+/// The struct to represent a figure in the list of figures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Figure {
+    /// The number of the figure, from 1 in document order.
+    pub number: usize,
+    /// The plain text of the caption.
+    pub caption: String,
+    /// The id of the figure, which is the anchor in the list of figures.
+    pub id: String,
+}
+
+impl Default for FigureListMaker {
+    fn default() -> Self {
+        Self {
+            id_prefix: "figure-".to_string(),
+            list_type: ListType::Ordered,
+            numbered: false,
+            messages: Messages::default(),
+        }
+    }
+}
+
+impl FigureListMaker {
+    /// Set the prefix of generated ids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().quote_citation(true))
+    ///     .figure_list_maker(FigureListMaker::default().id_prefix("quote-"));
+    ///
+    /// let (html, _) = markdown.execute_with_figures("> a\n> -- A");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<figure id=\"quote-1\"><blockquote><p>a</p></blockquote><figcaption>A</figcaption></figure>"
+    /// );
+    /// ```
+    pub fn id_prefix(mut self, prefix: &str) -> Self {
+        self.id_prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the type of the list.
+    pub fn list_type(mut self, list_type: ListType) -> Self {
+        self.list_type = list_type;
+        self
+    }
+
+    /// Set whether the items start with the label and the number of the
+    /// figure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().quote_citation(true))
+    ///     .figure_list_maker(FigureListMaker::default().numbered(true))
+    ///     .messages(Messages {
+    ///         figure_label: "Abbildung".to_string(),
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let (_, list) = markdown.execute_with_figures("> a\n> -- A");
+    ///
+    /// assert_eq!(list, "<ol><li><a href=\"#figure-1\">Abbildung 1: A</a></li></ol>");
+    /// ```
+    pub fn numbered(mut self, numbered: bool) -> Self {
+        self.numbered = numbered;
+        self
+    }
+
+    /// Set the messages for generated text.
+    pub fn messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+}
+
+impl FigureListMaker {
+    /// Collect the figures which have a caption in document order, and give
+    /// ids to those which do not have one.
+    ///
+    /// A generated id which is already used in the document gets a suffix.
+    pub fn figures(&self, input: &mut DocumentNode<'_>) -> Vec<Figure> {
+        let mut used = HashSet::new();
+
+        TocMaker::collect_ids(&input.root, &mut used);
+
+        let mut used = UsedIds::from(used);
+        let mut figures = vec![];
+
+        self.collect(&mut input.root, &mut used, &mut figures);
+
+        figures
+    }
+
+    /// Make a list of figures, whose items link to the figures.
+    ///
+    /// The list is empty if there is no figure with a caption.
+    pub fn make_list<'a>(&self, input: &mut DocumentNode<'a>) -> DocumentNode<'a> {
+        self.make_list_with_messages(input, &self.messages)
+    }
+
+    /// Make a list of figures with the messages instead of
+    /// [`messages`](Self::messages).
+    pub(crate) fn make_list_with_messages<'a>(
+        &self,
+        input: &mut DocumentNode<'a>,
+        messages: &Messages,
+    ) -> DocumentNode<'a> {
+        let figures = self.figures(input);
+
+        if figures.is_empty() {
+            return DocumentNode { root: vec![] };
+        }
+
+        let children = figures
+            .into_iter()
+            .map(|figure| {
+                let text = match self.numbered {
+                    true => format!(
+                        "{} {}: {}",
+                        messages.figure_label, figure.number, figure.caption
+                    ),
+                    false => figure.caption,
+                };
+
+                Node::Element(ElementNode {
+                    tag: ElementTag::Li,
+                    children: vec![Node::Element(ElementNode {
+                        tag: ElementTag::A,
+                        href: Some(format!("#{}", figure.id)),
+                        children: vec![Node::Text(TextNode { text: text.into() })],
+                        ..Default::default()
+                    })],
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        DocumentNode {
+            root: vec![Node::Element(ElementNode {
+                tag: self.list_type.to_tag(),
+                children,
+                ..Default::default()
+            })],
+        }
+    }
+
+    fn collect(&self, nodes: &mut [Node<'_>], used: &mut UsedIds, figures: &mut Vec<Figure>) {
+        for node in nodes {
+            let Node::Element(element) = node else {
+                continue;
+            };
+
+            if element.tag == ElementTag::Figure {
+                let caption = element.children.iter().find_map(|child| match child {
+                    Node::Element(child) if child.tag == ElementTag::Figcaption => {
+                        Some(get_text(&child.children))
+                    }
+                    _ => None,
+                });
+
+                if let Some(caption) = caption {
+                    let number = figures.len() + 1;
+
+                    let id = element
+                        .id
+                        .get_or_insert_with(|| used.dedupe(format!("{}{number}", self.id_prefix)));
+
+                    figures.push(Figure {
+                        number,
+                        caption,
+                        id: id.clone(),
+                    });
+                }
+            }
+
+            self.collect(&mut element.children, used, figures);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layer::{lexer::lex, parser::Parser, transformer::Transformer},
+        Markdown,
+    };
+
+    #[test]
+    fn test_figures() {
+        let markdown = Markdown::default().transformer(Transformer::new().quote_citation(true));
+
+        let input = concat![
+            "# Title {#figure-1}\n\n",
+            "> a\n>\n> > b\n> > -- *B*\n>\n> -- A\n\n",
+            "> c\n\n",
+            "> d\n> -- D\n",
+        ];
+
+        let (html, list) = markdown.execute_with_figures(input);
+
+        assert_eq!(
+            html,
+            concat![
+                "<h1 id=\"figure-1\">Title</h1>",
+                "<figure id=\"figure-1-1\"><blockquote><p>a</p>",
+                "<figure id=\"figure-2\"><blockquote><p>b</p></blockquote>",
+                "<figcaption><em>B</em></figcaption></figure></blockquote>",
+                "<figcaption>A</figcaption></figure>",
+                "<blockquote><p>c</p></blockquote>",
+                "<figure id=\"figure-3\"><blockquote><p>d</p></blockquote>",
+                "<figcaption>D</figcaption></figure>",
+            ]
+        );
+        assert_eq!(
+            list,
+            concat![
+                "<ol><li><a href=\"#figure-1-1\">A</a></li><li><a href=\"#figure-2\">B</a></li>",
+                "<li><a href=\"#figure-3\">D</a></li></ol>",
+            ]
+        );
+
+        let (_, list) = markdown.execute_with_figures("> a");
+
+        assert_eq!(list, "");
+
+        let input = "> a\n> -- *A*, 2020";
+        let tree = Parser::default().parse(input, lex(input));
+        let mut document = Transformer::new().quote_citation(true).transform(tree);
+
+        assert_eq!(
+            FigureListMaker::default().figures(&mut document),
+            vec![Figure {
+                number: 1,
+                caption: "A, 2020".to_string(),
+                id: "figure-1".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,761 @@
+//! Stringify MarkdownTree back to markdown.
+//!
+//! This module provides a formatter which writes normalized markdown, so the
+//! crate can be used as an autoformatter.
+
+use std::fmt;
+
+use crate::{
+    layer::parser::config::{IndentStyle, LineBreakStyle, Tables},
+    model::{
+        counter::{Counter, CounterStyle},
+        tree::*,
+    },
+};
+
+use config::*;
+
+/// Stringify MarkdownTree to normalized markdown.
+///
+/// Blocks are separated by one blank line, bullet list items use `-`,
+/// headlines use `#`, and emphasis uses `*`. Line breaks in paragraphs are
+/// kept, since they are hard breaks in note-mark.
+///
+/// Long lines are not wrapped to a width, because every line break in a
+/// paragraph is a hard break and wrapping would change the document.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let markdown = Markdown::default();
+///
+/// let output = markdown.reformat("#   Title\n\n\n\nSome **text**\n\n1. a\n1. b\n  - c");
+///
+/// assert_eq!(output, "# Title\n\nSome **text**\n\n1. a\n2. b\n  - c\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkdownStringifier {
+    /// The indent of nested list items. Default is
+    /// [`IndentStyle::Space(2)`](IndentStyle), which is the default of the
+    /// parser.
+    pub list_indent_style: IndentStyle,
+    /// How to number ordered list items. Default is
+    /// [`OrderedNumbers::Increment`](config::OrderedNumbers).
+    pub ordered_numbers: OrderedNumbers,
+    /// The style of line breaks. Hard breaks are written as `\` at the end
+    /// of the line with [`LineBreakStyle::SoftBreak`]. Default is
+    /// [`LineBreakStyle::HardBreak`], which is the default of the parser.
+    pub line_break_style: LineBreakStyle,
+    /// The parsing of tables. Lines like the delimiter row of a table are
+    /// escaped only if tables are parsed. Default is [`Tables::Off`], which
+    /// is the default of the parser.
+    pub tables: Tables,
+}
+
+pub mod config {
+    //! Configuration options for formatting markdown.
+
+    use std::{fmt, str::FromStr};
+
+    use crate::error::ParseConfigError;
+
+    /// Numbers of ordered list items.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum OrderedNumbers {
+        /// `1.`, `2.`, `3.`, ... from the number of the first item.
+        Increment,
+        /// The number of the first item for all items.
+        Repeat,
+    }
+
+    impl FromStr for OrderedNumbers {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "increment" => Ok(Self::Increment),
+                "repeat" => Ok(Self::Repeat),
+                _ => Err(ParseConfigError::new("OrderedNumbers", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for OrderedNumbers {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Increment => f.write_str("increment"),
+                Self::Repeat => f.write_str("repeat"),
+            }
+        }
+    }
+}
+
+impl Default for MarkdownStringifier {
+    fn default() -> Self {
+        Self {
+            list_indent_style: IndentStyle::Space(2),
+            ordered_numbers: OrderedNumbers::Increment,
+            line_break_style: LineBreakStyle::HardBreak,
+            tables: Tables::Off,
+        }
+    }
+}
+
+/// Write the tree as markdown with the default [`MarkdownStringifier`].
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{model::tree::*, prelude::*};
+///
+/// let tree = MarkdownTree::from("#   Title\n\n\n*a*");
+///
+/// assert_eq!(tree.to_string(), "# Title\n\n*a*\n");
+/// ```
+impl fmt::Display for MarkdownTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&MarkdownStringifier::new().stringify(self))
+    }
+}
+
+impl MarkdownStringifier {
+    /// Create a new MarkdownStringifier.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the indent of nested list items.
+    ///
+    /// This should be the same as the indent style of the parser.
+    pub fn list_indent_style(mut self, style: IndentStyle) -> Self {
+        self.list_indent_style = style;
+        self
+    }
+
+    /// Set how to number ordered list items.
+    pub fn ordered_numbers(mut self, numbers: OrderedNumbers) -> Self {
+        self.ordered_numbers = numbers;
+        self
+    }
+
+    /// Set the style of line breaks.
+    ///
+    /// This should be the same as the line break style of the parser.
+    pub fn line_break_style(mut self, style: LineBreakStyle) -> Self {
+        self.line_break_style = style;
+        self
+    }
+
+    /// Set the parsing of tables.
+    ///
+    /// This should be the same as the parsing of tables of the parser.
+    pub fn tables(mut self, tables: Tables) -> Self {
+        self.tables = tables;
+        self
+    }
+}
+
+impl MarkdownStringifier {
+    /// Stringify MarkdownTree to markdown.
+    pub fn stringify(&self, tree: &MarkdownTree) -> String {
+        let mut output = self.blocks(&tree.root.root);
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn blocks(&self, items: &[BlockItem]) -> String {
+        items
+            .iter()
+            .map(|item| self.block(item))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn block(&self, item: &BlockItem) -> String {
+        match item {
+            BlockItem::Paragraph(tree) => self.text(tree),
+            BlockItem::Headline(level, tree) => {
+                format!("{} {}", "#".repeat(*level as usize), self.text(tree))
+            }
+            BlockItem::BulletList(tree) => self.list(tree, |_| "-".to_string()),
+            BlockItem::OrderedList(start, marker, tree) => self.list(tree, |index| {
+                let number = match self.ordered_numbers {
+                    OrderedNumbers::Increment => start.saturating_add(index as u64),
+                    OrderedNumbers::Repeat => *start,
+                };
+
+                let number = match marker.counter {
+                    Counter::Decimal => number.to_string(),
+                    counter => counter.format(usize::try_from(number).unwrap_or(usize::MAX)),
+                };
+
+                format!("{number}{}", marker.delimiter.char())
+            }),
+            BlockItem::BlockQuote(tree) => Self::prefix_lines(&self.blocks(&tree.root), "> "),
+            BlockItem::Container(names, tree) => {
+                format!("::: {}\n{}\n:::", names.join(" "), self.blocks(&tree.root))
+            }
+            BlockItem::Attributed(attributes, item) => {
+                // Attributes of headlines are in the same line, those of
+                // paragraphs are in the last line, and those of the other
+                // blocks are in the next paragraph.
+                let separator = match **item {
+                    BlockItem::Headline(..) => " ",
+                    BlockItem::Paragraph(_) => "\n",
+                    _ => "\n\n",
+                };
+
+                format!(
+                    "{}{separator}{}",
+                    self.block(item),
+                    Self::attributes(attributes)
+                )
+            }
+            BlockItem::Comment(text) => format!("<!--{text}-->"),
+            BlockItem::Include(target, _) => format!("![[{target}]]"),
+            BlockItem::Abbreviation(abbreviation, expansion) => {
+                format!("*[{abbreviation}]: {expansion}")
+            }
+            BlockItem::Table(table) => self.table(table),
+            BlockItem::CodeBlock(code) => Self::code_block(code),
+        }
+    }
+
+    /// Stringify a fenced code block. The fence is longer than any run of its
+    /// character in the code, and is made of tildes if the info string has a
+    /// backquote.
+    fn code_block(code: &CodeBlock) -> String {
+        let mut info = code.lang.as_deref().unwrap_or_default().to_string();
+
+        for (key, value) in &code.info {
+            let first = info.is_empty();
+
+            if !first {
+                info.push(',');
+            }
+
+            info.push_str(key);
+
+            // A first word without a value would be the language.
+            if value.contains([',', ' ', '\t']) || (value.is_empty() && first) {
+                info.push_str(&format!("=\"{value}\""));
+            } else if !value.is_empty() {
+                info.push('=');
+                info.push_str(value);
+            }
+        }
+
+        let char = if info.contains('`') { '~' } else { '`' };
+
+        let longest = code
+            .text
+            .split(|c| c != char)
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+
+        let fence = char.to_string().repeat(longest.max(2) + 1);
+        let newline = if code.text.is_empty() || code.text.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+
+        format!("{fence}{info}\n{}{newline}{fence}", code.text)
+    }
+
+    /// Stringify a table with leading and trailing pipes, so that it is
+    /// parsed back in every mode of [`Parser::tables`](crate::layer::parser::Parser::tables).
+    fn table(&self, table: &TableTree) -> String {
+        let row = |cells: &[TableCell]| {
+            let mut output = "|".to_string();
+
+            for cell in cells {
+                match cell {
+                    TableCell::Cell { tree, .. } => {
+                        output.push(' ');
+                        output.push_str(&self.inline(tree));
+                        output.push_str(" |");
+                    }
+                    TableCell::MergedLeft => output.push('|'),
+                    TableCell::MergedUp => output.push_str(" ^^ |"),
+                }
+            }
+
+            output
+        };
+
+        let delimiter = table
+            .aligns
+            .iter()
+            .map(|align| match align {
+                Alignment::None => "---",
+                Alignment::Left => ":--",
+                Alignment::Center => ":-:",
+                Alignment::Right => "--:",
+            })
+            .collect::<Vec<_>>();
+
+        let mut lines = vec![row(&table.head), format!("| {} |", delimiter.join(" | "))];
+
+        lines.extend(table.rows.iter().map(|cells| row(cells)));
+
+        lines.join("\n")
+    }
+
+    fn list(&self, tree: &ListTree, marker: impl Fn(usize) -> String) -> String {
+        let indent = match self.list_indent_style {
+            IndentStyle::Space(width) => " ".repeat(width as usize),
+            _ => "\t".to_string(),
+        };
+
+        tree.root
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let name = self.text(&item.name);
+                let mut children = item.children.iter();
+
+                // An item without a name starts with its first child, which is
+                // indented after the marker so that it is parsed as a block.
+                // An empty item keeps the space after the marker, since `-`
+                // alone is not a list item.
+                let mut output = match (name.is_empty(), item.children.first()) {
+                    (true, Some(first)) => {
+                        children.next();
+
+                        format!(
+                            "{} {}",
+                            marker(index),
+                            Self::prefix_lines(&self.block(first), &indent)
+                        )
+                    }
+                    _ => format!("{} {}", marker(index), name),
+                };
+
+                // Children are separated by blank lines like top level blocks,
+                // so that a paragraph does not continue into the next child.
+                // A child after the name is put on the next line.
+                let mut blank = name.is_empty();
+
+                for child in children {
+                    if blank {
+                        output.push('\n');
+                    }
+
+                    output.push('\n');
+                    output.push_str(&Self::prefix_lines(&self.block(child), &indent));
+
+                    blank = true;
+                }
+
+                output
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Stringify inline items of a block. White spaces at the start of lines
+    /// are removed, since they are not significant. A line which would then
+    /// start a block, such as ` - a` continuing a list item, is escaped.
+    ///
+    /// A line like the delimiter row of a table is escaped too if tables are
+    /// parsed and the line before it has a `|`, since they may then be a
+    /// table. Other lines such as `---` are kept, which smart punctuation
+    /// makes a dash.
+    fn text(&self, tree: &InlineTree) -> String {
+        let mut piped = false;
+
+        self.inline(tree)
+            .split('\n')
+            .map(|line| {
+                let trimmed = line.trim_start();
+
+                let output = if piped && is_delimiter_row(trimmed) {
+                    format!("\\{trimmed}")
+                } else if trimmed.len() == line.len() {
+                    trimmed.to_string()
+                } else {
+                    escape_block_start(trimmed)
+                };
+
+                piped = self.tables != Tables::Off && line.contains('|');
+
+                output
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn inline(&self, tree: &InlineTree) -> String {
+        let mut line_start = true;
+
+        tree.root
+            .iter()
+            .map(|item| {
+                let output = match item {
+                    InlineItem::Text(text) => escape_stars(text, line_start),
+                    item => self.inline_item(item),
+                };
+
+                line_start = matches!(item, InlineItem::Break | InlineItem::SoftBreak);
+
+                output
+            })
+            .collect()
+    }
+
+    fn inline_item(&self, item: &InlineItem) -> String {
+        match item {
+            InlineItem::Text(text) => escape_stars(text, true),
+            InlineItem::Escaped(text) => format!("\\{text}"),
+            InlineItem::Italic(tree) => format!("*{}*", self.inline(tree)),
+            InlineItem::Strong(tree) => format!("**{}**", self.inline(tree)),
+            InlineItem::Highlight(tree) => format!("=={}==", self.inline(tree)),
+            InlineItem::Footnote(tree) => format!("^[{}]", self.inline(tree)),
+            InlineItem::Link { url, title, tree } => {
+                // Parentheses are escaped only if they do not balance.
+                let balanced = url.chars().try_fold(0usize, |depth, c| match c {
+                    '(' => Some(depth + 1),
+                    ')' => depth.checked_sub(1),
+                    _ => Some(depth),
+                }) == Some(0);
+
+                let url = escape(url, if balanced { "" } else { "()" });
+
+                match title {
+                    Some(title) => {
+                        format!("[{}]({url} \"{}\")", self.inline(tree), escape(title, "\""))
+                    }
+                    None => format!("[{}]({url})", self.inline(tree)),
+                }
+            }
+            InlineItem::Span(tree) => format!("[{}]", self.inline(tree)),
+            InlineItem::Attributed(attributes, item) => {
+                format!("{}{}", self.inline_item(item), Self::attributes(attributes))
+            }
+            InlineItem::WikiLink { target, label } => match label {
+                Some(label) => format!("[[{}|{}]]", escape(target, "[]|"), escape(label, "[]")),
+                None => format!("[[{}]]", escape(target, "[]|")),
+            },
+            InlineItem::Tag { kind, name } => format!("{}{name}", kind.marker()),
+            InlineItem::Break => match self.line_break_style {
+                LineBreakStyle::SoftBreak => "\\\n".to_string(),
+                _ => "\n".to_string(),
+            },
+            InlineItem::SoftBreak => "\n".to_string(),
+            InlineItem::Comment(text) => format!("<!--{text}-->"),
+        }
+    }
+
+    fn attributes(attributes: &Attributes) -> String {
+        let id = attributes.id.iter().map(|id| format!("#{id}"));
+
+        let classes = attributes.classes.iter().map(|class| format!(".{class}"));
+
+        let attrs = attributes.attrs.iter().map(|(key, value)| {
+            if value.is_empty() || value.contains(char::is_whitespace) {
+                format!("{key}=\"{value}\"")
+            } else {
+                format!("{key}={value}")
+            }
+        });
+
+        format!(
+            "{{{}}}",
+            id.chain(classes).chain(attrs).collect::<Vec<_>>().join(" ")
+        )
+    }
+
+    /// Put the prefix before each line. Blank lines get the prefix without
+    /// trailing white spaces.
+    fn prefix_lines(input: &str, prefix: &str) -> String {
+        input
+            .split('\n')
+            .map(|line| match line {
+                "" => prefix.trim_end().to_string(),
+                _ => format!("{prefix}{line}"),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Escape runs of `*` in a text, which were not emphasis but may be paired
+/// when the text around them is written differently. A run between white
+/// spaces, such as in `2 * 3`, is kept since it cannot be emphasis.
+///
+/// White spaces at the start of a line are removed, so they do not count if
+/// the text is at `line_start`.
+fn escape_stars(text: &str, line_start: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut index = 0;
+
+    while let Some(offset) = text[index..].find('*') {
+        let start = index + offset;
+        let end = text.len() - text[start..].trim_start_matches('*').len();
+
+        let before = &text[..start];
+        let kept = before.ends_with(char::is_whitespace)
+            && !(line_start && before.trim_start().is_empty())
+            && text[end..].starts_with(char::is_whitespace);
+
+        output.push_str(&text[index..start]);
+
+        if kept {
+            output.push_str(&text[start..end]);
+        } else {
+            output.push_str(&"\\*".repeat(end - start));
+        }
+
+        index = end;
+    }
+
+    output.push_str(&text[index..]);
+    output
+}
+
+/// Check if the line may be the delimiter row of a table, such as `|:-|--:|`.
+fn is_delimiter_row(line: &str) -> bool {
+    line.contains('-')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | ':' | '-' | ' ' | '\t'))
+}
+
+/// Escape the marker of a line which starts a block: a headline, a
+/// blockquote, a bullet or ordered list item, or a fenced code block.
+fn escape_block_start(line: &str) -> String {
+    let pounds = line.len() - line.trim_start_matches('#').len();
+
+    if (1..=6).contains(&pounds) && line[pounds..].starts_with(' ')
+        || line.starts_with('>')
+        || line.starts_with("- ")
+        || line.starts_with("```")
+        || line.starts_with("~~~")
+    {
+        return format!("\\{line}");
+    }
+
+    // Ordered list markers are numbers of up to 9 digits, or letters and
+    // roman numerals of fancy lists.
+    let number = line
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(line.len());
+
+    let (number, rest) = line.split_at(number);
+
+    let is_number = number.len() <= 9 && number.chars().all(|c| c.is_ascii_digit())
+        || number.len() == 1
+        || number.chars().all(|c| "ivxlcdmIVXLCDM".contains(c));
+
+    if !number.is_empty() && is_number && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return format!("{number}\\{rest}");
+    }
+
+    line.to_string()
+}
+
+/// Escape the characters, and backslashes which would escape the next
+/// character.
+fn escape(text: &str, chars: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text.chars().peekable();
+
+    while let Some(c) = rest.next() {
+        let escaped = match c {
+            '\\' => match rest.peek() {
+                Some(next) => next.is_ascii_punctuation(),
+                None => true,
+            },
+            c => chars.contains(c),
+        };
+
+        if escaped {
+            output.push('\\');
+        }
+
+        output.push(c);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layer::{
+            lexer::lex,
+            parser::{config::Tables, Parser},
+        },
+        Markdown,
+    };
+
+    /// Check that the output parses to the same tree, and formatting it again
+    /// does not change it.
+    fn check(markdown: &Markdown, input: &str) -> String {
+        let output = markdown.reformat(input);
+
+        let parse = |input: &str| markdown.parser.parse(input, lex(input)).into_owned();
+
+        assert_eq!(parse(&output), parse(input), "output: {output:?}");
+        assert_eq!(markdown.reformat(&output), output);
+
+        output
+    }
+
+    #[test]
+    fn test_reformat() {
+        let markdown = Markdown::default();
+
+        let output = check(
+            &markdown,
+            concat![
+                "\n\n##  Hello *World* {.big   #top key=\"a b\"}\n\n\n",
+                "Line\\* one\n",
+                "line **two**\n\n",
+                "3. three\n",
+                "7. four\n",
+                "  - nested\n",
+                "    1. deep\n\n",
+                "> quote\n",
+                ">\n",
+                "> - a\n",
+                ">   - b\n",
+                "> > deeper\n",
+                "\n",
+                "End\n",
+            ],
+        );
+
+        assert_eq!(
+            output,
+            concat![
+                "## Hello *World* {#top .big key=\"a b\"}\n\n",
+                "Line\\* one\n",
+                "line **two**\n\n",
+                "3. three\n",
+                "4. four\n",
+                "  - nested\n",
+                "    1. deep\n\n",
+                "> quote\n",
+                ">\n",
+                "> - a\n",
+                ">   - b\n",
+                ">\n",
+                "> > deeper\n\n",
+                "End\n",
+            ]
+        );
+
+        assert_eq!(
+            check(&markdown, "- a\n\n  b\n\n  c\n  - d\n-   # e\n\n  f"),
+            "- a\n  b\n\n  c\n  - d\n-   # e\n\n  f\n"
+        );
+        assert_eq!(check(&markdown, "-   # b"), "-   # b\n");
+        assert_eq!(check(&markdown, "1.   # b\n  c"), "1.   # b\n  c\n");
+        assert_eq!(
+            check(&markdown, "- a\n\n-   # b\n  c"),
+            "- a\n-   # b\n  c\n"
+        );
+        assert_eq!(markdown.reformat("1. \n {.x}-d e"), "1.   {.x}-d e\n");
+        assert_eq!(check(&markdown, "1.   {.x}-d e"), "1.   {.x}-d e\n");
+
+        assert_eq!(
+            check(&markdown, "[*a*  [b]](/x_(1)   \"T t\" ) [c]( d )"),
+            "[*a*  [b]](/x_(1) \"T t\") [c](d)\n"
+        );
+
+        // Text which would be read differently after white spaces are removed
+        // or emphasis is written next to it is escaped.
+        assert_eq!(markdown.reformat("- a\n - b"), "- a\n\\- b\n");
+        assert_eq!(markdown.reformat("a\n  > b"), "a\n\\> b\n");
+        assert_eq!(markdown.reformat("a|b\n:-|-"), "a|b\n:-|-\n");
+        assert_eq!(markdown.reformat("a\n---\nb"), "a\n---\nb\n");
+        assert_eq!(markdown.reformat("a*b 2 * 3"), "a\\*b 2 * 3\n");
+        assert_eq!(markdown.reformat("**a*"), "\\**a*\n");
+
+        assert_eq!(
+            check(
+                &markdown,
+                "~~~ rust  linenos,caption=\"A, b\"\n```\n\n  x\n~~~~\n{.y}"
+            ),
+            "````rust,linenos,caption=\"A, b\"\n```\n\n  x\n````\n\n{.y}\n"
+        );
+        assert_eq!(
+            check(&markdown, "- ```a`=1\n\n  ``` x=1,y\n  > b\n  ```"),
+            "- ```a`=1\n  ```x=1,y\n  > b\n  ```\n"
+        );
+        assert_eq!(check(&markdown, "```\n```"), "```\n```\n");
+        assert_eq!(markdown.reformat("a\n ~~~"), "a\n\\~~~\n");
+
+        assert_eq!(markdown.reformat(""), "");
+        assert_eq!(markdown.reformat("\n\n"), "");
+        assert_eq!(markdown.reformat(" \t\n"), "");
+    }
+
+    #[test]
+    fn test_reformat_config() {
+        let markdown = Markdown::default()
+            .parser(Parser::default().list_indent_style(IndentStyle::Tab))
+            .markdown_stringifier(
+                MarkdownStringifier::new()
+                    .list_indent_style(IndentStyle::Tab)
+                    .ordered_numbers(OrderedNumbers::Repeat),
+            );
+
+        let output = check(&markdown, "1. a\n2. b\n\t- c\n3. d");
+
+        assert_eq!(output, "1. a\n1. b\n\t- c\n1. d\n");
+
+        let markdown = Markdown::default()
+            .parser(Parser::default().line_break_style(LineBreakStyle::SoftBreak))
+            .markdown_stringifier(
+                MarkdownStringifier::new().line_break_style(LineBreakStyle::SoftBreak),
+            );
+
+        let output = check(&markdown, "a  \nb\nc\\\n\n- d   \n  e\\\\\n  f");
+
+        assert_eq!(output, "a\\\nb\nc\\\n\n- d\n  e\\\\\n  f\n");
+
+        let markdown = Markdown::default().parser(Parser::default().fancy_lists(true));
+
+        let output = check(&markdown, "y) a\nz) b\nC. c\n\niii. d\n\n7) e");
+
+        assert_eq!(output, "y) a\nz) b\n\nC. c\n\niii. d\n\n7) e\n");
+
+        let markdown = Markdown::default().parser(Parser::default().tables(Tables::Optional));
+
+        let output = check(&markdown, "a|b\n:-:|--:\n\\|  |*c*\n> d");
+
+        assert_eq!(output, "| a | b |\n| :-: | --: |\n| \\| | *c* |\n\n> d\n");
+
+        let markdown =
+            markdown.markdown_stringifier(MarkdownStringifier::new().tables(Tables::Optional));
+
+        assert_eq!(markdown.reformat("a|b|c\n:-|-"), "a|b|c\n\\:-|-\n");
+        assert_eq!(check(&markdown, "a\n:-|-"), "a\n:-|-\n");
+
+        let markdown = Markdown::default()
+            .parser(Parser::default().tables(Tables::Optional).table_spans(true));
+
+        let output = check(&markdown, "a||b\n-|-|-\nc|d|e\n^^|f||");
+
+        assert_eq!(
+            output,
+            "| a || b |\n| --- | --- | --- |\n| c | d | e |\n| ^^ | f ||\n"
+        );
+
+        for numbers in [OrderedNumbers::Increment, OrderedNumbers::Repeat] {
+            assert_eq!(numbers.to_string().parse(), Ok(numbers));
+        }
+    }
+}
@@ -1,149 +1,178 @@
 //! Lexer for the Markdown syntax.
 //!
-//! This module contains the lexer for the Markdown syntax. At this stage, the
-//! text is joined and non-breakable spaces are removed.
+//! This module contains the lexer for the Markdown syntax. At this stage, runs
+//! of text are scanned into single tokens and non-breakable spaces are
+//! removed.
 
 use peekmore::{PeekMore, PeekMoreIterator};
-use std::{iter::Peekable, str::CharIndices};
 
 use crate::model::token::*;
 
 /// Split a string into tokens.
 ///
-/// This returns an iterator that yields tokens. This iterator has static
-/// lifetime, though tokens refer to the input string.
+/// This returns a lazy iterator that yields tokens. It reads the input only
+/// as far as the next token, and looks further ahead only over white spaces.
+/// Tokens do not borrow the input, but their ranges refer to it.
+///
+/// The token stream keeps these rules:
+///
+/// - Tokens are in order and do not overlap.
+/// - Every byte of the input is in a token, except spaces and tabs of lines
+///   which have only white spaces.
+/// - Adjacent text is joined into one [`TokenKind::Text`] token.
+/// - `\n` and `\r\n` are one [`TokenKind::Break`] token.
+/// - A backslash and the ASCII punctuation character after it are one
+///   [`TokenKind::Escaped`] token, as in CommonMark. Other backslashes are
+///   text.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let input = "# Hi\\*\n  \nok";
+///
+/// let tokens = lex(input)
+///     .map(|token| (token.kind, &input[token.range()]))
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         (TokenKind::Pound, "#"),
+///         (TokenKind::Space, " "),
+///         (TokenKind::Text, "Hi"),
+///         (TokenKind::Escaped, "\\*"),
+///         (TokenKind::Break, "\n"),
+///         (TokenKind::Break, "\n"),
+///         (TokenKind::Text, "ok"),
+///     ]
+/// );
+/// ```
 pub fn lex(input: &'_ str) -> impl Iterator<Item = Token> + '_ {
-    let lexer = Lexer::new(input);
-    let lexer = TextJoiner::new(lexer);
-    SpaceCutter::new(lexer)
+    SpaceCutter::new(Lexer::new(input))
 }
 
 struct Lexer<'a> {
-    chars: Peekable<CharIndices<'a>>,
+    input: &'a [u8],
+    index: usize,
 }
 
 impl<'a> Lexer<'a> {
     fn new(input: &'a str) -> Self {
         Self {
-            chars: input.char_indices().peekable(),
+            input: input.as_bytes(),
+            index: 0,
         }
     }
-}
-
-impl<'a> Iterator for Lexer<'a> {
-    type Item = Token;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let (kind, start, len) = if let Some((index, c)) = self.chars.next() {
-            let len = c.len_utf8();
-
-            let (kind, len) = match c {
-                '#' => (TokenKind::Pound, len),
-                '*' => (TokenKind::Star, len),
-                ':' => (TokenKind::Colon, len),
-                '`' => (TokenKind::Backquote, len),
-                '>' => (TokenKind::Gt, len),
-                '-' => (TokenKind::Hyphen, len),
-                '|' => (TokenKind::VerticalBar, len),
-                '.' => (TokenKind::Dot, len),
-                '(' => (TokenKind::OpenParen, len),
-                ')' => (TokenKind::CloseParen, len),
-                '{' => (TokenKind::OpenBrace, len),
-                '}' => (TokenKind::CloseBrace, len),
-                '[' => (TokenKind::OpenBracket, len),
-                ']' => (TokenKind::CloseBracket, len),
-                ' ' => (TokenKind::Space, len),
-                '\t' => (TokenKind::Tab, len),
-                '\n' => (TokenKind::Break, len),
-                '\r' => {
-                    if let Some((_, c2)) = self.chars.next_if(|(_, c2)| c2 == &'\n') {
-                        (TokenKind::Break, len + c2.len_utf8())
-                    } else {
-                        (TokenKind::Text, len)
-                    }
-                }
-                '\\' => {
-                    if let Some((_, c2)) = self.chars.next_if(|(_, c2)| {
-                        matches!(
-                            c2,
-                            '#' | '*'
-                                | ':'
-                                | '`'
-                                | '>'
-                                | '-'
-                                | '|'
-                                | '.'
-                                | '('
-                                | ')'
-                                | '{'
-                                | '}'
-                                | '['
-                                | ']'
-                                | '\\'
-                        )
-                    }) {
-                        return Some(Token {
-                            kind: TokenKind::Text,
-                            start: index + len,
-                            len: c2.len_utf8(),
-                        });
-                    } else {
-                        (TokenKind::Text, len)
-                    }
-                }
-                _ => (TokenKind::Text, len),
-            };
-
-            (kind, index, len)
-        } else {
-            return None;
+    /// Get the kind of a byte which is always a token by itself.
+    fn special(byte: u8) -> Option<TokenKind> {
+        let kind = match byte {
+            b'#' => TokenKind::Pound,
+            b'*' => TokenKind::Star,
+            b':' => TokenKind::Colon,
+            b'`' => TokenKind::Backquote,
+            b'>' => TokenKind::Gt,
+            b'-' => TokenKind::Hyphen,
+            b'|' => TokenKind::VerticalBar,
+            b'.' => TokenKind::Dot,
+            b'(' => TokenKind::OpenParen,
+            b')' => TokenKind::CloseParen,
+            b'{' => TokenKind::OpenBrace,
+            b'}' => TokenKind::CloseBrace,
+            b'[' => TokenKind::OpenBracket,
+            b']' => TokenKind::CloseBracket,
+            b'!' => TokenKind::Exclamation,
+            b'~' => TokenKind::Tilde,
+            b'=' => TokenKind::Equal,
+            b'_' => TokenKind::Underscore,
+            b'<' => TokenKind::Lt,
+            b'+' => TokenKind::Plus,
+            b'$' => TokenKind::Dollar,
+            b'^' => TokenKind::Caret,
+            b' ' => TokenKind::Space,
+            b'\t' => TokenKind::Tab,
+            b'\n' => TokenKind::Break,
+            _ => return None,
         };
 
-        Some(Token { kind, start, len })
+        Some(kind)
     }
-}
 
-struct TextJoiner<T: Iterator<Item = Token>> {
-    iter: Peekable<T>,
-}
+    /// Get the kind and the length of the token which is not text at the
+    /// index, such as `\r\n` and an escaped character.
+    fn token_at(&self, index: usize) -> Option<(TokenKind, usize)> {
+        let byte = *self.input.get(index)?;
+        let next = self.input.get(index + 1);
 
-impl<T: Iterator<Item = Token>> TextJoiner<T> {
-    fn new(iter: T) -> Self {
-        Self {
-            iter: iter.peekable(),
+        match byte {
+            b'\r' if next == Some(&b'\n') => Some((TokenKind::Break, 2)),
+            b'\\' if next.is_some_and(u8::is_ascii_punctuation) => Some((TokenKind::Escaped, 2)),
+            _ => Self::special(byte).map(|kind| (kind, 1)),
         }
     }
 }
 
-impl<T: Iterator<Item = Token>> Iterator for TextJoiner<T> {
+impl<'a> Iterator for Lexer<'a> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut token = self.iter.next()?;
-
-        if token.kind == TokenKind::Text {
-            while let Some(next) = self.iter.peek() {
-                if next.kind == TokenKind::Text {
-                    token.len += next.len;
-                    self.iter.next();
-                } else {
-                    break;
-                }
-            }
+        let start = self.index;
+
+        if start >= self.input.len() {
+            return None;
         }
 
-        Some(token)
+        let (kind, len) = match self.token_at(start) {
+            Some(token) => token,
+            None => {
+                // A run of text ends at the next token. Bytes of multibyte
+                // characters are never ASCII, so they are scanned as text.
+                let end = (start + 1..self.input.len())
+                    .find(|&index| self.token_at(index).is_some())
+                    .unwrap_or(self.input.len());
+
+                (TokenKind::Text, end - start)
+            }
+        };
+
+        self.index += len;
+
+        Some(Token { kind, start, len })
     }
 }
 
+/// The iterator which drops white spaces of lines which have nothing else, so
+/// that they are blank lines.
 struct SpaceCutter<T: Iterator<Item = Token>> {
     iter: PeekMoreIterator<T>,
+    /// Whether the first line has been checked.
+    started: bool,
 }
 
 impl<T: Iterator<Item = Token>> SpaceCutter<T> {
     fn new(iter: T) -> Self {
         Self {
             iter: iter.peekmore(),
+            started: false,
+        }
+    }
+
+    /// Skip the white spaces at the start of a line if nothing follows them.
+    fn cut_blank_line(&mut self) {
+        let mut n = 0;
+
+        while let Some(nth) = self.iter.peek_nth(n) {
+            match nth.kind {
+                TokenKind::Space | TokenKind::Tab => n += 1,
+                TokenKind::Break => break,
+                _ => return,
+            }
+        }
+
+        if n > 0 {
+            self.iter.nth(n - 1);
         }
     }
 }
@@ -152,29 +181,15 @@ impl<T: Iterator<Item = Token>> Iterator for SpaceCutter<T> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            self.cut_blank_line();
+        }
+
         let token = self.iter.next()?;
 
-        use TokenKind::*;
-
-        if token.kind == Break
-            && self.iter.peek().is_some()
-            && self.iter.peek().unwrap().kind != Break
-        {
-            for n in 0.. {
-                if let Some(nth) = self.iter.peek_nth(n) {
-                    match nth.kind {
-                        Space | Tab => continue,
-                        Break => {
-                            self.iter.nth(n - 1).unwrap();
-                            break;
-                        }
-                        _ => break,
-                    }
-                } else {
-                    self.iter.nth(n - 1).unwrap();
-                    break;
-                }
-            }
+        if token.kind == TokenKind::Break {
+            self.cut_blank_line();
         }
 
         Some(token)
@@ -192,11 +207,14 @@ mod tests {
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Pound);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Pound);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Space);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
+        assert_eq!(
+            lexer.next().unwrap(),
+            Token {
+                kind: TokenKind::Text,
+                start: 3,
+                len: 5
+            }
+        );
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Break);
         assert_eq!(lexer.next(), None);
 
@@ -212,58 +230,97 @@ mod tests {
 
         let mut lexer = Lexer::new(r"\# Q");
 
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
+        assert_eq!(
+            lexer.next().unwrap(),
+            Token {
+                kind: TokenKind::Escaped,
+                start: 0,
+                len: 2
+            }
+        );
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Space);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
         assert_eq!(lexer.next(), None);
 
-        let mut lexer = Lexer::new("あああ");
+        let mut lexer = Lexer::new(r"\a\\");
 
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next(), None);
-    }
-
-    #[test]
-    fn test_text_jointer() {
-        let mut lexer = TextJoiner::new(Lexer::new("## Hello Q\n"));
-
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Pound);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Pound);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Space);
         assert_eq!(
             lexer.next().unwrap(),
             Token {
                 kind: TokenKind::Text,
-                start: 3,
-                len: 5
+                start: 0,
+                len: 2
             }
         );
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Space);
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Escaped);
+        assert_eq!(lexer.next(), None);
+
+        let kinds = Lexer::new(r"!~=_<+$^\_").map(|token| token.kind);
+
+        assert_eq!(
+            kinds.collect::<Vec<_>>(),
+            vec![
+                TokenKind::Exclamation,
+                TokenKind::Tilde,
+                TokenKind::Equal,
+                TokenKind::Underscore,
+                TokenKind::Lt,
+                TokenKind::Plus,
+                TokenKind::Dollar,
+                TokenKind::Caret,
+                TokenKind::Escaped,
+            ]
+        );
+
+        let mut lexer = Lexer::new("あああ\ra\r\n");
+
         assert_eq!(
             lexer.next().unwrap(),
             Token {
                 kind: TokenKind::Text,
-                start: 9,
-                len: 1
+                start: 0,
+                len: 11
             }
         );
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Break);
         assert_eq!(lexer.next(), None);
     }
 
+    #[test]
+    fn test_lex_contract() {
+        let input = "- a \\b\\ c\r\n \t \n\n  1. *x* [y](z)\t\nあ\\";
+
+        let mut end = 0;
+
+        for token in lex(input) {
+            // Only white spaces of blank lines are skipped.
+            assert!(input[end..token.start].trim_matches([' ', '\t']).is_empty());
+            assert!(token.start >= end && token.len > 0);
+
+            end = token.start + token.len;
+        }
+
+        assert_eq!(end, input.len());
+    }
+
     #[test]
     fn test_space_cutter() {
         let mut lexer = SpaceCutter::new(Lexer::new("ABC\n  \nDEF"));
 
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Break);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Break);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
+        assert_eq!(lexer.next(), None);
+
+        let mut lexer = SpaceCutter::new(Lexer::new(" \t\nA"));
+
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Break);
         assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
-        assert_eq!(lexer.next().unwrap().kind, TokenKind::Text);
+        assert_eq!(lexer.next(), None);
+
+        let mut lexer = SpaceCutter::new(Lexer::new("  A"));
+
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Space);
     }
 }
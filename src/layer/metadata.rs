@@ -0,0 +1,674 @@
+//! Metadata of documents.
+//!
+//! This module provides front matter, title and excerpt extraction.
+
+use std::borrow::Cow;
+
+use crate::model::{html::*, stats::word_ends};
+
+use config::*;
+
+/// The struct to represent front matter at the start of a document.
+///
+/// Front matter is enclosed by `---` lines. Only `key: value` lines are read,
+/// and quotes around a value are removed. Other lines such as nested values
+/// are ignored.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let input = "---\ntitle: \"Hello\"\ntags: a, b\n---\n# Body\n";
+///
+/// let (front_matter, len) = FrontMatter::parse(input).unwrap();
+///
+/// assert_eq!(front_matter.get("title"), Some("Hello"));
+/// assert_eq!(front_matter.get("tags"), Some("a, b"));
+/// assert_eq!(&input[len..], "# Body\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter<'a> {
+    /// The source between the `---` lines.
+    pub raw: &'a str,
+    /// The `key: value` entries in order.
+    pub entries: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> FrontMatter<'a> {
+    /// Parse front matter at the start of the input.
+    ///
+    /// This returns the front matter and the length of it in bytes, including
+    /// the `---` lines.
+    pub fn parse(input: &'a str) -> Option<(Self, usize)> {
+        let mut lines = input.split_inclusive('\n');
+
+        if lines.next()?.trim_end() != "---" {
+            return None;
+        }
+
+        let start = input.find('\n')? + 1;
+        let mut offset = start;
+
+        for line in lines {
+            if matches!(line.trim_end(), "---" | "...") {
+                let raw = &input[start..offset];
+
+                let front_matter = Self {
+                    raw,
+                    entries: raw.lines().filter_map(Self::entry).collect(),
+                };
+
+                return Some((front_matter, offset + line.len()));
+            }
+
+            offset += line.len();
+        }
+
+        None
+    }
+
+    /// Get the value of the key.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.entries
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| *value)
+    }
+
+    /// Parse a `key: value` line.
+    fn entry(line: &'a str) -> Option<(&'a str, &'a str)> {
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            return None;
+        }
+
+        let (key, value) = line.split_once(':')?;
+
+        let value = value.trim();
+
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(value);
+
+        Some((key.trim(), value))
+    }
+}
+
+/// The struct to extract metadata from a document.
+#[derive(Debug, Clone)]
+pub struct MetadataExtractor {
+    /// The sources of the title, tried in order. Default is front matter, the
+    /// first `h1`, the first sentence, and the file name.
+    pub title_chain: Vec<TitleSource>,
+    /// Whether to remove the headline which the title is taken from, so the
+    /// title is not repeated in the body. Default is false.
+    pub drop_title_headline: bool,
+}
+
+pub mod config {
+    //! Configuration options for metadata.
+
+    use std::{fmt, str::FromStr};
+
+    use crate::error::ParseConfigError;
+
+    /// The source of the title of a document.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TitleSource {
+        /// The `title` key of front matter.
+        FrontMatter,
+        /// The first `h1` headline.
+        Headline,
+        /// The first sentence of the first paragraph.
+        FirstSentence,
+        /// The file name given by the caller, without the extension.
+        Filename,
+    }
+
+    impl FromStr for TitleSource {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "front-matter" => Ok(Self::FrontMatter),
+                "headline" => Ok(Self::Headline),
+                "first-sentence" => Ok(Self::FirstSentence),
+                "filename" => Ok(Self::Filename),
+                _ => Err(ParseConfigError::new("TitleSource", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for TitleSource {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::FrontMatter => f.write_str("front-matter"),
+                Self::Headline => f.write_str("headline"),
+                Self::FirstSentence => f.write_str("first-sentence"),
+                Self::Filename => f.write_str("filename"),
+            }
+        }
+    }
+
+    /// The rule to take an excerpt of a document.
+    ///
+    /// The string form of `Words(n)` is `words:n`.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ExcerptRule {
+        /// The first paragraph.
+        FirstParagraph,
+        /// The blocks up to the word of the number, which is cut in the
+        /// middle of its block. Words are counted as
+        /// [`TextStats::words`](crate::model::stats::TextStats::words).
+        Words(usize),
+        /// The blocks before a `<!-- more -->` line.
+        MoreMarker,
+    }
+
+    impl FromStr for ExcerptRule {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "first-paragraph" => Ok(Self::FirstParagraph),
+                "more-marker" => Ok(Self::MoreMarker),
+                _ => s
+                    .strip_prefix("words:")
+                    .and_then(|words| words.parse().ok())
+                    .map(Self::Words)
+                    .ok_or_else(|| ParseConfigError::new("ExcerptRule", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for ExcerptRule {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::FirstParagraph => f.write_str("first-paragraph"),
+                Self::Words(words) => write!(f, "words:{words}"),
+                Self::MoreMarker => f.write_str("more-marker"),
+            }
+        }
+    }
+
+    /// The struct to represent the title of a document.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Title {
+        /// The text of the title.
+        pub text: String,
+        /// The source which the title was taken from.
+        pub source: TitleSource,
+    }
+}
+
+impl Default for MetadataExtractor {
+    fn default() -> Self {
+        Self {
+            title_chain: vec![
+                TitleSource::FrontMatter,
+                TitleSource::Headline,
+                TitleSource::FirstSentence,
+                TitleSource::Filename,
+            ],
+            drop_title_headline: false,
+        }
+    }
+}
+
+impl MetadataExtractor {
+    /// Create a new extractor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the sources of the title, tried in order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().metadata(
+    ///     MetadataExtractor::new().title_chain(&[TitleSource::Headline, TitleSource::Filename]),
+    /// );
+    ///
+    /// let title = markdown.title("Just a note.", Some("notes/2024-01-01.md")).unwrap();
+    ///
+    /// assert_eq!(title.text, "2024-01-01");
+    /// assert_eq!(title.source, TitleSource::Filename);
+    /// ```
+    pub fn title_chain(mut self, chain: &[TitleSource]) -> Self {
+        self.title_chain = chain.to_vec();
+        self
+    }
+
+    /// Set whether to remove the headline which the title is taken from.
+    ///
+    /// This is used by
+    /// [`Markdown::execute_with_title`](crate::Markdown::execute_with_title).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().metadata(MetadataExtractor::new().drop_title_headline(true));
+    ///
+    /// let (html, title) = markdown.execute_with_title("# Note\n\nBody\n\n# Next", None);
+    ///
+    /// assert_eq!(html, "<p>Body</p><h1>Next</h1>");
+    /// assert_eq!(title.unwrap().text, "Note");
+    /// ```
+    pub fn drop_title_headline(mut self, drop_title_headline: bool) -> Self {
+        self.drop_title_headline = drop_title_headline;
+        self
+    }
+}
+
+impl MetadataExtractor {
+    /// Extract the title from the front matter and the document.
+    ///
+    /// This returns `None` if no source in the chain gives a non-empty title.
+    pub fn title(
+        &self,
+        front_matter: Option<&FrontMatter>,
+        document: &DocumentNode,
+        filename: Option<&str>,
+    ) -> Option<Title> {
+        self.title_chain.iter().find_map(|source| {
+            let text = match source {
+                TitleSource::FrontMatter => front_matter?.get("title")?.to_string(),
+                TitleSource::Headline => Self::first_element(&document.root, ElementTag::H1)
+                    .map(|element| get_text(&element.children))?,
+                TitleSource::FirstSentence => Self::first_element(&document.root, ElementTag::P)
+                    .map(|element| Self::first_sentence(&element.children))?,
+                TitleSource::Filename => Self::file_stem(filename?).to_string(),
+            };
+
+            let text = text.trim();
+
+            (!text.is_empty()).then(|| Title {
+                text: text.to_string(),
+                source: *source,
+            })
+        })
+    }
+
+    /// Extract the title like [`title`](Self::title), and remove the headline
+    /// which it is taken from if
+    /// [`drop_title_headline`](Self::drop_title_headline) is set.
+    pub fn take_title(
+        &self,
+        front_matter: Option<&FrontMatter>,
+        document: &mut DocumentNode,
+        filename: Option<&str>,
+    ) -> Option<Title> {
+        let title = self.title(front_matter, document, filename)?;
+
+        if self.drop_title_headline && title.source == TitleSource::Headline {
+            Self::remove_first_element(&mut document.root, ElementTag::H1);
+        }
+
+        Some(title)
+    }
+
+    /// Remove the first element with the tag in document order.
+    fn remove_first_element(nodes: &mut Vec<Node>, tag: ElementTag) -> bool {
+        for index in 0..nodes.len() {
+            if let Node::Element(element) = &mut nodes[index] {
+                if element.tag == tag {
+                    nodes.remove(index);
+                    return true;
+                }
+
+                if Self::remove_first_element(&mut element.children, tag) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Take an excerpt of the document by the rule.
+    ///
+    /// The ellipsis is put after the last text of an excerpt of
+    /// [`ExcerptRule::Words`] which is cut. With
+    /// [`ExcerptRule::MoreMarker`], the document is taken as it is, since it
+    /// is the input before the marker which decides the excerpt. See
+    /// [`more_marker`](Self::more_marker).
+    ///
+    /// This returns `None` if the document has no paragraph for
+    /// [`ExcerptRule::FirstParagraph`].
+    pub fn excerpt<'a>(
+        &self,
+        mut document: DocumentNode<'a>,
+        rule: ExcerptRule,
+        ellipsis: &str,
+    ) -> Option<DocumentNode<'a>> {
+        match rule {
+            ExcerptRule::FirstParagraph => {
+                let paragraph = Self::first_element(&document.root, ElementTag::P)?;
+
+                Some(DocumentNode {
+                    root: vec![Node::Element(paragraph.clone())],
+                })
+            }
+            ExcerptRule::Words(words) => {
+                if document.text_stats().words > words {
+                    let mut rest = words;
+
+                    Self::truncate_words(&mut document.root, &mut rest);
+                    Self::push_ellipsis(&mut document.root, ellipsis);
+                }
+
+                Some(document)
+            }
+            ExcerptRule::MoreMarker => Some(document),
+        }
+    }
+
+    /// Find the start of the first `<!-- more -->` line in the input. White
+    /// spaces around the line and in the comment are allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "Intro\n\n  <!--more-->\n\nRest";
+    ///
+    /// assert_eq!(MetadataExtractor::more_marker(input), Some(7));
+    /// ```
+    pub fn more_marker(input: &str) -> Option<usize> {
+        let mut offset = 0;
+
+        for line in input.split_inclusive('\n') {
+            let marker = line
+                .trim()
+                .strip_prefix("<!--")
+                .and_then(|rest| rest.strip_suffix("-->"));
+
+            if marker.map(str::trim) == Some("more") {
+                return Some(offset);
+            }
+
+            offset += line.len();
+        }
+
+        None
+    }
+
+    /// Keep the nodes up to the end of the word of the number, and remove
+    /// the rest.
+    fn truncate_words(nodes: &mut Vec<Node>, rest: &mut usize) {
+        for index in 0..nodes.len() {
+            if *rest == 0 {
+                nodes.truncate(index);
+                return;
+            }
+
+            match &mut nodes[index] {
+                Node::Element(element) => Self::truncate_words(&mut element.children, rest),
+                Node::Text(text) => {
+                    let ends = word_ends(&text.text);
+
+                    if ends.len() >= *rest {
+                        let end = ends[*rest - 1];
+
+                        text.text = Cow::Owned(text.text[..end].to_string());
+                        *rest = 0;
+                    } else {
+                        *rest -= ends.len();
+                    }
+                }
+                Node::Comment(_) => {}
+            }
+        }
+    }
+
+    /// Put the ellipsis after the last text of the nodes.
+    fn push_ellipsis(nodes: &mut [Node], ellipsis: &str) -> bool {
+        for node in nodes.iter_mut().rev() {
+            match node {
+                Node::Element(element) => {
+                    if Self::push_ellipsis(&mut element.children, ellipsis) {
+                        return true;
+                    }
+                }
+                Node::Text(text) => {
+                    text.text.to_mut().push_str(ellipsis);
+                    return true;
+                }
+                Node::Comment(_) => {}
+            }
+        }
+
+        false
+    }
+
+    /// Find the first element with the tag in document order.
+    fn first_element<'a, 'b>(
+        nodes: &'b [Node<'a>],
+        tag: ElementTag,
+    ) -> Option<&'b ElementNode<'a>> {
+        nodes.iter().find_map(|node| match node {
+            Node::Element(element) if element.tag == tag => Some(element),
+            Node::Element(element) => Self::first_element(&element.children, tag),
+            Node::Text(_) | Node::Comment(_) => None,
+        })
+    }
+
+    /// Get the text up to the end of the first sentence. Line breaks are
+    /// replaced with spaces.
+    fn first_sentence(nodes: &[Node]) -> String {
+        let text = nodes
+            .iter()
+            .map(|node| match node {
+                Node::Element(element) if element.tag == ElementTag::Br => " ".to_string(),
+                Node::Element(element) => get_text(&element.children),
+                Node::Text(text) => text.text.to_string(),
+                Node::Comment(_) => String::new(),
+            })
+            .collect::<String>();
+
+        // `.`, `!` and `?` end a sentence only before a white space or the
+        // end, so numbers such as `1.5` are kept. Full-width marks always do.
+        let end = text.char_indices().find(|&(index, c)| match c {
+            '.' | '!' | '?' => {
+                let rest = &text[index + 1..];
+
+                rest.is_empty() || rest.starts_with(char::is_whitespace)
+            }
+            '。' | '！' | '？' => true,
+            _ => false,
+        });
+
+        match end {
+            Some((index, _)) => text[..index].to_string(),
+            None => text,
+        }
+    }
+
+    /// Get the file name without the directory and the extension.
+    fn file_stem(filename: &str) -> &str {
+        let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+
+        match name.rfind('.') {
+            Some(index) if index > 0 => &name[..index],
+            _ => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layer::parser::Parser, Markdown};
+
+    #[test]
+    fn test_front_matter() {
+        let input = "---\r\ntitle: 'A: B'\n# comment\n  nested: x\nempty:\n...\nBody";
+
+        let (front_matter, len) = FrontMatter::parse(input).unwrap();
+
+        assert_eq!(front_matter.entries, vec![("title", "A: B"), ("empty", "")]);
+        assert_eq!(&input[len..], "Body");
+
+        let (front_matter, len) = FrontMatter::parse("---\n---").unwrap();
+
+        assert!(front_matter.entries.is_empty());
+        assert_eq!(len, 7);
+
+        assert_eq!(FrontMatter::parse("---\ntitle: A\n"), None);
+        assert_eq!(FrontMatter::parse("# A\n---\n---\n"), None);
+        assert_eq!(FrontMatter::parse("----\n----\n"), None);
+    }
+
+    #[test]
+    fn test_title_chain() {
+        let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+
+        let title = |input: &str, filename| {
+            markdown
+                .title(input, filename)
+                .map(|title| (title.text, title.source))
+        };
+
+        assert_eq!(
+            title("---\ntitle: Front\n---\n# Head", None),
+            Some(("Front".into(), TitleSource::FrontMatter))
+        );
+        assert_eq!(
+            title("---\ntitle: \"\"\n---\n## Sub\n\n# **Head**", None),
+            Some(("Head".into(), TitleSource::Headline))
+        );
+        assert_eq!(
+            title("- list\n\nFirst *line*\nsecond. Third.", None),
+            Some(("First line second".into(), TitleSource::FirstSentence))
+        );
+        assert_eq!(
+            title("- list", Some("C:\\notes\\.hidden")),
+            Some((".hidden".into(), TitleSource::Filename))
+        );
+        assert_eq!(
+            title("", Some("dir/a.b.md")),
+            Some(("a.b".into(), TitleSource::Filename))
+        );
+        assert_eq!(
+            title("Version 1.5 is out today. More soon", None),
+            Some((
+                "Version 1.5 is out today".into(),
+                TitleSource::FirstSentence
+            ))
+        );
+        assert_eq!(
+            title("Really?! Yes", None),
+            Some(("Really?".into(), TitleSource::FirstSentence))
+        );
+        assert_eq!(
+            title("今日は晴れ。明日は雨", None),
+            Some(("今日は晴れ".into(), TitleSource::FirstSentence))
+        );
+        assert_eq!(title("", None), None);
+    }
+
+    #[test]
+    fn test_drop_title_headline() {
+        let markdown = Markdown::default()
+            .parser(Parser::default().front_matter(true))
+            .metadata(MetadataExtractor::new().drop_title_headline(true));
+
+        let execute = |input: &str| {
+            let (html, title) = markdown.execute_with_title(input, None);
+            (html, title.map(|title| title.text))
+        };
+
+        assert_eq!(
+            execute("Intro\n\n## Sub\n\n# *Head*\n\n# Next"),
+            (
+                "<p>Intro</p><h2>Sub</h2><h1>Next</h1>".into(),
+                Some("Head".into())
+            )
+        );
+        assert_eq!(
+            execute("---\ntitle: Front\n---\n# Head"),
+            ("<h1>Head</h1>".into(), Some("Front".into()))
+        );
+        assert_eq!(
+            execute("First line. Second"),
+            (
+                "<p>First line. Second</p>".into(),
+                Some("First line".into())
+            )
+        );
+        assert_eq!(
+            Markdown::default().execute_with_title("# Head", None),
+            (
+                "<h1>Head</h1>".into(),
+                Some(Title {
+                    text: "Head".into(),
+                    source: TitleSource::Headline
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn test_excerpt() {
+        let markdown = Markdown::default();
+
+        let input = "# A b\n\n- c **d e**\n- f\n\n今日は晴れ";
+
+        for (words, expected) in [
+            (1, "<h1>A…</h1>"),
+            (2, "<h1>A b…</h1>"),
+            (4, "<h1>A b</h1><ul><li>c <strong>d…</strong></li></ul>"),
+            (
+                7,
+                "<h1>A b</h1><ul><li>c <strong>d e</strong></li><li>f</li></ul><p>今…</p>",
+            ),
+            (
+                11,
+                "<h1>A b</h1><ul><li>c <strong>d e</strong></li><li>f</li></ul><p>今日は晴れ</p>",
+            ),
+        ] {
+            assert_eq!(
+                markdown.excerpt(input, ExcerptRule::Words(words)).unwrap(),
+                expected,
+                "{words}"
+            );
+        }
+
+        assert_eq!(markdown.excerpt(input, ExcerptRule::Words(0)).unwrap(), "");
+        assert_eq!(
+            markdown
+                .excerpt(input, ExcerptRule::FirstParagraph)
+                .unwrap(),
+            "<p>今日は晴れ</p>"
+        );
+        assert_eq!(markdown.excerpt("# A", ExcerptRule::FirstParagraph), None);
+        assert_eq!(markdown.excerpt(input, ExcerptRule::MoreMarker), None);
+        assert_eq!(
+            markdown
+                .excerpt("a\n<!-- more -->\nb", ExcerptRule::MoreMarker)
+                .unwrap(),
+            "<p>a</p>"
+        );
+        assert_eq!(
+            MetadataExtractor::more_marker("<!-- more --> a\n<!-- mores -->"),
+            None
+        );
+
+        for rule in [
+            ExcerptRule::FirstParagraph,
+            ExcerptRule::Words(30),
+            ExcerptRule::MoreMarker,
+        ] {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Layer to transform a model to a model.
 
+pub mod figures;
+pub mod formatter;
 pub mod lexer;
+pub mod metadata;
 pub mod parser;
 pub mod stringifier;
 pub mod toc;
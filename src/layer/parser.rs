@@ -3,7 +3,26 @@
 //! This module provides a parser of tokens. The parser is implemented as a
 //! recursive descent parser.
 
-use crate::model::{token::*, tree::*};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    ops::Range,
+    sync::Arc,
+};
+
+use crate::{
+    error::{Diagnostic, DiagnosticKind},
+    layer::{lexer::lex, metadata::FrontMatter},
+    model::{
+        counter::{Counter, CounterStyle},
+        link::{LinkKind, LinkRef},
+        token::*,
+        tree::*,
+    },
+    unicode::is_mark,
+};
 use config::*;
 
 /// Parser of tokens.
@@ -32,7 +51,7 @@ use config::*;
 ///
 /// assert_eq!(html, "<h1>Hello, world!<br>This is a new line.</h1>");
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Parser {
     /// The end of paragraph is decided by at liest two consecutive line breaks.
     /// This determines whether to treat the previous sentence as a paragraph if
@@ -40,11 +59,142 @@ pub struct Parser {
     pub paragraph_ending: ParagraphEnding,
     /// This determines whether to allow a line break in a headline.
     pub headline_ending: HeadlineEnding,
+    /// This determines whether a line break in a paragraph is a hard break
+    /// or a soft break.
+    pub line_break_style: LineBreakStyle,
+    /// This determines which `*` can open and close emphasis.
+    pub emphasis_rule: EmphasisRule,
+    /// The block items which end a paragraph or a headline without a blank
+    /// line, if their ending allows soft breaks. Default is all of
+    /// [`Interrupter`].
+    pub interrupters: Vec<Interrupter>,
     /// This determines whether to make the indent rule of list strict or loose.
-    list_indent_rule: IndentRule,
+    pub list_indent_rule: IndentRule,
     /// This determines whether to make the indent style of list space, tab, or
     /// both.
     pub list_indent_style: IndentStyle,
+    /// The width of tab stops in columns. A tab in an indent moves to the next
+    /// tab stop. This is used by [`IndentStyle::Both`]. Default is 4.
+    pub tab_stop: u8,
+    /// This determines whether to skip front matter enclosed by `---` lines at
+    /// the start of the input. Default is false.
+    pub front_matter: bool,
+    /// The maximum depth of nested lists, blockquotes and inline elements
+    /// together. Deeper syntax is parsed as text. Default is `Some(128)`, and
+    /// `None` means no limit.
+    pub max_nesting: Option<usize>,
+    /// The maximum depth of nested lists and blockquotes. Default is `None`,
+    /// which means no limit.
+    pub max_block_nesting: Option<usize>,
+    /// The maximum depth of nested inline elements. Default is `None`, which
+    /// means no limit.
+    pub max_inline_nesting: Option<usize>,
+    /// The maximum length of the input in bytes. The rest of the input is
+    /// ignored. Default is `None`, which means no limit.
+    pub max_input_len: Option<usize>,
+    /// The maximum number of tokens of the input. The rest of the input is
+    /// ignored. Default is `None`, which means no limit.
+    pub max_tokens: Option<usize>,
+    /// This determines whether to parse wiki links such as `[[Page]]` and
+    /// `[[Page|label]]`. Default is false.
+    pub wiki_links: bool,
+    /// This determines whether to parse hashtags such as `#rust`. Default is
+    /// false.
+    pub hashtags: bool,
+    /// This determines whether to parse mentions such as `@alice`. Default is
+    /// false.
+    pub mentions: bool,
+    /// The characters of the names of hashtags and mentions. Default is
+    /// [`is_tag_char`].
+    pub tag_char: fn(char) -> bool,
+    /// This determines whether to parse highlights such as `==text==`.
+    /// Default is false.
+    pub highlight: bool,
+    /// This determines whether to parse ordered lists numbered with letters
+    /// such as `a.` or roman numerals such as `iv)`. Default is false.
+    pub fancy_lists: bool,
+    /// This determines how to parse HTML comments such as `<!-- note -->`.
+    /// Default is [`Comments::Text`].
+    pub comments: Comments,
+    /// This determines whether to parse abbreviation definitions such as
+    /// `*[HTML]: HyperText Markup Language`. Default is false.
+    pub abbreviations: bool,
+    /// This determines whether to parse inline footnotes such as `^[text]`.
+    /// Default is false.
+    pub inline_footnotes: bool,
+    /// This determines how to parse tables such as `| a | b |`. Default is
+    /// [`Tables::Off`].
+    pub tables: Tables,
+    /// If true, `||` and `^^` in tables merge cells. Default is false.
+    pub table_spans: bool,
+    /// The resolver of includes such as `![[note]]`. Default is `None`, which
+    /// does not parse includes.
+    pub include_resolver: Option<Arc<dyn IncludeResolver>>,
+    /// The maximum depth of nested includes. Deeper includes are parsed as
+    /// text. Default is 8.
+    pub max_include_depth: usize,
+}
+
+/// The trait to get the content of an include such as `![[note]]`.
+///
+/// This is implemented for closures which take the target and return the
+/// content.
+pub trait IncludeResolver: Send + Sync {
+    /// Get the content of the target, or `None` if it does not exist. An
+    /// include which is not resolved is parsed as text.
+    fn resolve(&self, target: &str) -> Option<Inclusion>;
+}
+
+impl<F> IncludeResolver for F
+where
+    F: Fn(&str) -> Option<Inclusion> + Send + Sync,
+{
+    fn resolve(&self, target: &str) -> Option<Inclusion> {
+        self(target)
+    }
+}
+
+/// The content of an include.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inclusion {
+    /// Markdown which is parsed with the same parser.
+    Markdown(String),
+    /// A tree which is already parsed.
+    Tree(MarkdownTree<'static>),
+}
+
+impl fmt::Debug for Parser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parser")
+            .field("paragraph_ending", &self.paragraph_ending)
+            .field("headline_ending", &self.headline_ending)
+            .field("line_break_style", &self.line_break_style)
+            .field("emphasis_rule", &self.emphasis_rule)
+            .field("interrupters", &self.interrupters)
+            .field("list_indent_rule", &self.list_indent_rule)
+            .field("list_indent_style", &self.list_indent_style)
+            .field("tab_stop", &self.tab_stop)
+            .field("front_matter", &self.front_matter)
+            .field("max_nesting", &self.max_nesting)
+            .field("max_block_nesting", &self.max_block_nesting)
+            .field("max_inline_nesting", &self.max_inline_nesting)
+            .field("max_input_len", &self.max_input_len)
+            .field("max_tokens", &self.max_tokens)
+            .field("wiki_links", &self.wiki_links)
+            .field("hashtags", &self.hashtags)
+            .field("mentions", &self.mentions)
+            .field("tag_char", &self.tag_char)
+            .field("highlight", &self.highlight)
+            .field("fancy_lists", &self.fancy_lists)
+            .field("comments", &self.comments)
+            .field("abbreviations", &self.abbreviations)
+            .field("inline_footnotes", &self.inline_footnotes)
+            .field("tables", &self.tables)
+            .field("table_spans", &self.table_spans)
+            .field("include_resolver", &self.include_resolver.is_some())
+            .field("max_include_depth", &self.max_include_depth)
+            .finish()
+    }
 }
 
 pub mod config {
@@ -52,15 +202,45 @@ pub mod config {
     //!
     //! This module provides configurations for parsing. The configurations are
     //! used in [Parser](super::Parser).
+    //!
+    //! Each configuration can be converted from and to a kebab-case string such
+    //! as `allow-soft-break` with [`FromStr`] and [`Display`](fmt::Display).
+
+    use std::{fmt, str::FromStr};
+
+    use crate::error::ParseConfigError;
 
     /// Ending of paragraph.
+    #[non_exhaustive]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum ParagraphEnding {
         AllowSoftBreak,
         HardBreak,
     }
 
+    impl FromStr for ParagraphEnding {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "allow-soft-break" => Ok(Self::AllowSoftBreak),
+                "hard-break" => Ok(Self::HardBreak),
+                _ => Err(ParseConfigError::new("ParagraphEnding", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for ParagraphEnding {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::AllowSoftBreak => f.write_str("allow-soft-break"),
+                Self::HardBreak => f.write_str("hard-break"),
+            }
+        }
+    }
+
     /// Ending of headline.
+    #[non_exhaustive]
     #[allow(clippy::enum_variant_names)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum HeadlineEnding {
@@ -69,20 +249,338 @@ pub mod config {
         HardBreak,
     }
 
+    impl FromStr for HeadlineEnding {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "soft-break" => Ok(Self::SoftBreak),
+                "allow-soft-break" => Ok(Self::AllowSoftBreak),
+                "hard-break" => Ok(Self::HardBreak),
+                _ => Err(ParseConfigError::new("HeadlineEnding", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for HeadlineEnding {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::SoftBreak => f.write_str("soft-break"),
+                Self::AllowSoftBreak => f.write_str("allow-soft-break"),
+                Self::HardBreak => f.write_str("hard-break"),
+            }
+        }
+    }
+
+    /// Dialect of markdown, which is a preset of the other configurations.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Dialect {
+        /// The default configurations of note-mark.
+        NoteMark,
+        /// Configurations closer to CommonMark.
+        CommonMark,
+    }
+
+    impl FromStr for Dialect {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "note-mark" => Ok(Self::NoteMark),
+                "common-mark" => Ok(Self::CommonMark),
+                _ => Err(ParseConfigError::new("Dialect", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for Dialect {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::NoteMark => f.write_str("note-mark"),
+                Self::CommonMark => f.write_str("common-mark"),
+            }
+        }
+    }
+
+    /// Rule of delimiters of emphasis.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum EmphasisRule {
+        /// Any `*` opens emphasis, and the nearest `*` after it closes it.
+        Nearest,
+        /// Delimiters must be flanking as in CommonMark. An opening delimiter
+        /// cannot be followed by a white space, and a closing delimiter
+        /// cannot follow one. A delimiter next to punctuation must also have a
+        /// white space or punctuation on its other side.
+        Flanking,
+    }
+
+    impl FromStr for EmphasisRule {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "nearest" => Ok(Self::Nearest),
+                "flanking" => Ok(Self::Flanking),
+                _ => Err(ParseConfigError::new("EmphasisRule", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for EmphasisRule {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Nearest => f.write_str("nearest"),
+                Self::Flanking => f.write_str("flanking"),
+            }
+        }
+    }
+
+    /// Style of line breaks in paragraphs and headlines.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LineBreakStyle {
+        /// Every line break is a hard break.
+        HardBreak,
+        /// A line break is a soft break, unless the line ends with `\` or at
+        /// least two spaces like CommonMark.
+        SoftBreak,
+    }
+
+    impl FromStr for LineBreakStyle {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "hard-break" => Ok(Self::HardBreak),
+                "soft-break" => Ok(Self::SoftBreak),
+                _ => Err(ParseConfigError::new("LineBreakStyle", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for LineBreakStyle {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::HardBreak => f.write_str("hard-break"),
+                Self::SoftBreak => f.write_str("soft-break"),
+            }
+        }
+    }
+
+    /// A block item which can interrupt a paragraph or a headline.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Interrupter {
+        Headline,
+        Blockquote,
+        BulletList,
+        OrderedList,
+        /// A fenced code block such as ` ``` `.
+        CodeBlock,
+        /// A table, if [`tables`](super::Parser::tables) are parsed.
+        Table,
+    }
+
+    impl Interrupter {
+        /// All interrupters.
+        pub const ALL: [Self; 6] = [
+            Self::Headline,
+            Self::Blockquote,
+            Self::BulletList,
+            Self::OrderedList,
+            Self::CodeBlock,
+            Self::Table,
+        ];
+    }
+
+    impl FromStr for Interrupter {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "headline" => Ok(Self::Headline),
+                "blockquote" => Ok(Self::Blockquote),
+                "bullet-list" => Ok(Self::BulletList),
+                "ordered-list" => Ok(Self::OrderedList),
+                "code-block" => Ok(Self::CodeBlock),
+                "table" => Ok(Self::Table),
+                _ => Err(ParseConfigError::new("Interrupter", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for Interrupter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Headline => f.write_str("headline"),
+                Self::Blockquote => f.write_str("blockquote"),
+                Self::BulletList => f.write_str("bullet-list"),
+                Self::OrderedList => f.write_str("ordered-list"),
+                Self::CodeBlock => f.write_str("code-block"),
+                Self::Table => f.write_str("table"),
+            }
+        }
+    }
+
     /// Indent rule of list.
+    ///
+    /// The indent of a list marker is measured in levels of the
+    /// [`IndentStyle`], and the columns left over are the extra indent.
+    #[non_exhaustive]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum IndentRule {
+        /// A list starts only at a line without extra indent. Nested items
+        /// may still have extra indent, which is removed with the indent of
+        /// their parent.
         Strict,
+        /// Any list marker may have extra indent of less than one level, as
+        /// CommonMark allows up to 3 spaces. The extra indent is ignored, so
+        /// the item belongs to the level below it.
         Loose,
     }
 
+    impl FromStr for IndentRule {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "strict" => Ok(Self::Strict),
+                "loose" => Ok(Self::Loose),
+                _ => Err(ParseConfigError::new("IndentRule", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for IndentRule {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Strict => f.write_str("strict"),
+                Self::Loose => f.write_str("loose"),
+            }
+        }
+    }
+
     /// Indent style of list.
+    ///
+    /// The string form of `Space(n)` is `space:n`.
+    #[non_exhaustive]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum IndentStyle {
+        /// Each level is the number of spaces. Tabs are not counted.
         Space(u8),
+        /// Each level is a tab. Spaces are not counted.
         Tab,
+        /// Each level is the width of a tab stop, and a tab moves to the next
+        /// tab stop. See [`Parser::tab_stop`](super::Parser::tab_stop).
         Both,
     }
+
+    impl FromStr for IndentStyle {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "tab" => Ok(Self::Tab),
+                "both" => Ok(Self::Both),
+                _ => s
+                    .strip_prefix("space:")
+                    .and_then(|width| width.parse().ok())
+                    .map(Self::Space)
+                    .ok_or_else(|| ParseConfigError::new("IndentStyle", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for IndentStyle {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Space(width) => write!(f, "space:{width}"),
+                Self::Tab => f.write_str("tab"),
+                Self::Both => f.write_str("both"),
+            }
+        }
+    }
+
+    /// Parsing of HTML comments such as `<!-- note -->`.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Comments {
+        /// Comments are not parsed, so they are text.
+        Text,
+        /// Comments are kept in the output as comments, such as markers for
+        /// post-processing.
+        Preserve,
+        /// Comments are removed, such as from user-generated content.
+        Strip,
+    }
+
+    impl FromStr for Comments {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "text" => Ok(Self::Text),
+                "preserve" => Ok(Self::Preserve),
+                "strip" => Ok(Self::Strip),
+                _ => Err(ParseConfigError::new("Comments", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for Comments {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Text => f.write_str("text"),
+                Self::Preserve => f.write_str("preserve"),
+                Self::Strip => f.write_str("strip"),
+            }
+        }
+    }
+
+    /// Parsing of tables such as `| a | b |`.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Tables {
+        /// Tables are not parsed, so they are paragraphs.
+        Off,
+        /// Every row starts and ends with `|`.
+        Piped,
+        /// The `|` at the start and the end of rows can be left out like GFM,
+        /// but the header and the delimiter rows have at least one `|`.
+        Optional,
+    }
+
+    impl FromStr for Tables {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "off" => Ok(Self::Off),
+                "piped" => Ok(Self::Piped),
+                "optional" => Ok(Self::Optional),
+                _ => Err(ParseConfigError::new("Tables", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for Tables {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Off => f.write_str("off"),
+                Self::Piped => f.write_str("piped"),
+                Self::Optional => f.write_str("optional"),
+            }
+        }
+    }
+
+    /// Check if the character can be in the name of a hashtag or a mention,
+    /// which is a letter, a digit, `_`, `-` or a combining mark such as an
+    /// accent or a virama.
+    pub fn is_tag_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '-' || crate::unicode::is_mark(c)
+    }
 }
 
 impl Default for Parser {
@@ -90,12 +588,52 @@ impl Default for Parser {
         Self {
             paragraph_ending: ParagraphEnding::HardBreak,
             headline_ending: HeadlineEnding::HardBreak,
+            line_break_style: LineBreakStyle::HardBreak,
+            emphasis_rule: EmphasisRule::Flanking,
+            interrupters: Interrupter::ALL.to_vec(),
             list_indent_rule: IndentRule::Strict,
             list_indent_style: IndentStyle::Space(2),
+            tab_stop: 4,
+            front_matter: false,
+            max_nesting: Some(128),
+            max_block_nesting: None,
+            max_inline_nesting: None,
+            max_input_len: None,
+            max_tokens: None,
+            wiki_links: false,
+            hashtags: false,
+            mentions: false,
+            tag_char: is_tag_char,
+            highlight: false,
+            fancy_lists: false,
+            comments: Comments::Text,
+            abbreviations: false,
+            inline_footnotes: false,
+            tables: Tables::Off,
+            table_spans: false,
+            include_resolver: None,
+            max_include_depth: 8,
         }
     }
 }
 
+/// Parse the input with the default [`Parser`].
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{model::tree::*, prelude::*};
+///
+/// let tree = MarkdownTree::from("# Title");
+///
+/// assert!(matches!(tree.root.root[..], [BlockItem::Headline(1, _)]));
+/// ```
+impl<'a> From<&'a str> for MarkdownTree<'a> {
+    fn from(input: &'a str) -> Self {
+        Parser::default().parse(input, lex(input))
+    }
+}
+
 impl Parser {
     /// Create a new parser.
     pub fn new() -> Self {
@@ -160,11 +698,161 @@ impl Parser {
         self
     }
 
+    /// Set the endings, line breaks and list indents of a dialect.
+    ///
+    /// [`Dialect::CommonMark`] lets all [`Interrupter`]s interrupt
+    /// paragraphs, ends headlines at the line break, makes line breaks soft
+    /// unless they are marked, and allows up to 3 spaces before list markers
+    /// with [`IndentRule::Loose`]. [`Dialect::NoteMark`] restores the
+    /// defaults. Other syntax is the same in both dialects, so CommonMark is
+    /// not fully supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default().dialect(Dialect::CommonMark);
+    ///
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// let html = markdown.execute("# Title\nText\nmore  \ntext\n- item");
+    ///
+    /// assert_eq!(html, "<h1>Title</h1><p>Text\nmore<br>text</p><ul><li>item</li></ul>");
+    ///
+    /// let html = markdown.execute("Text\n - a\n   - b");
+    ///
+    /// assert_eq!(html, "<p>Text</p><ul><li>a<ul><li>b</li></ul></li></ul>");
+    /// ```
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        let (paragraph, headline, line_break, indent_rule) = match dialect {
+            Dialect::NoteMark => (
+                ParagraphEnding::HardBreak,
+                HeadlineEnding::HardBreak,
+                LineBreakStyle::HardBreak,
+                IndentRule::Strict,
+            ),
+            Dialect::CommonMark => (
+                ParagraphEnding::AllowSoftBreak,
+                HeadlineEnding::SoftBreak,
+                LineBreakStyle::SoftBreak,
+                IndentRule::Loose,
+            ),
+        };
+
+        self.paragraph_ending = paragraph;
+        self.headline_ending = headline;
+        self.line_break_style = line_break;
+        self.interrupters = Interrupter::ALL.to_vec();
+        self.list_indent_rule = indent_rule;
+
+        self
+    }
+
+    /// Set style of line breaks.
+    ///
+    /// This is independent of [`paragraph_ending`](Self::paragraph_ending),
+    /// which decides where a paragraph ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default().line_break_style(LineBreakStyle::SoftBreak);
+    ///
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// let html = markdown.execute("a\nb  \nc\\\nd");
+    ///
+    /// assert_eq!(html, "<p>a\nb<br>c<br>d</p>");
+    /// ```
+    pub fn line_break_style(mut self, style: LineBreakStyle) -> Self {
+        self.line_break_style = style;
+
+        self
+    }
+
+    /// Set the block items which can interrupt a paragraph or a headline.
+    ///
+    /// This takes effect only if [`ParagraphEnding::AllowSoftBreak`] or
+    /// [`HeadlineEnding::AllowSoftBreak`] is set. The other lines are
+    /// continued in the paragraph or the headline. List items are still ended
+    /// by all of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default()
+    ///     .paragraph_ending(ParagraphEnding::AllowSoftBreak)
+    ///     .interrupters(&[Interrupter::Headline, Interrupter::BulletList]);
+    ///
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// let html = markdown.execute("a\n1. b\n- c");
+    ///
+    /// assert_eq!(html, "<p>a<br>1. b</p><ul><li>c</li></ul>");
+    /// ```
+    pub fn interrupters(mut self, interrupters: &[Interrupter]) -> Self {
+        self.interrupters = interrupters.to_vec();
+
+        self
+    }
+
+    /// Set the rule of delimiters of emphasis.
+    ///
+    /// With [`EmphasisRule::Flanking`], which is the default, `2 * 3 * 4` is
+    /// text and `*a * b*` is emphasis of `a * b`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// assert_eq!(markdown.execute("2 * 3 * 4"), "<p>2 * 3 * 4</p>");
+    ///
+    /// let markdown =
+    ///     Markdown::default().parser(Parser::default().emphasis_rule(EmphasisRule::Nearest));
+    ///
+    /// assert_eq!(markdown.execute("2 * 3 * 4"), "<p>2 <em> 3 </em> 4</p>");
+    /// ```
+    pub fn emphasis_rule(mut self, rule: EmphasisRule) -> Self {
+        self.emphasis_rule = rule;
+
+        self
+    }
+
     /// Set indent rule of list.
     ///
-    /// **This config did not work correctly.**
-    #[allow(dead_code)]
-    fn list_indent_rule(mut self, rule: IndentRule) -> Self {
+    /// With [`IndentRule::Loose`], ` - a` is a list item and ` 2. b` after
+    /// `1. a` continues the list. The rule does not change
+    /// [`IndentStyle::Tab`], which has no extra indent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = " - a\n  - b\n   - c\n - d";
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// assert_eq!(markdown.execute(input), "<p> - a<br>  - b<br>   - c<br> - d</p>");
+    ///
+    /// let parser = Parser::default().list_indent_rule(IndentRule::Loose);
+    ///
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// assert_eq!(
+    ///     markdown.execute(input),
+    ///     "<ul><li>a<ul><li>b</li><li>c</li></ul></li><li>d</li></ul>"
+    /// );
+    /// ```
+    pub fn list_indent_rule(mut self, rule: IndentRule) -> Self {
         self.list_indent_rule = rule;
 
         self
@@ -199,1176 +887,5213 @@ impl Parser {
         self
     }
 
-    /// Set all indent style.
+    /// Set the width of tab stops in columns.
     ///
-    /// Currently, this setting is only for list.
-    #[allow(dead_code)]
-    fn indent_style(mut self, style: IndentStyle) -> Self {
-        self.list_indent_style = style;
+    /// With [`IndentStyle::Both`], each level of list is the width of a tab
+    /// stop, and a tab moves to the next tab stop, so spaces and tabs can be
+    /// mixed in an indent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default()
+    ///     .list_indent_style(IndentStyle::Both)
+    ///     .tab_stop(4);
+    ///
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// let html = markdown.execute("- a\n\t- b\n  \t- c\n    \t- d");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<ul><li>a<ul><li>b</li><li>c<ul><li>d</li></ul></li></ul></li></ul>"
+    /// );
+    /// ```
+    pub fn tab_stop(mut self, tab_stop: u8) -> Self {
+        self.tab_stop = tab_stop;
 
         self
     }
 
-    /// Parse tokens to markdown tree.
-    pub fn parse<'a>(
-        &self,
-        input: &'a str,
-        tokens: impl Iterator<Item = Token>,
-    ) -> MarkdownTree<'a> {
-        Executor::with_config(input, self.clone()).parse(tokens.collect::<Vec<Token>>())
+    /// Set whether to skip front matter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+    ///
+    /// let html = markdown.execute("---\ntitle: Hello\n---\n# Hello\n");
+    ///
+    /// assert_eq!(html, "<h1>Hello</h1>");
+    /// ```
+    pub fn front_matter(mut self, front_matter: bool) -> Self {
+        self.front_matter = front_matter;
+
+        self
     }
-}
 
-/// Executor of parser.
-struct Executor<'a> {
-    input: &'a str,
-    config: Parser,
-}
+    /// Set whether to parse wiki links such as `[[Page]]` and
+    /// `[[Page|label]]`.
+    ///
+    /// The target is the text before the first `|`, and the label is the text
+    /// after it. A wiki link cannot contain brackets or line breaks. The URL
+    /// of the link is given by
+    /// [`Transformer::wiki_resolver`](crate::layer::transformer::Transformer::wiki_resolver).
+    ///
+    /// `[[toc]]` is a wiki link when this is enabled, so use `[TOC]` for
+    /// [`Transformer::toc_placeholder`](crate::layer::transformer::Transformer::toc_placeholder).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().wiki_links(true));
+    ///
+    /// let html = markdown.execute("See [[Home]] and [[Getting Started|the guide]].");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<p>See <a class=\"wiki-link\" href=\"Home\">Home</a> and ",
+    ///         "<a class=\"wiki-link\" href=\"Getting%20Started\">the guide</a>.</p>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn wiki_links(mut self, wiki_links: bool) -> Self {
+        self.wiki_links = wiki_links;
 
-/// # Functions for constructing Executor and parsing tokens.
-impl<'a> Executor<'a> {
-    /// Create a new executor.
-    #[allow(dead_code)]
-    fn new(input: &'a str) -> Self {
-        Self {
-            input,
-            config: Parser::new(),
-        }
+        self
     }
 
-    /// Create a new executor with config.
-    fn with_config(input: &'a str, config: Parser) -> Self {
-        Self { input, config }
-    }
+    /// Set whether to parse hashtags such as `#rust`.
+    ///
+    /// A hashtag starts with `#` at the start of text or after a white space
+    /// or `(`, and its name is the characters accepted by
+    /// [`tag_char`](Self::tag_char) after it. A name of only digits, such as
+    /// `#1`, is not a hashtag. Hashtags in links are not parsed.
+    ///
+    /// The transformer writes hashtags with
+    /// [`Transformer::tag_resolver`](crate::layer::transformer::Transformer::tag_resolver).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().hashtags(true).mentions(true));
+    ///
+    /// let html = markdown.execute("#rust by @alice, not a@b or #1");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<p><span class=\"tag\">#rust</span> by ",
+    ///         "<span class=\"mention\">@alice</span>, not a@b or #1</p>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn hashtags(mut self, hashtags: bool) -> Self {
+        self.hashtags = hashtags;
 
-    /// Parse tokens to markdown tree.
-    fn parse(&self, tokens: Vec<Token>) -> MarkdownTree<'a> {
-        self.markdown_tree(&tokens)
+        self
     }
-}
-
-/// # Utility functions for parsing.
-#[allow(dead_code)]
-impl<'a, 'b> Executor<'a> {
-    /// Trim tokens from start.
-    fn trim_start(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
-        let mut temp = tokens;
 
-        loop {
-            if temp.is_empty() {
-                break;
-            }
-
-            if temp[0].kind == kind {
-                temp = &temp[1..];
-            } else {
-                break;
-            }
-        }
+    /// Set whether to parse mentions such as `@alice`. Mentions follow the
+    /// same rules as [`hashtags`](Self::hashtags) with `@` instead of `#`.
+    pub fn mentions(mut self, mentions: bool) -> Self {
+        self.mentions = mentions;
 
-        temp
+        self
     }
 
-    /// Trim tokens from end.
-    fn trim_end(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
-        let mut temp = tokens;
+    /// Set the characters of the names of hashtags and mentions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default()
+    ///     .hashtags(true)
+    ///     .tag_char(|c| is_tag_char(c) || c == '/');
+    ///
+    /// let html = Markdown::default().parser(parser).execute("#lang/rust");
+    ///
+    /// assert_eq!(html, "<p><span class=\"tag\">#lang/rust</span></p>");
+    /// ```
+    pub fn tag_char(mut self, tag_char: fn(char) -> bool) -> Self {
+        self.tag_char = tag_char;
 
-        loop {
-            if temp.is_empty() {
-                break;
-            }
+        self
+    }
 
-            if temp[temp.len() - 1].kind == kind {
-                temp = &temp[..temp.len() - 1];
-            } else {
-                break;
-            }
-        }
+    /// Set whether to parse highlights such as `==text==`, which are written
+    /// as `<mark>`.
+    ///
+    /// The text cannot start or end with a white space, so `a == b == c` is
+    /// not a highlight. A highlight cannot contain line breaks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().highlight(true));
+    ///
+    /// let html = markdown.execute("==**Note**== if a == b == c");
+    ///
+    /// assert_eq!(html, "<p><mark><strong>Note</strong></mark> if a == b == c</p>");
+    /// ```
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
 
-        temp
+        self
     }
 
-    /// Trim tokens from start and end.
-    fn trim(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
-        Self::trim_start(Self::trim_end(tokens, kind), kind)
+    /// Set whether to parse ordered lists numbered with letters or roman
+    /// numerals, which are written with the `type` attribute of `<ol>`.
+    ///
+    /// A letter is one of `a` to `z` or `A` to `Z`, and a roman numeral is one
+    /// of `i`, `I` or two or more roman digits of the same case. So `c.` is a
+    /// letter, and `ii.` after `i.` is a roman numeral. The items of a list
+    /// must have the same numbering and delimiter, either `.` or `)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().fancy_lists(true));
+    ///
+    /// let html = markdown.execute("c) a\nd) b\n\niv. c");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<ol start=\"3\" type=\"a\"><li>a</li><li>b</li></ol>",
+    ///         "<ol start=\"4\" type=\"i\"><li>c</li></ol>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn fancy_lists(mut self, fancy_lists: bool) -> Self {
+        self.fancy_lists = fancy_lists;
+
+        self
     }
 
-    /// Trim white spaces from start.
-    fn trim_white_spaces(tokens: &'b [Token]) -> &'b [Token] {
-        let mut rest = tokens;
+    /// Set how to parse HTML comments such as `<!-- note -->`.
+    ///
+    /// A comment ends at the first `-->`, and cannot span paragraphs. A
+    /// paragraph of only a comment is a comment without `<p>`, and is removed
+    /// with [`Comments::Strip`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "<!-- toc -->\n\nSome <!-- hidden -->text";
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().comments(Comments::Preserve));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute(input),
+    ///     "<!-- toc --><p>Some <!-- hidden -->text</p>"
+    /// );
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().comments(Comments::Strip));
+    ///
+    /// assert_eq!(markdown.execute(input), "<p>Some text</p>");
+    /// ```
+    pub fn comments(mut self, comments: Comments) -> Self {
+        self.comments = comments;
 
-        loop {
-            let mut new_rest = Self::trim_start(rest, TokenKind::Space);
-            new_rest = Self::trim_start(new_rest, TokenKind::Tab);
+        self
+    }
 
-            if new_rest.len() == rest.len() {
-                break rest;
-            }
+    /// Set whether to parse abbreviation definitions such as
+    /// `*[HTML]: HyperText Markup Language`.
+    ///
+    /// A definition is a line of its own and is not written. Each occurrence
+    /// of the abbreviation as a whole word in the document, before or after
+    /// the definition, is written as `<abbr>` with the expansion as its
+    /// title. The first definition of an abbreviation is used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().abbreviations(true));
+    ///
+    /// let html = markdown.execute("HTML is not HTMLX.\n\n*[HTML]: HyperText Markup Language");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<p><abbr title=\"HyperText Markup Language\">HTML</abbr> is not HTMLX.</p>"
+    /// );
+    /// ```
+    pub fn abbreviations(mut self, abbreviations: bool) -> Self {
+        self.abbreviations = abbreviations;
 
-            rest = new_rest;
-        }
+        self
     }
 
-    /// Get a line of tokens.
+    /// Set whether to parse inline footnotes such as `^[text]`.
     ///
-    /// # Arguments
+    /// Footnotes are numbered in document order and moved to a
+    /// `<section class="footnotes">` at the end of the document, so they need
+    /// no labels. A footnote cannot contain line breaks.
     ///
-    /// * `trim` - Trim white spaces of rest tokens from start.
-    fn get_line(tokens: &'b [Token], trim: bool) -> (&'b [Token], &'b [Token]) {
-        if let Some(index) = tokens
-            .iter()
-            .position(|token| token.kind == TokenKind::Break)
-        {
-            if trim {
-                (
-                    &tokens[..index],
-                    Self::trim_start(&tokens[index + 1..], TokenKind::Break),
-                )
-            } else {
-                (&tokens[..index], &tokens[index + 1..])
-            }
-        } else {
-            (Self::trim_end(tokens, TokenKind::Break), &[])
-        }
-    }
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().inline_footnotes(true));
+    ///
+    /// let html = markdown.execute("Text.^[A *note*.] x^2");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<p>Text.<sup class=\"footnote-ref\"><a id=\"fnref-1\" href=\"#fn-1\">1</a></sup> x^2</p>",
+    ///         "<section class=\"footnotes\"><ol><li id=\"fn-1\">A <em>note</em>. ",
+    ///         "<a class=\"footnote-backref\" href=\"#fnref-1\">↩</a></li></ol></section>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn inline_footnotes(mut self, inline_footnotes: bool) -> Self {
+        self.inline_footnotes = inline_footnotes;
 
-    /// Get a paragraph of tokens.
-    fn get_paragraph(tokens: &'b [Token]) -> (&'b [Token], &'b [Token]) {
-        if let Some(index) = tokens.windows(2).position(|tokens| {
-            tokens[0].kind == TokenKind::Break && tokens[1].kind == TokenKind::Break
-        }) {
-            (
-                &tokens[..index],
-                Self::trim_start(&tokens[index + 2..], TokenKind::Break),
-            )
-        } else {
-            (Self::trim_end(tokens, TokenKind::Break), &[])
-        }
+        self
     }
 
-    /// Count indent level.
+    /// Set how to parse tables.
     ///
-    /// # Returns
+    /// A table is a header row, a delimiter row such as `| :-- | --: |` with
+    /// as many cells, and body rows until a blank line or a line which starts
+    /// another block. Cells are split at `|`, so `\|` is a pipe in a cell,
+    /// and each cell is parsed as inline items. Body rows are cut or filled
+    /// up to the number of header cells.
     ///
-    /// (level: u32, remainder: u32)
-    fn indent_level(tokens: &[Token], style: IndentStyle) -> (u32, u32) {
-        match style {
-            IndentStyle::Space(n) => {
-                let mut level = 0;
-
-                for token in tokens {
-                    match token.kind {
-                        TokenKind::Space => level += 1,
-                        _ => break,
-                    }
-                }
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().tables(Tables::Optional));
+    ///
+    /// let html = markdown.execute("Name | Op\n:-- | --:\n*or* | `a \\| b`");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<table><thead><tr><th align=\"left\">Name</th><th align=\"right\">Op</th></tr></thead>",
+    ///         "<tbody><tr><td align=\"left\"><em>or</em></td><td align=\"right\">`a | b`</td></tr></tbody></table>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn tables(mut self, tables: Tables) -> Self {
+        self.tables = tables;
 
-                ((level / n) as u32, (level % n) as u32)
-            }
-            IndentStyle::Tab => {
-                let mut level = 0;
+        self
+    }
 
-                for token in tokens {
-                    match token.kind {
-                        TokenKind::Tab => level += 1,
-                        _ => break,
-                    }
-                }
+    /// Set whether to merge cells of tables like MultiMarkdown.
+    ///
+    /// An empty cell right after another cell, such as the one between `||`,
+    /// is merged into the cell on its left, which spans one more column. A
+    /// body cell of only `^^` is merged into the cell above, which spans one
+    /// more row. The header row can only span columns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let parser = Parser::default().tables(Tables::Piped).table_spans(true);
+    /// let markdown = Markdown::default().parser(parser);
+    ///
+    /// let html = markdown.execute("| a || b |\n| - | - | - |\n| c | d | e |\n| ^^ | f ||");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<table><thead><tr><th colspan=\"2\">a</th><th>b</th></tr></thead><tbody>",
+    ///         "<tr><td rowspan=\"2\">c</td><td>d</td><td>e</td></tr>",
+    ///         "<tr><td colspan=\"2\">f</td></tr></tbody></table>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn table_spans(mut self, table_spans: bool) -> Self {
+        self.table_spans = table_spans;
 
-                (level, 0)
-            }
-            IndentStyle::Both => {
-                let mut level = 0;
+        self
+    }
 
-                for token in tokens {
-                    match token.kind {
-                        TokenKind::Space => level += 1,
-                        TokenKind::Tab => level += 2,
-                        _ => break,
-                    }
-                }
+    /// Set the maximum depth of nested lists, blockquotes and inline
+    /// elements.
+    ///
+    /// This bounds the recursion of the parser, and so the recursion of the
+    /// transformer and the stringifier on the tree. Syntax nested deeper than
+    /// the limit is parsed as text, and the first one is reported as
+    /// [`DiagnosticKind::NestingTooDeep`].
+    ///
+    /// The default limit of 128 keeps the recursion within the 2 MiB stack of
+    /// a spawned thread. Without a limit, input nested deeply enough, such as
+    /// thousands of `>`, overflows the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().max_nesting(Some(2)));
+    ///
+    /// let html = markdown.execute("> > > a");
+    ///
+    /// assert_eq!(html, "<blockquote><blockquote><p>> a</p></blockquote></blockquote>");
+    /// ```
+    pub fn max_nesting(mut self, max_nesting: Option<usize>) -> Self {
+        self.max_nesting = max_nesting;
 
-                (level / 2, level % 2)
-            }
-        }
+        self
     }
 
-    /// Reduce indent level.
+    /// Set the maximum depth of nested lists and blockquotes, apart from
+    /// inline elements.
     ///
-    /// # Arguments
+    /// # Example
     ///
-    /// * `format` - If true, remove remainder.
-    fn reduce_indent(tokens: &[Token], style: IndentStyle, format: bool) -> Vec<Token> {
-        let mut output = vec![];
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().max_block_nesting(Some(1)));
+    ///
+    /// let (html, diagnostics) = markdown.execute_with_diagnostics("> > *a*");
+    ///
+    /// assert_eq!(html, "<blockquote><p>> <em>a</em></p></blockquote>");
+    /// assert_eq!(diagnostics[0].to_string(), "nested too deeply");
+    /// assert_eq!(diagnostics[0].span, 2..3);
+    /// ```
+    pub fn max_block_nesting(mut self, max_block_nesting: Option<usize>) -> Self {
+        self.max_block_nesting = max_block_nesting;
 
-        let mut rest = tokens;
+        self
+    }
 
-        loop {
-            let (line, new_rest) = Self::get_line(rest, false);
+    /// Set the maximum depth of nested inline elements, apart from lists and
+    /// blockquotes.
+    pub fn max_inline_nesting(mut self, max_inline_nesting: Option<usize>) -> Self {
+        self.max_inline_nesting = max_inline_nesting;
 
-            if rest.is_empty() {
-                break;
-            }
+        self
+    }
 
-            let (level, remainder) = Self::indent_level(line, style);
+    /// Set the maximum length of the input in bytes.
+    ///
+    /// The input after the limit is ignored. If the limit is not on a char
+    /// boundary, the limit is moved back to the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().max_input_len(Some(9)));
+    ///
+    /// let html = markdown.execute("# Hello\n\nworld");
+    ///
+    /// assert_eq!(html, "<h1>Hello</h1>");
+    /// ```
+    pub fn max_input_len(mut self, max_input_len: Option<usize>) -> Self {
+        self.max_input_len = max_input_len;
 
-            if level == 0 {
-                output.extend_from_slice(line);
-            } else {
-                match style {
-                    IndentStyle::Space(n) => {
-                        if format {
-                            output.extend_from_slice(&line[(n as usize) + (remainder as usize)..]);
-                        } else {
-                            output.extend_from_slice(&line[n as usize..]);
-                        }
-                    }
-                    IndentStyle::Tab => {
-                        output.extend_from_slice(&line[1..]);
-                    }
-                    IndentStyle::Both => {
-                        if line[0].kind == TokenKind::Space {
-                            if format {
-                                output.extend_from_slice(&line[2 + (remainder as usize)..]);
-                            } else {
-                                output.extend_from_slice(&line[2..]);
-                            }
-                        } else {
-                            output.extend_from_slice(&line[1..]);
-                        }
-                    }
-                }
-            }
+        self
+    }
 
-            if let Some(break_token) = rest.get(line.len()) {
-                output.push(*break_token);
-            }
+    /// Set the maximum number of tokens of the input.
+    ///
+    /// This bounds the work of the parser by the amount of markup, which the
+    /// length in bytes does not. The input from the first token over the
+    /// limit is ignored like the input over
+    /// [`max_input_len`](Self::max_input_len), and reported as
+    /// [`DiagnosticKind::InputTruncated`]. Tokens of front matter count too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().max_tokens(Some(5)));
+    ///
+    /// let (html, diagnostics) = markdown.execute_with_diagnostics("# Hi\n\nthere");
+    ///
+    /// assert_eq!(html, "<h1>Hi</h1>");
+    /// assert_eq!(diagnostics[0].to_string(), "the input is cut here");
+    /// assert_eq!(diagnostics[0].span, 6..11);
+    /// ```
+    pub fn max_tokens(mut self, max_tokens: Option<usize>) -> Self {
+        self.max_tokens = max_tokens;
 
-            rest = new_rest;
-        }
+        self
+    }
 
-        output
+    /// Set the resolver of includes such as `![[note]]`, which put the
+    /// blocks of another document in place of a line of only the include.
+    ///
+    /// The target is the text between the brackets before any `|`. Included
+    /// markdown is parsed with this parser, so it can include other
+    /// documents. An include which is not resolved, of a target which is
+    /// already being included, or deeper than
+    /// [`max_include_depth`](Self::max_include_depth), is parsed as text and
+    /// reported as a [`Diagnostic`]. The input itself
+    /// has no target, so it can be included into itself once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().include_resolver(
+    ///     |target: &str| match target {
+    ///         "greeting" => Some(Inclusion::Markdown("## Hello\n\n![[signature]]".into())),
+    ///         "signature" => Some(Inclusion::Markdown("*Ann*\n\n![[greeting]]".into())),
+    ///         _ => None,
+    ///     },
+    /// ));
+    ///
+    /// let (html, diagnostics) = markdown.execute_with_diagnostics("![[greeting|Hi]]\n\n![[other]]");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<h2>Hello</h2><p><em>Ann</em></p><p>![[greeting]]</p><p>![[other]]</p>"
+    /// );
+    /// assert_eq!(diagnostics[0].to_string(), "`greeting` includes itself");
+    /// assert_eq!(diagnostics[0].span, 0..16);
+    /// assert_eq!(diagnostics[1].to_string(), "`other` is not found");
+    /// ```
+    pub fn include_resolver(mut self, resolver: impl IncludeResolver + 'static) -> Self {
+        self.include_resolver = Some(Arc::new(resolver));
+
+        self
     }
 
-    fn align_indent(tokens: &'b [Token], style: IndentStyle, rule: IndentRule) -> &'b [Token] {
-        match rule {
-            IndentRule::Strict => tokens,
-            IndentRule::Loose => {
-                let (_, remainder) = Self::indent_level(tokens, style);
+    /// Set the maximum depth of nested includes.
+    pub fn max_include_depth(mut self, max_include_depth: usize) -> Self {
+        self.max_include_depth = max_include_depth;
 
-                &tokens[remainder as usize..]
-            }
-        }
+        self
     }
-}
 
-/// # Fuctions for building block tree.
-impl<'a, 'b> Executor<'a> {
-    /// Parse tokens to markdown tree.
-    fn markdown_tree(&self, tokens: &'b [Token]) -> MarkdownTree<'a> {
-        MarkdownTree {
-            root: self.block_tree(tokens),
+    /// Get the length of the input which is parsed, within
+    /// [`max_input_len`](Self::max_input_len) and
+    /// [`max_tokens`](Self::max_tokens).
+    fn input_len(&self, input: &str) -> usize {
+        let len = match self.max_input_len {
+            Some(max) if max < input.len() => (0..=max)
+                .rev()
+                .find(|&index| input.is_char_boundary(index))
+                .unwrap_or(0),
+            _ => input.len(),
+        };
+
+        match self.max_tokens {
+            Some(max) => lex(&input[..len]).nth(max).map_or(len, |token| token.start),
+            None => len,
         }
     }
 
-    /// Parse tokens to block tree.
-    fn block_tree(&self, tokens: &'b [Token]) -> BlockTree<'a> {
-        let mut tree = BlockTree { root: vec![] };
+    /// Set all indent style.
+    ///
+    /// Currently, this setting is only for list.
+    #[allow(dead_code)]
+    fn indent_style(mut self, style: IndentStyle) -> Self {
+        self.list_indent_style = style;
 
-        let mut rest = tokens;
+        self
+    }
 
-        'root: while !rest.is_empty() {
-            for f in [Self::not_paragraph, Self::paragraph] {
-                if let Some((item, new_rest)) = f(self, rest) {
-                    tree.root.push(item);
-                    rest = new_rest;
-                    continue 'root;
-                }
-            }
-        }
+    /// Split the input into chunks of top level blocks.
+    ///
+    /// Each chunk is a byte range of the input, and parsing chunks one by one
+    /// gives the same blocks as parsing the whole input. A chunk boundary is put
+    /// only after a blank line, before a line which cannot continue the
+    /// previous block, and never in a fenced code block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "# Title\n\n- a\n\n- b\n\nParagraph\n";
+    ///
+    /// let chunks = Parser::default().split_blocks(input);
+    ///
+    /// assert_eq!(chunks, vec![0..19, 19..29]);
+    /// ```
+    pub fn split_blocks(&self, input: &str) -> Vec<Range<usize>> {
+        let mut chunks = vec![];
 
-        tree
+        self.each_block(input, |chunk| {
+            chunks.push(chunk);
+            true
+        });
+
+        chunks
     }
 
-    /// Parse tokens to paragraph item.
-    fn paragraph(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        match self.config.paragraph_ending {
-            ParagraphEnding::HardBreak => {
-                let (input, rest) = Self::get_paragraph(tokens);
+    /// Call `f` with the chunks of [`split_blocks`](Self::split_blocks) in
+    /// order, until it returns false.
+    pub(crate) fn each_block(&self, input: &str, mut f: impl FnMut(Range<usize>) -> bool) {
+        let input = &input[..self.input_len(input)];
 
-                Some((BlockItem::Paragraph(self.inline_tree(input)), rest))
-            }
-            ParagraphEnding::AllowSoftBreak => {
-                let (input, rest) = self.get_until_maybe_block_item(tokens);
+        // Front matter is never split, since it can contain blank lines.
+        let front_matter = match self.front_matter {
+            true => FrontMatter::parse(input).map_or(0, |(_, len)| len),
+            false => 0,
+        };
 
-                Some((BlockItem::Paragraph(self.inline_tree(input)), rest))
+        let mut start = 0;
+        let mut offset = 0;
+        let mut previous_blank = false;
+        let mut has_content = false;
+        // The fence of the code block the line is in. A fence which may be
+        // in another block or may be text, such as one right after a
+        // paragraph, makes the rest unsplittable, since it is not known
+        // which later fences open code blocks.
+        let mut fence: Option<Fence> = None;
+        let mut unsplittable = false;
+
+        for line in input.split_inclusive('\n') {
+            let blank = line.trim().is_empty();
+
+            if previous_blank
+                && !blank
+                && has_content
+                && offset >= front_matter
+                && fence.is_none()
+                && !unsplittable
+                && self.starts_block(line)
+            {
+                if !f(start..offset) {
+                    return;
+                }
+
+                start = offset;
             }
-        }
-    }
 
-    /// Parse tokens to not paragraph item.
-    fn not_paragraph(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        for f in [
-            Self::headline,
-            Self::bullet_list,
-            Self::ordered_list,
-            Self::blockquote,
-        ] {
-            if let Some((item, rest)) = f(self, tokens) {
-                return Some((item, rest));
+            if offset >= front_matter {
+                match fence {
+                    Some(open) if open.closes(line) => fence = None,
+                    Some(_) => {}
+                    None => {
+                        if let Some((open, _)) = Fence::open(line) {
+                            let first = previous_blank || offset == front_matter;
+
+                            unsplittable |= open.indent > 0 || !first;
+                            fence = Some(open);
+                        }
+                    }
+                }
             }
+
+            previous_blank = blank;
+            has_content |= !blank;
+            offset += line.len();
         }
 
-        None
+        if start != input.len() || start == 0 {
+            f(start..input.len());
+        }
     }
 
-    /// Parse tokens to headline item.
-    fn headline(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        let tokens = Self::trim_white_spaces(tokens);
+    /// Get the parser of a chunk of [`split_blocks`](Self::split_blocks)
+    /// which starts at the byte `start` of the document.
+    ///
+    /// Front matter can only be at the start of the document, so later
+    /// chunks are parsed without it.
+    pub(crate) fn for_chunk(&self, start: usize) -> Cow<'_, Self> {
+        match self.front_matter && start != 0 {
+            true => Cow::Owned(Self {
+                front_matter: false,
+                ..self.clone()
+            }),
+            false => Cow::Borrowed(self),
+        }
+    }
 
-        let mut level = 0;
+    /// Judge if a line after a blank line always starts a new block.
+    fn starts_block(&self, line: &str) -> bool {
+        if line.starts_with(char::is_whitespace) {
+            return false;
+        }
 
-        for i in 0..7 {
-            if let Some(token) = tokens.get(i) {
-                match token.kind {
-                    TokenKind::Pound => continue,
-                    TokenKind::Space => {
-                        level = i;
-                        break;
-                    }
-                    _ => return None,
-                }
-            }
+        if line.starts_with("- ") || line.trim_end() == "-" {
+            return false;
         }
 
-        if level == 0 {
-            return None;
+        // A line of only an attribute block gives attributes to the block
+        // before it.
+        let trimmed = line.trim_end();
+
+        if trimmed.starts_with('{') && trimmed.ends_with('}') {
+            return false;
         }
 
-        let content = Self::trim_start(&tokens[level..], TokenKind::Space);
+        // A line which may be an ordered list item may continue a list.
+        let number = line
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || (self.fancy_lists && c.is_ascii_alphabetic()))
+            .count();
 
-        match self.config.headline_ending {
-            HeadlineEnding::SoftBreak => {
-                let (input, rest) = Self::get_line(content, true);
+        !(number > 0 && line[number..].starts_with(['.', ')']))
+    }
 
-                Some((
-                    BlockItem::Headline(level as u8, self.inline_tree(input)),
-                    rest,
-                ))
-            }
-            HeadlineEnding::AllowSoftBreak => {
-                let (input, rest) = self.get_until_maybe_block_item(content);
+    /// Parse tokens to markdown tree.
+    pub fn parse<'a>(
+        &self,
+        input: &'a str,
+        tokens: impl Iterator<Item = Token>,
+    ) -> MarkdownTree<'a> {
+        self.parse_with_diagnostics(input, tokens).0
+    }
 
-                Some((
-                    BlockItem::Headline(level as u8, self.inline_tree(input)),
-                    rest,
-                ))
-            }
-            HeadlineEnding::HardBreak => {
-                let (input, rest) = Self::get_paragraph(content);
+    /// Parse tokens to markdown tree, and report markup which is parsed as
+    /// text because it is not closed.
+    pub fn parse_with_diagnostics<'a>(
+        &self,
+        input: &'a str,
+        tokens: impl Iterator<Item = Token>,
+    ) -> (MarkdownTree<'a>, Vec<Diagnostic>) {
+        let executor = Executor::with_config(input, self.clone());
 
-                Some((
-                    BlockItem::Headline(level as u8, self.inline_tree(input)),
-                    rest,
-                ))
-            }
-        }
+        let tree = executor.parse(self.tokens(input, tokens));
+
+        (tree, executor.diagnostics.into_inner())
     }
 
-    /// Parse tokens to bullet list item.
-    fn bullet_list(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        let mut tree = ListTree { root: vec![] };
+    /// Parse tokens to markdown tree, giving blocks their source positions
+    /// as `data-sourcepos` attributes. The input starts at the line
+    /// `first_line`, counted from 1.
+    ///
+    /// See [`Stringifier::source_positions`](crate::layer::stringifier::Stringifier::source_positions).
+    pub(crate) fn parse_with_positions<'a>(
+        &self,
+        input: &'a str,
+        tokens: impl Iterator<Item = Token>,
+        first_line: usize,
+    ) -> (MarkdownTree<'a>, Vec<Diagnostic>) {
+        let mut executor = Executor::with_config(input, self.clone());
+        executor.lines = Some(Lines::new(input, first_line));
 
-        let mut rest = tokens;
+        let tree = executor.parse(self.tokens(input, tokens));
 
-        let input2 = Self::align_indent(
-            tokens,
-            self.config.list_indent_style,
-            self.config.list_indent_rule,
-        );
+        (tree, executor.diagnostics.into_inner())
+    }
 
-        if input2.get(0)?.kind != TokenKind::Hyphen || input2.get(1)?.kind != TokenKind::Space {
-            return None;
-        }
+    /// Parse tokens and collect the links and wiki links in document order.
+    ///
+    /// See [`Markdown::extract_links`](crate::Markdown::extract_links).
+    pub fn extract_links(&self, input: &str, tokens: impl Iterator<Item = Token>) -> Vec<LinkRef> {
+        let mut executor = Executor::with_config(input, self.clone());
+        executor.links = Some(RefCell::new(BTreeMap::new()));
 
-        while !rest.is_empty() {
-            let input3 = Self::align_indent(
-                rest,
-                self.config.list_indent_style,
-                self.config.list_indent_rule,
-            );
+        executor.parse(self.tokens(input, tokens));
 
-            if input3.get(0)?.kind != TokenKind::Hyphen || input3.get(1)?.kind != TokenKind::Space {
-                break;
-            }
+        executor.links.unwrap().into_inner().into_values().collect()
+    }
+
+    /// Parse tokens to markdown tree, and collect the byte ranges of the
+    /// headlines in document order.
+    pub(crate) fn parse_with_headlines<'a>(
+        &self,
+        input: &'a str,
+        tokens: impl Iterator<Item = Token>,
+    ) -> (MarkdownTree<'a>, Vec<Range<usize>>) {
+        let mut executor = Executor::with_config(input, self.clone());
+        executor.headlines = Some(RefCell::new(BTreeMap::new()));
 
-            let (input, new_rest) = self.get_until_maybe_block_item(&rest[2..]);
+        let tree = executor.parse(self.tokens(input, tokens));
 
-            tree.root.push(self.list_item(input));
+        let headlines = executor.headlines.unwrap().into_inner().into_values();
 
-            rest = new_rest;
-        }
+        (tree, headlines.collect())
+    }
 
-        Some((BlockItem::BulletList(tree), rest))
+    /// Remove the tokens of front matter, and cut the tokens at
+    /// [`max_input_len`](Self::max_input_len) and
+    /// [`max_tokens`](Self::max_tokens).
+    fn tokens(&self, input: &str, tokens: impl Iterator<Item = Token>) -> Vec<Token> {
+        let start = match self.front_matter {
+            true => FrontMatter::parse(input).map_or(0, |(_, len)| len),
+            false => 0,
+        };
+
+        let end = self.input_len(input);
+
+        // Tokens across the end are cut at the end.
+        tokens
+            .filter(|token| token.start >= start && token.start < end)
+            .map(|token| Token {
+                len: token.len.min(end - token.start),
+                ..token
+            })
+            .collect()
     }
+}
 
-    fn ordered_list(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        let mut tree = ListTree { root: vec![] };
+/// The kinds of nesting which are limited apart.
+#[derive(Debug, Clone, Copy)]
+enum Nesting {
+    Block,
+    Inline,
+}
 
-        let mut rest = tokens;
+/// The fence which opens a fenced code block, such as ` ``` ` or `~~~~`.
+#[derive(Debug, Clone, Copy)]
+struct Fence {
+    /// The number of spaces before the fence.
+    indent: usize,
+    /// The character of the fence, `` ` `` or `~`.
+    char: char,
+    /// The number of the fence characters.
+    len: usize,
+}
 
-        let input2 = Self::align_indent(
-            tokens,
-            self.config.list_indent_style,
-            self.config.list_indent_rule,
-        );
+impl Fence {
+    /// Parse a line which opens a fenced code block, and get the fence and
+    /// the info string after it.
+    ///
+    /// A fence is three or more backquotes or tildes after at most three
+    /// spaces. The info string after backquotes cannot contain a backquote.
+    fn open(line: &str) -> Option<(Self, &str)> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let char = line[indent..].chars().next()?;
 
-        if input2.get(0)?.kind != TokenKind::Text
-            || input2.get(1)?.kind != TokenKind::Dot
-            || input2.get(2)?.kind != TokenKind::Space
-        {
+        if indent > 3 || !matches!(char, '`' | '~') {
             return None;
         }
 
-        if !self.input[tokens[0].range()]
-            .chars()
-            .all(|c| c.is_ascii_digit())
-        {
+        let info = line[indent..].trim_start_matches(char);
+        let len = line.len() - indent - info.len();
+
+        if len < 3 || (char == '`' && info.contains('`')) {
             return None;
         }
 
-        while !rest.is_empty() {
-            let input3 = Self::align_indent(
-                rest,
-                self.config.list_indent_style,
-                self.config.list_indent_rule,
-            );
+        Some((Self { indent, char, len }, info))
+    }
 
-            if input3.get(0)?.kind != TokenKind::Text
-                || input3.get(1)?.kind != TokenKind::Dot
-                || input3.get(2)?.kind != TokenKind::Space
-            {
-                break;
-            }
+    /// Judge if a line closes the code block: a fence of the same character
+    /// which is at least as long, and nothing but white spaces after it.
+    fn closes(&self, line: &str) -> bool {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let rest = line[indent..].trim_start_matches(self.char);
 
-            if !self.input[input3[0].range()]
-                .chars()
-                .all(|c| c.is_ascii_digit())
-            {
-                break;
-            }
+        indent <= 3 && line.len() - indent - rest.len() >= self.len && rest.trim().is_empty()
+    }
+}
 
-            let (input, new_rest) = self.get_until_maybe_block_item(&rest[3..]);
+/// Executor of parser.
+struct Executor<'a> {
+    input: &'a str,
+    config: Parser,
+    /// The current depth of nested lists and blockquotes.
+    block_depth: Cell<usize>,
+    /// The current depth of nested inline elements.
+    inline_depth: Cell<usize>,
+    /// Whether syntax nested too deeply is already reported.
+    too_deep: Cell<bool>,
+    /// Problems found while parsing.
+    diagnostics: RefCell<Vec<Diagnostic>>,
+    /// The results of matching brackets and parentheses, by the start of the
+    /// open token.
+    matches: RefCell<HashMap<usize, Match>>,
+    /// The closing delimiters of emphasis and their lengths, by the start of
+    /// the opening delimiter.
+    emphasis: RefCell<HashMap<usize, (usize, usize)>>,
+    /// The results of matching the `==` of highlights, by the start of the
+    /// opening `==`.
+    highlights: RefCell<HashMap<usize, Match>>,
+    /// The byte range searched for the `-->` of a comment last, and the
+    /// range of the first `-->` in it, so comments which are not closed do
+    /// not search the same tokens again.
+    comment_close: Cell<(Range<usize>, Option<Range<usize>>)>,
+    /// The byte range searched for the `}` of an attribute block or the end
+    /// of the line last, and the range of the first one in it.
+    attribute_close: Cell<(Range<usize>, Option<Range<usize>>)>,
+    /// Whether the tokens being parsed are the text of a link.
+    in_link: Cell<bool>,
+    /// The links found while parsing by their start, if they are collected.
+    /// Tokens can be parsed more than once, so a link may be found again.
+    links: Option<RefCell<BTreeMap<usize, LinkRef>>>,
+    /// The byte ranges of the headlines parsed as blocks by their start, if
+    /// they are collected.
+    ///
+    /// The headlines of an include are after its start, and have its range.
+    headlines: Option<RefCell<HeadlineRanges>>,
+    /// The targets of the includes being parsed, from the outermost.
+    includes: Vec<String>,
+    /// The lines of the input, if the positions of blocks are recorded.
+    lines: Option<Lines>,
+}
 
-            tree.root.push(self.list_item(input));
+/// The starts of the lines of an input, to write the positions of blocks.
+#[derive(Debug)]
+struct Lines {
+    /// The number of the first line, counted from 1.
+    first: usize,
+    /// The byte offsets of the starts of lines.
+    starts: Vec<usize>,
+}
 
-            rest = new_rest;
-        }
+impl Lines {
+    fn new(input: &str, first: usize) -> Self {
+        let starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
 
-        Some((BlockItem::OrderedList(tree), rest))
+        Self { first, starts }
     }
 
-    fn list_item(&self, tokens: &'b [Token]) -> ListItem<'a> {
-        let (name, children_rest) = {
-            let mut this_rest = tokens;
+    /// Format the byte range as `3:1-4:10`, the lines and the columns of the
+    /// first and the last bytes counted from 1, like `data-sourcepos` of
+    /// cmark.
+    fn format(&self, range: Range<usize>) -> String {
+        let position = |offset: usize| {
+            let line = self.starts.partition_point(|start| *start <= offset) - 1;
 
-            let mut name = InlineTree { root: vec![] };
+            (self.first + line, offset - self.starts[line] + 1)
+        };
 
-            while !this_rest.is_empty() {
-                let (input, rest) = Self::get_line(this_rest, false);
+        let (start_line, start_column) = position(range.start);
+        let (end_line, end_column) = position(range.end.saturating_sub(1).max(range.start));
 
-                if input.is_empty() {
-                    break;
-                }
+        format!("{start_line}:{start_column}-{end_line}:{end_column}")
+    }
+}
 
-                if Self::indent_level(input, self.config.list_indent_style).0 != 0 {
-                    break;
-                }
+/// The byte ranges of headlines by their start and their index in an include.
+type HeadlineRanges = BTreeMap<(usize, usize), Range<usize>>;
 
-                name.root.append(&mut self.inline_tree(input).root);
+/// The cells of the header row of a table and the alignments of its columns.
+type TableHead<'b> = (Vec<&'b [Token]>, Vec<Alignment>);
 
-                name.root.push(InlineItem::Break);
+/// The result of matching an open token with its close token.
+#[derive(Debug, Clone, Copy)]
+enum Match {
+    /// Closed by the token which starts at the position.
+    Closed(usize),
+    /// Not closed until the token which starts at the position, or until the
+    /// end of the line if it is `usize::MAX`.
+    Unclosed(usize),
+}
 
-                this_rest = rest;
+/// # Functions for constructing Executor and parsing tokens.
+impl<'a> Executor<'a> {
+    /// Create a new executor.
+    #[allow(dead_code)]
+    fn new(input: &'a str) -> Self {
+        Self::with_config(input, Parser::new())
+    }
+
+    /// Create a new executor with config.
+    fn with_config(input: &'a str, config: Parser) -> Self {
+        Self {
+            input,
+            config,
+            block_depth: Cell::new(0),
+            inline_depth: Cell::new(0),
+            too_deep: Cell::new(false),
+            diagnostics: RefCell::new(vec![]),
+            matches: RefCell::new(HashMap::new()),
+            emphasis: RefCell::new(HashMap::new()),
+            highlights: RefCell::new(HashMap::new()),
+            comment_close: Cell::new((0..0, None)),
+            attribute_close: Cell::new((0..0, None)),
+            in_link: Cell::new(false),
+            links: None,
+            headlines: None,
+            includes: vec![],
+            lines: None,
+        }
+    }
+
+    /// Get the depth of the kind of nesting.
+    fn depth(&self, nesting: Nesting) -> &Cell<usize> {
+        match nesting {
+            Nesting::Block => &self.block_depth,
+            Nesting::Inline => &self.inline_depth,
+        }
+    }
+
+    /// Judge if another nested element is allowed by
+    /// [`max_nesting`](Parser::max_nesting) and the limit of its kind.
+    fn nesting_allowed(&self, nesting: Nesting) -> bool {
+        let max = match nesting {
+            Nesting::Block => self.config.max_block_nesting,
+            Nesting::Inline => self.config.max_inline_nesting,
+        };
+
+        let total = self.block_depth.get() + self.inline_depth.get();
+
+        !matches!(self.config.max_nesting, Some(max) if total >= max)
+            && !matches!(max, Some(max) if self.depth(nesting).get() >= max)
+    }
+
+    /// Judge if another nested element which starts at the token is allowed,
+    /// and report the first one which is not.
+    fn can_nest(&self, nesting: Nesting, token: &Token) -> bool {
+        let allowed = self.nesting_allowed(nesting);
+
+        if !allowed && !self.too_deep.replace(true) {
+            self.diagnostics.borrow_mut().push(Diagnostic {
+                kind: DiagnosticKind::NestingTooDeep,
+                span: token.range(),
+            });
+        }
+
+        allowed
+    }
+
+    /// Run the function one level deeper.
+    fn nested<T>(&self, nesting: Nesting, f: impl FnOnce() -> T) -> T {
+        let depth = self.depth(nesting);
+
+        depth.set(depth.get() + 1);
+
+        let output = f();
+
+        depth.set(depth.get() - 1);
+
+        output
+    }
+
+    /// Parse tokens to markdown tree, and report the input which is cut by
+    /// the limits.
+    fn parse(&self, tokens: Vec<Token>) -> MarkdownTree<'a> {
+        let end = self.config.input_len(self.input);
+
+        if end < self.input.len() {
+            self.diagnostics.borrow_mut().push(Diagnostic {
+                kind: DiagnosticKind::InputTruncated,
+                span: end..self.input.len(),
+            });
+        }
+
+        self.markdown_tree(&tokens)
+    }
+}
+
+/// # Utility functions for parsing.
+#[allow(dead_code)]
+impl<'a, 'b> Executor<'a> {
+    /// Get the source text of tokens in a line.
+    fn source(&self, line: &[Token]) -> &'a str {
+        match (line.first(), line.last()) {
+            (Some(first), Some(last)) => &self.input[first.start..last.range().end],
+            _ => "",
+        }
+    }
+
+    /// Trim tokens from start.
+    fn trim_start(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
+        let mut temp = tokens;
+
+        loop {
+            if temp.is_empty() {
+                break;
             }
 
-            name.root.pop();
+            if temp[0].kind == kind {
+                temp = &temp[1..];
+            } else {
+                break;
+            }
+        }
 
-            (name, this_rest)
+        temp
+    }
+
+    /// Trim tokens from end.
+    fn trim_end(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
+        let mut temp = tokens;
+
+        loop {
+            if temp.is_empty() {
+                break;
+            }
+
+            if temp[temp.len() - 1].kind == kind {
+                temp = &temp[..temp.len() - 1];
+            } else {
+                break;
+            }
+        }
+
+        temp
+    }
+
+    /// Trim tokens from start and end.
+    fn trim(tokens: &'b [Token], kind: TokenKind) -> &'b [Token] {
+        Self::trim_start(Self::trim_end(tokens, kind), kind)
+    }
+
+    /// Judge if tokens start with the kinds.
+    fn starts_with(tokens: &[Token], kinds: &[TokenKind]) -> bool {
+        tokens.len() >= kinds.len()
+            && tokens
+                .iter()
+                .zip(kinds)
+                .all(|(token, kind)| token.kind == *kind)
+    }
+
+    /// Trim white spaces from start.
+    fn trim_white_spaces(tokens: &'b [Token]) -> &'b [Token] {
+        let mut rest = tokens;
+
+        loop {
+            let mut new_rest = Self::trim_start(rest, TokenKind::Space);
+            new_rest = Self::trim_start(new_rest, TokenKind::Tab);
+
+            if new_rest.len() == rest.len() {
+                break rest;
+            }
+
+            rest = new_rest;
+        }
+    }
+
+    /// Get a line of tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `trim` - Trim white spaces of rest tokens from start.
+    fn get_line(tokens: &'b [Token], trim: bool) -> (&'b [Token], &'b [Token]) {
+        if let Some(index) = tokens
+            .iter()
+            .position(|token| token.kind == TokenKind::Break)
+        {
+            if trim {
+                (
+                    &tokens[..index],
+                    Self::trim_start(&tokens[index + 1..], TokenKind::Break),
+                )
+            } else {
+                (&tokens[..index], &tokens[index + 1..])
+            }
+        } else {
+            (Self::trim_end(tokens, TokenKind::Break), &[])
+        }
+    }
+
+    /// Get a paragraph of tokens.
+    fn get_paragraph(tokens: &'b [Token]) -> (&'b [Token], &'b [Token]) {
+        if let Some(index) = tokens.windows(2).position(|tokens| {
+            tokens[0].kind == TokenKind::Break && tokens[1].kind == TokenKind::Break
+        }) {
+            (
+                &tokens[..index],
+                Self::trim_start(&tokens[index + 2..], TokenKind::Break),
+            )
+        } else {
+            (Self::trim_end(tokens, TokenKind::Break), &[])
+        }
+    }
+
+    /// Count indent level.
+    ///
+    /// # Returns
+    ///
+    /// (level: u32, remainder: u32)
+    fn indent_level(tokens: &[Token], style: IndentStyle, tab_stop: u8) -> (u32, u32) {
+        let width = Self::level_width(style, tab_stop);
+
+        let mut columns = 0;
+
+        for token in tokens {
+            match Self::indent_width(style, tab_stop, columns, token.kind) {
+                Some(token_width) => columns += token_width,
+                None => break,
+            }
+        }
+
+        // A width of 0 cannot count indents, so nothing is indented.
+        (
+            columns.checked_div(width).unwrap_or(0),
+            columns.checked_rem(width).unwrap_or(0),
+        )
+    }
+
+    /// Get the width of one indent level in columns.
+    fn level_width(style: IndentStyle, tab_stop: u8) -> u32 {
+        match style {
+            IndentStyle::Space(n) => n as u32,
+            IndentStyle::Tab => 1,
+            IndentStyle::Both => tab_stop as u32,
+        }
+    }
+
+    /// Get the width in columns of a white space at the column in an indent,
+    /// or `None` if the style does not count it.
+    fn indent_width(style: IndentStyle, tab_stop: u8, column: u32, kind: TokenKind) -> Option<u32> {
+        match (style, kind) {
+            (IndentStyle::Space(_), TokenKind::Space) => Some(1),
+            (IndentStyle::Tab, TokenKind::Tab) => Some(1),
+            (IndentStyle::Both, TokenKind::Space) => Some(1),
+            (IndentStyle::Both, TokenKind::Tab) => {
+                let tab_stop = (tab_stop as u32).max(1);
+
+                Some(tab_stop - column % tab_stop)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reduce indent level.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - If true, remove remainder.
+    fn reduce_indent(
+        tokens: &[Token],
+        style: IndentStyle,
+        tab_stop: u8,
+        format: bool,
+    ) -> Vec<Token> {
+        let mut output = vec![];
+
+        let mut rest = tokens;
+
+        loop {
+            let (line, new_rest) = Self::get_line(rest, false);
+
+            if rest.is_empty() {
+                break;
+            }
+
+            let (level, remainder) = Self::indent_level(line, style, tab_stop);
+
+            if level == 0 {
+                output.extend_from_slice(line);
+            } else {
+                // The width of one level, and the remainder if formatting.
+                let width = Self::level_width(style, tab_stop) + if format { remainder } else { 0 };
+
+                output.extend_from_slice(Self::skip_indent(line, style, tab_stop, width));
+            }
+
+            if let Some(break_token) = rest.get(line.len()) {
+                output.push(*break_token);
+            }
+
+            rest = new_rest;
+        }
+
+        output
+    }
+
+    /// Skip white spaces at the start of a line up to the width, counted as
+    /// [`indent_level`](Self::indent_level) counts them.
+    ///
+    /// A white space which crosses the width is not skipped.
+    fn skip_indent(line: &'b [Token], style: IndentStyle, tab_stop: u8, width: u32) -> &'b [Token] {
+        let mut skipped = 0;
+
+        let count = line
+            .iter()
+            .take_while(|token| {
+                let Some(token_width) = Self::indent_width(style, tab_stop, skipped, token.kind)
+                else {
+                    return false;
+                };
+
+                skipped += token_width;
+
+                skipped <= width
+            })
+            .count();
+
+        &line[count..]
+    }
+
+    /// Split a trailing attribute block such as `{#id .class}` from tokens.
+    ///
+    /// If tokens do not end with a valid attribute block, this returns tokens
+    /// as is.
+    fn trailing_attributes(&self, tokens: &'b [Token]) -> (&'b [Token], Option<Attributes>) {
+        let trimmed = Self::trim_end(tokens, TokenKind::Space);
+
+        if trimmed.last().map(|token| token.kind) != Some(TokenKind::CloseBrace) {
+            return (tokens, None);
+        }
+
+        let Some(open) = trimmed
+            .iter()
+            .rposition(|token| token.kind == TokenKind::OpenBrace)
+        else {
+            return (tokens, None);
         };
 
-        let tokens = Self::reduce_indent(children_rest, self.config.list_indent_style, true);
+        let block = &trimmed[open..];
 
-        ListItem {
-            name,
-            children: self.block_tree(&tokens).root,
+        if block.iter().any(|token| token.kind == TokenKind::Break) {
+            return (tokens, None);
+        }
+
+        let inner = &self.input[block[0].start + 1..block[block.len() - 1].start];
+
+        match Self::parse_attributes(inner) {
+            Some(attributes) => (
+                Self::trim_end(&trimmed[..open], TokenKind::Space),
+                Some(attributes),
+            ),
+            None => (tokens, None),
         }
     }
 
-    fn blockquote(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
-        if tokens.get(0)?.kind != TokenKind::Gt {
+    /// Parse a line of only an attribute block.
+    fn attribute_line(&self, line: &[Token]) -> Option<Attributes> {
+        let line = Self::trim_white_spaces(line);
+
+        if line.first()?.kind != TokenKind::OpenBrace {
             return None;
         }
 
-        let mut rest = tokens;
+        match self.trailing_attributes(line) {
+            ([], attributes) => attributes,
+            _ => None,
+        }
+    }
+
+    /// Give attributes to the item, adding them to its attributes if it has
+    /// some. A later id replaces the earlier one. An include or a definition
+    /// is not a single element, so it takes no attributes.
+    fn attach_attributes(item: BlockItem<'a>, attributes: Attributes) -> BlockItem<'a> {
+        match item {
+            item @ (BlockItem::Include(..) | BlockItem::Abbreviation(..)) => item,
+            BlockItem::Attributed(mut old, item) => {
+                if attributes.id.is_some() {
+                    old.id = attributes.id;
+                }
+
+                old.classes.extend(attributes.classes);
+                old.attrs.extend(attributes.attrs);
+
+                BlockItem::Attributed(old, item)
+            }
+            item => BlockItem::Attributed(attributes, Box::new(item)),
+        }
+    }
+
+    /// Parse the inside of an attribute block such as `#id .class key=value`.
+    ///
+    /// Values can be quoted by `"` to contain white spaces. If any part is
+    /// invalid, this returns `None`.
+    fn parse_attributes(input: &str) -> Option<Attributes> {
+        let is_name = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | ':');
+
+        let mut attributes = Attributes::default();
+
+        let mut rest = input.trim_start();
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        while !rest.is_empty() {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+            if let Some(id) = rest.strip_prefix('#') {
+                let id = &id[..end - 1];
+
+                if id.is_empty() || !id.chars().all(is_name) {
+                    return None;
+                }
+
+                attributes.id = Some(id.to_string());
+                rest = &rest[end..];
+            } else if let Some(class) = rest.strip_prefix('.') {
+                let class = &class[..end - 1];
+
+                if class.is_empty() || !class.chars().all(is_name) {
+                    return None;
+                }
+
+                attributes.classes.push(class.to_string());
+                rest = &rest[end..];
+            } else {
+                let (key, value) = rest.split_once('=')?;
+
+                if key.is_empty() || !key.chars().all(is_name) {
+                    return None;
+                }
+
+                let (value, new_rest) = if let Some(quoted) = value.strip_prefix('"') {
+                    let close = quoted.find('"')?;
+
+                    (&quoted[..close], &quoted[close + 1..])
+                } else {
+                    let end = value.find(char::is_whitespace).unwrap_or(value.len());
+
+                    (&value[..end], &value[end..])
+                };
+
+                if !new_rest.is_empty() && !new_rest.starts_with(char::is_whitespace) {
+                    return None;
+                }
+
+                attributes.attrs.push((key.to_string(), value.to_string()));
+                rest = new_rest;
+            }
+
+            rest = rest.trim_start();
+        }
+
+        Some(attributes)
+    }
+
+    fn align_indent(
+        tokens: &'b [Token],
+        style: IndentStyle,
+        tab_stop: u8,
+        rule: IndentRule,
+    ) -> &'b [Token] {
+        match rule {
+            IndentRule::Strict => tokens,
+            IndentRule::Loose => {
+                let (_, remainder) = Self::indent_level(tokens, style, tab_stop);
+
+                Self::skip_indent(tokens, style, tab_stop, remainder)
+            }
+        }
+    }
+}
+
+/// # Fuctions for building block tree.
+impl<'a, 'b> Executor<'a> {
+    /// Parse tokens to markdown tree.
+    fn markdown_tree(&self, tokens: &'b [Token]) -> MarkdownTree<'a> {
+        MarkdownTree {
+            root: self.block_tree(tokens),
+        }
+    }
+
+    /// Parse tokens to block tree.
+    fn block_tree(&self, tokens: &'b [Token]) -> BlockTree<'a> {
+        let mut tree = BlockTree { root: vec![] };
+
+        let mut rest = tokens;
+
+        'root: while !rest.is_empty() {
+            rest = Self::trim_start(rest, TokenKind::Break);
+
+            if rest.is_empty() {
+                break;
+            }
+
+            // A paragraph of only an attribute block gives attributes to the
+            // block before it.
+            let (line, after) = Self::get_line(rest, false);
+
+            if !tree.root.is_empty()
+                && matches!(
+                    after.first(),
+                    None | Some(Token {
+                        kind: TokenKind::Break,
+                        ..
+                    })
+                )
+            {
+                if let Some(attributes) = self.attribute_line(line) {
+                    let item = tree.root.pop().unwrap();
+
+                    tree.root.push(Self::attach_attributes(item, attributes));
+                    rest = after;
+                    continue;
+                }
+            }
+
+            if let Some((item, new_rest)) = self.abbreviation(rest) {
+                tree.root.push(item);
+                rest = new_rest;
+                continue;
+            }
+
+            if let Some((item, new_rest)) = self.include(rest) {
+                tree.root.push(item);
+                rest = new_rest;
+                continue;
+            }
+
+            for f in [Self::not_paragraph, Self::paragraph] {
+                if let Some((item, new_rest)) = f(self, rest) {
+                    match item {
+                        BlockItem::Comment(_) if self.config.comments == Comments::Strip => {}
+                        item => tree
+                            .root
+                            .push(self.with_position(item, &rest[..rest.len() - new_rest.len()])),
+                    }
+                    rest = new_rest;
+                    continue 'root;
+                }
+            }
+        }
+
+        tree
+    }
+
+    /// Give the item the position of its tokens as a `data-sourcepos`
+    /// attribute, if positions are recorded.
+    fn with_position(&self, item: BlockItem<'a>, tokens: &'b [Token]) -> BlockItem<'a> {
+        let Some(lines) = &self.lines else {
+            return item;
+        };
+
+        let tokens = Self::trim_white_spaces(tokens);
+
+        let Some(end) = tokens.iter().rposition(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::Break | TokenKind::Space | TokenKind::Tab
+            )
+        }) else {
+            return item;
+        };
+
+        let attributes = Attributes {
+            attrs: vec![(
+                "data-sourcepos".to_string(),
+                lines.format(tokens[0].start..tokens[end].range().end),
+            )],
+            ..Default::default()
+        };
+
+        Self::attach_attributes(item, attributes)
+    }
+
+    /// Parse tokens to paragraph item.
+    fn paragraph(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let (input, rest) = match self.config.paragraph_ending {
+            ParagraphEnding::HardBreak => Self::get_paragraph(tokens),
+            ParagraphEnding::AllowSoftBreak => self.get_until_maybe_block_item(tokens),
+        };
+
+        if let Some(text) = self.block_comment(input) {
+            return Some((BlockItem::Comment(text), rest));
+        }
+
+        // A last line of only an attribute block gives attributes to the
+        // paragraph.
+        if let Some(index) = input
+            .iter()
+            .rposition(|token| token.kind == TokenKind::Break)
+        {
+            if let Some(attributes) = self.attribute_line(&input[index + 1..]) {
+                let item = BlockItem::Paragraph(self.inline_tree(&input[..index]));
+
+                return Some((BlockItem::Attributed(attributes, Box::new(item)), rest));
+            }
+        }
+
+        Some((BlockItem::Paragraph(self.inline_tree(input)), rest))
+    }
+
+    /// Parse a line such as `*[HTML]: HyperText Markup Language` to an
+    /// abbreviation definition, if
+    /// [`abbreviations`](Parser::abbreviations) is enabled.
+    fn abbreviation(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        if !self.config.abbreviations {
+            return None;
+        }
+
+        let (line, rest) = Self::get_line(tokens, true);
+        let line = Self::trim_white_spaces(line);
+
+        let text = &self.input[line.first()?.start..line.last()?.range().end];
+        let (abbreviation, expansion) = text.strip_prefix("*[")?.split_once("]:")?;
+        let (abbreviation, expansion) = (abbreviation.trim(), expansion.trim());
+
+        if abbreviation.is_empty() || abbreviation.contains(['[', ']']) || expansion.is_empty() {
+            return None;
+        }
+
+        Some((
+            BlockItem::Abbreviation(abbreviation.into(), expansion.into()),
+            rest,
+        ))
+    }
+
+    /// Parse a line of only an include such as `![[note]]` to the blocks of
+    /// the included document, if it is resolved.
+    fn include(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let resolver = self.config.include_resolver.as_ref()?;
+
+        let (line, rest) = Self::get_line(tokens, true);
+        let line = Self::trim_white_spaces(line);
+        let line = Self::trim_end(Self::trim_end(line, TokenKind::Space), TokenKind::Tab);
+
+        let len = line.len();
+
+        if len < 6
+            || !Self::starts_with(
+                line,
+                &[
+                    TokenKind::Exclamation,
+                    TokenKind::OpenBracket,
+                    TokenKind::OpenBracket,
+                ],
+            )
+            || !Self::starts_with(
+                &line[len - 2..],
+                &[TokenKind::CloseBracket, TokenKind::CloseBracket],
+            )
+        {
+            return None;
+        }
+
+        let content = &line[3..len - 2];
+
+        if content
+            .iter()
+            .any(|token| matches!(token.kind, TokenKind::OpenBracket | TokenKind::CloseBracket))
+        {
+            return None;
+        }
+
+        let text = &self.input[content[0].start..content[content.len() - 1].range().end];
+        let target = text.split('|').next().unwrap().trim();
+
+        if target.is_empty() {
+            return None;
+        }
+
+        let span = line[0].start..line[len - 1].range().end;
+
+        let report = |kind| {
+            self.diagnostics.borrow_mut().push(Diagnostic {
+                kind,
+                span: span.clone(),
+            })
+        };
+
+        if self.includes.iter().any(|include| include == target) {
+            report(DiagnosticKind::IncludeCycle {
+                target: target.to_string(),
+            });
+
+            return None;
+        }
+
+        if self.includes.len() >= self.config.max_include_depth {
+            report(DiagnosticKind::IncludeTooDeep {
+                target: target.to_string(),
+            });
+
+            return None;
+        }
+
+        let Some(inclusion) = resolver.resolve(target) else {
+            report(DiagnosticKind::UnresolvedInclude {
+                target: target.to_string(),
+            });
+
+            return None;
+        };
+
+        let tree = match inclusion {
+            Inclusion::Tree(tree) => tree.root,
+            Inclusion::Markdown(input) => {
+                let mut executor = Executor::with_config(&input, self.config.clone());
+                executor.block_depth.set(self.block_depth.get());
+                executor.inline_depth.set(self.inline_depth.get());
+                executor.includes = self.includes.clone();
+                executor.includes.push(target.to_string());
+
+                let tree = executor
+                    .parse(self.config.tokens(&input, lex(&input)))
+                    .root
+                    .into_owned();
+
+                // The problems of nested includes are reported at this
+                // include, since their spans are in another document.
+                for diagnostic in executor.diagnostics.into_inner() {
+                    if diagnostic.kind.is_include() {
+                        report(diagnostic.kind);
+                    }
+                }
+
+                tree
+            }
+        };
+
+        if let Some(headlines) = &self.headlines {
+            let count = tree
+                .root
+                .iter()
+                .map(BlockItem::headline_count)
+                .sum::<usize>();
+
+            let mut headlines = headlines.borrow_mut();
+
+            for index in 0..count {
+                headlines.insert((span.start, index + 1), span.clone());
+            }
+        }
+
+        Some((
+            BlockItem::Include(Cow::Owned(target.to_string()), tree),
+            rest,
+        ))
+    }
+
+    /// Parse tokens to not paragraph item.
+    fn not_paragraph(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        if let Some((item, rest)) = self.code_block(tokens) {
+            return Some((item, rest));
+        }
+
+        if let Some((item, rest)) = self.headline(tokens) {
+            self.found_headline(&tokens[..tokens.len() - rest.len()]);
+
+            return Some((item, rest));
+        }
+
+        if let Some((item, rest)) = self.table(tokens) {
+            return Some((item, rest));
+        }
+
+        // Lists and blockquotes contain blocks, so they are parsed only if
+        // they can nest.
+        if !self.nesting_allowed(Nesting::Block) {
+            if let Some(
+                Interrupter::Blockquote | Interrupter::BulletList | Interrupter::OrderedList,
+            ) = self.block_kind(Self::trim_white_spaces(tokens))
+            {
+                self.can_nest(Nesting::Block, &tokens[0]);
+            }
+
+            return None;
+        }
+
+        for f in [Self::bullet_list, Self::ordered_list, Self::blockquote] {
+            if let Some((item, rest)) = f(self, tokens) {
+                return Some((item, rest));
+            }
+        }
+
+        None
+    }
+
+    /// Parse tokens to fenced code block item.
+    ///
+    /// The block ends at a line which [closes](Fence::closes) the fence, or
+    /// at the end of the tokens. Its lines lose as many spaces as are before
+    /// the opening fence.
+    fn code_block(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let (line, mut rest) = Self::get_line(tokens, false);
+        let (fence, info) = Fence::open(self.source(line))?;
+        let (lang, info) = code_info(info.trim());
+
+        let mut text = String::new();
+
+        while !rest.is_empty() {
+            let (line, new_rest) = Self::get_line(rest, false);
+
+            rest = new_rest;
+
+            if fence.closes(self.source(line)) {
+                break;
+            }
+
+            let indent = line
+                .iter()
+                .take(fence.indent)
+                .take_while(|token| token.kind == TokenKind::Space)
+                .count();
+
+            text.push_str(self.source(&line[indent..]));
+            text.push('\n');
+        }
+
+        let item = CodeBlock {
+            lang: lang.map(Cow::Borrowed),
+            info,
+            text: Cow::Owned(text),
+        };
+
+        Some((
+            BlockItem::CodeBlock(item),
+            Self::trim_start(rest, TokenKind::Break),
+        ))
+    }
+
+    /// Parse tokens to table item, if [`tables`](Parser::tables) are parsed.
+    fn table(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let ((head, aligns), mut rest) = self.table_head(tokens)?;
+
+        let mut rows = vec![];
+
+        while let Some(first) = rest.first() {
+            let (line, after) = Self::get_line(rest, false);
+
+            if first.kind == TokenKind::Break || self.maybe_block_item(line, true) {
+                break;
+            }
+
+            let Some(cells) = self.table_cells(line, false) else {
+                break;
+            };
+
+            let row = self.table_row(cells, aligns.len(), !rows.is_empty());
+
+            rows.push(row);
+            rest = after;
+        }
+
+        let mut table = TableTree {
+            head: self.table_row(head, aligns.len(), false),
+            aligns,
+            rows,
+        };
+
+        if self.config.table_spans {
+            Self::count_spans(&mut table);
+        }
+
+        Some((
+            BlockItem::Table(table),
+            Self::trim_start(rest, TokenKind::Break),
+        ))
+    }
+
+    /// Get the cells of the header row of a table, the alignments of its
+    /// delimiter row and the tokens after them, if the tokens start a table.
+    fn table_head(&self, tokens: &'b [Token]) -> Option<(TableHead<'b>, &'b [Token])> {
+        if self.config.tables == Tables::Off {
+            return None;
+        }
+
+        let (head, rest) = Self::get_line(tokens, false);
+        let (delimiter, rest) = Self::get_line(rest, false);
+
+        let head = self.table_cells(head, true)?;
+
+        let aligns = self
+            .table_cells(delimiter, true)?
+            .into_iter()
+            .map(|cell| Self::alignment(Self::trim_cell(cell)))
+            .collect::<Option<Vec<_>>>()?;
+
+        if aligns.len() != head.len() {
+            return None;
+        }
+
+        Some(((head, aligns), rest))
+    }
+
+    /// Split a row of a table into its cells at `|` tokens, so escaped pipes
+    /// stay in the cells. The cells are not trimmed, so that `||` is an empty
+    /// cell. The header and the delimiter rows need at least one `|`.
+    fn table_cells(&self, line: &'b [Token], head: bool) -> Option<Vec<&'b [Token]>> {
+        let line = Self::trim_cell(line);
+
+        let leading = line.first()?.kind == TokenKind::VerticalBar;
+        let trailing = line.len() > 1 && line[line.len() - 1].kind == TokenKind::VerticalBar;
+
+        let piped = match self.config.tables {
+            Tables::Piped => leading && trailing,
+            _ => {
+                !head
+                    || line
+                        .iter()
+                        .any(|token| token.kind == TokenKind::VerticalBar)
+            }
+        };
+
+        if !piped {
+            return None;
+        }
+
+        let line = &line[leading as usize..line.len() - trailing as usize];
+
+        Some(
+            line.split(|token| token.kind == TokenKind::VerticalBar)
+                .collect(),
+        )
+    }
+
+    /// Parse the cells of a row of a table, cut or filled up to the width.
+    ///
+    /// If [`table_spans`](Parser::table_spans) is set, an empty cell after
+    /// another cell is merged into it, and a cell of only `^^` is merged into
+    /// the cell above if the row is not the first body row.
+    fn table_row(&self, cells: Vec<&[Token]>, width: usize, above: bool) -> Vec<TableCell<'a>> {
+        let mut row = vec![];
+
+        for cell in cells.into_iter().take(width) {
+            let trimmed = Self::trim_cell(cell);
+
+            let cell = match trimmed {
+                _ if !self.config.table_spans => TableCell::new(self.inline_tree(trimmed)),
+                [] if cell.is_empty() && !row.is_empty() => TableCell::MergedLeft,
+                [first, second]
+                    if above
+                        && first.kind == TokenKind::Caret
+                        && second.kind == TokenKind::Caret =>
+                {
+                    TableCell::MergedUp
+                }
+                _ => TableCell::new(self.inline_tree(trimmed)),
+            };
+
+            row.push(cell);
+        }
+
+        row.resize_with(width, || TableCell::new(InlineTree { root: vec![] }));
+
+        row
+    }
+
+    /// Count the columns and the rows which cells span, from the places
+    /// merged into them.
+    fn count_spans(table: &mut TableTree) {
+        for row in std::iter::once(&mut table.head).chain(&mut table.rows) {
+            for index in 0..row.len() {
+                let merged = row[index + 1..]
+                    .iter()
+                    .take_while(|cell| **cell == TableCell::MergedLeft)
+                    .count();
+
+                if let TableCell::Cell { colspan, .. } = &mut row[index] {
+                    *colspan += merged;
+                }
+            }
+        }
+
+        for index in 0..table.rows.len() {
+            for column in 0..table.aligns.len() {
+                let merged = table.rows[index + 1..]
+                    .iter()
+                    .take_while(|row| row[column] == TableCell::MergedUp)
+                    .count();
+
+                if let TableCell::Cell { rowspan, .. } = &mut table.rows[index][column] {
+                    *rowspan += merged;
+                }
+            }
+        }
+    }
+
+    /// Get the alignment of a cell of the delimiter row such as `:--:`.
+    fn alignment(cell: &[Token]) -> Option<Alignment> {
+        let left = cell.first()?.kind == TokenKind::Colon;
+        let right = cell.len() > 1 && cell[cell.len() - 1].kind == TokenKind::Colon;
+
+        let hyphens = &cell[left as usize..cell.len() - right as usize];
+
+        if hyphens.is_empty() || hyphens.iter().any(|token| token.kind != TokenKind::Hyphen) {
+            return None;
+        }
+
+        Some(match (left, right) {
+            (false, false) => Alignment::None,
+            (true, false) => Alignment::Left,
+            (true, true) => Alignment::Center,
+            (false, true) => Alignment::Right,
+        })
+    }
+
+    /// Trim spaces and tabs at both ends of a cell of a table.
+    fn trim_cell(tokens: &'b [Token]) -> &'b [Token] {
+        let white = |token: &Token| matches!(token.kind, TokenKind::Space | TokenKind::Tab);
+
+        let start = tokens
+            .iter()
+            .position(|token| !white(token))
+            .unwrap_or(tokens.len());
+        let end = tokens
+            .iter()
+            .rposition(|token| !white(token))
+            .map_or(start, |end| end + 1);
+
+        &tokens[start..end]
+    }
+
+    /// Parse tokens to headline item.
+    fn headline(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let tokens = Self::trim_white_spaces(tokens);
+
+        let mut level = 0;
+
+        for i in 0..7 {
+            if let Some(token) = tokens.get(i) {
+                match token.kind {
+                    TokenKind::Pound => continue,
+                    TokenKind::Space => {
+                        level = i;
+                        break;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        if level == 0 {
+            return None;
+        }
+
+        let content = Self::trim_start(&tokens[level..], TokenKind::Space);
+
+        let (input, rest) = match self.config.headline_ending {
+            HeadlineEnding::SoftBreak => Self::get_line(content, true),
+            HeadlineEnding::AllowSoftBreak => self.get_until_maybe_block_item(content),
+            HeadlineEnding::HardBreak => Self::get_paragraph(content),
+        };
+
+        let (input, attributes) = self.trailing_attributes(input);
+
+        let item = BlockItem::Headline(level as u8, self.inline_tree(input));
+
+        match attributes {
+            Some(attributes) => Some((BlockItem::Attributed(attributes, Box::new(item)), rest)),
+            None => Some((item, rest)),
+        }
+    }
+
+    /// Record the range of the tokens of a headline without white spaces
+    /// around them, if headlines are collected.
+    fn found_headline(&self, tokens: &'b [Token]) {
+        let Some(headlines) = &self.headlines else {
+            return;
+        };
+
+        let tokens = Self::trim_white_spaces(tokens);
+
+        let Some(end) = tokens.iter().rposition(|token| {
+            !matches!(
+                token.kind,
+                TokenKind::Break | TokenKind::Space | TokenKind::Tab
+            )
+        }) else {
+            return;
+        };
+
+        headlines.borrow_mut().insert(
+            (tokens[0].start, 0),
+            tokens[0].start..tokens[end].range().end,
+        );
+    }
+
+    /// Skip the extra indent before a list marker which the indent rule
+    /// allows.
+    fn align_list_indent(&self, tokens: &'b [Token]) -> &'b [Token] {
+        Self::align_indent(
+            tokens,
+            self.config.list_indent_style,
+            self.config.tab_stop,
+            self.config.list_indent_rule,
+        )
+    }
+
+    /// Parse tokens to bullet list item.
+    fn bullet_list(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let mut tree = ListTree { root: vec![] };
+
+        let mut rest = tokens;
+
+        let input2 = self.align_list_indent(tokens);
+
+        if !Self::starts_with(input2, &[TokenKind::Hyphen, TokenKind::Space]) {
+            return None;
+        }
+
+        while !rest.is_empty() {
+            let input3 = self.align_list_indent(rest);
+
+            if !Self::starts_with(input3, &[TokenKind::Hyphen, TokenKind::Space]) {
+                break;
+            }
+
+            let (input, new_rest) = self.get_list_item(&input3[2..]);
+
+            tree.root.push(self.list_item(input));
+
+            rest = new_rest;
+        }
+
+        Some((BlockItem::BulletList(tree), rest))
+    }
+
+    fn ordered_list(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        let mut tree = ListTree { root: vec![] };
+
+        let mut rest = tokens;
+
+        let (start, marker) = self.ordered_marker(self.align_list_indent(tokens), None)?;
+
+        while !rest.is_empty() {
+            let input3 = self.align_list_indent(rest);
+
+            if self.ordered_marker(input3, Some(marker)).is_none() {
+                break;
+            }
+
+            let (input, new_rest) = self.get_list_item(&input3[3..]);
+
+            tree.root.push(self.list_item(input));
+
+            rest = new_rest;
+        }
+
+        Some((BlockItem::OrderedList(start, marker, tree), rest))
+    }
+
+    /// Parse the marker of an ordered list item such as `1.`, `b)` or `iv.`,
+    /// and return its number.
+    ///
+    /// If the marker of the list is given, the item must have the same one.
+    fn ordered_marker(
+        &self,
+        tokens: &[Token],
+        list: Option<ListMarker>,
+    ) -> Option<(u64, ListMarker)> {
+        let [number, delimiter, space, ..] = tokens else {
+            return None;
+        };
+
+        if number.kind != TokenKind::Text || space.kind != TokenKind::Space {
+            return None;
+        }
+
+        let delimiter = match delimiter.kind {
+            TokenKind::Dot => ListDelimiter::Period,
+            TokenKind::CloseParen => ListDelimiter::Paren,
+            _ => return None,
+        };
+
+        let text = &self.input[number.range()];
+
+        let (value, counter) = if text.chars().all(|c| c.is_ascii_digit()) {
+            // A number of more than 9 digits is text, as in CommonMark.
+            if text.len() > 9 {
+                return None;
+            }
+
+            (text.parse().ok()?, Counter::Decimal)
+        } else if self.config.fancy_lists {
+            fancy_number(text, list.map(|list| list.counter))?
+        } else {
+            return None;
+        };
+
+        let marker = ListMarker { counter, delimiter };
+
+        match list {
+            Some(list) if list != marker => None,
+            _ => Some((value, marker)),
+        }
+    }
+
+    fn list_item(&self, tokens: &'b [Token]) -> ListItem<'a> {
+        let (name, children_rest) = {
+            let mut this_rest = tokens;
+
+            let mut name = InlineTree { root: vec![] };
+
+            while !this_rest.is_empty() {
+                let (input, rest) = Self::get_line(this_rest, false);
+
+                // White spaces after the marker are not a name, so `1.  ` and
+                // `1. ` are the same.
+                if Self::trim_white_spaces(input).is_empty() {
+                    this_rest = &this_rest[input.len()..];
+                    break;
+                }
+
+                if Self::indent_level(input, self.config.list_indent_style, self.config.tab_stop).0
+                    != 0
+                {
+                    break;
+                }
+
+                name.root.append(&mut self.inline_tree(input).root);
+
+                self.line_break(&mut name);
+
+                this_rest = rest;
+            }
+
+            name.root.pop();
+
+            (name, this_rest)
+        };
+
+        let tokens = Self::reduce_indent(
+            children_rest,
+            self.config.list_indent_style,
+            self.config.tab_stop,
+            true,
+        );
+
+        ListItem {
+            name,
+            children: self.nested(Nesting::Block, || self.block_tree(&tokens).into_root()),
+        }
+    }
+
+    fn blockquote(&self, tokens: &'b [Token]) -> Option<(BlockItem<'a>, &'b [Token])> {
+        if tokens.first()?.kind != TokenKind::Gt {
+            return None;
+        }
+
+        let mut rest = tokens;
+
+        let mut indented_tokens = vec![];
+
+        // The open fence of a code block, and the number of spaces its lines
+        // lose, which keeps the indent of code.
+        let mut fence: Option<(Fence, usize)> = None;
+
+        while !rest.is_empty() {
+            if rest.first()?.kind != TokenKind::Gt {
+                break;
+            }
+
+            let (input, new_rest) = Self::get_line(&rest[1..], false);
+
+            let input2 = match fence {
+                Some((open, skip)) => {
+                    let spaces = input
+                        .iter()
+                        .take(skip)
+                        .take_while(|token| token.kind == TokenKind::Space)
+                        .count();
+
+                    if open.closes(self.source(&input[spaces..])) {
+                        fence = None;
+                    }
+
+                    &input[spaces..]
+                }
+                None if self.maybe_block_item(input, true) => {
+                    let input2 =
+                        Self::align_indent(input, IndentStyle::Space(2), 0, IndentRule::Loose);
+
+                    fence = Fence::open(self.source(input2))
+                        .map(|(open, _)| (open, input.len() - input2.len()));
+
+                    input2
+                }
+                None => Self::trim_white_spaces(input),
+            };
+
+            indented_tokens.extend_from_slice(input2);
+
+            if let Some(token) = rest.get(1 + input.len()) {
+                indented_tokens.push(*token);
+            }
+
+            rest = new_rest;
+        }
+
+        let tree = self.nested(Nesting::Block, || self.block_tree(&indented_tokens));
+
+        Some((
+            BlockItem::BlockQuote(tree),
+            Self::trim_start(rest, TokenKind::Break),
+        ))
+    }
+
+    /// Judge if tokens is maybe block item.
+    fn maybe_block_item(&self, tokens: &[Token], trim: bool) -> bool {
+        let tokens = if trim {
+            Self::trim_white_spaces(tokens)
+        } else {
+            self.align_list_indent(tokens)
+        };
+
+        self.block_kind(tokens).is_some()
+    }
+
+    /// Judge if tokens is maybe block item which interrupts a paragraph or a
+    /// headline.
+    fn interrupts(&self, tokens: &[Token]) -> bool {
+        match self.block_kind(self.align_list_indent(tokens)) {
+            Some(kind) => self.config.interrupters.contains(&kind),
+            None => false,
+        }
+    }
+
+    /// Get the kind of block item which tokens maybe start.
+    fn block_kind(&self, tokens: &[Token]) -> Option<Interrupter> {
+        if Fence::open(self.source(Self::get_line(tokens, false).0)).is_some() {
+            return Some(Interrupter::CodeBlock);
+        }
+
+        if self.headline(tokens).is_some() {
+            return Some(Interrupter::Headline);
+        }
+
+        match tokens {
+            [first, ..] if first.kind == TokenKind::Gt => Some(Interrupter::Blockquote),
+            [first, second, ..]
+                if first.kind == TokenKind::Hyphen && second.kind == TokenKind::Space =>
+            {
+                Some(Interrupter::BulletList)
+            }
+            _ => self
+                .ordered_marker(tokens, None)
+                .map(|_| Interrupter::OrderedList)
+                .or_else(|| self.table_head(tokens).map(|_| Interrupter::Table)),
+        }
+    }
+
+    /// Get tokens of a list item after its marker.
+    ///
+    /// Indented lines continue the item even after blank lines, so the item
+    /// can contain paragraphs and other blocks. A line without indent
+    /// continues the item only if it is not a block item and no blank line is
+    /// before it.
+    fn get_list_item(&self, tokens: &'b [Token]) -> (&'b [Token], &'b [Token]) {
+        let style = self.config.list_indent_style;
+
+        let mut index = 0;
+
+        let (front, back) = loop {
+            let Some(offset) = tokens[index..]
+                .iter()
+                .position(|token| token.kind == TokenKind::Break)
+            else {
+                break (tokens, &[][..]);
+            };
+
+            let end = index + offset;
+
+            let next = end
+                + 1
+                + tokens[end + 1..]
+                    .iter()
+                    .take_while(|token| token.kind == TokenKind::Break)
+                    .count();
+
+            let line = &tokens[next..];
+
+            if line.is_empty() {
+                break (&tokens[..end], line);
+            }
+
+            if Self::indent_level(line, style, self.config.tab_stop).0 >= 1 {
+                index = next;
+            } else if next > end + 1 || self.maybe_block_item(line, false) {
+                break (&tokens[..end], line);
+            } else {
+                index = next;
+            }
+        };
+
+        (Self::trim_end(front, TokenKind::Break), back)
+    }
+
+    /// Get tokens until maybe block item which interrupts them.
+    fn get_until_maybe_block_item(&self, tokens: &'b [Token]) -> (&'b [Token], &'b [Token]) {
+        let mut iter = Self::trim_end(tokens, TokenKind::Break).iter().enumerate();
+
+        let (front, back) = loop {
+            if let Some((index, _)) = iter.find(|(_, token)| token.kind == TokenKind::Break) {
+                if self.interrupts(&tokens[index + 1..]) {
+                    break (&tokens[..index], &tokens[index + 1..]);
+                } else if tokens[index].kind == TokenKind::Break
+                    && tokens[index + 1].kind == TokenKind::Break
+                {
+                    break (&tokens[..index], &tokens[index + 2..]);
+                }
+            } else {
+                break (tokens, &[]);
+            }
+        };
+
+        (
+            Self::trim_end(front, TokenKind::Break),
+            Self::trim_start(back, TokenKind::Break),
+        )
+    }
+}
+
+/// # Functions for building inline tree.
+impl<'a, 'b> Executor<'a> {
+    /// Parse tokens to inline tree.
+    ///
+    /// This function parses all tokens to inline tree.
+    /// So confirm that tokens does not include block items.
+    fn inline_tree(&self, tokens: &[Token]) -> InlineTree<'a> {
+        self.match_emphasis(tokens);
+
+        self.inline_items(tokens)
+    }
+
+    /// Parse tokens to inline tree whose emphasis delimiters have been
+    /// matched already, such as the content of an inline element.
+    fn inline_items(&self, tokens: &[Token]) -> InlineTree<'a> {
+        let mut tree = InlineTree { root: vec![] };
+
+        let mut rest = tokens;
+
+        // The source range of the last text item while it borrows the input.
+        let mut borrowed: Option<Range<usize>> = None;
+
+        'root: while !rest.is_empty() {
+            for f in &[
+                Self::escaped,
+                Self::comment,
+                Self::wiki_link,
+                Self::link,
+                Self::span,
+                Self::emphasis,
+                Self::highlight,
+                Self::inline_footnote,
+                Self::r#break,
+            ] {
+                if let Some((item, new_rest)) = f(self, rest) {
+                    let (item, new_rest) = self.inline_attributes(item, new_rest);
+
+                    match item {
+                        InlineItem::Break => self.line_break(&mut tree),
+                        InlineItem::Comment(_) if self.config.comments == Comments::Strip => {}
+                        item => tree.root.push(item),
+                    }
+                    rest = new_rest;
+                    borrowed = None;
+                    continue 'root;
+                }
+            }
+
+            self.diagnose(&tokens[..tokens.len() - rest.len()], rest);
+
+            let range = rest[0].range();
+            rest = &rest[1..];
+
+            match (tree.root.last_mut(), &mut borrowed) {
+                // Contiguous text is kept as a slice of the input.
+                (Some(InlineItem::Text(text)), Some(borrowed)) if borrowed.end == range.start => {
+                    borrowed.end = range.end;
+                    *text = Cow::Borrowed(&self.input[borrowed.clone()]);
+                }
+                (Some(InlineItem::Text(text)), _) => {
+                    text.to_mut().push_str(&self.input[range]);
+                    borrowed = None;
+                }
+                _ => {
+                    tree.root
+                        .push(InlineItem::Text(self.input[range.clone()].into()));
+                    borrowed = Some(range);
+                }
+            }
+        }
+
+        if (self.config.hashtags || self.config.mentions) && !self.in_link.get() {
+            // An escaped character before a text is its previous character, so
+            // that `\>@a` is not a mention like `>@a`.
+            let mut escaped = None;
+
+            tree.root = std::mem::take(&mut tree.root)
+                .into_iter()
+                .flat_map(|item| match item {
+                    InlineItem::Text(text) => self.tags(text, escaped.take()),
+                    InlineItem::Escaped(text) => {
+                        escaped = text.chars().last();
+                        vec![InlineItem::Escaped(text)]
+                    }
+                    item => {
+                        escaped = None;
+                        vec![item]
+                    }
+                })
+                .collect();
+        }
+
+        tree
+    }
+
+    /// Split hashtags and mentions out of the text, which is after the
+    /// character `prev` if it is given.
+    fn tags(&self, text: Cow<'a, str>, mut prev: Option<char>) -> Vec<InlineItem<'a>> {
+        let slice = |range: Range<usize>| match &text {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+            Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+        };
+
+        let mut items = vec![];
+        let mut start = 0;
+
+        for (index, c) in text.char_indices() {
+            let after_boundary = match prev {
+                Some(prev) => prev.is_whitespace() || prev == '(',
+                None => true,
+            };
+            prev = Some(c);
+
+            let kind = match c {
+                '#' if self.config.hashtags => TagKind::Hashtag,
+                '@' if self.config.mentions => TagKind::Mention,
+                _ => continue,
+            };
+
+            if index < start || !after_boundary {
+                continue;
+            }
+
+            let name_len = text[index + 1..]
+                .chars()
+                .take_while(|&c| (self.config.tag_char)(c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+            let name = &text[index + 1..index + 1 + name_len];
+
+            // A mark belongs to the character before it, which is the marker.
+            if name.chars().all(|c| c.is_ascii_digit()) || name.starts_with(is_mark) {
+                continue;
+            }
+
+            if start < index {
+                items.push(InlineItem::Text(slice(start..index)));
+            }
+
+            items.push(InlineItem::Tag {
+                kind,
+                name: slice(index + 1..index + 1 + name_len),
+            });
+
+            start = index + 1 + name_len;
+        }
+
+        if start < text.len() || items.is_empty() {
+            items.push(InlineItem::Text(slice(start..text.len())));
+        }
+
+        items
+    }
+
+    /// Report the first token of the rest if it is markup parsed as text.
+    ///
+    /// A `*` between white spaces is not reported, since it is not likely to
+    /// be emphasis. Nothing is reported where inline elements cannot nest,
+    /// since the markup may be closed but too deep.
+    fn diagnose(&self, before: &[Token], rest: &[Token]) {
+        if !self.nesting_allowed(Nesting::Inline) {
+            return;
+        }
+
+        let blank = |token: Option<&Token>| match token {
+            Some(token) => matches!(
+                token.kind,
+                TokenKind::Space | TokenKind::Tab | TokenKind::Break
+            ),
+            None => true,
+        };
+
+        let kind = match rest[0].kind {
+            TokenKind::Star if !(blank(before.last()) && blank(rest.get(1))) => {
+                DiagnosticKind::UnclosedEmphasis {
+                    delimiter: "*".to_string(),
+                }
+            }
+            TokenKind::OpenBracket
+                if self
+                    .closing(rest, TokenKind::OpenBracket, TokenKind::CloseBracket)
+                    .is_some_and(|index| {
+                        rest.get(index + 1)
+                            .is_some_and(|token| token.kind == TokenKind::OpenParen)
+                    }) =>
+            {
+                DiagnosticKind::MalformedLink
+            }
+            _ => return,
+        };
+
+        let span = rest[0].range();
+        let mut diagnostics = self.diagnostics.borrow_mut();
+
+        // Contiguous delimiters such as `**` are reported together.
+        match (diagnostics.last_mut(), &kind) {
+            (
+                Some(Diagnostic {
+                    kind: DiagnosticKind::UnclosedEmphasis { delimiter },
+                    span: last,
+                }),
+                DiagnosticKind::UnclosedEmphasis { .. },
+            ) if last.end == span.start => {
+                delimiter.push('*');
+                last.end = span.end;
+            }
+            _ => diagnostics.push(Diagnostic { kind, span }),
+        }
+    }
+
+    /// Parse tokens to escaped item.
+    fn escaped(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if tokens[0].kind != TokenKind::Escaped {
+            return None;
+        }
+
+        // Skip the backslash, which is always one byte.
+        let range = tokens[0].range();
+        let text = &self.input[range.start + 1..range.end];
+
+        Some((InlineItem::Escaped(text.into()), &tokens[1..]))
+    }
+
+    /// Parse tokens to italic or strong item, whose delimiters are matched by
+    /// [`match_emphasis`](Self::match_emphasis).
+    ///
+    /// Triple delimiters such as `***a***` are an italic item around a strong
+    /// item.
+    fn emphasis(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if tokens[0].kind != TokenKind::Star {
+            return None;
+        }
+
+        let (close, len) = *self.emphasis.borrow().get(&tokens[0].start)?;
+
+        let index = tokens
+            .binary_search_by_key(&close, |token| token.start)
+            .ok()?;
+
+        if !self.can_nest(Nesting::Inline, &tokens[0]) {
+            return None;
+        }
+
+        let tree = self.nested(Nesting::Inline, || self.inline_items(&tokens[len..index]));
+
+        let item = match len {
+            1 => InlineItem::Italic(tree),
+            _ => InlineItem::Strong(tree),
+        };
+
+        Some((item, &tokens[index + len..]))
+    }
+
+    /// Match the delimiters of emphasis in tokens with a delimiter stack, as
+    /// CommonMark does.
+    ///
+    /// Each closing run is matched with the nearest opening run before it.
+    /// Two delimiters are used if both runs have two or more left, so
+    /// `***a***` is `*` around `**`. Runs which can both open and close, such
+    /// as the `*` in `a*b`, are not matched if the sum of the lengths is a
+    /// multiple of 3, unless both are.
+    ///
+    /// The openers between a matched pair are removed from the stack, and a
+    /// failed search is not repeated for closers of the same kind, so this
+    /// takes linear time. The content of an inline element is not matched
+    /// again, since its delimiters are matched with the whole tokens.
+    fn match_emphasis(&self, tokens: &[Token]) {
+        struct Run {
+            /// The index of the first delimiter left.
+            index: usize,
+            /// The number of delimiters left.
+            len: usize,
+            /// The number of delimiters at first.
+            original: usize,
+            open: bool,
+            close: bool,
+        }
+
+        let mut emphasis = self.emphasis.borrow_mut();
+
+        // The runs which can open emphasis and have delimiters left.
+        let mut stack: Vec<Run> = vec![];
+
+        // The number of runs at the bottom of the stack which are not openers
+        // for closers of the same kind, since they have been searched already.
+        let mut bottoms = [[0; 3]; 2];
+
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let len = tokens[index..]
+                .iter()
+                .take_while(|token| token.kind == TokenKind::Star)
+                .count();
+
+            // Emphasis cannot cross a comment, a wiki link whose text is not
+            // parsed, or the text of a link, which is matched by itself.
+            if let Some((_, rest)) = self.comment(&tokens[index..]) {
+                index = tokens.len() - rest.len();
+                continue;
+            }
+
+            if let Some((_, len)) = self.wiki_link_item(&tokens[index..]) {
+                index += len;
+                continue;
+            }
+
+            if tokens[index].kind == TokenKind::OpenBracket {
+                if let Some((close_bracket, close_paren)) = self.link_brackets(&tokens[index..]) {
+                    index += close_bracket + close_paren + 2;
+                    continue;
+                }
+            }
+
+            if len == 0 {
+                index += 1;
+                continue;
+            }
+
+            let mut closer = Run {
+                index,
+                len,
+                original: len,
+                open: self.can_open(tokens, index..index + len),
+                close: self.can_close(tokens, index..index + len),
+            };
+
+            index += len;
+
+            while closer.close && closer.len > 0 {
+                let kind = (closer.open as usize, closer.original % 3);
+                let bottom = bottoms[kind.0][kind.1];
+
+                let opener = stack[bottom..].iter().rposition(|opener| {
+                    let multiple_of_3 = (opener.close || closer.open)
+                        && (opener.original + closer.original) % 3 == 0
+                        && !(opener.original % 3 == 0 && closer.original % 3 == 0);
+
+                    !multiple_of_3
+                });
+
+                let Some(opener) = opener.map(|opener| bottom + opener) else {
+                    bottoms[kind.0][kind.1] = stack.len();
+                    break;
+                };
+
+                // The runs between them cannot be matched across the emphasis.
+                stack.truncate(opener + 1);
+
+                let run = &mut stack[opener];
+
+                let len = match run.len >= 2 && closer.len >= 2 {
+                    true => 2,
+                    false => 1,
+                };
+
+                run.len -= len;
+
+                let open = run.index + run.len;
+
+                emphasis.insert(tokens[open].start, (tokens[closer.index].start, len));
+
+                if run.len == 0 {
+                    stack.pop();
+                }
+
+                for bottom in bottoms.iter_mut().flatten() {
+                    *bottom = (*bottom).min(stack.len());
+                }
+
+                closer.index += len;
+                closer.len -= len;
+            }
+
+            if closer.open && closer.len > 0 {
+                stack.push(closer);
+            }
+        }
+    }
+
+    /// Judge if the delimiter run can open emphasis by the
+    /// [`emphasis_rule`](Parser::emphasis_rule).
+    fn can_open(&self, tokens: &[Token], run: Range<usize>) -> bool {
+        match self.config.emphasis_rule {
+            EmphasisRule::Flanking => self.flanking(tokens, run).0,
+            _ => true,
+        }
+    }
+
+    /// Judge if the delimiter run can close emphasis by the
+    /// [`emphasis_rule`](Parser::emphasis_rule).
+    fn can_close(&self, tokens: &[Token], run: Range<usize>) -> bool {
+        match self.config.emphasis_rule {
+            EmphasisRule::Flanking => self.flanking(tokens, run).1,
+            _ => true,
+        }
+    }
+
+    /// Judge if the delimiter run of the tokens in the range is left-flanking
+    /// and right-flanking.
+    ///
+    /// The characters around the run are taken from the tokens, since markers
+    /// such as `>` of blockquotes are not a part of them. The start and the
+    /// end of the tokens count as white spaces, and symbols which are not
+    /// letters or digits count as punctuation.
+    fn flanking(&self, tokens: &[Token], run: Range<usize>) -> (bool, bool) {
+        let before = run
+            .start
+            .checked_sub(1)
+            .and_then(|index| self.input[tokens[index].range()].chars().next_back());
+        let after = tokens
+            .get(run.end)
+            .and_then(|token| self.input[token.range()].chars().next());
+
+        let space = |c: Option<char>| match c {
+            Some(c) => c.is_whitespace(),
+            None => true,
+        };
+        let punctuation = |c: Option<char>| {
+            c.is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace() && !is_mark(c))
+        };
+
+        let left = !space(after) && (!punctuation(after) || space(before) || punctuation(before));
+        let right = !space(before) && (!punctuation(before) || space(after) || punctuation(after));
+
+        (left, right)
+    }
+
+    /// Parse tokens to highlight item, if
+    /// [`highlight`](Parser::highlight) is enabled.
+    fn highlight(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if !self.config.highlight || !Self::starts_with(tokens, &[TokenKind::Equal; 2]) {
+            return None;
+        }
+
+        if Self::highlight_blank(tokens.get(2)?) {
+            return None;
+        }
+
+        let index = self.highlight_closing(tokens)?;
+
+        if !self.can_nest(Nesting::Inline, &tokens[0]) {
+            return None;
+        }
+
+        let tree = self.nested(Nesting::Inline, || self.inline_items(&tokens[2..index]));
+
+        Some((InlineItem::Highlight(tree), &tokens[index + 2..]))
+    }
+
+    /// Whether the token cannot be next to the inside of a `==`.
+    fn highlight_blank(token: &Token) -> bool {
+        matches!(
+            token.kind,
+            TokenKind::Space | TokenKind::Tab | TokenKind::Break | TokenKind::Equal
+        )
+    }
+
+    /// Find the index of the closing `==` of the highlight, which starts with
+    /// the tokens. The results are cached like [`closing`](Self::closing).
+    fn highlight_closing(&self, tokens: &[Token]) -> Option<usize> {
+        let first = tokens.first()?;
+
+        let cached = self.highlights.borrow().get(&first.start).copied();
+
+        let result = match cached {
+            Some(Match::Unclosed(end)) if tokens.last()?.start > end => None,
+            cached => cached,
+        };
+
+        let result = match result {
+            Some(result) => result,
+            None => {
+                self.match_highlights(tokens);
+
+                self.highlights.borrow()[&first.start]
+            }
+        };
+
+        match result {
+            // The closing `==` can be after the end of the tokens.
+            Match::Closed(start) => tokens
+                .binary_search_by_key(&start, |t| t.start)
+                .ok()
+                .filter(|index| tokens.get(index + 1).is_some()),
+            Match::Unclosed(_) => None,
+        }
+    }
+
+    /// Match the opening and closing `==` until the end of the line, and
+    /// cache the results. Each `==` is closed by the first closing `==` at
+    /// least a token after it.
+    fn match_highlights(&self, tokens: &[Token]) {
+        let mut highlights = self.highlights.borrow_mut();
+        let mut open = VecDeque::new();
+
+        // The start of the last token scanned, as in `match_pairs`.
+        let mut end = usize::MAX;
+
+        for (index, token) in tokens.iter().enumerate() {
+            if token.kind == TokenKind::Break {
+                end = usize::MAX;
+                break;
+            }
+
+            let equals = token.kind == TokenKind::Equal
+                && tokens.get(index + 1).map(|t| t.kind) == Some(TokenKind::Equal);
+
+            if equals && index > 0 && !Self::highlight_blank(&tokens[index - 1]) {
+                while let Some(&(_, start)) = open.front().filter(|(open, _)| open + 3 <= index) {
+                    highlights.insert(start, Match::Closed(token.start));
+                    open.pop_front();
+                }
+            }
+
+            if equals
+                && tokens
+                    .get(index + 2)
+                    .is_some_and(|t| !Self::highlight_blank(t))
+            {
+                open.push_back((index, token.start));
+            }
+
+            end = token.start;
+        }
+
+        for (_, start) in open {
+            highlights.insert(start, Match::Unclosed(end));
+        }
+    }
+
+    /// Parse tokens to link item.
+    ///
+    /// The destination is the source between the parentheses, and it can be
+    /// followed by a title in double quotes. Brackets and parentheses must be
+    /// balanced, and a link cannot contain line breaks.
+    fn link(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if tokens[0].kind != TokenKind::OpenBracket {
+            return None;
+        }
+
+        let (close_bracket, close_paren) = self.link_brackets(tokens)?;
+
+        if !self.can_nest(Nesting::Inline, &tokens[0]) {
+            return None;
+        }
+
+        let destination = &tokens[close_bracket + 1..];
+
+        let source = self.input[destination[0].range().end..destination[close_paren].start].trim();
+
+        let (url, title) = match source
+            .strip_suffix('"')
+            .and_then(|rest| rest.rsplit_once(" \""))
+        {
+            Some((url, title)) => (url.trim_end(), Some(title)),
+            None => (source, None),
+        };
+
+        let in_link = self.in_link.replace(true);
+
+        let item = InlineItem::Link {
+            url: unescape(url),
+            title: title.map(unescape),
+            tree: self.nested(Nesting::Inline, || {
+                self.inline_tree(&tokens[1..close_bracket])
+            }),
+        };
+
+        self.in_link.set(in_link);
+
+        self.found_link(&item, tokens[0].start..destination[close_paren].range().end);
+
+        Some((item, &destination[close_paren + 1..]))
+    }
+
+    /// Find the close bracket of the text and the close parenthesis of the
+    /// destination of a link, which starts with the tokens. The index of the
+    /// parenthesis is counted from the token after the bracket.
+    fn link_brackets(&self, tokens: &[Token]) -> Option<(usize, usize)> {
+        let close_bracket =
+            self.closing(tokens, TokenKind::OpenBracket, TokenKind::CloseBracket)?;
+
+        let destination = &tokens[close_bracket + 1..];
+
+        if destination.first()?.kind != TokenKind::OpenParen {
+            return None;
+        }
+
+        let close_paren = self.closing(destination, TokenKind::OpenParen, TokenKind::CloseParen)?;
+
+        Some((close_bracket, close_paren))
+    }
+
+    /// Parse tokens to comment item, if comments are parsed. The comment ends
+    /// at the first `-->`.
+    fn comment(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        use TokenKind::*;
+
+        if self.config.comments == Comments::Text
+            || !Self::starts_with(tokens, &[Lt, Exclamation, Hyphen, Hyphen])
+        {
+            return None;
+        }
+
+        let close = self.comment_close(&tokens[4..])?;
+        let close = tokens.partition_point(|token| token.start < close.start);
+
+        let text = &self.input[tokens[3].range().end..tokens[close].start];
+
+        Some((InlineItem::Comment(text.into()), &tokens[close + 3..]))
+    }
+
+    /// Find the byte range of the first `-->` in the tokens.
+    fn comment_close(&self, tokens: &[Token]) -> Option<Range<usize>> {
+        use TokenKind::*;
+
+        self.find_cached(&self.comment_close, tokens, 3, |tokens| {
+            Self::starts_with(tokens, &[Hyphen, Hyphen, Gt])
+        })
+    }
+
+    /// Find the byte range of the first `width` tokens that match.
+    ///
+    /// The result of the last search with the cache is reused if the tokens
+    /// are in its range, so it is linear even when searching from many
+    /// positions of the same range.
+    fn find_cached(
+        &self,
+        cache: &Cell<(Range<usize>, Option<Range<usize>>)>,
+        tokens: &[Token],
+        width: usize,
+        matches: impl Fn(&[Token]) -> bool,
+    ) -> Option<Range<usize>> {
+        let range = tokens.first()?.start..tokens.last()?.range().end;
+
+        let (searched, found) = cache.take();
+
+        // Nothing matches in the range searched before its first match, so
+        // that is also the first of the tokens if they are in the range and
+        // do not start after it.
+        let reused = searched.start <= range.start
+            && range.end <= searched.end
+            && found
+                .as_ref()
+                .map_or(true, |found| range.start <= found.start);
+
+        let (searched, found) = match reused {
+            true => (searched, found),
+            false => {
+                let found = tokens
+                    .windows(width)
+                    .find(|tokens| matches(tokens))
+                    .map(|tokens| tokens[0].start..tokens[width - 1].range().end);
+
+                (range.clone(), found)
+            }
+        };
+
+        cache.set((searched, found.clone()));
+
+        found.filter(|found| found.end <= range.end)
+    }
+
+    /// Get the text of the comment, if the tokens are only a comment and
+    /// white spaces.
+    fn block_comment(&self, tokens: &[Token]) -> Option<Cow<'a, str>> {
+        let tokens = Self::trim_white_spaces(tokens);
+
+        match self.comment(tokens)? {
+            (InlineItem::Comment(text), rest)
+                if rest
+                    .iter()
+                    .all(|token| matches!(token.kind, TokenKind::Space | TokenKind::Tab)) =>
+            {
+                Some(text)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse tokens to wiki link item, if
+    /// [`wiki_links`](Parser::wiki_links) is enabled.
+    fn wiki_link(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        let (item, len) = self.wiki_link_item(tokens)?;
+
+        self.found_link(&item, tokens[0].start..tokens[len - 1].range().end);
+
+        Some((item, &tokens[len..]))
+    }
+
+    /// Parse tokens to wiki link item without recording it, and return the
+    /// number of its tokens.
+    fn wiki_link_item(&self, tokens: &[Token]) -> Option<(InlineItem<'a>, usize)> {
+        if !self.config.wiki_links || !Self::starts_with(tokens, &[TokenKind::OpenBracket; 2]) {
+            return None;
+        }
+
+        // Stopping at any bracket keeps a run of `[` linear.
+        let close = tokens.iter().skip(2).position(|token| {
+            matches!(
+                token.kind,
+                TokenKind::OpenBracket | TokenKind::CloseBracket | TokenKind::Break
+            )
+        })? + 2;
+
+        if tokens[close].kind != TokenKind::CloseBracket
+            || tokens.get(close + 1)?.kind != TokenKind::CloseBracket
+        {
+            return None;
+        }
+
+        let source = |tokens: &[Token]| match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => unescape(self.input[first.start..last.range().end].trim()),
+            _ => Cow::Borrowed(""),
+        };
+
+        // An escaped `|` is a part of the target.
+        let content = &tokens[2..close];
+
+        let (target, label) = match content
+            .iter()
+            .position(|token| token.kind == TokenKind::VerticalBar)
+        {
+            Some(bar) => (source(&content[..bar]), Some(source(&content[bar + 1..]))),
+            None => (source(content), None),
+        };
+
+        if target.is_empty() {
+            return None;
+        }
+
+        let item = InlineItem::WikiLink {
+            target,
+            label: label.filter(|label| !label.is_empty()),
+        };
+
+        Some((item, close + 2))
+    }
+
+    /// Record a link or a wiki link, if links are collected.
+    fn found_link(&self, item: &InlineItem, span: Range<usize>) {
+        let Some(links) = &self.links else {
+            return;
+        };
+
+        let link = match item {
+            InlineItem::Link { url, title, tree } => LinkRef {
+                kind: LinkKind::Link,
+                target: url.to_string(),
+                title: title.as_ref().map(|title| title.to_string()),
+                text: tree.plain_text(),
+                span,
+            },
+            InlineItem::WikiLink { target, label } => LinkRef {
+                kind: LinkKind::WikiLink,
+                target: target.to_string(),
+                title: None,
+                text: label.as_ref().unwrap_or(target).to_string(),
+                span,
+            },
+            _ => return,
+        };
+
+        links.borrow_mut().insert(link.span.start, link);
+    }
+
+    /// Parse tokens to footnote item such as `^[text]`, if
+    /// [`inline_footnotes`](Parser::inline_footnotes) is enabled.
+    fn inline_footnote(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if !self.config.inline_footnotes
+            || !Self::starts_with(tokens, &[TokenKind::Caret, TokenKind::OpenBracket])
+        {
+            return None;
+        }
+
+        let close = self.closing(
+            &tokens[1..],
+            TokenKind::OpenBracket,
+            TokenKind::CloseBracket,
+        )? + 1;
+
+        if !self.can_nest(Nesting::Inline, &tokens[0]) {
+            return None;
+        }
+
+        let tree = self.nested(Nesting::Inline, || self.inline_items(&tokens[2..close]));
+
+        Some((InlineItem::Footnote(tree), &tokens[close + 1..]))
+    }
+
+    /// Parse tokens to span item such as `[text]{.class}`, which is a
+    /// bracketed text followed by an attribute block.
+    fn span(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if tokens[0].kind != TokenKind::OpenBracket {
+            return None;
+        }
+
+        let close = self.closing(tokens, TokenKind::OpenBracket, TokenKind::CloseBracket)?;
+
+        // The attribute block is given to the span by `inline_attributes`.
+        self.attribute_block(&tokens[close + 1..])?;
+
+        if !self.can_nest(Nesting::Inline, &tokens[0]) {
+            return None;
+        }
+
+        let tree = self.nested(Nesting::Inline, || self.inline_items(&tokens[1..close]));
+
+        Some((InlineItem::Span(tree), &tokens[close + 1..]))
+    }
+
+    /// Parse an attribute block at the start of the tokens in the same line,
+    /// and return the attributes and the rest.
+    fn attribute_block(&self, tokens: &'b [Token]) -> Option<(Attributes, &'b [Token])> {
+        if tokens.first()?.kind != TokenKind::OpenBrace {
+            return None;
+        }
+
+        let close = self.find_cached(&self.attribute_close, tokens, 1, |tokens| {
+            matches!(tokens[0].kind, TokenKind::CloseBrace | TokenKind::Break)
+        })?;
+        let close = tokens.partition_point(|token| token.start < close.start);
+
+        if tokens[close].kind != TokenKind::CloseBrace {
+            return None;
+        }
+
+        let attributes =
+            Self::parse_attributes(&self.input[tokens[0].range().end..tokens[close].start])?;
+
+        Some((attributes, &tokens[close + 1..]))
+    }
+
+    /// Give the attribute block right after an inline element to it.
+    fn inline_attributes(
+        &self,
+        item: InlineItem<'a>,
+        tokens: &'b [Token],
+    ) -> (InlineItem<'a>, &'b [Token]) {
+        if matches!(
+            item,
+            InlineItem::Text(_)
+                | InlineItem::Escaped(_)
+                | InlineItem::Break
+                | InlineItem::SoftBreak
+        ) {
+            return (item, tokens);
+        }
+
+        match self.attribute_block(tokens) {
+            Some((attributes, rest)) => (InlineItem::Attributed(attributes, Box::new(item)), rest),
+            None => (item, tokens),
+        }
+    }
+
+    /// Find the index of the token which closes the first token in the same
+    /// line, counting nested pairs.
+    ///
+    /// All pairs in the rest of the line are matched at once and cached, so
+    /// a line of many open tokens is scanned once instead of once per token.
+    fn closing(&self, tokens: &[Token], open: TokenKind, close: TokenKind) -> Option<usize> {
+        let first = tokens.first()?;
+
+        let cached = self.matches.borrow().get(&first.start).copied();
+
+        let result = match cached {
+            Some(Match::Unclosed(end)) if tokens.last()?.start > end => None,
+            cached => cached,
+        };
+
+        let result = match result {
+            Some(result) => result,
+            None => {
+                self.match_pairs(tokens, open, close);
+
+                self.matches.borrow()[&first.start]
+            }
+        };
+
+        match result {
+            // The close token can be after the end of the tokens.
+            Match::Closed(start) => tokens.binary_search_by_key(&start, |t| t.start).ok(),
+            Match::Unclosed(_) => None,
+        }
+    }
+
+    /// Match the open token at the start of the tokens and the open tokens
+    /// after it until its close token or the end of the line, and cache the
+    /// results.
+    fn match_pairs(&self, tokens: &[Token], open: TokenKind, close: TokenKind) {
+        let mut matches = self.matches.borrow_mut();
+        let mut stack = vec![];
+
+        // The start of the last token scanned. If the tokens end before the
+        // line, longer tokens of the same line must be scanned again.
+        let mut end = usize::MAX;
+
+        let mut index = 0;
+
+        while let Some(token) = tokens.get(index) {
+            index += 1;
+
+            if token.kind == TokenKind::Break {
+                end = usize::MAX;
+                break;
+            } else if token.kind == open {
+                // The tokens between an open token matched before and its
+                // close token are balanced, so they are skipped. If it is not
+                // closed in the tokens, neither are the open tokens before it.
+                match matches.get(&token.start) {
+                    Some(Match::Closed(start)) => {
+                        match tokens.binary_search_by_key(start, |t| t.start) {
+                            Ok(close) => index = close + 1,
+                            Err(_) => {
+                                end = tokens[tokens.len() - 1].start;
+                                break;
+                            }
+                        }
+                    }
+                    Some(&Match::Unclosed(unclosed))
+                        if unclosed >= tokens[tokens.len() - 1].start =>
+                    {
+                        end = unclosed;
+                        break;
+                    }
+                    _ => stack.push(token.start),
+                }
+            } else if token.kind == close {
+                if let Some(start) = stack.pop() {
+                    matches.insert(start, Match::Closed(token.start));
+                }
+
+                // The rest is matched when it is needed.
+                if stack.is_empty() {
+                    return;
+                }
+            }
+
+            end = tokens[index - 1].start;
+        }
+
+        for start in stack {
+            matches.insert(start, Match::Unclosed(end));
+        }
+    }
+
+    /// Push a break item for the end of the line.
+    ///
+    /// With [`LineBreakStyle::SoftBreak`], the break is hard only if the line
+    /// ends with `\` or at least two spaces, which are removed from the text.
+    fn line_break(&self, tree: &mut InlineTree<'a>) {
+        if self.config.line_break_style == LineBreakStyle::HardBreak {
+            tree.root.push(InlineItem::Break);
+            return;
+        }
+
+        let mut hard = false;
+
+        if let Some(InlineItem::Text(text)) = tree.root.last_mut() {
+            let trimmed = text.trim_end_matches(' ');
+
+            let len = match trimmed.strip_suffix('\\') {
+                Some(rest) => {
+                    hard = true;
+                    rest.len()
+                }
+                None => {
+                    hard = text.len() - trimmed.len() >= 2;
+                    trimmed.len()
+                }
+            };
+
+            match text {
+                Cow::Borrowed(text) => *text = &text[..len],
+                Cow::Owned(text) => text.truncate(len),
+            }
+
+            if text.is_empty() {
+                tree.root.pop();
+            }
+        }
+
+        tree.root.push(match hard {
+            true => InlineItem::Break,
+            false => InlineItem::SoftBreak,
+        });
+    }
+
+    /// Parse tokens to break item.
+    fn r#break(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
+        if tokens[0].kind != TokenKind::Break {
+            return None;
+        }
+
+        Some((InlineItem::Break, &tokens[1..]))
+    }
+}
+
+/// Split the info string of a fenced code block, such as
+/// `rust,linenos,hl_lines="2-3"`, to the language and the other words as keys
+/// and values.
+///
+/// Words are separated by commas or white spaces, and a value can be quoted.
+/// The first word is the language if it has no value. A word without a value
+/// has an empty value, and a word whose key is not a name of letters, digits,
+/// `-` and `_` is skipped.
+fn code_info(info: &str) -> (Option<&str>, Vec<(String, String)>) {
+    let is_separator = |c: char| c == ',' || c.is_whitespace();
+
+    let mut lang = None;
+    let mut pairs = vec![];
+    let mut rest = info;
+    let mut first = true;
+
+    loop {
+        rest = rest.trim_start_matches(is_separator);
+
+        if rest.is_empty() {
+            break;
+        }
+
+        let end = rest
+            .find(|c| c == '=' || is_separator(c))
+            .unwrap_or(rest.len());
+        let (key, after) = rest.split_at(end);
+
+        let value = match after.strip_prefix('=') {
+            Some(after) => {
+                let (value, after) = match after.strip_prefix('"') {
+                    Some(quoted) => match quoted.split_once('"') {
+                        Some((value, after)) => (value, after),
+                        None => (quoted, ""),
+                    },
+                    None => after.split_at(after.find(is_separator).unwrap_or(after.len())),
+                };
+
+                rest = after;
+                Some(value)
+            }
+            None => {
+                rest = after;
+                None
+            }
+        };
+
+        let is_name = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+
+        match value {
+            None if first => lang = Some(key),
+            _ if is_name => pairs.push((key.to_string(), value.unwrap_or("").to_string())),
+            _ => {}
+        }
+
+        first = false;
+    }
+
+    (lang, pairs)
+}
+
+/// Get the number of a letter or a roman numeral in a list marker.
+///
+/// The counter of the list is preferred, so `i` is a letter in a list of
+/// letters. Without it, `i` and numerals of two or more digits are roman.
+fn fancy_number(text: &str, list: Option<Counter>) -> Option<(u64, Counter)> {
+    let upper = text.chars().all(|c| c.is_ascii_uppercase());
+
+    if !upper && !text.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+
+    let letter = match text.as_bytes() {
+        [c] => Some((c.to_ascii_lowercase() - b'a') as u64 + 1),
+        _ => None,
+    };
+
+    let lower = text.to_ascii_lowercase();
+
+    let roman = lower
+        .chars()
+        .map(|c| match c {
+            'i' => Some(1),
+            'v' => Some(5),
+            'x' => Some(10),
+            'l' => Some(50),
+            'c' => Some(100),
+            'd' => Some(500),
+            'm' => Some(1000),
+            _ => None,
+        })
+        .collect::<Option<Vec<i64>>>()
+        .map(|digits| {
+            // A digit before a greater digit is subtracted, as in `iv`.
+            digits.iter().enumerate().fold(0, |value, (index, &digit)| {
+                match digits.get(index + 1) {
+                    Some(&next) if next > digit => value - digit,
+                    _ => value + digit,
+                }
+            })
+        })
+        // A numeral is roman only if it is written in the usual way.
+        .filter(|&value| value > 0 && Counter::LowerRoman.format(value as usize) == lower)
+        .map(|value| value as u64);
+
+    let (letter_counter, roman_counter) = match upper {
+        true => (Counter::UpperAlpha, Counter::UpperRoman),
+        false => (Counter::LowerAlpha, Counter::LowerRoman),
+    };
+
+    match (list, roman, letter) {
+        (Some(counter), _, Some(value)) if counter == letter_counter => {
+            Some((value, letter_counter))
+        }
+        (Some(counter), Some(value), _) if counter == roman_counter => Some((value, roman_counter)),
+        (Some(_), _, _) => None,
+        (None, Some(value), _) if text.len() > 1 || lower == "i" => Some((value, roman_counter)),
+        (None, _, Some(value)) => Some((value, letter_counter)),
+        (None, ..) => None,
+    }
+}
+
+/// Remove the backslashes of escaped ASCII punctuation characters.
+fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('\\') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(next) if c == '\\' && next.is_ascii_punctuation() => {
+                output.push(*next);
+                chars.next();
+            }
+            _ => output.push(c),
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::lexer::lex;
+
+    fn lex_to_vec(input: &str) -> Vec<Token> {
+        lex(input).collect()
+    }
+
+    #[test]
+    fn test_config_from_str() {
+        for ending in [ParagraphEnding::AllowSoftBreak, ParagraphEnding::HardBreak] {
+            assert_eq!(ending.to_string().parse(), Ok(ending));
+        }
+
+        for ending in [
+            HeadlineEnding::SoftBreak,
+            HeadlineEnding::AllowSoftBreak,
+            HeadlineEnding::HardBreak,
+        ] {
+            assert_eq!(ending.to_string().parse(), Ok(ending));
+        }
+
+        for dialect in [Dialect::NoteMark, Dialect::CommonMark] {
+            assert_eq!(dialect.to_string().parse(), Ok(dialect));
+        }
+
+        for style in [LineBreakStyle::HardBreak, LineBreakStyle::SoftBreak] {
+            assert_eq!(style.to_string().parse(), Ok(style));
+        }
+
+        for interrupter in Interrupter::ALL {
+            assert_eq!(interrupter.to_string().parse(), Ok(interrupter));
+        }
+
+        for rule in [EmphasisRule::Nearest, EmphasisRule::Flanking] {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+
+        for rule in [IndentRule::Strict, IndentRule::Loose] {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+
+        for style in [IndentStyle::Space(4), IndentStyle::Tab, IndentStyle::Both] {
+            assert_eq!(style.to_string().parse(), Ok(style));
+        }
+
+        assert_eq!("space:2".parse(), Ok(IndentStyle::Space(2)));
+        assert_eq!(
+            "space:x".parse::<IndentStyle>(),
+            Err(crate::error::ParseConfigError::new(
+                "IndentStyle",
+                "space:x"
+            ))
+        );
+        assert!("HardBreak".parse::<ParagraphEnding>().is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = "# Hello *World*!\n\nparagraph\n\n";
+
+        let tokens = lex(input);
+
+        let tree = Parser::new().parse(input, tokens);
+
+        assert_eq!(
+            tree,
+            MarkdownTree {
+                root: BlockTree {
+                    root: vec![
+                        BlockItem::Headline(
+                            1,
+                            InlineTree {
+                                root: vec![
+                                    InlineItem::Text("Hello ".into()),
+                                    InlineItem::Italic(InlineTree {
+                                        root: vec![InlineItem::Text("World".into())]
+                                    }),
+                                    InlineItem::Text("!".into()),
+                                ]
+                            }
+                        ),
+                        BlockItem::Paragraph(InlineTree {
+                            root: vec![InlineItem::Text("paragraph".into())]
+                        }),
+                    ]
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_indent_level_tab_stop() {
+        let levels = ["\t", "  \t", "   \t", "    \t", "\t  ", "     "]
+            .into_iter()
+            .map(|indent| {
+                let tokens = lex_to_vec(&format!("{indent}a"));
+
+                (
+                    Executor::indent_level(&tokens, IndentStyle::Both, 4),
+                    Executor::indent_level(&tokens, IndentStyle::Both, 2),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            levels,
+            vec![
+                ((1, 0), (1, 0)),
+                ((1, 0), (2, 0)),
+                ((1, 0), (2, 0)),
+                ((2, 0), (3, 0)),
+                ((1, 2), (2, 0)),
+                ((1, 1), (2, 1)),
+            ]
+        );
+
+        let markdown = crate::Markdown::default()
+            .parser(Parser::default().list_indent_style(IndentStyle::Both))
+            .markdown_stringifier(
+                crate::layer::formatter::MarkdownStringifier::default()
+                    .list_indent_style(IndentStyle::Both),
+            );
+
+        assert_eq!(
+            markdown.reformat("- a\n \t- b\n    c\n\n\t  d"),
+            "- a\n\t- b\n\tc\n\n\td\n"
+        );
+    }
+
+    #[test]
+    fn test_reduce_indent() {
+        let input = "  # Hello *World*!\n\nparagraph\n\n";
+        let tokens = lex_to_vec(input);
+
+        let result = Executor::reduce_indent(&tokens, IndentStyle::Space(2), 4, true)
+            .into_iter()
+            .map(|token| token.kind)
+            .collect::<Vec<_>>();
+
+        let expected = "# Hello *World*!\n\nparagraph\n\n";
+        let expected_tokens = lex(expected).map(|token| token.kind).collect::<Vec<_>>();
+
+        assert_eq!(result, expected_tokens);
+    }
+
+    #[test]
+    fn test_block_tree() {
+        let input = "# Hello *World*!\n\nparagraph\n\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let tree = parser.block_tree(&tokens);
+
+        assert_eq!(
+            tree,
+            BlockTree {
+                root: vec![
+                    BlockItem::Headline(
+                        1,
+                        InlineTree {
+                            root: vec![
+                                InlineItem::Text("Hello ".into()),
+                                InlineItem::Italic(InlineTree {
+                                    root: vec![InlineItem::Text("World".into())]
+                                }),
+                                InlineItem::Text("!".into()),
+                            ]
+                        }
+                    ),
+                    BlockItem::Paragraph(InlineTree {
+                        root: vec![InlineItem::Text("paragraph".into())]
+                    }),
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_paragraph() {
+        let input = "Hello *World*!\n\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.paragraph(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::Paragraph(InlineTree {
+                root: vec![
+                    InlineItem::Text("Hello ".into()),
+                    InlineItem::Italic(InlineTree {
+                        root: vec![InlineItem::Text("World".into())]
+                    }),
+                    InlineItem::Text("!".into()),
+                ]
+            })
+        );
+        assert_eq!(rest.len(), 0);
+
+        let input = "Hello\n";
+
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.paragraph(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::Paragraph(InlineTree {
+                root: vec![InlineItem::Text("Hello".into())]
+            })
+        );
+
+        assert_eq!(rest.len(), 0);
+    }
+
+    #[test]
+    fn test_paragraph_before_not_paragraph() {
+        let input = "Hello *World*!\n# Hello\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(
+            input,
+            Parser::new().paragraph_ending(ParagraphEnding::AllowSoftBreak),
+        );
+
+        let (item, rest) = parser.paragraph(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::Paragraph(InlineTree {
+                root: vec![
+                    InlineItem::Text("Hello ".into()),
+                    InlineItem::Italic(InlineTree {
+                        root: vec![InlineItem::Text("World".into())]
+                    }),
+                    InlineItem::Text("!".into()),
+                ]
+            })
+        );
+
+        assert_eq!(rest.len(), 4);
+    }
+
+    #[test]
+    fn test_headline() {
+        let input = "###  Hello *World*!\n\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.headline(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::Headline(
+                3,
+                InlineTree {
+                    root: vec![
+                        InlineItem::Text("Hello ".into()),
+                        InlineItem::Italic(InlineTree {
+                            root: vec![InlineItem::Text("World".into())]
+                        }),
+                        InlineItem::Text("!".into()),
+                    ]
+                }
+            )
+        );
+
+        assert_eq!(rest.len(), 0);
+    }
+
+    #[test]
+    fn test_headline2() {
+        let input = "# Hello World!\n# Goodbye\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, _) = parser.headline(&tokens).unwrap();
+
+        assert_ne!(
+            item,
+            BlockItem::Headline(
+                1,
+                InlineTree {
+                    root: vec![InlineItem::Text("Hello World!".into())]
+                }
+            )
+        );
+
+        let parser = Executor::with_config(
+            input,
+            Parser::default().headline_ending(HeadlineEnding::AllowSoftBreak),
+        );
+
+        let (item, _) = parser.headline(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::Headline(
+                1,
+                InlineTree {
+                    root: vec![InlineItem::Text("Hello World!".into())]
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_headline_attributes() {
+        let input = "# Hello *World* {#hello .big data-x=\"a b\"}\n\n# Not {attributes}\n";
+        let tokens = lex(input);
+        let parser = Parser::new();
+
+        let tree = parser.parse(input, tokens);
+
+        assert_eq!(
+            tree,
+            MarkdownTree {
+                root: BlockTree {
+                    root: vec![
+                        BlockItem::Attributed(
+                            Attributes {
+                                id: Some("hello".into()),
+                                classes: vec!["big".into()],
+                                attrs: vec![("data-x".into(), "a b".into())],
+                            },
+                            Box::new(BlockItem::Headline(
+                                1,
+                                InlineTree {
+                                    root: vec![
+                                        InlineItem::Text("Hello ".into()),
+                                        InlineItem::Italic(InlineTree {
+                                            root: vec![InlineItem::Text("World".into())]
+                                        }),
+                                    ]
+                                }
+                            ))
+                        ),
+                        BlockItem::Headline(
+                            1,
+                            InlineTree {
+                                root: vec![InlineItem::Text("Not {attributes}".into())]
+                            }
+                        ),
+                    ]
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        assert_eq!(
+            Executor::parse_attributes(" #a .b .c  k=v q=\"x y\" "),
+            Some(Attributes {
+                id: Some("a".into()),
+                classes: vec!["b".into(), "c".into()],
+                attrs: vec![("k".into(), "v".into()), ("q".into(), "x y".into())],
+            })
+        );
+        assert_eq!(Executor::parse_attributes(""), None);
+        assert_eq!(Executor::parse_attributes("#"), None);
+        assert_eq!(Executor::parse_attributes("word"), None);
+        assert_eq!(Executor::parse_attributes("k=\"open"), None);
+        assert_eq!(Executor::parse_attributes("k=\"v\"x"), None);
+    }
+
+    #[test]
+    fn test_bullet_list() {
+        let input = "- Hello *World*!\n- Hello *World*!\n\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.bullet_list(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::BulletList(ListTree {
+                root: vec![
+                    ListItem {
+                        name: InlineTree {
+                            root: vec![
+                                InlineItem::Text("Hello ".into()),
+                                InlineItem::Italic(InlineTree {
+                                    root: vec![InlineItem::Text("World".into())]
+                                }),
+                                InlineItem::Text("!".into()),
+                            ]
+                        },
+                        children: vec![]
+                    },
+                    ListItem {
+                        name: InlineTree {
+                            root: vec![
+                                InlineItem::Text("Hello ".into()),
+                                InlineItem::Italic(InlineTree {
+                                    root: vec![InlineItem::Text("World".into())]
+                                }),
+                                InlineItem::Text("!".into()),
+                            ]
+                        },
+                        children: vec![]
+                    },
+                ]
+            }),
+        );
+
+        assert_eq!(rest.len(), 0);
+
+        let input = "- Hello!\n  - Fooo!\nHappy\n  - hogee!\n- Good\njobs\n# End\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.bullet_list(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::BulletList(ListTree {
+                root: vec![
+                    ListItem {
+                        name: InlineTree {
+                            root: vec![InlineItem::Text("Hello!".into())]
+                        },
+                        children: vec![BlockItem::BulletList(ListTree {
+                            root: vec![
+                                ListItem {
+                                    name: InlineTree {
+                                        root: vec![
+                                            InlineItem::Text("Fooo!".into()),
+                                            InlineItem::Break,
+                                            InlineItem::Text("Happy".into())
+                                        ]
+                                    },
+                                    children: vec![]
+                                },
+                                ListItem {
+                                    name: InlineTree {
+                                        root: vec![InlineItem::Text("hogee!".into())]
+                                    },
+                                    children: vec![]
+                                }
+                            ]
+                        }),]
+                    },
+                    ListItem {
+                        name: InlineTree {
+                            root: vec![
+                                InlineItem::Text("Good".into()),
+                                InlineItem::Break,
+                                InlineItem::Text("jobs".into())
+                            ]
+                        },
+                        children: vec![]
+                    },
+                ]
+            }),
+        );
+
+        assert_eq!(rest.len(), 4);
+    }
+
+    #[test]
+    fn ordered_list() {
+        let input = "1. Hello!\n  1. Fooo!\nHappy\n  1. hogee!\n1. Good\njobs\n# End\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
+
+        let (item, rest) = parser.ordered_list(&tokens).unwrap();
+
+        assert_eq!(
+            item,
+            BlockItem::OrderedList(
+                1,
+                ListMarker::default(),
+                ListTree {
+                    root: vec![
+                        ListItem {
+                            name: InlineTree {
+                                root: vec![InlineItem::Text("Hello!".into())]
+                            },
+                            children: vec![BlockItem::OrderedList(
+                                1,
+                                ListMarker::default(),
+                                ListTree {
+                                    root: vec![
+                                        ListItem {
+                                            name: InlineTree {
+                                                root: vec![
+                                                    InlineItem::Text("Fooo!".into()),
+                                                    InlineItem::Break,
+                                                    InlineItem::Text("Happy".into())
+                                                ]
+                                            },
+                                            children: vec![]
+                                        },
+                                        ListItem {
+                                            name: InlineTree {
+                                                root: vec![InlineItem::Text("hogee!".into())]
+                                            },
+                                            children: vec![]
+                                        }
+                                    ]
+                                }
+                            ),]
+                        },
+                        ListItem {
+                            name: InlineTree {
+                                root: vec![
+                                    InlineItem::Text("Good".into()),
+                                    InlineItem::Break,
+                                    InlineItem::Text("jobs".into())
+                                ]
+                            },
+                            children: vec![]
+                        },
+                    ]
+                }
+            ),
+        );
+
+        assert_eq!(rest.len(), 4);
+    }
+
+    #[test]
+    fn test_ordered_list_start() {
+        let markdown = crate::Markdown::default();
+
+        assert_eq!(
+            markdown.execute("123456789. a"),
+            "<ol start=\"123456789\"><li>a</li></ol>"
+        );
+        assert_eq!(markdown.execute("1234567890. a"), "<p>1234567890. a</p>");
+        assert_eq!(
+            markdown.execute("99999999999999999999999. a"),
+            "<p>99999999999999999999999. a</p>"
+        );
+    }
+
+    #[test]
+    fn test_blockquote() {
+        let input = ">Hello\n>\n>>Yeah\nHappy";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        let mut indented_tokens = vec![];
+        let (item, rest) = parser.blockquote(&tokens).unwrap();
 
-        while !rest.is_empty() {
-            if rest.get(0)?.kind != TokenKind::Gt {
-                break;
-            }
+        assert_eq!(
+            item,
+            BlockItem::BlockQuote(BlockTree {
+                root: vec![
+                    BlockItem::Paragraph(InlineTree {
+                        root: vec![InlineItem::Text("Hello".into())]
+                    }),
+                    BlockItem::BlockQuote(BlockTree {
+                        root: vec![BlockItem::Paragraph(InlineTree {
+                            root: vec![InlineItem::Text("Yeah".into())]
+                        }),]
+                    }),
+                ]
+            })
+        );
 
-            let (input, new_rest) = Self::get_line(&rest[1..], false);
+        assert_eq!(rest.len(), 1);
+    }
 
-            let input2 = if self.maybe_block_item(input, true) {
-                Self::align_indent(input, IndentStyle::Space(2), IndentRule::Loose)
-            } else {
-                Self::trim_start(input, TokenKind::Space)
-            };
+    #[test]
+    fn test_inline_tree() {
+        let input = "Hello *World*!\n";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-            indented_tokens.extend_from_slice(input2);
+        let tree = parser.inline_tree(&tokens);
 
-            if let Some(token) = rest.get(1 + input.len()) {
-                indented_tokens.push(*token);
+        assert_eq!(
+            tree,
+            InlineTree {
+                root: vec![
+                    InlineItem::Text("Hello ".into()),
+                    InlineItem::Italic(InlineTree {
+                        root: vec![InlineItem::Text("World".into())]
+                    }),
+                    InlineItem::Text("!".into()),
+                    InlineItem::Break,
+                ]
             }
+        );
+    }
 
-            rest = new_rest;
-        }
-
-        let tree = self.block_tree(&indented_tokens);
+    #[test]
+    fn test_italic() {
+        let input = r"*Hello*";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        Some((BlockItem::BlockQuote(tree), rest))
-    }
+        parser.match_emphasis(&tokens);
 
-    /// Judge if tokens is maybe block item.
-    fn maybe_block_item(&self, tokens: &[Token], trim: bool) -> bool {
-        let tokens = if trim {
-            Self::trim_white_spaces(tokens)
-        } else {
-            tokens
-        };
+        let (item, rest) = parser.emphasis(&tokens).unwrap();
 
-        if self.headline(tokens).is_some() {
-            return true;
-        }
+        assert_eq!(
+            item,
+            InlineItem::Italic(InlineTree {
+                root: vec![InlineItem::Text("Hello".into())]
+            })
+        );
+        assert_eq!(rest.len(), 0);
 
-        if tokens.is_empty() {
-            return false;
-        }
+        let input = "*";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        if tokens[0].kind == TokenKind::Gt {
-            return true;
-        }
+        parser.match_emphasis(&tokens);
 
-        if tokens.len() < 2 {
-            return false;
-        }
+        assert_eq!(parser.emphasis(&tokens), None);
+    }
 
-        if tokens[0].kind == TokenKind::Hyphen && tokens[1].kind == TokenKind::Space {
-            return true;
-        }
+    #[test]
+    fn test_strong() {
+        let input = r"**Hello**";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        if tokens.len() < 3 {
-            return false;
-        }
+        parser.match_emphasis(&tokens);
 
-        if (tokens[0].kind == TokenKind::Text
-            && tokens[1].kind == TokenKind::Dot
-            && tokens[2].kind == TokenKind::Space)
-            && self.input[tokens[0].range()]
-                .chars()
-                .all(|c| c.is_ascii_digit())
-        {
-            return true;
-        }
+        let (item, rest) = parser.emphasis(&tokens).unwrap();
 
-        false
+        assert_eq!(
+            item,
+            InlineItem::Strong(InlineTree {
+                root: vec![InlineItem::Text("Hello".into())]
+            })
+        );
+        assert_eq!(rest.len(), 0);
     }
 
-    /// Get tokens until maybe block item.
-    fn get_until_maybe_block_item(&self, tokens: &'b [Token]) -> (&'b [Token], &'b [Token]) {
-        let mut iter = Self::trim_end(tokens, TokenKind::Break).iter().enumerate();
+    #[test]
+    fn test_line_break() {
+        let input = "a \nb  \n  \\\nc\\\\\nd";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(
+            input,
+            Parser::new().line_break_style(LineBreakStyle::SoftBreak),
+        );
 
-        let (front, back) = loop {
-            if let Some((index, _)) = iter.find(|(_, token)| token.kind == TokenKind::Break) {
-                if self.maybe_block_item(&tokens[index + 1..], false) {
-                    break (&tokens[..index], &tokens[index + 1..]);
-                } else if tokens[index].kind == TokenKind::Break
-                    && tokens[index + 1].kind == TokenKind::Break
-                {
-                    break (&tokens[..index], &tokens[index + 2..]);
-                }
-            } else {
-                break (tokens, &[]);
+        assert_eq!(
+            parser.inline_tree(&tokens),
+            InlineTree {
+                root: vec![
+                    InlineItem::Text("a".into()),
+                    InlineItem::SoftBreak,
+                    InlineItem::Text("b".into()),
+                    InlineItem::Break,
+                    InlineItem::Text("  ".into()),
+                    InlineItem::Break,
+                    InlineItem::Text("c".into()),
+                    InlineItem::Escaped("\\".into()),
+                    InlineItem::SoftBreak,
+                    InlineItem::Text("d".into()),
+                ]
             }
-        };
+        );
 
-        (
-            Self::trim_end(front, TokenKind::Break),
-            Self::trim_start(back, TokenKind::Break),
-        )
+        let parser = Executor::new(input);
+
+        assert_eq!(parser.inline_tree(&tokens).root[1], InlineItem::Break);
     }
-}
 
-/// # Functions for building inline tree.
-impl<'a, 'b> Executor<'a> {
-    /// Parse tokens to inline tree.
-    ///
-    /// This function parses all tokens to inline tree.
-    /// So confirm that tokens does not include block items.
-    fn inline_tree(&self, tokens: &[Token]) -> InlineTree<'a> {
-        let mut tree = InlineTree { root: vec![] };
+    #[test]
+    fn test_link() {
+        let input = r#"[**a** [b]](/x_(1) "T t") [c](d
+e) [f] (g) [h](i"#;
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        let mut rest = tokens;
+        let tree = parser.inline_tree(&tokens);
 
-        'root: while !rest.is_empty() {
-            for f in &[Self::strong, Self::italic, Self::r#break] {
-                if let Some((item, new_rest)) = f(self, rest) {
-                    tree.root.push(item);
-                    rest = new_rest;
-                    continue 'root;
-                }
+        assert_eq!(
+            tree,
+            InlineTree {
+                root: vec![
+                    InlineItem::Link {
+                        url: "/x_(1)".into(),
+                        title: Some("T t".into()),
+                        tree: InlineTree {
+                            root: vec![
+                                InlineItem::Strong(InlineTree {
+                                    root: vec![InlineItem::Text("a".into())]
+                                }),
+                                InlineItem::Text(" [b]".into()),
+                            ]
+                        },
+                    },
+                    InlineItem::Text(" [c](d".into()),
+                    InlineItem::Break,
+                    InlineItem::Text("e) [f] (g) [h](i".into()),
+                ]
             }
+        );
 
-            if let Some(InlineItem::Text(text)) = tree.root.last_mut() {
-                *text += &self.input[rest[0].range()];
-                rest = &rest[1..];
-                continue;
-            } else {
-                tree.root
-                    .push(InlineItem::Text(self.input[rest[0].range()].into()));
-                rest = &rest[1..];
-                continue;
-            }
-        }
+        // Pairs are matched within the tokens, even if they are cached, and
+        // emphasis does not cross the text of a link.
+        let input = "*[a* b](c) [[d](e) [f]((g)";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::new(input);
 
-        tree
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            [
+                InlineItem::Text("*".into()),
+                InlineItem::Link {
+                    url: "c".into(),
+                    title: None,
+                    tree: InlineTree {
+                        root: vec![InlineItem::Text("a* b".into())]
+                    },
+                },
+                InlineItem::Text(" [".into()),
+                InlineItem::Link {
+                    url: "e".into(),
+                    title: None,
+                    tree: InlineTree {
+                        root: vec![InlineItem::Text("d".into())]
+                    },
+                },
+                InlineItem::Text(" [f]((g)".into()),
+            ]
+        );
     }
 
-    /// Parse tokens to italic item.
-    fn italic(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
-        if tokens[0].kind != TokenKind::Star {
-            return None;
-        }
+    #[test]
+    fn test_wiki_link() {
+        let input = "[[ A b | *c* ]] [[d]] [[|e]] [[f [g]] [[h]";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().wiki_links(true));
 
-        if tokens.get(1)?.kind == TokenKind::Star {
-            return None;
-        }
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::WikiLink {
+                    target: "A b".into(),
+                    label: Some("*c*".into()),
+                },
+                InlineItem::Text(" ".into()),
+                InlineItem::WikiLink {
+                    target: "d".into(),
+                    label: None,
+                },
+                InlineItem::Text(" [[|e]] [[f [g]] [[h]".into()),
+            ]
+        );
 
-        let (index, _) = tokens
-            .iter()
-            .enumerate()
-            .skip(1)
-            .find(|(_, token)| token.kind == TokenKind::Star)?;
+        // `[[d]]` is text without the setting.
+        assert_eq!(
+            Executor::new(input).inline_tree(&tokens).root[2],
+            InlineItem::Text(" ]] [[d]] [[|e]] [[f [g]] [[h]".into())
+        );
 
-        let tree = self.inline_tree(&tokens[1..index]);
+        let markdown = crate::Markdown::default().parser(Parser::new().wiki_links(true));
 
-        Some((InlineItem::Italic(tree), &tokens[index + 1..]))
+        assert_eq!(
+            markdown.reformat(input),
+            format!("[[A b|*c*]]{}\n", &input[15..])
+        );
+
+        // `*` in a wiki link is not paired with emphasis around it.
+        assert_eq!(
+            markdown.execute("*a [[b|*]] c*"),
+            "<p><em>a <a class=\"wiki-link\" href=\"b\">*</a> c</em></p>"
+        );
     }
 
-    /// Parse tokens to strong item.
-    fn strong(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
-        if tokens[0].kind != TokenKind::Star || tokens.get(1)?.kind != TokenKind::Star {
-            return None;
-        }
+    #[test]
+    fn test_tags() {
+        let input = "#a (@b) c#d [#e](f) *#g*\\#h #あ-1 #12 @";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().hashtags(true).mentions(true));
 
-        if tokens.get(2)?.kind == TokenKind::Star {
-            return None;
-        }
+        let tag = |kind, name: &'static str| InlineItem::Tag {
+            kind,
+            name: name.into(),
+        };
 
-        let (index, _) = tokens
-            .windows(2)
-            .enumerate()
-            .skip(1)
-            .find(|(_, t)| t[0].kind == TokenKind::Star && t[1].kind == TokenKind::Star)?;
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                tag(TagKind::Hashtag, "a"),
+                InlineItem::Text(" (".into()),
+                tag(TagKind::Mention, "b"),
+                InlineItem::Text(") c#d ".into()),
+                InlineItem::Link {
+                    url: "f".into(),
+                    title: None,
+                    tree: InlineTree {
+                        root: vec![InlineItem::Text("#e".into())]
+                    },
+                },
+                InlineItem::Text(" ".into()),
+                InlineItem::Italic(InlineTree {
+                    root: vec![tag(TagKind::Hashtag, "g")]
+                }),
+                InlineItem::Escaped("#".into()),
+                InlineItem::Text("h ".into()),
+                tag(TagKind::Hashtag, "あ-1"),
+                InlineItem::Text(" #12 @".into()),
+            ]
+        );
 
-        let tree = self.inline_tree(&tokens[2..index]);
+        let parser = Executor::with_config(input, Parser::new().mentions(true));
 
-        Some((InlineItem::Strong(tree), &tokens[index + 2..]))
-    }
+        assert_eq!(
+            parser.inline_tree(&tokens).root[..2],
+            [InlineItem::Text("#a (".into()), tag(TagKind::Mention, "b"),]
+        );
 
-    /// Parse tokens to break item.
-    fn r#break(&self, tokens: &'b [Token]) -> Option<(InlineItem<'a>, &'b [Token])> {
-        if tokens[0].kind != TokenKind::Break {
-            return None;
-        }
+        // An escaped character is before the text like an unescaped one.
+        let input = "\\>@a \\(@b";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().mentions(true));
 
-        Some((InlineItem::Break, &tokens[1..]))
-    }
-}
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::Escaped(">".into()),
+                InlineItem::Text("@a ".into()),
+                InlineItem::Escaped("(".into()),
+                tag(TagKind::Mention, "b"),
+            ]
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::layer::lexer::lex;
+        // Combining marks are kept in names, but a name cannot start with one.
+        let input = "#cafe\u{301} @हिन्दी #\u{301}a #日本😀";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().hashtags(true).mentions(true));
 
-    fn lex_to_vec(input: &str) -> Vec<Token> {
-        lex(input).collect()
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                tag(TagKind::Hashtag, "cafe\u{301}"),
+                InlineItem::Text(" ".into()),
+                tag(TagKind::Mention, "हिन्दी"),
+                InlineItem::Text(" #\u{301}a ".into()),
+                tag(TagKind::Hashtag, "日本"),
+                InlineItem::Text("😀".into()),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse() {
-        let input = "# Hello *World*!\n\nparagraph\n\n";
-
-        let tokens = lex(input);
-
-        let tree = Parser::new().parse(input, tokens);
+    fn test_highlight() {
+        let input = "==a *b*== = ==c ==d== ===e== ==f\ng==";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().highlight(true));
 
         assert_eq!(
-            tree,
-            MarkdownTree {
-                root: BlockTree {
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::Highlight(InlineTree {
                     root: vec![
-                        BlockItem::Headline(
-                            1,
-                            InlineTree {
-                                root: vec![
-                                    InlineItem::Text("Hello ".into()),
-                                    InlineItem::Italic(InlineTree {
-                                        root: vec![InlineItem::Text("World".into())]
-                                    }),
-                                    InlineItem::Text("!".into()),
-                                ]
-                            }
-                        ),
-                        BlockItem::Paragraph(InlineTree {
-                            root: vec![InlineItem::Text("paragraph".into())]
+                        InlineItem::Text("a ".into()),
+                        InlineItem::Italic(InlineTree {
+                            root: vec![InlineItem::Text("b".into())]
                         }),
                     ]
-                }
-            }
+                }),
+                InlineItem::Text(" = ".into()),
+                InlineItem::Highlight(InlineTree {
+                    root: vec![InlineItem::Text("c ==d".into())]
+                }),
+                InlineItem::Text(" =".into()),
+                InlineItem::Highlight(InlineTree {
+                    root: vec![InlineItem::Text("e".into())]
+                }),
+                InlineItem::Text(" ==f".into()),
+                InlineItem::Break,
+                InlineItem::Text("g==".into()),
+            ]
+        );
+
+        assert_eq!(
+            Executor::new(input).inline_tree(&tokens).root[0],
+            InlineItem::Text("==a ".into())
+        );
+
+        // The `==` inside the emphasis is matched with the `==` after it.
+        let input = "*==a* b==";
+        let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().highlight(true));
+
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::Italic(InlineTree {
+                    root: vec![InlineItem::Text("==a".into())]
+                }),
+                InlineItem::Text(" b==".into()),
+            ]
         );
     }
 
     #[test]
-    fn test_reduce_indent() {
-        let input = "  # Hello *World*!\n\nparagraph\n\n";
+    fn test_inline_footnote() {
+        let input = "a^[b *c*] ^[d^[e]] ^ [f] ^[g\nh]";
         let tokens = lex_to_vec(input);
+        let parser = Executor::with_config(input, Parser::new().inline_footnotes(true));
 
-        let result = Executor::reduce_indent(&tokens, IndentStyle::Space(2), true)
-            .into_iter()
-            .map(|token| token.kind)
-            .collect::<Vec<_>>();
-
-        let expected = "# Hello *World*!\n\nparagraph\n\n";
-        let expected_tokens = lex(expected)
-            .into_iter()
-            .map(|token| token.kind)
-            .collect::<Vec<_>>();
+        assert_eq!(
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::Text("a".into()),
+                InlineItem::Footnote(InlineTree {
+                    root: vec![
+                        InlineItem::Text("b ".into()),
+                        InlineItem::Italic(InlineTree {
+                            root: vec![InlineItem::Text("c".into())]
+                        }),
+                    ]
+                }),
+                InlineItem::Text(" ".into()),
+                InlineItem::Footnote(InlineTree {
+                    root: vec![
+                        InlineItem::Text("d".into()),
+                        InlineItem::Footnote(InlineTree {
+                            root: vec![InlineItem::Text("e".into())]
+                        }),
+                    ]
+                }),
+                InlineItem::Text(" ^ [f] ^[g".into()),
+                InlineItem::Break,
+                InlineItem::Text("h]".into()),
+            ]
+        );
 
-        assert_eq!(result, expected_tokens);
+        assert_eq!(
+            Executor::new(input).inline_tree(&tokens).root[0],
+            InlineItem::Text("a^[b ".into())
+        );
     }
 
     #[test]
-    fn test_block_tree() {
-        let input = "# Hello *World*!\n\nparagraph\n\n";
+    fn test_inline_attributes() {
+        let input = "*a*{.b #c} [d]{k=\"v w\"} [e] {.f} **g**{h}";
         let tokens = lex_to_vec(input);
         let parser = Executor::new(input);
 
-        let tree = parser.block_tree(&tokens);
+        let attributes = |input| Executor::parse_attributes(input).unwrap();
 
         assert_eq!(
-            tree,
-            BlockTree {
-                root: vec![
-                    BlockItem::Headline(
-                        1,
-                        InlineTree {
-                            root: vec![
-                                InlineItem::Text("Hello ".into()),
-                                InlineItem::Italic(InlineTree {
-                                    root: vec![InlineItem::Text("World".into())]
-                                }),
-                                InlineItem::Text("!".into()),
-                            ]
-                        }
-                    ),
-                    BlockItem::Paragraph(InlineTree {
-                        root: vec![InlineItem::Text("paragraph".into())]
-                    }),
-                ]
-            }
+            parser.inline_tree(&tokens).root,
+            vec![
+                InlineItem::Attributed(
+                    attributes(".b #c"),
+                    Box::new(InlineItem::Italic(InlineTree {
+                        root: vec![InlineItem::Text("a".into())]
+                    })),
+                ),
+                InlineItem::Text(" ".into()),
+                InlineItem::Attributed(
+                    attributes("k=\"v w\""),
+                    Box::new(InlineItem::Span(InlineTree {
+                        root: vec![InlineItem::Text("d".into())]
+                    })),
+                ),
+                InlineItem::Text(" [e] {.f} ".into()),
+                InlineItem::Strong(InlineTree {
+                    root: vec![InlineItem::Text("g".into())]
+                }),
+                InlineItem::Text("{h}".into()),
+            ]
         );
     }
 
     #[test]
-    fn test_paragraph() {
-        let input = "Hello *World*!\n\n";
+    fn test_block_attributes() {
+        let input = "a\n{.b}\n\n- c\n\n{#d}\n\n{.e .f}";
         let tokens = lex_to_vec(input);
         let parser = Executor::new(input);
 
-        let (item, rest) = parser.paragraph(&tokens).unwrap();
+        let attributes = |input| Executor::parse_attributes(input).unwrap();
 
         assert_eq!(
-            item,
-            BlockItem::Paragraph(InlineTree {
-                root: vec![
-                    InlineItem::Text("Hello ".into()),
-                    InlineItem::Italic(InlineTree {
-                        root: vec![InlineItem::Text("World".into())]
-                    }),
-                    InlineItem::Text("!".into()),
-                ]
-            })
+            parser.block_tree(&tokens).root,
+            vec![
+                BlockItem::Attributed(
+                    attributes(".b"),
+                    Box::new(BlockItem::Paragraph(InlineTree {
+                        root: vec![InlineItem::Text("a".into())]
+                    })),
+                ),
+                BlockItem::Attributed(
+                    attributes("#d .e .f"),
+                    Box::new(BlockItem::BulletList(ListTree {
+                        root: vec![ListItem {
+                            name: InlineTree {
+                                root: vec![InlineItem::Text("c".into())]
+                            },
+                            children: vec![],
+                        }]
+                    })),
+                ),
+            ]
         );
-        assert_eq!(rest.len(), 0);
+    }
 
-        let input = "Hello\n";
+    #[test]
+    fn test_diagnostics() {
+        let input = "**a* 2 * 3\n- [b](c\n\n[d] (e) [f](g) *h*";
+        let (_, diagnostics) = Parser::new().parse_with_diagnostics(input, lex(input));
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    kind: DiagnosticKind::UnclosedEmphasis {
+                        delimiter: "*".into()
+                    },
+                    span: 0..1,
+                },
+                Diagnostic {
+                    kind: DiagnosticKind::MalformedLink,
+                    span: 13..14,
+                },
+            ]
+        );
+
+        let input = "a **b";
+        let (_, diagnostics) = Parser::new().parse_with_diagnostics(input, lex(input));
+
+        assert_eq!(diagnostics[0].span, 2..4);
+        assert_eq!(diagnostics[0].to_string(), "unclosed `**`");
+    }
 
+    #[test]
+    fn test_text_borrowed() {
+        let input = "Hello, world - **a** b # c\nd";
         let tokens = lex_to_vec(input);
         let parser = Executor::new(input);
 
-        let (item, rest) = parser.paragraph(&tokens).unwrap();
+        let tree = parser.inline_tree(&tokens);
 
         assert_eq!(
-            item,
-            BlockItem::Paragraph(InlineTree {
-                root: vec![InlineItem::Text("Hello".into())]
-            })
+            tree.root
+                .iter()
+                .map(|item| match item {
+                    InlineItem::Text(Cow::Borrowed(text)) => Some(*text),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                Some("Hello, world - "),
+                None,
+                Some(" b # c"),
+                None,
+                Some("d")
+            ]
         );
-
-        assert_eq!(rest.len(), 0);
     }
 
     #[test]
-    fn test_paragraph_before_not_paragraph() {
-        let input = "Hello *World*!\n# Hello\n";
+    fn test_escaped() {
+        let input = r"\*Hello\* *World\**";
         let tokens = lex_to_vec(input);
-        let parser = Executor::with_config(
-            input,
-            Parser::new().paragraph_ending(ParagraphEnding::AllowSoftBreak),
-        );
+        let parser = Executor::new(input);
 
-        let (item, rest) = parser.paragraph(&tokens).unwrap();
+        let tree = parser.inline_tree(&tokens);
 
         assert_eq!(
-            item,
-            BlockItem::Paragraph(InlineTree {
+            tree,
+            InlineTree {
                 root: vec![
-                    InlineItem::Text("Hello ".into()),
+                    InlineItem::Escaped("*".into()),
+                    InlineItem::Text("Hello".into()),
+                    InlineItem::Escaped("*".into()),
+                    InlineItem::Text(" ".into()),
                     InlineItem::Italic(InlineTree {
-                        root: vec![InlineItem::Text("World".into())]
+                        root: vec![
+                            InlineItem::Text("World".into()),
+                            InlineItem::Escaped("*".into()),
+                        ]
                     }),
-                    InlineItem::Text("!".into()),
                 ]
-            })
+            }
         );
 
-        assert_eq!(rest.len(), 4);
-    }
-
-    #[test]
-    fn test_headline() {
-        let input = "###  Hello *World*!\n\n";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+        let input = "\\# Hello\n\n\\- World";
+        let tokens = lex(input);
 
-        let (item, rest) = parser.headline(&tokens).unwrap();
+        let tree = Parser::new().parse(input, tokens);
 
         assert_eq!(
-            item,
-            BlockItem::Headline(
-                3,
-                InlineTree {
+            tree,
+            MarkdownTree {
+                root: BlockTree {
                     root: vec![
-                        InlineItem::Text("Hello ".into()),
-                        InlineItem::Italic(InlineTree {
-                            root: vec![InlineItem::Text("World".into())]
+                        BlockItem::Paragraph(InlineTree {
+                            root: vec![
+                                InlineItem::Escaped("#".into()),
+                                InlineItem::Text(" Hello".into()),
+                            ]
+                        }),
+                        BlockItem::Paragraph(InlineTree {
+                            root: vec![
+                                InlineItem::Escaped("-".into()),
+                                InlineItem::Text(" World".into()),
+                            ]
                         }),
-                        InlineItem::Text("!".into()),
                     ]
                 }
-            )
+            }
         );
-
-        assert_eq!(rest.len(), 0);
     }
 
     #[test]
-    fn test_headline2() {
-        let input = "# Hello World!\n# Goodbye\n";
+    fn test_break() {
+        let input = "\r\nHello";
         let tokens = lex_to_vec(input);
         let parser = Executor::new(input);
 
-        let (item, _) = parser.headline(&tokens).unwrap();
+        let (item, rest) = parser.r#break(&tokens).unwrap();
 
-        assert_ne!(
-            item,
-            BlockItem::Headline(
-                1,
-                InlineTree {
-                    root: vec![InlineItem::Text("Hello World!".into())]
-                }
-            )
-        );
+        assert_eq!(item, InlineItem::Break);
+        assert_eq!(rest.len(), 1);
+    }
 
-        let parser = Executor::with_config(
-            input,
-            Parser::default().headline_ending(HeadlineEnding::AllowSoftBreak),
-        );
+    #[test]
+    fn test_emphasis_rule() {
+        let markdown = crate::Markdown::default();
 
-        let (item, _) = parser.headline(&tokens).unwrap();
+        for (input, expected) in [
+            ("*a * b*", "<p><em>a * b</em></p>"),
+            ("2 * 3 * 4 ** 5 ** 6", "<p>2 * 3 * 4 ** 5 ** 6</p>"),
+            ("a*b*c **d**e", "<p>a<em>b</em>c <strong>d</strong>e</p>"),
+            (
+                "*(a)* **\"b\"**",
+                "<p><em>(a)</em> <strong>\"b\"</strong></p>",
+            ),
+            ("a*\"b\"*c", "<p>a*\"b\"*c</p>"),
+            // A combining mark is a part of the letter before it.
+            ("e\u{301}*\"b\"*c", "<p>e\u{301}*\"b\"*c</p>"),
+            ("** a **", "<p>** a **</p>"),
+            ("**a *b* c**", "<p><strong>a <em>b</em> c</strong></p>"),
+            // `>` is not before the second `*`, which cannot close.
+            (">*a\n>* b", "<blockquote><p>*a<br>* b</p></blockquote>"),
+        ] {
+            assert_eq!(markdown.execute(input), expected, "{input:?}");
+        }
+    }
 
-        assert_eq!(
-            item,
-            BlockItem::Headline(
-                1,
-                InlineTree {
-                    root: vec![InlineItem::Text("Hello World!".into())]
-                }
-            )
-        );
+    #[test]
+    fn test_nested_emphasis() {
+        let markdown = crate::Markdown::default();
+
+        for (input, expected) in [
+            ("***a***", "<p><em><strong>a</strong></em></p>"),
+            ("***a** b*", "<p><em><strong>a</strong> b</em></p>"),
+            ("***a* b**", "<p><strong><em>a</em> b</strong></p>"),
+            ("*a **b***", "<p><em>a <strong>b</strong></em></p>"),
+            ("*a *b* c*", "<p><em>a <em>b</em> c</em></p>"),
+            (
+                "**a **b** c**",
+                "<p><strong>a <strong>b</strong> c</strong></p>",
+            ),
+            ("**a*", "<p>*<em>a</em></p>"),
+            ("*a**", "<p><em>a</em>*</p>"),
+            ("*a [b*](c)", "<p>*a <a href=\"c\">b*</a></p>"),
+            ("*a **b** c*", "<p><em>a <strong>b</strong> c</em></p>"),
+            ("*a**b*", "<p><em>a**b</em></p>"),
+            ("*a *b c* d* e*", "<p><em>a <em>b c</em> d</em> e*</p>"),
+            ("*a **b c* d**", "<p><em>a <em><em>b c</em> d</em></em></p>"),
+        ] {
+            assert_eq!(markdown.execute(input), expected, "{input:?}");
+        }
     }
 
     #[test]
-    fn test_bullet_list() {
-        let input = "- Hello *World*!\n- Hello *World*!\n\n";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_comments() {
+        let input = "<!-- a -->\n\n*b <!-- c* -->d*\n<!--\ne\n-->\n\n<!-- f";
 
-        let (item, rest) = parser.bullet_list(&tokens).unwrap();
+        let markdown = crate::Markdown::default();
 
         assert_eq!(
-            item,
-            BlockItem::BulletList(ListTree {
-                root: vec![
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![
-                                InlineItem::Text("Hello ".into()),
-                                InlineItem::Italic(InlineTree {
-                                    root: vec![InlineItem::Text("World".into())]
-                                }),
-                                InlineItem::Text("!".into()),
-                            ]
-                        },
-                        children: vec![]
-                    },
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![
-                                InlineItem::Text("Hello ".into()),
-                                InlineItem::Italic(InlineTree {
-                                    root: vec![InlineItem::Text("World".into())]
-                                }),
-                                InlineItem::Text("!".into()),
-                            ]
-                        },
-                        children: vec![]
-                    },
-                ]
-            }),
+            markdown.execute(input),
+            "<p><!-- a --></p><p><em>b <!-- c</em> -->d*<br><!--<br>e<br>--></p><p><!-- f</p>"
         );
 
-        assert_eq!(rest.len(), 0);
-
-        let input = "- Hello!\n  - Fooo!\nHappy\n  - hogee!\n- Good\njobs\n# End\n";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
-
-        let (item, rest) = parser.bullet_list(&tokens).unwrap();
-
-        assert_eq!(
-            item,
-            BlockItem::BulletList(ListTree {
-                root: vec![
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![InlineItem::Text("Hello!".into())]
-                        },
-                        children: vec![BlockItem::BulletList(ListTree {
-                            root: vec![
-                                ListItem {
-                                    name: InlineTree {
-                                        root: vec![
-                                            InlineItem::Text("Fooo!".into()),
-                                            InlineItem::Break,
-                                            InlineItem::Text("Happy".into())
-                                        ]
-                                    },
-                                    children: vec![]
-                                },
-                                ListItem {
-                                    name: InlineTree {
-                                        root: vec![InlineItem::Text("hogee!".into())]
-                                    },
-                                    children: vec![]
-                                }
-                            ]
-                        }),]
-                    },
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![
-                                InlineItem::Text("Good".into()),
-                                InlineItem::Break,
-                                InlineItem::Text("jobs".into())
-                            ]
-                        },
-                        children: vec![]
-                    },
-                ]
-            }),
+        let markdown =
+            crate::Markdown::default().parser(Parser::new().comments(Comments::Preserve));
+
+        assert_eq!(
+            markdown.execute(input),
+            "<!-- a --><p><em>b <!-- c* -->d</em><br><!--\ne\n--></p><p><!-- f</p>"
         );
 
-        assert_eq!(rest.len(), 4);
+        let markdown = crate::Markdown::default().parser(Parser::new().comments(Comments::Strip));
+
+        assert_eq!(
+            markdown.execute(input),
+            "<p><em>b d</em><br></p><p><!-- f</p>"
+        );
+
+        // Comments which are not closed do not hide the `-->` of the next
+        // ones.
+        assert_eq!(
+            markdown.execute("<!-- a <!-- b --> c <!-- d\n\n<!-- e --> f <!-- g"),
+            "<p> c <!-- d</p><p> f <!-- g</p>"
+        );
+
+        for comments in [Comments::Text, Comments::Preserve, Comments::Strip] {
+            assert_eq!(comments.to_string().parse(), Ok(comments));
+        }
     }
 
     #[test]
-    fn ordered_list() {
-        let input = "1. Hello!\n  1. Fooo!\nHappy\n  1. hogee!\n1. Good\njobs\n# End\n";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_tables() {
+        let input = "a | b\n:-: | -\n*x* \\| y | | z\n`c\n# d\n\n| e | f |\n| --- |\n";
 
-        let (item, rest) = parser.ordered_list(&tokens).unwrap();
+        let markdown = crate::Markdown::default().parser(Parser::new().tables(Tables::Optional));
 
         assert_eq!(
-            item,
-            BlockItem::OrderedList(ListTree {
-                root: vec![
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![InlineItem::Text("Hello!".into())]
+            markdown.execute(input),
+            concat![
+                "<table><thead><tr><th align=\"center\">a</th><th>b</th></tr></thead><tbody>",
+                "<tr><td align=\"center\"><em>x</em> | y</td><td></td></tr>",
+                "<tr><td align=\"center\">`c</td><td></td></tr></tbody></table>",
+                "<h1>d</h1><p>| e | f |<br>| --- |</p>",
+            ]
+        );
+        assert_eq!(
+            markdown.execute_text("a|b\n-|-\nc|d\n|e"),
+            "a\tb\nc\td\ne\t"
+        );
+
+        let markdown = crate::Markdown::default().parser(Parser::new().tables(Tables::Piped));
+
+        assert!(markdown.execute(input).starts_with("<p>a | b<br>"));
+
+        let input = "| a |\n|--:|\n| b | c |\n|\n";
+        let tree = Parser::new().tables(Tables::Piped).parse(input, lex(input));
+
+        assert_eq!(
+            tree.root.root,
+            vec![
+                BlockItem::Table(TableTree {
+                    aligns: vec![Alignment::Right],
+                    head: vec![TableCell::new(InlineTree {
+                        root: vec![InlineItem::Text("a".into())]
+                    })],
+                    rows: vec![vec![TableCell::new(InlineTree {
+                        root: vec![InlineItem::Text("b".into())]
+                    })]],
+                }),
+                BlockItem::Paragraph(InlineTree {
+                    root: vec![InlineItem::Text("|".into())]
+                }),
+            ]
+        );
+
+        assert_eq!(
+            crate::Markdown::default().execute("| a |\n| - |"),
+            "<p>| a |<br>| - |</p>"
+        );
+
+        let soft = Parser::new()
+            .tables(Tables::Piped)
+            .paragraph_ending(ParagraphEnding::AllowSoftBreak);
+        let input = "para\n| a | b |\n|---|---|\n| 1 | 2 |";
+
+        assert_eq!(
+            crate::Markdown::default()
+                .parser(soft.clone())
+                .execute(input),
+            concat![
+                "<p>para</p><table><thead><tr><th>a</th><th>b</th></tr></thead>",
+                "<tbody><tr><td>1</td><td>2</td></tr></tbody></table>",
+            ]
+        );
+        assert_eq!(
+            crate::Markdown::default()
+                .parser(soft.interrupters(&[Interrupter::Headline]))
+                .execute(input),
+            "<p>para<br>| a | b |<br>|---|---|<br>| 1 | 2 |</p>"
+        );
+        assert_eq!(
+            crate::Markdown::default()
+                .parser(Parser::new().tables(Tables::Piped))
+                .execute("- a\n| b |\n| - |"),
+            "<ul><li>a</li></ul><table><thead><tr><th>b</th></tr></thead></table>"
+        );
+
+        let text = |text: &'static str| {
+            TableCell::new(InlineTree {
+                root: vec![InlineItem::Text(text.into())],
+            })
+        };
+        let empty = || TableCell::new(InlineTree { root: vec![] });
+
+        let input = "a || ^^\n-|-|-\n^^ || b\nc | ^^ | ^^\n^^ | d\n|| e |";
+        let tree = Parser::new()
+            .tables(Tables::Optional)
+            .table_spans(true)
+            .parse(input, lex(input));
+
+        assert_eq!(
+            tree.root.root,
+            vec![BlockItem::Table(TableTree {
+                aligns: vec![Alignment::None; 3],
+                head: vec![
+                    TableCell::Cell {
+                        tree: InlineTree {
+                            root: vec![InlineItem::Text("a".into())]
                         },
-                        children: vec![BlockItem::OrderedList(ListTree {
-                            root: vec![
-                                ListItem {
-                                    name: InlineTree {
-                                        root: vec![
-                                            InlineItem::Text("Fooo!".into()),
-                                            InlineItem::Break,
-                                            InlineItem::Text("Happy".into())
-                                        ]
-                                    },
-                                    children: vec![]
-                                },
-                                ListItem {
-                                    name: InlineTree {
-                                        root: vec![InlineItem::Text("hogee!".into())]
-                                    },
-                                    children: vec![]
-                                }
-                            ]
-                        }),]
+                        colspan: 2,
+                        rowspan: 1,
                     },
-                    ListItem {
-                        name: InlineTree {
-                            root: vec![
-                                InlineItem::Text("Good".into()),
-                                InlineItem::Break,
-                                InlineItem::Text("jobs".into())
-                            ]
+                    TableCell::MergedLeft,
+                    text("^^"),
+                ],
+                rows: vec![
+                    vec![
+                        TableCell::Cell {
+                            tree: InlineTree {
+                                root: vec![InlineItem::Text("^^".into())]
+                            },
+                            colspan: 2,
+                            rowspan: 1,
                         },
-                        children: vec![]
-                    },
-                ]
-            }),
+                        TableCell::MergedLeft,
+                        TableCell::Cell {
+                            tree: InlineTree {
+                                root: vec![InlineItem::Text("b".into())]
+                            },
+                            colspan: 1,
+                            rowspan: 2,
+                        },
+                    ],
+                    vec![
+                        TableCell::Cell {
+                            tree: InlineTree {
+                                root: vec![InlineItem::Text("c".into())]
+                            },
+                            colspan: 1,
+                            rowspan: 2,
+                        },
+                        TableCell::MergedUp,
+                        TableCell::MergedUp,
+                    ],
+                    vec![TableCell::MergedUp, text("d"), empty()],
+                    vec![empty(), text("e"), empty()],
+                ],
+            })]
         );
 
-        assert_eq!(rest.len(), 4);
+        for tables in [Tables::Off, Tables::Piped, Tables::Optional] {
+            assert_eq!(tables.to_string().parse(), Ok(tables));
+        }
     }
 
     #[test]
-    fn test_blockquote() {
-        let input = ">Hello\n>\n>>Yeah\nHappy";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_code_blocks() {
+        let code = |lang: Option<&'static str>, info: &[(&str, &str)], text: &'static str| {
+            BlockItem::CodeBlock(CodeBlock {
+                lang: lang.map(Cow::Borrowed),
+                info: info
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                text: text.into(),
+            })
+        };
 
-        let (item, rest) = parser.blockquote(&tokens).unwrap();
+        let parse =
+            |parser: Parser, input: &'static str| parser.parse(input, lex(input)).root.into_root();
 
         assert_eq!(
-            item,
-            BlockItem::BlockQuote(BlockTree {
+            parse(
+                Parser::new(),
+                "``` rust,linenos hl_lines=\"2-3\" x=1\n*a*\n\n  # b\n````\n"
+            ),
+            vec![code(
+                Some("rust"),
+                &[("linenos", ""), ("hl_lines", "2-3"), ("x", "1")],
+                "*a*\n\n  # b\n"
+            )]
+        );
+        assert_eq!(
+            parse(Parser::new(), "  ~~~~\n   a\n b\n~~~\n  ~~~~  \nc"),
+            vec![
+                code(None, &[], " a\nb\n~~~\n"),
+                BlockItem::Paragraph(InlineTree {
+                    root: vec![InlineItem::Text("c".into())]
+                }),
+            ]
+        );
+        // A code block without a closing fence ends at the end.
+        assert_eq!(
+            parse(Parser::new(), "```x=1,lang,,a\"b,-c\n\na"),
+            vec![code(None, &[("x", "1"), ("lang", ""), ("-c", "")], "\na\n")]
+        );
+        // Lines in a blockquote lose as many spaces as the fence.
+        assert_eq!(
+            parse(Parser::new(), "> ```\n>   a\n>\n> ```\n> b"),
+            vec![BlockItem::BlockQuote(BlockTree {
                 root: vec![
+                    code(None, &[], "  a\n\n"),
                     BlockItem::Paragraph(InlineTree {
-                        root: vec![InlineItem::Text("Hello".into())]
-                    }),
-                    BlockItem::BlockQuote(BlockTree {
-                        root: vec![BlockItem::Paragraph(InlineTree {
-                            root: vec![InlineItem::Text("Yeah".into())]
-                        }),]
+                        root: vec![InlineItem::Text("b".into())]
                     }),
                 ]
-            })
+            })]
         );
 
-        assert_eq!(rest.len(), 1);
-    }
+        // A fence with a backquote in the info string is text.
+        let text = |text: &'static str| {
+            BlockItem::Paragraph(InlineTree {
+                root: vec![InlineItem::Text(text.into())],
+            })
+        };
 
-    #[test]
-    fn test_inline_tree() {
-        let input = "Hello *World*!\n";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+        assert_eq!(parse(Parser::new(), "```a`"), vec![text("```a`")]);
+        assert_eq!(parse(Parser::new(), "``a"), vec![text("``a")]);
 
-        let tree = parser.inline_tree(&tokens);
+        let soft = Parser::new().paragraph_ending(ParagraphEnding::AllowSoftBreak);
 
         assert_eq!(
-            tree,
-            InlineTree {
+            parse(soft.clone(), "a\n```\nb\n```"),
+            vec![text("a"), code(None, &[], "b\n")]
+        );
+        assert_eq!(
+            parse(soft.interrupters(&[Interrupter::Headline]), "a\n```"),
+            vec![BlockItem::Paragraph(InlineTree {
                 root: vec![
-                    InlineItem::Text("Hello ".into()),
-                    InlineItem::Italic(InlineTree {
-                        root: vec![InlineItem::Text("World".into())]
-                    }),
-                    InlineItem::Text("!".into()),
+                    InlineItem::Text("a".into()),
                     InlineItem::Break,
+                    InlineItem::Text("```".into()),
                 ]
-            }
+            })]
         );
     }
 
     #[test]
-    fn test_italic() {
-        let input = r"*Hello*";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_extract_links() {
+        let input = "---\nx: 1\n---\n# あ [a](b)\n\n> - [c `d`\\]](e) [[F|g]]\n\n[h](i";
+        let parser = Parser::new().front_matter(true).wiki_links(true);
 
-        let (item, rest) = parser.italic(&tokens).unwrap();
+        let links = parser
+            .extract_links(input, lex(input))
+            .into_iter()
+            .map(|link| (link.kind, link.target, link.text, &input[link.span]))
+            .collect::<Vec<_>>();
 
         assert_eq!(
-            item,
-            InlineItem::Italic(InlineTree {
-                root: vec![InlineItem::Text("Hello".into())]
-            })
+            links,
+            vec![
+                (LinkKind::Link, "b".into(), "a".into(), "[a](b)"),
+                (LinkKind::Link, "e".into(), "c `d`]".into(), "[c `d`\\]](e)"),
+                (LinkKind::WikiLink, "F".into(), "g".into(), "[[F|g]]"),
+            ]
         );
-        assert_eq!(rest.len(), 0);
+    }
 
-        let input = "*";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    #[test]
+    fn test_fancy_lists() {
+        let markdown = crate::Markdown::default();
+
+        assert_eq!(
+            markdown.execute("1) a\n2) b\n3. c\n\na. d"),
+            "<ol><li>a</li><li>b</li></ol><ol start=\"3\"><li>c</li></ol><p>a. d</p>"
+        );
+
+        let markdown = crate::Markdown::default().parser(Parser::default().fancy_lists(true));
 
-        assert_eq!(parser.italic(&tokens), None);
+        for (input, expected) in [
+            ("a. x\nb. y", "<ol type=\"a\"><li>x</li><li>y</li></ol>"),
+            ("B) x", "<ol start=\"2\" type=\"A\"><li>x</li></ol>"),
+            (
+                "i. x\nii. y\nv. z",
+                "<ol type=\"i\"><li>x</li><li>y</li><li>z</li></ol>",
+            ),
+            ("XIV. x", "<ol start=\"14\" type=\"I\"><li>x</li></ol>"),
+            (
+                "h. x\ni. y",
+                "<ol start=\"8\" type=\"a\"><li>x</li><li>y</li></ol>",
+            ),
+            ("v. x", "<ol start=\"22\" type=\"a\"><li>x</li></ol>"),
+            (
+                "a. x\nB. y",
+                "<ol type=\"a\"><li>x</li></ol><ol start=\"2\" type=\"A\"><li>y</li></ol>",
+            ),
+            ("iiii. x", "<p>iiii. x</p>"),
+            ("ab. x", "<p>ab. x</p>"),
+            ("aB. x", "<p>aB. x</p>"),
+        ] {
+            assert_eq!(markdown.execute(input), expected, "{input:?}");
+        }
+
+        assert_eq!(
+            markdown.parser.split_blocks("a. x\n\nb. y\n\nc"),
+            vec![0..12, 12..13]
+        );
     }
 
     #[test]
-    fn test_strong() {
-        let input = r"**Hello**";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_split_blocks() {
+        let parser = Parser::new();
+
+        assert_eq!(parser.split_blocks(""), vec![0..0]);
+        assert_eq!(parser.split_blocks("\n\nA\n"), vec![0..4]);
+        assert_eq!(parser.split_blocks("A\nB\n\n\nC"), vec![0..6, 6..7]);
+        assert_eq!(
+            parser.split_blocks("- a\n\n- b\n\n  c\n\n1. d"),
+            vec![0..19]
+        );
+        assert_eq!(
+            parser.split_blocks("> a\n\n> b\n\n# c\n"),
+            vec![0..5, 5..10, 10..14]
+        );
+        assert_eq!(parser.split_blocks("- t\n\n{.cls}\n"), vec![0..12]);
+        assert_eq!(
+            parser.split_blocks("```\na\n\n# b\n```\n\nc"),
+            vec![0..16, 16..17]
+        );
+        // A fence after a paragraph line may be text, so no later line is
+        // known to be out of a code block.
+        assert_eq!(
+            parser.split_blocks("a\n```\n\n# b\n\n```\n\nc"),
+            vec![0..18]
+        );
+
+        let inputs = [
+            "1. x\n\n2. y\n\nEnd",
+            "# Hello\n\nWorld\n\n\n- a\n  - b\n\n- c\n\n> q\n>\n> - r\n\n1. x\n\n2. y\n\nEnd",
+            "- a\n  - b\n\n  - c\n\nd\n\n  e\n",
+            "> a\n\n> b\n\nc",
+            "- t\n\n{.cls}\n\nu\n\n{#v}",
+        ];
+
+        for input in inputs {
+            let whole = parser.parse(input, lex(input));
+
+            let chunked = parser
+                .split_blocks(input)
+                .into_iter()
+                .flat_map(|range| {
+                    let chunk = &input[range];
+
+                    parser.parse(chunk, lex(chunk)).root.into_root()
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(whole.root.root, chunked, "{input:?}");
+        }
+
+        let parser = Parser::new().front_matter(true);
+
+        let input = "---\ntitle: a\n\nfoo: b\n---\n# X\n\nY\n";
+
+        assert_eq!(parser.split_blocks(input), vec![0..30, 30..32]);
+
+        let chunked = parser
+            .split_blocks(input)
+            .into_iter()
+            .flat_map(|range| {
+                let chunk = &input[range];
+
+                parser.parse(chunk, lex(chunk)).root.into_root()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(parser.parse(input, lex(input)).root.root, chunked);
+    }
+
+    #[test]
+    fn test_limits() {
+        let parser = Parser::new().max_nesting(Some(2));
 
-        let (item, rest) = parser.strong(&tokens).unwrap();
+        let input = "- a\n  - b\n    - c";
+        let tree = parser.parse(input, lex(input));
+
+        let item = |name: &str, children| ListItem {
+            name: InlineTree {
+                root: vec![InlineItem::Text(name.to_string().into())],
+            },
+            children,
+        };
 
         assert_eq!(
-            item,
-            InlineItem::Strong(InlineTree {
-                root: vec![InlineItem::Text("Hello".into())]
+            tree.root.root,
+            vec![BlockItem::BulletList(ListTree {
+                root: vec![item(
+                    "a",
+                    vec![BlockItem::BulletList(ListTree {
+                        root: vec![item(
+                            "b",
+                            vec![BlockItem::Paragraph(InlineTree {
+                                root: vec![InlineItem::Text("- c".into())]
+                            })]
+                        )]
+                    })]
+                )]
+            }),]
+        );
+
+        let input = "**x *y* z**";
+        let tree = Parser::new().max_nesting(Some(1)).parse(input, lex(input));
+
+        assert_eq!(
+            tree.root.root,
+            vec![BlockItem::Paragraph(InlineTree {
+                root: vec![InlineItem::Strong(InlineTree {
+                    root: vec![InlineItem::Text("x *y* z".into())]
+                })]
+            })]
+        );
+
+        let deep = "> ".repeat(10_000) + "a";
+        let tree = Parser::new().max_nesting(Some(64)).parse(&deep, lex(&deep));
+
+        assert_eq!(tree.root.root.len(), 1);
+
+        let parser = Parser::new().max_input_len(Some(9));
+
+        for (input, expected) in [("# A\n\nBCDEF", "BCDE"), ("# A\n\nBCあ", "BC")] {
+            let tree = parser.parse(input, lex(input));
+
+            assert_eq!(
+                tree.root.root[1],
+                BlockItem::Paragraph(InlineTree {
+                    root: vec![InlineItem::Text(expected.into())]
+                })
+            );
+            assert_eq!(
+                parser.split_blocks(input),
+                vec![0..5, 5..5 + expected.len()]
+            );
+        }
+
+        let input = "> - **a *b***\n> - [c](d)\n\n> > e\n\nf";
+
+        let (tree, diagnostics) = Parser::new()
+            .max_block_nesting(Some(2))
+            .max_inline_nesting(Some(1))
+            .parse_with_diagnostics(input, lex(input));
+
+        let text = |text: &str| InlineTree {
+            root: vec![InlineItem::Text(text.to_string().into())],
+        };
+
+        assert_eq!(
+            tree.root.root[0],
+            BlockItem::BlockQuote(BlockTree {
+                root: vec![BlockItem::BulletList(ListTree {
+                    root: vec![
+                        ListItem {
+                            name: InlineTree {
+                                root: vec![InlineItem::Strong(text("a *b*"))]
+                            },
+                            children: vec![],
+                        },
+                        ListItem {
+                            name: InlineTree {
+                                root: vec![InlineItem::Link {
+                                    url: "d".into(),
+                                    title: None,
+                                    tree: text("c"),
+                                }]
+                            },
+                            children: vec![],
+                        },
+                    ]
+                })]
             })
         );
-        assert_eq!(rest.len(), 0);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::NestingTooDeep,
+                span: 8..9,
+            }]
+        );
+
+        let parser = Parser::new().max_tokens(Some(12));
+        let input = "# A\n\n*B* *C*\n\nG";
+
+        let (tree, diagnostics) = parser.parse_with_diagnostics(input, lex(input));
+
+        assert_eq!(tree.root.root.len(), 2);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::InputTruncated,
+                span: 12..15,
+            }]
+        );
+        assert_eq!(parser.split_blocks(input), vec![0..5, 5..12]);
     }
 
     #[test]
-    fn test_break() {
-        let input = "\r\nHello";
-        let tokens = lex_to_vec(input);
-        let parser = Executor::new(input);
+    fn test_blockquote_before_blank_line() {
+        let input = "> a\n\n> b\n\nc";
+        let tokens = lex(input);
 
-        let (item, rest) = parser.r#break(&tokens).unwrap();
+        let tree = Parser::new().parse(input, tokens);
 
-        assert_eq!(item, InlineItem::Break);
-        assert_eq!(rest.len(), 1);
+        assert_eq!(
+            tree.root.root,
+            vec![
+                BlockItem::BlockQuote(BlockTree {
+                    root: vec![BlockItem::Paragraph(InlineTree {
+                        root: vec![InlineItem::Text("a".into())]
+                    })]
+                }),
+                BlockItem::BlockQuote(BlockTree {
+                    root: vec![BlockItem::Paragraph(InlineTree {
+                        root: vec![InlineItem::Text("b".into())]
+                    })]
+                }),
+                BlockItem::Paragraph(InlineTree {
+                    root: vec![InlineItem::Text("c".into())]
+                }),
+            ]
+        );
     }
 
     #[test]
@@ -1413,4 +6138,77 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_include() {
+        use crate::model::event::{Event, Tag};
+
+        let parser =
+            Parser::new()
+                .front_matter(true)
+                .include_resolver(|target: &str| match target {
+                    "a" => Some(Inclusion::Markdown(
+                        "---\ntitle: A\n---\n# A\n\n![[b]]".into(),
+                    )),
+                    "b" => Some(Inclusion::Tree(MarkdownTree {
+                        root: BlockTree {
+                            root: vec![BlockItem::Headline(
+                                2,
+                                InlineTree {
+                                    root: vec![InlineItem::Text("B".into())],
+                                },
+                            )],
+                        },
+                    })),
+                    _ if target.starts_with('d') => {
+                        Some(Inclusion::Markdown(format!("{target}\n\n![[{target}x]]")))
+                    }
+                    _ => None,
+                });
+
+        let markdown = crate::Markdown::default().parser(parser.clone());
+
+        let input = "# Top\n\n- item\n\n   ![[a]]  \n\n![[a|x]] text\n\n## End";
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<h1>Top</h1><ul><li>item<h1>A</h1><h2>B</h2></li></ul>",
+                "<p>![[a|x]] text</p><h2>End</h2>",
+            ]
+        );
+        assert_eq!(
+            markdown
+                .outline(input)
+                .into_iter()
+                .map(|heading| (heading.text, &input[heading.span]))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Top".to_string(), "# Top"),
+                ("A".to_string(), "![[a]]"),
+                ("B".to_string(), "![[a]]"),
+                ("End".to_string(), "## End"),
+            ]
+        );
+        assert_eq!(markdown.reformat("![[a]]"), "![[a]]\n");
+
+        let events = parser.parse("![[b]]", lex("![[b]]")).into_events();
+
+        assert_eq!(events[0], Event::Start(Tag::Headline(2)));
+
+        let (html, diagnostics) = crate::Markdown::default()
+            .parser(parser.max_include_depth(2))
+            .execute_with_diagnostics("![[d]]");
+
+        assert_eq!(html, "<p>d</p><p>dx</p><p>![[dxx]]</p>");
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::IncludeTooDeep {
+                    target: "dxx".into()
+                },
+                span: 0..6,
+            }]
+        );
+    }
 }
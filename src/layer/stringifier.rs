@@ -1,16 +1,240 @@
 //! Stringify DocumentNode to html string.
 
-use crate::model::html::*;
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
+use crate::model::{
+    counter::{Counter, CounterStyle},
+    html::*,
+};
+
+use config::*;
 
 /// Stringify DocumentNode to html string.
 ///
 /// This contains some options.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Stringifier {
     /// Whether to format the output. Default is false.
     pub format: bool,
-    /// The width of the line to break the code and indent. Default is 20.
+    /// The width of the text in an element to break the element and indent
+    /// its text. Tags and attributes are not counted. Default is 20.
     pub width: u32,
+    /// The string to indent formatted lines with. Default is four spaces.
+    pub indent: String,
+    /// Whether to keep elements without block children on one line when
+    /// formatting, so the formatted output renders the same as the compact
+    /// output. Default is false.
+    pub preserve_inline: bool,
+    /// Whether to write byte-stable output for snapshot tests. Default is
+    /// false.
+    ///
+    /// The output is never formatted, classes are sorted without
+    /// duplicates, and the attributes are written in the order `class`,
+    /// `id`, `href`, and then the others sorted by name. The same document
+    /// is written the same regardless of the order the attributes were set
+    /// in.
+    pub stable: bool,
+    /// Whether to escape `&`, `<`, `>` and `"` in text. Default is false,
+    /// which lets HTML in the input through. Attribute values and text in
+    /// `<code>` are always escaped.
+    pub escape: bool,
+    /// The URL schemes allowed in links. A link with another scheme loses its
+    /// href. Relative URLs are always allowed. Default is `None`, which
+    /// allows any scheme. URLs are normalized with [`url_sanitize`] in any
+    /// case.
+    pub url_schemes: Option<Vec<String>>,
+    /// Whether to drop attributes which can run scripts, such as `onclick`,
+    /// `style` and `srcdoc`. Default is false.
+    pub sanitize: bool,
+    /// How to write void elements such as `<br>`. Default is
+    /// [`VoidStyle::Html`](config::VoidStyle).
+    pub void_style: VoidStyle,
+    /// The options of the full HTML document to wrap the output in. Default
+    /// is `None`, which writes only the body.
+    pub document: Option<DocumentOptions>,
+    /// The renderer consulted before writing each element. Default is
+    /// `None`, which writes every element as it is.
+    pub renderer: Option<Arc<dyn Render>>,
+    /// The values of `{{key}}` placeholders in text. Default is empty.
+    ///
+    /// Values are always escaped, even if [`escape`](Self::escape) is not
+    /// set. Placeholders with unknown keys and in `<code>` are written as
+    /// they are.
+    pub variables: BTreeMap<String, String>,
+    /// Whether to write the source positions of blocks as `data-sourcepos`
+    /// attributes. Default is false.
+    ///
+    /// The positions are recorded while parsing, so they are written when
+    /// the input is rendered by [`Markdown`](crate::Markdown).
+    pub source_positions: bool,
+}
+
+/// The trait to customize how elements are written.
+///
+/// [`element`](Self::element) is called when an element and its children
+/// are written. If it returns `Some`, the HTML replaces the element.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{model::html::*, prelude::*};
+///
+/// struct Bold;
+///
+/// impl Render for Bold {
+///     fn element(&self, element: &ElementNode, children: &str) -> Option<String> {
+///         match element.tag {
+///             ElementTag::Strong => Some(format!("<b>{children}</b>")),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let markdown = Markdown::default().stringifier(Stringifier::new().renderer(Bold));
+///
+/// assert_eq!(markdown.execute("**Hello**"), "<p><b>Hello</b></p>");
+/// ```
+pub trait Render: Send + Sync {
+    /// Write an element, or return `None` to write it by default.
+    ///
+    /// `children` is the HTML of the children, which are already rendered.
+    fn element(&self, element: &ElementNode, children: &str) -> Option<String>;
+}
+
+impl<F> Render for F
+where
+    F: Fn(&ElementNode, &str) -> Option<String> + Send + Sync,
+{
+    fn element(&self, element: &ElementNode, children: &str) -> Option<String> {
+        self(element, children)
+    }
+}
+
+impl fmt::Debug for Stringifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stringifier")
+            .field("format", &self.format)
+            .field("width", &self.width)
+            .field("indent", &self.indent)
+            .field("preserve_inline", &self.preserve_inline)
+            .field("stable", &self.stable)
+            .field("escape", &self.escape)
+            .field("url_schemes", &self.url_schemes)
+            .field("sanitize", &self.sanitize)
+            .field("void_style", &self.void_style)
+            .field("document", &self.document)
+            .field("renderer", &self.renderer.is_some())
+            .field("variables", &self.variables)
+            .field("source_positions", &self.source_positions)
+            .finish()
+    }
+}
+
+pub mod config {
+    //! Configuration options for stringifying HTML.
+
+    use std::{fmt, str::FromStr};
+
+    use crate::error::ParseConfigError;
+
+    /// The style of void elements.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum VoidStyle {
+        /// `<br>`
+        Html,
+        /// `<br />`, which XML parsers can read.
+        Xhtml,
+    }
+
+    impl FromStr for VoidStyle {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "html" => Ok(Self::Html),
+                "xhtml" => Ok(Self::Xhtml),
+                _ => Err(ParseConfigError::new("VoidStyle", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for VoidStyle {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Html => f.write_str("html"),
+                Self::Xhtml => f.write_str("xhtml"),
+            }
+        }
+    }
+
+    /// The options of the full HTML document written by
+    /// [`Stringifier::document`](super::Stringifier::document).
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct DocumentOptions {
+        /// The title of the document. Default is empty.
+        pub title: String,
+        /// The charset of the document. Default is `utf-8`.
+        pub charset: String,
+        /// The URLs of the stylesheets to link. Default is empty.
+        pub stylesheets: Vec<String>,
+        /// The stylesheets to inline in `<style>` elements, such as
+        /// `theme::DEFAULT_CSS` of the `theme` feature. They are written as
+        /// they are. Default is empty.
+        pub styles: Vec<String>,
+        /// The language of the document. Default is `None`, which writes no
+        /// `lang` attribute.
+        pub lang: Option<String>,
+    }
+
+    impl Default for DocumentOptions {
+        fn default() -> Self {
+            Self {
+                title: String::new(),
+                charset: "utf-8".to_string(),
+                stylesheets: vec![],
+                styles: vec![],
+                lang: None,
+            }
+        }
+    }
+
+    impl DocumentOptions {
+        /// Create new DocumentOptions.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set the title of the document.
+        pub fn title(mut self, title: &str) -> Self {
+            self.title = title.to_string();
+            self
+        }
+
+        /// Set the charset of the document.
+        pub fn charset(mut self, charset: &str) -> Self {
+            self.charset = charset.to_string();
+            self
+        }
+
+        /// Add a stylesheet to link.
+        pub fn stylesheet(mut self, url: &str) -> Self {
+            self.stylesheets.push(url.to_string());
+            self
+        }
+
+        /// Add a stylesheet to inline.
+        pub fn style(mut self, css: &str) -> Self {
+            self.styles.push(css.to_string());
+            self
+        }
+
+        /// Set the language of the document.
+        pub fn lang(mut self, lang: &str) -> Self {
+            self.lang = Some(lang.to_string());
+            self
+        }
+    }
 }
 
 impl Default for Stringifier {
@@ -18,6 +242,17 @@ impl Default for Stringifier {
         Self {
             format: false,
             width: 20,
+            indent: "    ".to_string(),
+            preserve_inline: false,
+            stable: false,
+            escape: false,
+            url_schemes: None,
+            sanitize: false,
+            void_style: VoidStyle::Html,
+            document: None,
+            renderer: None,
+            variables: BTreeMap::new(),
+            source_positions: false,
         }
     }
 }
@@ -39,6 +274,344 @@ impl Stringifier {
         self.width = width;
         self
     }
+
+    /// Set the string to indent formatted lines with.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_string();
+        self
+    }
+
+    /// Set whether to keep elements without block children on one line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let stringifier = Stringifier::new().format(true).width(4);
+    ///
+    /// let markdown = Markdown::default().stringifier(stringifier.clone());
+    ///
+    /// assert_eq!(markdown.execute("Hello"), "<p>\n    Hello\n</p>");
+    ///
+    /// let markdown = Markdown::default().stringifier(stringifier.preserve_inline(true));
+    ///
+    /// assert_eq!(markdown.execute("Hello"), "<p>Hello</p>");
+    /// ```
+    pub fn preserve_inline(mut self, preserve_inline: bool) -> Self {
+        self.preserve_inline = preserve_inline;
+        self
+    }
+
+    /// Set whether to write byte-stable output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().class_for(ElementTag::P, "b").class_for(ElementTag::P, "a"))
+    ///     .stringifier(Stringifier::new().format(true).stable(true));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("A long paragraph of text\n{#x lang=en .c data-a=1}"),
+    ///     "<p class=\"a b c\" id=\"x\" data-a=\"1\" lang=\"en\">A long paragraph of text</p>"
+    /// );
+    /// ```
+    pub fn stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
+
+    /// Set whether to escape text and attribute values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(Stringifier::new().escape(true));
+    ///
+    /// let html = markdown.execute("<script>alert(\"&\")</script>");
+    ///
+    /// assert_eq!(html, "<p>&lt;script&gt;alert(&quot;&amp;&quot;)&lt;/script&gt;</p>");
+    /// ```
+    pub fn escape(mut self, escape: bool) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Set the URL schemes allowed in links.
+    ///
+    /// Schemes are compared case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(Stringifier::new().url_schemes(&["https"]));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("[a](https://a.org) [b](javascript:x)"),
+    ///     "<p><a href=\"https://a.org\">a</a> <a>b</a></p>"
+    /// );
+    /// ```
+    pub fn url_schemes(mut self, schemes: &[&str]) -> Self {
+        self.url_schemes = Some(
+            schemes
+                .iter()
+                .map(|scheme| scheme.to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Set whether to drop attributes which can run scripts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(Stringifier::new().sanitize(true));
+    ///
+    /// let html = markdown.execute("# Title {.a onclick=alert(1) data-x=1}");
+    ///
+    /// assert_eq!(html, "<h1 class=\"a\" data-x=\"1\">Title</h1>");
+    /// ```
+    pub fn sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    /// Set how to write void elements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(Stringifier::new().void_style(VoidStyle::Xhtml));
+    ///
+    /// assert_eq!(markdown.execute("a\nb"), "<p>a<br />b</p>");
+    /// ```
+    pub fn void_style(mut self, style: VoidStyle) -> Self {
+        self.void_style = style;
+        self
+    }
+
+    /// Set the options of the full HTML document to wrap the output in.
+    ///
+    /// The title, the charset, the stylesheet URLs and the language are
+    /// always escaped. Inlined stylesheets are written as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(
+    ///     Stringifier::new().document(
+    ///         DocumentOptions::new()
+    ///             .title("Hello")
+    ///             .stylesheet("style.css")
+    ///             .lang("en"),
+    ///     ),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("# Hello"),
+    ///     "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><title>Hello</title><link rel=\"stylesheet\" href=\"style.css\"></head><body><h1>Hello</h1></body></html>"
+    /// );
+    /// ```
+    pub fn document(mut self, options: DocumentOptions) -> Self {
+        self.document = Some(options);
+        self
+    }
+
+    /// Set the renderer consulted before writing each element.
+    pub fn renderer(mut self, renderer: impl Render + 'static) -> Self {
+        self.renderer = Some(Arc::new(renderer));
+        self
+    }
+
+    /// Set the value of `{{key}}` placeholders in text, such as for note
+    /// templates.
+    ///
+    /// Spaces around the key are ignored, and placeholders whose braces are
+    /// escaped with backslashes are not replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(
+    ///     Stringifier::new()
+    ///         .variable("date", "2024-05-01")
+    ///         .variable("author", "<Ann>"),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("# {{ date }}\n\nBy *{{author}}*, not \\{{date}} or {{other}}"),
+    ///     "<h1>2024-05-01</h1><p>By <em>&lt;Ann&gt;</em>, not {{date}} or {{other}}</p>"
+    /// );
+    /// ```
+    pub fn variable(mut self, key: &str, value: &str) -> Self {
+        self.variables.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Set whether to write the source positions of blocks, such as to
+    /// scroll an editor and its preview together.
+    ///
+    /// Positions are written like `data-sourcepos="3:1-4:10"` of cmark: the
+    /// lines and the columns of the first and the last bytes of the block,
+    /// counted from 1. Columns are counted in bytes. Paragraphs, headlines,
+    /// lists and blockquotes have positions, including those nested in lists
+    /// and blockquotes, and an attribute line or a citation belongs to its
+    /// block. List items, inline elements, elements made by the transformer
+    /// and included blocks do not have positions.
+    ///
+    /// The positions are recorded while parsing, so they are written when
+    /// the input is rendered by [`Markdown`](crate::Markdown), except by
+    /// [`IncrementalDocument`](crate::incremental::IncrementalDocument).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().stringifier(Stringifier::new().source_positions(true));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("# Title\n\n- one\n- two\n\n> quote\n> more"),
+    ///     concat![
+    ///         "<h1 data-sourcepos=\"1:1-1:7\">Title</h1>",
+    ///         "<ul data-sourcepos=\"3:1-4:5\"><li>one</li><li>two</li></ul>",
+    ///         "<blockquote data-sourcepos=\"6:1-7:6\">",
+    ///         "<p data-sourcepos=\"6:3-7:6\">quote<br>more</p></blockquote>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn source_positions(mut self, source_positions: bool) -> Self {
+        self.source_positions = source_positions;
+        self
+    }
+
+    /// Filter the attribute by [`sanitize`](Self::sanitize), and normalize
+    /// the URL in it and filter it by [`url_schemes`](Self::url_schemes).
+    fn allowed_attr<'a>(&self, name: &str, value: &'a str) -> Option<Cow<'a, str>> {
+        let name = name.to_ascii_lowercase();
+
+        if self.sanitize && (name.starts_with("on") || name == "style" || name == "srcdoc") {
+            return None;
+        }
+
+        match name.as_str() {
+            "href" | "src" | "action" | "formaction" | "xlink:href" | "poster" | "cite" => {
+                url_sanitize(value, self.url_schemes.as_deref())
+            }
+            _ => Some(Cow::Borrowed(value)),
+        }
+    }
+}
+
+/// Escape `&`, `<`, `>` and `"` in an attribute value, so the value cannot
+/// close the attribute or the tag.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::layer::stringifier::attr_escape;
+///
+/// let mut output = String::new();
+///
+/// attr_escape("a \"b\" & <c>", &mut output);
+///
+/// assert_eq!(output, "a &quot;b&quot; &amp; &lt;c&gt;");
+/// ```
+pub fn attr_escape(text: &str, output: &mut String) {
+    escape_html(text, output);
+}
+
+/// Normalize a URL, and check its scheme.
+///
+/// White spaces at both ends and control characters are removed, since
+/// browsers ignore them as in `java\tscript:`, and the other spaces are
+/// percent-encoded. If `schemes` is given, `None` is returned for a URL with
+/// a scheme which is not in it. Schemes are compared case-insensitively, and
+/// relative URLs are always allowed.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::layer::stringifier::url_sanitize;
+///
+/// let schemes = ["https".to_string()];
+///
+/// assert_eq!(url_sanitize(" a b.html\n", None).as_deref(), Some("a%20b.html"));
+/// assert_eq!(url_sanitize("HTTPS://a.org", Some(&schemes)).as_deref(), Some("HTTPS://a.org"));
+/// assert_eq!(url_sanitize("java\tscript:x", Some(&schemes)), None);
+/// ```
+pub fn url_sanitize<'a>(url: &'a str, schemes: Option<&[String]>) -> Option<Cow<'a, str>> {
+    let trimmed = url.trim_matches(|c: char| c.is_ascii_whitespace() || c.is_control());
+
+    let url = match trimmed.contains(|c: char| c == ' ' || c.is_control()) {
+        true => Cow::Owned(
+            trimmed
+                .chars()
+                .filter(|c| !c.is_control())
+                .map(|c| match c {
+                    ' ' => "%20".to_string(),
+                    c => c.to_string(),
+                })
+                .collect(),
+        ),
+        false => Cow::Borrowed(trimmed),
+    };
+
+    let Some(schemes) = schemes else {
+        return Some(url);
+    };
+
+    match url.find([':', '/', '?', '#']) {
+        Some(index) if url[index..].starts_with(':') => {
+            let scheme = url[..index].to_ascii_lowercase();
+
+            schemes.contains(&scheme).then_some(url)
+        }
+        _ => Some(url),
+    }
+}
+
+/// The state of an element while stringifying its children.
+struct Frame {
+    /// The position of the output where the element starts.
+    open: usize,
+    /// The position of the output where the children start.
+    start: usize,
+    /// The number of children written.
+    children: usize,
+    /// Whether the children are separated by line breaks.
+    separated: bool,
+    /// Whether the last child written is a block item.
+    after_block: bool,
+}
+
+/// Write text with HTML special characters escaped.
+pub(crate) fn escape_html(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
 }
 
 fn tag_to_str(tag: ElementTag) -> &'static str {
@@ -56,9 +629,25 @@ fn tag_to_str(tag: ElementTag) -> &'static str {
         ElementTag::Ol => "ol",
         ElementTag::Li => "li",
         ElementTag::Blockquote => "blockquote",
+        ElementTag::Nav => "nav",
+        ElementTag::Section => "section",
+        ElementTag::Figure => "figure",
+        ElementTag::Figcaption => "figcaption",
+        ElementTag::Table => "table",
+        ElementTag::Thead => "thead",
+        ElementTag::Tbody => "tbody",
+        ElementTag::Tr => "tr",
+        ElementTag::Th => "th",
+        ElementTag::Td => "td",
+        ElementTag::Pre => "pre",
+        ElementTag::Code => "code",
         ElementTag::A => "a",
         ElementTag::Strong => "strong",
         ElementTag::Em => "em",
+        ElementTag::Mark => "mark",
+        ElementTag::Abbr => "abbr",
+        ElementTag::Sup => "sup",
+        ElementTag::Button => "button",
         ElementTag::Br => "br",
     }
 }
@@ -66,104 +655,591 @@ fn tag_to_str(tag: ElementTag) -> &'static str {
 impl Stringifier {
     /// Stringify DocumentNode to html string.
     pub fn stringify(&self, document: DocumentNode) -> String {
-        let list = document
-            .root
-            .into_iter()
-            .map(|node| self.stringify_node(node))
-            .collect::<Vec<_>>();
+        let mut output = String::new();
 
-        if self.format {
-            list.join("\n")
-        } else {
-            list.join("")
+        match &self.document {
+            Some(options) => self.write_document(options, &mut output, |output| {
+                self.stringify_into(&document, output)
+            }),
+            None => self.stringify_into(&document, &mut output),
         }
+
+        output
     }
 
-    fn stringify_node(&self, node: Node) -> String {
-        match node {
-            Node::Element(element) => self.stringify_element(element),
-            Node::Text(text) => self.stringify_text(text),
+    /// Write the full HTML document of the options around the body written
+    /// by the function.
+    pub(crate) fn write_document(
+        &self,
+        options: &DocumentOptions,
+        output: &mut String,
+        body: impl FnOnce(&mut String),
+    ) {
+        // Parts of the wrapper are put on their own lines when formatted.
+        let newline = if self.formatted() { "\n" } else { "" };
+
+        output.push_str("<!DOCTYPE html>");
+        output.push_str(newline);
+        output.push_str("<html");
+
+        if let Some(lang) = &options.lang {
+            output.push_str(" lang=\"");
+            escape_html(lang, output);
+            output.push('"');
+        }
+
+        output.push('>');
+        output.push_str(newline);
+        output.push_str("<head><meta charset=\"");
+        escape_html(&options.charset, output);
+        output.push('"');
+        self.close_void(output);
+        output.push_str("<title>");
+        escape_html(&options.title, output);
+        output.push_str("</title>");
+
+        for url in &options.stylesheets {
+            output.push_str("<link rel=\"stylesheet\" href=\"");
+            escape_html(url, output);
+            output.push('"');
+            self.close_void(output);
+        }
+
+        for css in &options.styles {
+            output.push_str("<style>");
+            output.push_str(css);
+            output.push_str("</style>");
         }
+
+        output.push_str("</head>");
+        output.push_str(newline);
+        output.push_str("<body>");
+        output.push_str(newline);
+
+        body(output);
+
+        output.push_str(newline);
+        output.push_str("</body>");
+        output.push_str(newline);
+        output.push_str("</html>");
     }
 
-    fn stringify_element(&self, element: ElementNode) -> String {
-        let tag = tag_to_str(element.tag);
+    /// Stringify DocumentNode and append it to the output.
+    ///
+    /// Nodes are written into the output directly, so this is useful to render
+    /// many documents into one buffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{model::html::*, prelude::*};
+    ///
+    /// let document = DocumentNode {
+    ///     root: vec![Node::Text(TextNode { text: "Hello".into() })],
+    /// };
+    ///
+    /// let mut output = String::from("<main>");
+    ///
+    /// Stringifier::new().stringify_into(&document, &mut output);
+    ///
+    /// assert_eq!(output, "<main>Hello");
+    /// ```
+    pub fn stringify_into(&self, document: &DocumentNode, output: &mut String) {
+        // The frames of the root and the open elements.
+        let mut stack = vec![Frame {
+            open: output.len(),
+            start: output.len(),
+            children: 0,
+            separated: self.formatted(),
+            after_block: true,
+        }];
 
-        match element.tag {
-            ElementTag::Br => format!("<{tag}>"),
-            _ => {
-                let mut attrs = String::new();
+        // The number of open `<code>` elements, whose text is code.
+        let mut code = 0;
 
-                if !element.class.is_empty() {
-                    attrs += &format!(
-                        " class=\"{}\"",
-                        element.class.into_iter().collect::<Vec<_>>().join(" ")
-                    );
+        walk(&document.root, &mut |event| match event {
+            WalkEvent::Enter(element) => {
+                Self::separate(&mut stack, element.tag.is_block_item(), output);
+
+                if element.tag == ElementTag::Code {
+                    code += 1;
                 }
 
-                if !element.id.is_empty() {
-                    attrs += &format!(
-                        " id=\"{}\"",
-                        element.id.into_iter().collect::<Vec<_>>().join(" ")
-                    );
+                let open = output.len();
+
+                self.open_element(element, output);
+
+                // White spaces are not rendered at the start and the end of a
+                // block, nor next to a block in it, so only those get line
+                // breaks.
+                stack.push(Frame {
+                    open,
+                    start: output.len(),
+                    children: 0,
+                    separated: self.formatted()
+                        && element.tag.is_block_item()
+                        && element.children.len() != 1
+                        && element.children.iter().any(|node| node.is_block_item()),
+                    after_block: true,
+                });
+            }
+            WalkEvent::Leave(element) => {
+                let frame = stack.pop().unwrap();
+
+                if element.tag == ElementTag::Code {
+                    code -= 1;
                 }
 
-                if let Some(href) = element.href {
-                    attrs += &format!(" href=\"{href}\"");
+                if let Some(renderer) = &self.renderer {
+                    if let Some(html) = renderer.element(element, &output[frame.start..]) {
+                        output.truncate(frame.open);
+                        output.push_str(&html);
+
+                        return;
+                    }
+                }
+
+                if element.tag.is_void() {
+                    return;
+                }
+
+                // The text of `<pre>` is never formatted.
+                let long = self.formatted()
+                    && element.tag.is_block_item()
+                    && element.tag != ElementTag::Pre
+                    && element.children.len() == 1
+                    && (!self.preserve_inline || element.children[0].is_block_item())
+                    && get_text(&element.children).chars().count() >= self.width as usize;
+
+                if self.formatted() && (frame.separated || long) {
+                    self.indent_from(output, frame.start);
+                }
+
+                output.push_str("</");
+                output.push_str(tag_to_str(element.tag));
+                output.push('>');
+            }
+            WalkEvent::Text(text) => {
+                Self::separate(&mut stack, false, output);
+
+                match code {
+                    0 => self.write_text(&text.text, output),
+                    _ => escape_html(&text.text, output),
                 }
+            }
+            WalkEvent::Comment(comment) => {
+                Self::separate(&mut stack, false, output);
+
+                // Browsers also end a comment at `<!-->`, `<!--->` and `--!>`,
+                // so no `>` of the text is written as it is.
+                output.push_str("<!--");
+                output.push_str(&comment.text.replace('>', "&gt;"));
+                output.push_str("-->");
+            }
+        });
+    }
+
+    /// Whether the output is formatted, which is never in stable output.
+    fn formatted(&self) -> bool {
+        self.format && !self.stable
+    }
+
+    /// Count a child of the current element, and put a line break before it
+    /// if the children are separated and it or the child before it is a
+    /// block item.
+    fn separate(stack: &mut [Frame], block: bool, output: &mut String) {
+        let frame = stack.last_mut().unwrap();
 
-                attrs += &element
+        if frame.separated && frame.children != 0 && (block || frame.after_block) {
+            output.push('\n');
+        }
+
+        frame.children += 1;
+        frame.after_block = block;
+    }
+
+    fn open_element(&self, element: &ElementNode, output: &mut String) {
+        output.push('<');
+        output.push_str(tag_to_str(element.tag));
+
+        let (class, attrs) = match self.stable {
+            true => element.sorted_attrs(),
+            false => (
+                element.class.iter().map(AsRef::as_ref).collect(),
+                element
                     .attrs
                     .iter()
-                    .map(|(name, value)| format!(" {name}=\"{value}\""))
-                    .collect::<String>();
+                    .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                    .collect(),
+            ),
+        };
 
-                let list = element
-                    .children
-                    .iter()
-                    .cloned()
-                    .map(|node| self.stringify_node(node))
-                    .collect::<Vec<_>>();
+        if !class.is_empty() {
+            self.write_attr(output, "class", &class);
+        }
+
+        if let Some(id) = &element.id {
+            self.write_attr(output, "id", &[id]);
+        }
+
+        if let Some(href) = element
+            .href
+            .as_ref()
+            .and_then(|url| url_sanitize(url, self.url_schemes.as_deref()))
+        {
+            self.write_attr(output, "href", &[href]);
+        }
+
+        for (name, value) in attrs {
+            if let Some(value) = self.allowed_attr(name, value) {
+                self.write_attr(output, name, &[value]);
+            }
+        }
+
+        match element.tag.is_void() {
+            true => self.close_void(output),
+            false => output.push('>'),
+        }
+    }
+
+    /// Close the start tag of a void element.
+    fn close_void(&self, output: &mut String) {
+        match self.void_style {
+            VoidStyle::Html => output.push('>'),
+            VoidStyle::Xhtml => output.push_str(" />"),
+        }
+    }
+
+    /// Write an attribute whose values are joined with spaces.
+    fn write_attr(&self, output: &mut String, name: &str, values: &[impl AsRef<str>]) {
+        output.push(' ');
+        output.push_str(name);
+        output.push_str("=\"");
+
+        for (index, value) in values.iter().enumerate() {
+            if index != 0 {
+                output.push(' ');
+            }
+
+            attr_escape(value.as_ref(), output);
+        }
+
+        output.push('"');
+    }
+
+    /// Write text, replacing placeholders of
+    /// [`variables`](Self::variables) with their escaped values.
+    fn write_text(&self, text: &str, output: &mut String) {
+        let mut rest = text;
+
+        while !self.variables.is_empty() {
+            let Some(start) = rest.find("{{") else {
+                break;
+            };
+
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+
+            match self.variables.get(rest[start + 2..start + 2 + len].trim()) {
+                Some(value) => {
+                    self.write_plain(&rest[..start], output);
+                    escape_html(value, output);
+
+                    rest = &rest[start + len + 4..];
+                }
+                None => {
+                    self.write_plain(&rest[..start + 2], output);
+
+                    rest = &rest[start + 2..];
+                }
+            }
+        }
+
+        self.write_plain(rest, output);
+    }
+
+    /// Write text, escaping it if [`escape`](Self::escape) is set.
+    fn write_plain(&self, text: &str, output: &mut String) {
+        match self.escape {
+            true => escape_html(text, output),
+            false => output.push_str(text),
+        }
+    }
+
+    /// Indent every line of the output after `start`, and put it on its own
+    /// lines. Lines which start in `<pre>` are not indented.
+    fn indent_from(&self, output: &mut String, start: usize) {
+        let inner = output.split_off(start);
+
+        output.push('\n');
+
+        let mut pre = false;
+
+        for (index, line) in inner.lines().enumerate() {
+            if index != 0 {
+                output.push('\n');
+            }
+
+            if !pre {
+                output.push_str(&self.indent);
+            }
+
+            output.push_str(line);
+
+            // Text in `<pre>` is escaped, so `<pre` and `</pre>` are tags.
+            pre = match (line.rfind("<pre"), line.rfind("</pre>")) {
+                (Some(open), close) => close.map_or(true, |close| open > close),
+                (None, close) => pre && close.is_none(),
+            };
+        }
+
+        output.push('\n');
+    }
+}
+
+/// Stringify DocumentNode to readable plain text.
+///
+/// Blocks are separated by blank lines, list items get bullets or numbers,
+/// and blockquotes get `> ` prefixes. Tags and attributes are dropped.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let markdown = Markdown::default();
+///
+/// let input = "# Title\n\nHello **world**\n\n- a\n  - b\n\n> quote";
+///
+/// let text = markdown.execute_text(input);
+///
+/// assert_eq!(text, "Title\n\nHello world\n\n- a\n  - b\n\n> quote");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextStringifier {
+    /// The marker of bullet list items. Default is `- `.
+    pub bullet: String,
+}
+
+impl Default for TextStringifier {
+    fn default() -> Self {
+        Self {
+            bullet: "- ".to_string(),
+        }
+    }
+}
 
-                let inner = if self.format {
-                    if element.children.len() == 1 {
-                        let child = list[0].clone();
+impl TextStringifier {
+    /// Create a new TextStringifier.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the marker of bullet list items.
+    pub fn bullet(mut self, bullet: &str) -> Self {
+        self.bullet = bullet.to_string();
+        self
+    }
+}
+
+impl TextStringifier {
+    /// Stringify DocumentNode to plain text.
+    pub fn stringify(&self, document: &DocumentNode) -> String {
+        let mut writer = TextWriter::default();
+
+        walk(&document.root, &mut |event| match event {
+            WalkEvent::Enter(element) => match element.tag {
+                ElementTag::Br => writer.line_break(1),
+                ElementTag::Ul => {
+                    writer.block_break();
+                    writer.lists.push(None);
+                }
+                ElementTag::Ol => {
+                    // The last value of an attribute is the one written.
+                    let attr = |key: &str| {
+                        element
+                            .attrs
+                            .iter()
+                            .rev()
+                            .find(|(name, _)| name == key)
+                            .map(|(_, value)| value.as_ref())
+                    };
+
+                    let start = attr("start").and_then(|start| start.parse().ok());
+                    let counter = match attr("type") {
+                        Some("a") => Counter::LowerAlpha,
+                        Some("A") => Counter::UpperAlpha,
+                        Some("i") => Counter::LowerRoman,
+                        Some("I") => Counter::UpperRoman,
+                        _ => Counter::Decimal,
+                    };
+
+                    writer.block_break();
+                    writer.lists.push(Some((start.unwrap_or(1), counter)));
+                }
+                ElementTag::Li => {
+                    writer.line_break(1);
 
-                        if child.len() >= self.width as usize {
-                            let child = Self::add_indent(&child);
+                    let marker = match writer.lists.last_mut() {
+                        Some(Some((number, counter))) => {
+                            let marker = format!("{}. ", counter.format(*number));
 
-                            format!("\n{child}\n")
-                        } else {
-                            child
+                            *number = number.saturating_add(1);
+
+                            marker
                         }
-                    } else if !element.children.iter().any(|node| node.is_block_item()) {
-                        list.join("")
-                    } else {
-                        let children = list.join("\n");
+                        _ => self.bullet.clone(),
+                    };
 
-                        let children = Self::add_indent(&children);
+                    writer.write(&marker);
+                    writer.prefixes.push(" ".repeat(marker.chars().count()));
+                    writer.item_start = true;
+                }
+                ElementTag::Blockquote => {
+                    writer.block_break();
+                    writer.prefixes.push("> ".to_string());
+                }
+                ElementTag::Thead | ElementTag::Tbody => {}
+                ElementTag::Tr => {
+                    writer.line_break(1);
+                    writer.row_start = true;
+                }
+                ElementTag::Th | ElementTag::Td => {
+                    if !writer.row_start {
+                        writer.write("\t");
+                    }
+
+                    writer.row_start = false;
+                }
+                tag if tag.is_block_item() => writer.block_break(),
+                _ => {}
+            },
+            WalkEvent::Leave(element) => match element.tag {
+                ElementTag::Ul | ElementTag::Ol => {
+                    writer.lists.pop();
+                    writer.block_break();
+                }
+                ElementTag::Li | ElementTag::Blockquote => {
+                    writer.prefixes.pop();
+                    writer.block_break();
+                }
+                ElementTag::Thead | ElementTag::Tbody | ElementTag::Th | ElementTag::Td => {}
+                ElementTag::Tr => writer.line_break(1),
+                tag if tag.is_block_item() => writer.block_break(),
+                _ => {}
+            },
+            WalkEvent::Text(text) => {
+                // Blank lines in text such as code are kept.
+                let mut breaks = 0;
+
+                for (index, line) in text.text.split('\n').enumerate() {
+                    if index != 0 {
+                        breaks += 1;
+                    }
+
+                    if !line.is_empty() {
+                        if breaks != 0 {
+                            writer.text_break(breaks);
+                            breaks = 0;
+                        }
 
-                        format!("\n{children}\n")
+                        writer.write(line);
                     }
-                } else {
-                    list.join("")
-                };
+                }
 
-                format!("<{tag}{attrs}>{inner}</{tag}>")
+                if breaks != 0 {
+                    writer.text_break(breaks);
+                }
             }
+            WalkEvent::Comment(_) => {}
+        });
+
+        writer.output
+    }
+}
+
+/// The state of writing plain text.
+#[derive(Default)]
+struct TextWriter {
+    output: String,
+    /// The prefixes of lines, such as `> ` and list indents.
+    prefixes: Vec<String>,
+    /// The numbers of the next items of open lists and their styles. `None`
+    /// is a bullet list.
+    lists: Vec<Option<(usize, Counter)>>,
+    /// The number of line breaks to put before the next text.
+    breaks: usize,
+    /// The number of the line breaks which are newlines in text, such as
+    /// between the lines of wrapped code.
+    text_breaks: usize,
+    /// The number of prefixes of blank lines between the breaks, which is the
+    /// least number of prefixes since the breaks are requested.
+    blank_prefixes: usize,
+    /// Whether nothing is written after the marker of a list item.
+    item_start: bool,
+    /// Whether no cell is written in the current row of a table. Cells are
+    /// separated by tabs.
+    row_start: bool,
+}
+
+impl TextWriter {
+    /// Put a blank line before the next block. Blocks in a list are put on
+    /// the next line instead.
+    fn block_break(&mut self) {
+        if self.item_start {
+            return;
         }
+
+        self.line_break(if self.lists.is_empty() { 2 } else { 1 });
+    }
+
+    /// Put line breaks before the next text.
+    fn line_break(&mut self, count: usize) {
+        self.blank_prefixes = match self.breaks {
+            0 => self.prefixes.len(),
+            _ => self.blank_prefixes.min(self.prefixes.len()),
+        };
+
+        self.breaks = self.breaks.max(count);
+        self.item_start = false;
     }
 
-    fn stringify_text(&self, text: TextNode) -> String {
-        text.text.to_string()
+    /// Put the line breaks of newlines in text, which add up with the
+    /// newlines of the text before them.
+    fn text_break(&mut self, count: usize) {
+        self.text_breaks += count;
+        self.line_break(self.text_breaks);
     }
 
-    fn add_indent(input: &str) -> String {
-        input
-            .lines()
-            .map(|line| String::from("    ") + line)
-            .collect::<Vec<_>>()
-            .join("\n")
+    /// Write text on the current line.
+    fn write(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let prefix = self.prefixes.concat();
+
+        if self.output.is_empty() {
+            self.output.push_str(&prefix);
+        } else if self.breaks != 0 {
+            self.output.push('\n');
+
+            let depth = self.blank_prefixes.min(self.prefixes.len());
+            let blank = self.prefixes[..depth].concat();
+
+            for _ in 1..self.breaks {
+                self.output.push_str(blank.trim_end());
+                self.output.push('\n');
+            }
+
+            self.output.push_str(&prefix);
+        }
+
+        self.breaks = 0;
+        self.text_breaks = 0;
+        self.item_start = false;
+        self.output.push_str(text);
     }
 }
 
@@ -231,13 +1307,295 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_stringify_format() {
+        let input = "# A\n\n- a **b**\n- long long long line here\n  - c";
+
+        let markdown = crate::Markdown::default().stringifier(Stringifier::new().format(true));
+
+        assert_eq!(
+            markdown.execute(input),
+            "<h1>A</h1>\n<ul>\n    <li>a <strong>b</strong></li>\n    <li>\n        long long long line here\n        <ul><li>c</li></ul>\n    </li>\n</ul>"
+        );
+        let markdown = crate::Markdown::default()
+            .stringifier(Stringifier::new().format(true).void_style(VoidStyle::Xhtml));
+
+        assert_eq!(
+            markdown.execute("- a long line\n  - b\n\ntext with a\nbreak"),
+            "<ul><li>\n    a long line\n    <ul><li>b</li></ul>\n</li></ul>\n<p>text with a<br />break</p>"
+        );
+
+        // Only the text is counted, so the link is not broken.
+        let markdown =
+            crate::Markdown::default().stringifier(Stringifier::new().format(true).indent("\t"));
+
+        assert_eq!(
+            markdown.execute("- [short](https://example.com/a/long/url)\n- a long long long line"),
+            "<ul>\n\t<li><a href=\"https://example.com/a/long/url\">short</a></li>\n\t<li>\n\t\ta long long long line\n\t</li>\n</ul>"
+        );
+
+        // Inline children are not broken apart, and inline elements keep
+        // their text on one line.
+        let markdown =
+            crate::Markdown::default().stringifier(Stringifier::new().format(true).width(4));
+
+        assert_eq!(
+            markdown.execute("- a*long*\n  - c"),
+            "<ul>\n    <li>\n        a<em>long</em>\n        <ul><li>c</li></ul>\n    </li>\n</ul>"
+        );
+
+        // The text of code blocks is never indented.
+        assert_eq!(
+            markdown.execute("> ```\n> a long\n>   line\n> ```\n> b"),
+            "<blockquote>\n    <pre><code>a long\n  line\n</code></pre>\n    <p>b</p>\n</blockquote>"
+        );
+
+        for style in [VoidStyle::Html, VoidStyle::Xhtml] {
+            assert_eq!(style.to_string().parse(), Ok(style));
+        }
+    }
+
+    #[test]
+    fn test_stringify_document() {
+        let markdown = crate::Markdown::default().stringifier(
+            Stringifier::new()
+                .format(true)
+                .void_style(VoidStyle::Xhtml)
+                .document(DocumentOptions::new().title("<a> & b").charset("\"x")),
+        );
+
+        assert_eq!(
+            markdown.execute("a"),
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"&quot;x\" /><title>&lt;a&gt; &amp; b</title></head>\n<body>\n<p>a</p>\n</body>\n</html>"
+        );
+
+        let (_, toc) = markdown.execute_with_toc("# a");
+
+        assert!(!toc.contains("<html>"));
+
+        let markdown = crate::Markdown::default().stringifier(
+            Stringifier::new().document(DocumentOptions::new().style("p > a {}").lang("en")),
+        );
+
+        assert_eq!(
+            markdown.execute("a"),
+            "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><title></title><style>p > a {}</style></head><body><p>a</p></body></html>"
+        );
+    }
+
+    #[test]
+    fn test_stringify_renderer() {
+        let renderer = |element: &ElementNode, children: &str| match element.tag {
+            ElementTag::Ul => Some(format!("<div class=\"scroll\"><ul>{children}</ul></div>")),
+            ElementTag::Strong => Some(format!("<b>{children}</b>")),
+            ElementTag::Br => Some("<br/>".to_string()),
+            _ => None,
+        };
+
+        let markdown =
+            crate::Markdown::default().stringifier(Stringifier::new().renderer(renderer));
+
+        assert_eq!(
+            markdown.execute("- a **b**\n- d\n\ne\nf"),
+            "<div class=\"scroll\"><ul><li>a <b>b</b></li><li>d</li></ul></div><p>e<br/>f</p>"
+        );
+
+        // A highlighter sees the language and the info string of code.
+        let highlighter = |element: &ElementNode, children: &str| match element.tag {
+            ElementTag::Code => {
+                let lines = element
+                    .attrs
+                    .iter()
+                    .find(|(name, _)| name == "data-hl_lines")?;
+
+                Some(format!(
+                    "<code class=\"{}\" data-lines=\"{}\">{children}</code>",
+                    element.class.join(" "),
+                    lines.1
+                ))
+            }
+            _ => None,
+        };
+
+        let markdown =
+            crate::Markdown::default().stringifier(Stringifier::new().renderer(highlighter));
+
+        assert_eq!(
+            markdown.execute("```rust hl_lines=1\na < b\n```"),
+            "<pre><code class=\"language-rust\" data-lines=\"1\">a &lt; b\n</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_stringify_url_schemes() {
+        let link = |href: &str| DocumentNode {
+            root: vec![Node::Element(ElementNode {
+                tag: ElementTag::A,
+                href: Some(href.into()),
+                ..Default::default()
+            })],
+        };
+
+        let stringifier = Stringifier::new().url_schemes(&["https", "mailto"]);
+
+        for href in [
+            "https://example.com",
+            "HTTPS://a",
+            "mailto:a@b",
+            "#top",
+            "/a:b",
+            "a?b:c",
+        ] {
+            assert_eq!(
+                stringifier.stringify(link(href)),
+                format!("<a href=\"{href}\"></a>")
+            );
+        }
+
+        for href in [
+            "javascript:alert(1)",
+            "JavaScript:x",
+            "java\tscript:x",
+            " data:x",
+            "http://a",
+        ] {
+            assert_eq!(stringifier.stringify(link(href)), "<a></a>");
+        }
+
+        for (href, expected) in [
+            (" /a b\n", "/a%20b"),
+            ("/a\tb?c=\"d\"&e", "/ab?c=&quot;d&quot;&amp;e"),
+            ("mailto:\u{7f}a", "mailto:a"),
+        ] {
+            assert_eq!(
+                stringifier.stringify(link(href)),
+                format!("<a href=\"{expected}\"></a>")
+            );
+        }
+
+        let markdown = crate::Markdown::default();
+
+        assert_eq!(
+            markdown.execute(r#"[a](<b> "c \"d\" <e>")"#),
+            "<p><a href=\"&lt;b&gt;\" title=\"c &quot;d&quot; &lt;e&gt;\">a</a></p>"
+        );
+    }
+
+    #[test]
+    fn test_stringify_comment() {
+        let document = DocumentNode {
+            root: vec![Node::Comment(CommentNode {
+                text: " a --> b --!> ".into(),
+            })],
+        };
+
+        assert_eq!(
+            Stringifier::new().stringify(document),
+            "<!-- a --&gt; b --!&gt; -->"
+        );
+
+        for (text, expected) in [
+            (
+                "><script>alert(1)</script>",
+                "<!--&gt;<script&gt;alert(1)</script&gt;-->",
+            ),
+            ("-><script>", "<!---&gt;<script&gt;-->"),
+        ] {
+            let document = DocumentNode {
+                root: vec![Node::Comment(CommentNode { text: text.into() })],
+            };
+
+            assert_eq!(Stringifier::new().stringify(document), expected);
+        }
+
+        use crate::layer::parser::{config::Comments, Parser};
+
+        let markdown = crate::Markdown::default()
+            .parser(Parser::new().comments(Comments::Preserve))
+            .stringifier(Stringifier::new().escape(true).sanitize(true));
+
+        assert_eq!(
+            markdown.execute("<!--><script>alert(1)</script>-->"),
+            "<!--&gt;<script&gt;alert(1)</script&gt;-->"
+        );
+        assert_eq!(
+            markdown.execute("<!---><script>alert(1)</script>-->"),
+            "<!---&gt;<script&gt;alert(1)</script&gt;-->"
+        );
+    }
+
+    #[test]
+    fn test_text_stringify() {
+        let input = concat![
+            "# Title\n\n",
+            "Hello **world**\n",
+            "next line\n\n",
+            "- a\n",
+            "- b\n",
+            "  1. c\n",
+            "  1. d\n",
+            "- e\n\n",
+            "> quote\n",
+            ">\n",
+            "> > - x\n\n",
+            "End",
+        ];
+
+        let markdown = crate::Markdown::default();
+
+        assert_eq!(
+            markdown.execute_text(input),
+            concat![
+                "Title\n\n",
+                "Hello world\n",
+                "next line\n\n",
+                "- a\n",
+                "- b\n",
+                "  1. c\n",
+                "  2. d\n",
+                "- e\n\n",
+                "> quote\n",
+                ">\n",
+                "> > - x\n\n",
+                "End",
+            ]
+        );
+
+        let markdown = markdown.text_stringifier(TextStringifier::new().bullet("* "));
+
+        assert_eq!(markdown.execute_text("> - a\n>   - b"), "> * a\n>   * b");
+        assert_eq!(markdown.execute_text("3. a\n4. b"), "3. a\n4. b");
+
+        let fancy = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().fancy_lists(true));
+
+        assert_eq!(fancy.execute_text("c. a\nd. b"), "c. a\nd. b");
+        assert_eq!(fancy.execute_text("IV. a\nV. b"), "IV. a\nV. b");
+        assert_eq!(markdown.execute_text(""), "");
+        assert_eq!(
+            markdown.execute_text("> ```\n>   a\n>\n> b\n> ```\n\nc"),
+            ">   a\n>\n> b\n\nc"
+        );
+
+        let wrapped = crate::Markdown::default().transformer(
+            crate::layer::transformer::Transformer::new()
+                .code_block_wrapper(true)
+                .copy_button(true),
+        );
+
+        assert_eq!(
+            wrapped.execute_text("- x\n\n  ```\n  a\n\n\n  b\n  ```"),
+            "- x\n  a\n\n\n  b"
+        );
+    }
+
     #[test]
     fn test_stringify_attrs() {
         let document = DocumentNode {
             root: vec![Node::Element(ElementNode {
                 tag: ElementTag::P,
                 class: vec!["test".into(), "test2".into()],
-                id: vec!["ttt".into()],
+                id: Some("ttt".into()),
                 href: Some("https://example.com".into()),
                 attrs: vec![
                     ("data-test".into(), "ok".into()),
@@ -246,7 +1604,6 @@ mod tests {
                 children: vec![Node::Text(TextNode {
                     text: "Hello, world!".into(),
                 })],
-                ..Default::default()
             })],
         };
 
@@ -257,4 +1614,120 @@ mod tests {
             "<p class=\"test test2\" id=\"ttt\" href=\"https://example.com\" data-test=\"ok\" data-test2=\"ok2\">Hello, world!</p>".to_string()
         );
     }
+
+    #[test]
+    fn test_stringify_variables() {
+        let stringifier = Stringifier::new()
+            .variable("a", "A & B")
+            .variable("", "empty");
+
+        let text = |text: &'static str| DocumentNode {
+            root: vec![Node::Text(TextNode { text: text.into() })],
+        };
+
+        for (input, expected) in [
+            ("{{a}}{{ a }}", "A &amp; BA &amp; B"),
+            ("{{{{a}}}}", "{{A &amp; B}}"),
+            ("{{b}} {{a", "{{b}} {{a"),
+            ("{{}} <i>", "empty <i>"),
+        ] {
+            assert_eq!(stringifier.stringify(text(input)), expected, "{input:?}");
+        }
+
+        assert_eq!(
+            stringifier.escape(true).stringify(text("<{{a}}>")),
+            "&lt;A &amp; B&gt;"
+        );
+    }
+
+    #[test]
+    fn test_stringify_stable() {
+        let document = DocumentNode {
+            root: vec![Node::Element(ElementNode {
+                tag: ElementTag::Section,
+                class: vec!["z".into(), "a".into(), "z".into()],
+                attrs: vec![
+                    ("onclick".into(), "x()".into()),
+                    ("lang".into(), "en".into()),
+                    ("data-b".into(), "1".into()),
+                    ("lang".into(), "ja".into()),
+                ],
+                children: vec![Node::Element(ElementNode {
+                    tag: ElementTag::P,
+                    children: vec![Node::Text(TextNode {
+                        text: "A paragraph which is long".into(),
+                    })],
+                    ..Default::default()
+                })],
+                ..Default::default()
+            })],
+        };
+
+        let stringifier = Stringifier::new()
+            .format(true)
+            .sanitize(true)
+            .document(DocumentOptions::new())
+            .stable(true);
+
+        assert_eq!(
+            stringifier.stringify(document),
+            concat![
+                "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title></title></head><body>",
+                "<section class=\"a z\" data-b=\"1\" lang=\"ja\"><p>A paragraph which is long</p></section>",
+                "</body></html>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stringify_source_positions() {
+        use crate::layer::{
+            parser::{config::Comments, Parser},
+            transformer::Transformer,
+        };
+
+        let markdown = crate::Markdown::default()
+            .parser(Parser::new().comments(Comments::Preserve))
+            .transformer(Transformer::new().quote_citation(true))
+            .stringifier(Stringifier::new().source_positions(true));
+
+        let input = concat![
+            "Intro\r\nline\n\n",
+            "- a\n\n  é **b**\n{.x}\n\n",
+            "> q\n> -- Ann\n\n",
+            "<!-- c -->\n\n",
+            "  ## é",
+        ];
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<p data-sourcepos=\"1:1-2:4\">Intro<br>line</p>",
+                "<ul data-sourcepos=\"4:1-7:4\"><li>a",
+                "<p class=\"x\" data-sourcepos=\"6:3-7:4\">é <strong>b</strong></p></li></ul>",
+                "<figure data-sourcepos=\"9:1-10:8\"><blockquote><p data-sourcepos=\"9:3-10:8\">q</p>",
+                "</blockquote><figcaption>Ann</figcaption></figure>",
+                "<!-- c --><h2 data-sourcepos=\"14:3-14:7\">é</h2>",
+            ]
+        );
+
+        let plain = markdown
+            .clone()
+            .stringifier(Stringifier::new())
+            .execute(input);
+        let stripped = markdown
+            .execute(input)
+            .split(" data-sourcepos=\"")
+            .enumerate()
+            .map(|(index, part)| match index {
+                0 => part,
+                _ => &part[part.find('"').unwrap() + 1..],
+            })
+            .collect::<String>();
+
+        assert_eq!(stripped, plain);
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(markdown.execute_parallel(input), markdown.execute(input));
+    }
 }
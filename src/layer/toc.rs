@@ -2,20 +2,121 @@
 //!
 //! This module contains some configuration options for the table of contents.
 
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
-use crate::model::html::*;
+use crate::{
+    layer::transformer::is_heading_number,
+    model::{
+        counter::{LevelCounters, Numbering},
+        html::*,
+        messages::Messages,
+    },
+};
 
 use config::*;
 
 /// The struct to make a table of contents.
 #[derive(Debug, Clone)]
 pub struct TocMaker {
+    /// The minimum level of the table of contents. Default is 1.
+    pub min_level: u8,
     /// The maximum level of the table of contents. Default is 3.
-    pub level: u8,
+    ///
+    /// This was named `level` before `min_level` was added, and the `level`
+    /// method was renamed to [`max_level`](Self::max_level) too.
+    pub max_level: u8,
     /// The type of the list. Default is
     /// [`ListType::Unordered`](config::ListType).
     pub list_type: ListType,
+    /// The function to make an id from the text of a headline. Default is
+    /// [`slugify`].
+    pub slugifier: fn(&str) -> String,
+    /// The policy when a generated id collides with an explicit id. Default
+    /// is [`IdCollisionPolicy::PreferExplicit`](config::IdCollisionPolicy).
+    pub id_collision: IdCollisionPolicy,
+    /// The element to wrap the table of contents and its classes. Default is
+    /// `None`.
+    pub wrapper: Option<(ElementTag, Vec<String>)>,
+    /// The headline to put before the list. Its text is
+    /// [`Messages::toc_label`]. Default is `None`.
+    pub heading: Option<ElementTag>,
+    /// The numbering to put before the text of each entry, such as `1.2`.
+    /// Default is `None`.
+    pub numbering: Option<Numbering>,
+    /// The messages for generated text. The heading and the label of the
+    /// wrapper are [`Messages::toc_label`]. Messages set with
+    /// [`Markdown::messages`](crate::Markdown::messages) are used instead of
+    /// these.
+    pub messages: Messages,
+}
+
+/// The struct to represent a headline in the outline of a document.
+///
+/// Headlines are returned by [`Markdown::outline`](crate::Markdown::outline).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// The level of the headline from 1 to 6.
+    pub level: u8,
+    /// The plain text of the headline.
+    pub text: String,
+    /// The id of the headline, which is the anchor in the table of contents.
+    /// This is `None` for a headline out of the range of levels of the table
+    /// of contents, unless the transformer gives it an id.
+    pub id: Option<String>,
+    /// The byte range of the headline in the input.
+    pub span: Range<usize>,
+}
+
+/// The ids used in a document, to make new ids unique.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UsedIds {
+    ids: HashSet<String>,
+    /// The next suffix to try for each id which has been taken, so repeated
+    /// ids do not try the same suffixes again.
+    suffixes: HashMap<String, usize>,
+}
+
+impl From<HashSet<String>> for UsedIds {
+    fn from(ids: HashSet<String>) -> Self {
+        Self {
+            ids,
+            suffixes: HashMap::new(),
+        }
+    }
+}
+
+impl UsedIds {
+    /// Make an id unique by adding a suffix, and mark it as used.
+    ///
+    /// An empty id, such as the slug of a headline with only punctuation,
+    /// becomes `section`.
+    pub(crate) fn dedupe(&mut self, id: String) -> String {
+        let id = match id.is_empty() {
+            true => "section".to_string(),
+            false => id,
+        };
+
+        if self.ids.insert(id.clone()) {
+            return id;
+        }
+
+        // Ids are never removed, so the suffixes tried before are still used.
+        let index = self.suffixes.entry(id.clone()).or_insert(1);
+
+        loop {
+            let suffixed = format!("{id}-{index}");
+
+            *index += 1;
+
+            if self.ids.insert(suffixed.clone()) {
+                return suffixed;
+            }
+        }
+    }
 }
 
 pub mod config {
@@ -23,10 +124,20 @@ pub mod config {
     //!
     //! This module contains some configuration options for the table of
     //! contents.
+    //!
+    //! Each enum can be converted from and to a kebab-case string such as
+    //! `prefer-explicit` with [`FromStr`] and [`Display`](fmt::Display).
 
-    use crate::model::html::ElementTag;
+    use std::{fmt, str::FromStr};
+
+    use crate::{
+        error::ParseConfigError,
+        model::html::ElementTag,
+        unicode::{is_mark, transliterate},
+    };
 
     /// The type of the list.
+    #[non_exhaustive]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum ListType {
         Unordered,
@@ -41,74 +152,685 @@ pub mod config {
             }
         }
     }
+
+    impl FromStr for ListType {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "unordered" => Ok(Self::Unordered),
+                "ordered" => Ok(Self::Ordered),
+                _ => Err(ParseConfigError::new("ListType", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for ListType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Unordered => f.write_str("unordered"),
+                Self::Ordered => f.write_str("ordered"),
+            }
+        }
+    }
+
+    /// The policy when a generated id collides with an explicit id.
+    ///
+    /// Explicit ids are ids which are already in the document, for example
+    /// ones given by `# Headline {#id}`.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum IdCollisionPolicy {
+        /// Explicit ids win. Generated ids are renamed with a suffix.
+        PreferExplicit,
+        /// Generated ids win. Explicit ids are renamed with a suffix.
+        PreferGenerated,
+        /// Nothing is renamed. Collisions are reported by
+        /// [`TocMaker::make_toc_with_collisions`](super::TocMaker::make_toc_with_collisions).
+        Report,
+    }
+
+    impl FromStr for IdCollisionPolicy {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "prefer-explicit" => Ok(Self::PreferExplicit),
+                "prefer-generated" => Ok(Self::PreferGenerated),
+                "report" => Ok(Self::Report),
+                _ => Err(ParseConfigError::new("IdCollisionPolicy", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for IdCollisionPolicy {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::PreferExplicit => f.write_str("prefer-explicit"),
+                Self::PreferGenerated => f.write_str("prefer-generated"),
+                Self::Report => f.write_str("report"),
+            }
+        }
+    }
+
+    /// The struct to represent a collision between a generated id and an
+    /// explicit id.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct IdCollision {
+        /// The id which collides.
+        pub id: String,
+        /// The text of the headline whose id was generated.
+        pub text: String,
+    }
+
+    /// Make a GitHub style slug from the text.
+    ///
+    /// The text is lowercased, white spaces are replaced with `-`, and
+    /// characters other than letters, numbers, `-` and `_` are removed. Letters
+    /// and numbers of any script are kept with their combining marks, such as
+    /// accents and viramas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// assert_eq!(slugify("Hello, World!"), "hello-world");
+    /// assert_eq!(slugify("What's new in v0.2?"), "whats-new-in-v02");
+    /// assert_eq!(slugify("日本語 の見出し"), "日本語-の見出し");
+    /// ```
+    pub fn slugify(text: &str) -> String {
+        text.trim()
+            .chars()
+            .flat_map(char::to_lowercase)
+            .filter_map(|c| match c {
+                c if c.is_alphanumeric() || c == '-' || c == '_' || is_mark(c) => Some(c),
+                c if c.is_whitespace() => Some('-'),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The style of the slugs made from the text of headlines.
+    ///
+    /// Some static hosts and old browsers handle only ASCII in the fragments
+    /// of URLs, and the other styles make ids which are ASCII.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SlugStyle {
+        /// Keep letters and numbers of any script, as [`slugify`] does.
+        KeepUnicode,
+        /// Transliterate Latin, Greek and Cyrillic letters, kana and Hangul
+        /// into ASCII, and percent-encode the other letters and numbers, such
+        /// as Han characters.
+        Transliterate,
+        /// Percent-encode the characters of the slug of [`slugify`] which are
+        /// not ASCII.
+        PercentEncode,
+    }
+
+    impl SlugStyle {
+        /// Get the function to make a slug in this style, which can be set
+        /// with [`TocMaker::slugifier`](super::TocMaker::slugifier) or
+        /// [`Transformer::slugifier`](crate::layer::transformer::Transformer::slugifier).
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use note_mark::prelude::*;
+        ///
+        /// let slugify = SlugStyle::Transliterate.slugifier();
+        ///
+        /// assert_eq!(slugify("Crème Brûlée"), "creme-brulee");
+        /// assert_eq!(slugify("Привет, мир"), "privet-mir");
+        /// assert_eq!(slugify("ラーメン 日本"), "ramen-%E6%97%A5%E6%9C%AC");
+        ///
+        /// let slugify = SlugStyle::PercentEncode.slugifier();
+        ///
+        /// assert_eq!(slugify("日本語の見出し"), "%E6%97%A5%E6%9C%AC%E8%AA%9E%E3%81%AE%E8%A6%8B%E5%87%BA%E3%81%97");
+        /// ```
+        pub fn slugifier(&self) -> fn(&str) -> String {
+            match self {
+                Self::KeepUnicode => slugify,
+                Self::Transliterate => transliterated_slug,
+                Self::PercentEncode => |text| percent_encode(&slugify(text)),
+            }
+        }
+    }
+
+    impl FromStr for SlugStyle {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "keep-unicode" => Ok(Self::KeepUnicode),
+                "transliterate" => Ok(Self::Transliterate),
+                "percent-encode" => Ok(Self::PercentEncode),
+                _ => Err(ParseConfigError::new("SlugStyle", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for SlugStyle {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::KeepUnicode => f.write_str("keep-unicode"),
+                Self::Transliterate => f.write_str("transliterate"),
+                Self::PercentEncode => f.write_str("percent-encode"),
+            }
+        }
+    }
+
+    fn transliterated_slug(text: &str) -> String {
+        let text = text
+            .trim()
+            .chars()
+            .flat_map(char::to_lowercase)
+            .collect::<String>();
+
+        percent_encode(&slugify(&transliterate(&text)))
+    }
+
+    /// Percent-encode the UTF-8 bytes of the characters which are not ASCII.
+    fn percent_encode(slug: &str) -> String {
+        let mut encoded = String::with_capacity(slug.len());
+
+        for c in slug.chars() {
+            if c.is_ascii() {
+                encoded.push(c);
+            } else {
+                for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                    encoded.push_str(&format!("%{byte:02X}"));
+                }
+            }
+        }
+
+        encoded
+    }
 }
 
 impl Default for TocMaker {
     fn default() -> Self {
         Self {
-            level: 3,
+            min_level: 1,
+            max_level: 3,
             list_type: ListType::Unordered,
+            slugifier: slugify,
+            id_collision: IdCollisionPolicy::PreferExplicit,
+            wrapper: None,
+            heading: None,
+            numbering: None,
+            messages: Messages::default(),
         }
     }
 }
 
 impl TocMaker {
+    /// Set the minimum level of the table of contents.
+    ///
+    /// Headlines outside the range of `min_level` and `max_level` are not
+    /// listed and do not get an id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().toc_maker(TocMaker::default().min_level(2).max_level(4));
+    ///
+    /// let (_, toc) = markdown.execute_with_toc("# Title\n\n## A\n\n#### B\n\n##### C\n\n## D");
+    ///
+    /// assert_eq!(toc, "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li><li><a href=\"#d\">D</a></li></ul>");
+    /// ```
+    pub fn min_level(mut self, level: u8) -> Self {
+        self.min_level = level;
+        self
+    }
+
     /// Set the maximum level of the table of contents.
-    pub fn level(mut self, level: u8) -> Self {
-        self.level = level;
+    pub fn max_level(mut self, level: u8) -> Self {
+        self.max_level = level;
         self
     }
 
     /// Set the type of the list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().toc_maker(TocMaker::default().list_type(ListType::Ordered));
+    ///
+    /// let (_, toc) = markdown.execute_with_toc("# A\n\n## B");
+    ///
+    /// assert_eq!(toc, "<ol><li><a href=\"#a\">A</a><ol><li><a href=\"#b\">B</a></li></ol></li></ol>");
+    /// ```
     pub fn list_type(mut self, list_type: ListType) -> Self {
         self.list_type = list_type;
         self
     }
+
+    /// Set the function to make an id from the text of a headline.
+    ///
+    /// Duplicated ids are suffixed with `-1`, `-2`, and so on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .toc_maker(TocMaker::default().slugifier(|text| text.replace(' ', "_")));
+    ///
+    /// let (html, _) = markdown.execute_with_toc("# Hello World\n\n# Hello World");
+    ///
+    /// assert_eq!(html, "<h1 id=\"Hello_World\">Hello World</h1><h1 id=\"Hello_World-1\">Hello World</h1>");
+    /// ```
+    pub fn slugifier(mut self, slugifier: fn(&str) -> String) -> Self {
+        self.slugifier = slugifier;
+        self
+    }
+
+    /// Set the [`slugifier`](Self::slugifier) to the one of a style.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .toc_maker(TocMaker::default().slug_style(SlugStyle::Transliterate));
+    ///
+    /// let (html, toc) = markdown.execute_with_toc("# Café\n\n# 한국어");
+    ///
+    /// assert_eq!(html, "<h1 id=\"cafe\">Café</h1><h1 id=\"hangukeo\">한국어</h1>");
+    /// assert_eq!(
+    ///     toc,
+    ///     "<ul><li><a href=\"#cafe\">Café</a></li><li><a href=\"#hangukeo\">한국어</a></li></ul>"
+    /// );
+    /// ```
+    pub fn slug_style(self, style: SlugStyle) -> Self {
+        self.slugifier(style.slugifier())
+    }
+
+    /// Wrap the table of contents in an element with classes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let toc_maker = TocMaker::default()
+    ///     .wrapper(ElementTag::Nav, &["toc"])
+    ///     .heading(ElementTag::H2);
+    ///
+    /// let markdown = Markdown::default().toc_maker(toc_maker);
+    ///
+    /// let (_, toc) = markdown.execute_with_toc("# A");
+    ///
+    /// assert_eq!(toc, "<nav class=\"toc\" aria-label=\"Table of contents\"><h2>Table of contents</h2><ul><li><a href=\"#a\">A</a></li></ul></nav>");
+    /// ```
+    pub fn wrapper(mut self, tag: ElementTag, classes: &[&str]) -> Self {
+        let classes = classes.iter().map(|class| class.to_string()).collect();
+
+        self.wrapper = Some((tag, classes));
+        self
+    }
+
+    /// Put a headline before the list of the table of contents.
+    ///
+    /// The text of the headline is [`Messages::toc_label`].
+    pub fn heading(mut self, tag: ElementTag) -> Self {
+        self.heading = Some(tag);
+        self
+    }
+
+    /// Number the entries hierarchically.
+    ///
+    /// The numbers follow the nesting of the list, and are separated from the
+    /// text by a space.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let numbering = Numbering::default().styles(&[Counter::UpperRoman, Counter::Decimal]);
+    ///
+    /// let markdown = Markdown::default().toc_maker(TocMaker::default().numbering(numbering));
+    ///
+    /// let (_, toc) = markdown.execute_with_toc("# A\n\n## B\n\n## C\n\n# D");
+    ///
+    /// assert_eq!(toc, "<ul><li><a href=\"#a\">I A</a><ul><li><a href=\"#b\">I.1 B</a></li><li><a href=\"#c\">I.2 C</a></li></ul></li><li><a href=\"#d\">II D</a></li></ul>");
+    /// ```
+    pub fn numbering(mut self, numbering: Numbering) -> Self {
+        self.numbering = Some(numbering);
+        self
+    }
+
+    /// Set the messages for generated text.
+    pub fn messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Set the policy when a generated id collides with an explicit id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "# Intro\n\n# Usage {#intro}";
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let (html, _) = markdown.execute_with_toc(input);
+    ///
+    /// assert_eq!(html, "<h1 id=\"intro-1\">Intro</h1><h1 id=\"intro\">Usage</h1>");
+    ///
+    /// let markdown = Markdown::default()
+    ///     .toc_maker(TocMaker::default().id_collision(IdCollisionPolicy::PreferGenerated));
+    ///
+    /// let (html, _) = markdown.execute_with_toc(input);
+    ///
+    /// assert_eq!(html, "<h1 id=\"intro\">Intro</h1><h1 id=\"intro-1\">Usage</h1>");
+    /// ```
+    pub fn id_collision(mut self, policy: IdCollisionPolicy) -> Self {
+        self.id_collision = policy;
+        self
+    }
 }
 
 impl TocMaker {
     /// Make a table of contents.
     pub fn make_toc<'a>(&self, input: &mut DocumentNode<'a>) -> DocumentNode<'a> {
-        let mut list = vec![];
+        self.make_toc_with_collisions(input).0
+    }
 
-        let mut set = HashSet::new();
+    /// Make a table of contents and report collisions between generated ids
+    /// and explicit ids.
+    ///
+    /// Collisions are reported only with [`IdCollisionPolicy::Report`].
+    pub fn make_toc_with_collisions<'a>(
+        &self,
+        input: &mut DocumentNode<'a>,
+    ) -> (DocumentNode<'a>, Vec<IdCollision>) {
+        self.make_toc_with_messages(input, &self.messages)
+    }
 
-        for node in input.root.iter_mut() {
-            let Node::Element(element) = node else { continue; };
+    /// Make a table of contents with the messages instead of
+    /// [`messages`](Self::messages).
+    pub(crate) fn make_toc_with_messages<'a>(
+        &self,
+        input: &mut DocumentNode<'a>,
+        messages: &Messages,
+    ) -> (DocumentNode<'a>, Vec<IdCollision>) {
+        let collisions = self.assign_ids(input);
 
-            use ElementTag::*;
+        let mut list = self
+            .headlines(&mut input.root)
+            .into_iter()
+            .map(|element| {
+                (
+                    element.tag.get_headline_level().unwrap(),
+                    Self::headline_text(element),
+                    element.id.clone().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
 
-            let headline_level = match element.tag {
-                H1 | H2 | H3 | H4 | H5 | H6 => element.tag.get_headline_level().unwrap(),
-                _ => continue,
-            };
+        if let Some(numbering) = &self.numbering {
+            // The numbers follow the nesting of the list.
+            let mut counters = LevelCounters::default();
 
-            if headline_level > self.level {
-                continue;
+            for (level, text, _) in list.iter_mut() {
+                *text = format!("{} {}", numbering.format(&counters.next(*level)), text);
             }
+        }
 
-            let text = get_text(&element.children);
+        let mut root = vec![];
 
-            let (text, id) = if set.insert(text.clone()) {
-                (text.clone(), text)
-            } else {
-                let mut index = 1;
+        if let Some(tag) = self.heading {
+            root.push(Node::Element(ElementNode {
+                tag,
+                children: vec![Node::Text(TextNode {
+                    text: messages.toc_label.clone().into(),
+                })],
+                ..Default::default()
+            }));
+        }
+
+        root.push(self.nest(&list));
+
+        if let Some((tag, classes)) = &self.wrapper {
+            root = vec![Node::Element(ElementNode {
+                tag: *tag,
+                class: classes.iter().cloned().map(Cow::Owned).collect(),
+                attrs: vec![("aria-label".into(), messages.toc_label.clone().into())],
+                children: root,
+                ..Default::default()
+            })];
+        }
+
+        (DocumentNode { root }, collisions)
+    }
+
+    /// Give ids to headlines in the range of levels which do not have one,
+    /// and report collisions between generated ids and explicit ids.
+    ///
+    /// Ids are made by [`slugifier`](Self::slugifier), and collisions are
+    /// resolved by [`id_collision`](Self::id_collision). Headlines which
+    /// already have ids keep them.
+    pub fn assign_ids(&self, input: &mut DocumentNode<'_>) -> Vec<IdCollision> {
+        let mut explicit = HashSet::new();
+
+        Self::collect_ids(&input.root, &mut explicit);
+
+        let mut used = match self.id_collision {
+            IdCollisionPolicy::PreferExplicit => UsedIds::from(explicit.clone()),
+            IdCollisionPolicy::PreferGenerated | IdCollisionPolicy::Report => UsedIds::default(),
+        };
+
+        let mut collisions = vec![];
+
+        // Generate ids first, and assign them after explicit ids are renamed.
+        let ids = self
+            .headlines(&mut input.root)
+            .into_iter()
+            .map(|element| {
+                if element.id.is_some() {
+                    return None;
+                }
+
+                let text = Self::id_text(element);
+
+                let id = used.dedupe((self.slugifier)(&text));
+
+                if self.id_collision == IdCollisionPolicy::Report && explicit.contains(&id) {
+                    collisions.push(IdCollision {
+                        id: id.clone(),
+                        text,
+                    });
+                }
+
+                Some(id)
+            })
+            .collect::<Vec<_>>();
 
-                while !set.insert(text.clone() + &index.to_string()) {
-                    index += 1;
+        if self.id_collision == IdCollisionPolicy::PreferGenerated {
+            let generated = ids.iter().flatten().cloned().collect::<HashSet<_>>();
+
+            let mut used =
+                UsedIds::from(explicit.union(&generated).cloned().collect::<HashSet<_>>());
+
+            Self::rename_explicit_ids(&mut input.root, &generated, &mut used);
+        }
+
+        for (element, id) in self.headlines(&mut input.root).into_iter().zip(ids) {
+            if id.is_some() {
+                element.id = id;
+            }
+        }
+
+        collisions
+    }
+
+    /// Give ids to headlines like [`make_toc`](Self::make_toc), and get all
+    /// headlines in the document with the byte ranges of the headlines
+    /// parsed from the input, which are in the same order.
+    pub(crate) fn outline(
+        &self,
+        input: &mut DocumentNode<'_>,
+        spans: Vec<Range<usize>>,
+    ) -> Vec<Heading> {
+        fn collect<'b, 'a>(nodes: &'b [Node<'a>], headlines: &mut Vec<&'b ElementNode<'a>>) {
+            for node in nodes {
+                if let Node::Element(element) = node {
+                    match element.tag.get_headline_level() {
+                        Some(_) => headlines.push(element),
+                        None => collect(&element.children, headlines),
+                    }
                 }
+            }
+        }
+
+        self.assign_ids(input);
+
+        let mut headlines = vec![];
+
+        collect(&input.root, &mut headlines);
+
+        headlines
+            .into_iter()
+            .zip(spans)
+            .map(|(element, span)| Heading {
+                level: element.tag.get_headline_level().unwrap(),
+                text: Self::headline_text(element),
+                id: element.id.clone(),
+                span,
+            })
+            .collect()
+    }
+
+    /// Find the headline which has the id after ids are given like
+    /// [`make_toc`](Self::make_toc), and get the indices of the children on
+    /// the path to it. The input is not changed.
+    pub(crate) fn find_headline(&self, input: &DocumentNode<'_>, id: &str) -> Option<Vec<usize>> {
+        fn find(nodes: &[Node<'_>], id: &str, path: &mut Vec<usize>) -> bool {
+            for (index, node) in nodes.iter().enumerate() {
+                let Node::Element(element) = node else {
+                    continue;
+                };
+
+                path.push(index);
+
+                let found = match element.tag.get_headline_level() {
+                    Some(_) => element.id.as_deref() == Some(id),
+                    None => find(&element.children, id, path),
+                };
+
+                if found {
+                    return true;
+                }
+
+                path.pop();
+            }
+
+            false
+        }
+
+        let mut document = input.clone();
+
+        self.assign_ids(&mut document);
+
+        let mut path = vec![];
+
+        find(&document.root, id, &mut path).then_some(path)
+    }
+
+    /// Get the text of a headline to make its id from, which is without the
+    /// number of `Transformer::heading_numbering`.
+    fn id_text(element: &ElementNode<'_>) -> String {
+        match element.children.split_first() {
+            Some((first, rest)) if is_heading_number(first) => {
+                get_text(rest).trim_start().to_string()
+            }
+            _ => get_text(&element.children),
+        }
+    }
+
+    /// Get the text of a headline without links to the headline itself, such
+    /// as the anchors of `Transformer::heading_anchor`.
+    fn headline_text(element: &ElementNode<'_>) -> String {
+        let children = element
+            .children
+            .iter()
+            .filter(|node| {
+                !matches!(node, Node::Element(link)
+                    if link.tag == ElementTag::A
+                        && element.id.is_some()
+                        && link.href.as_deref().and_then(|href| href.strip_prefix('#'))
+                            == element.id.as_deref())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        get_text(&children)
+    }
+
+    /// Get headlines in the range of levels, including those in sections.
+    fn headlines<'a, 'b>(&self, nodes: &'b mut [Node<'a>]) -> Vec<&'b mut ElementNode<'a>> {
+        let mut headlines = vec![];
 
-                (text.clone(), text + &index.to_string())
+        for node in nodes {
+            let Node::Element(element) = node else {
+                continue;
             };
 
-            element.id.push(id.clone());
+            match element.tag.get_headline_level() {
+                Some(level) if self.min_level <= level && level <= self.max_level => {
+                    headlines.push(element)
+                }
+                None if element.tag == ElementTag::Section => {
+                    headlines.extend(self.headlines(&mut element.children))
+                }
+                _ => {}
+            }
+        }
+
+        headlines
+    }
+
+    /// Collect all ids in nodes.
+    pub(crate) fn collect_ids(nodes: &[Node<'_>], ids: &mut HashSet<String>) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                ids.extend(element.id.iter().cloned());
 
-            list.push((headline_level, text, id));
+                Self::collect_ids(&element.children, ids);
+            }
         }
+    }
 
-        let output = self.nest(&list);
+    /// Rename explicit ids which collide with generated ids.
+    fn rename_explicit_ids(
+        nodes: &mut [Node<'_>],
+        generated: &HashSet<String>,
+        used: &mut UsedIds,
+    ) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                for id in element.id.iter_mut() {
+                    if generated.contains(id) {
+                        *id = used.dedupe(id.clone());
+                    }
+                }
 
-        DocumentNode { root: vec![output] }
+                Self::rename_explicit_ids(&mut element.children, generated, used);
+            }
+        }
     }
 
     fn nest(&self, rest: &[(u8, String, String)]) -> Node<'static> {
@@ -170,7 +892,10 @@ impl TocMaker {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{layer::lexer::lex, Markdown};
+    use crate::{
+        layer::transformer::{config::AnchorPosition, Transformer},
+        Markdown,
+    };
 
     #[test]
     fn test_make_toc() {
@@ -179,7 +904,7 @@ mod tests {
 
         let markdown = Markdown::default();
 
-        let tokens = lex(input);
+        let tokens = Markdown::lex(input);
         let tree = markdown.parser.parse(input, tokens);
         let mut document = markdown.transformer.transform(tree);
 
@@ -187,10 +912,250 @@ mod tests {
 
         let output1 = markdown.stringifier.stringify(document);
 
-        assert_eq!(output1, "<h1 id=\"H1AAAAAA\">H1AAAAAA</h1><h1 id=\"H1AAAAAA1\">H1AAAAAA</h1><h1 id=\"H1BBBBBB\">H1BBBBBB</h1><h2 id=\"H2AAAAAA\">H2AAAAAA</h2><h2 id=\"H2BBBBBB\">H2BBBBBB</h2><h1 id=\"H1CCCCCC\">H1CCCCCC</h1>");
+        assert_eq!(output1, "<h1 id=\"h1aaaaaa\">H1AAAAAA</h1><h1 id=\"h1aaaaaa-1\">H1AAAAAA</h1><h1 id=\"h1bbbbbb\">H1BBBBBB</h1><h2 id=\"h2aaaaaa\">H2AAAAAA</h2><h2 id=\"h2bbbbbb\">H2BBBBBB</h2><h1 id=\"h1cccccc\">H1CCCCCC</h1>");
 
         let output2 = markdown.stringifier.stringify(toc);
 
-        assert_eq!(output2, "<ul><li><a href=\"#H1AAAAAA\">H1AAAAAA</a></li><li><a href=\"#H1AAAAAA1\">H1AAAAAA</a></li><li><a href=\"#H1BBBBBB\">H1BBBBBB</a><ul><li><a href=\"#H2AAAAAA\">H2AAAAAA</a></li><li><a href=\"#H2BBBBBB\">H2BBBBBB</a></li></ul></li><li><a href=\"#H1CCCCCC\">H1CCCCCC</a></li></ul>")
+        assert_eq!(output2, "<ul><li><a href=\"#h1aaaaaa\">H1AAAAAA</a></li><li><a href=\"#h1aaaaaa-1\">H1AAAAAA</a></li><li><a href=\"#h1bbbbbb\">H1BBBBBB</a><ul><li><a href=\"#h2aaaaaa\">H2AAAAAA</a></li><li><a href=\"#h2bbbbbb\">H2BBBBBB</a></li></ul></li><li><a href=\"#h1cccccc\">H1CCCCCC</a></li></ul>")
+    }
+
+    #[test]
+    fn test_make_toc_level_range() {
+        let input = "# Title\n\n### A\n\n## B\n\n#### C\n\n## D\n\n###### E\n\n";
+
+        let markdown = Markdown::default().toc_maker(TocMaker::default().min_level(2).max_level(4));
+
+        let (output, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(output, "<h1>Title</h1><h3 id=\"a\">A</h3><h2 id=\"b\">B</h2><h4 id=\"c\">C</h4><h2 id=\"d\">D</h2><h6>E</h6>");
+        assert_eq!(toc, "<ul><li><a href=\"#a\">A</a></li><li><a href=\"#b\">B</a><ul><li><a href=\"#c\">C</a></li></ul></li><li><a href=\"#d\">D</a></li></ul>");
+
+        let markdown = Markdown::default().toc_maker(TocMaker::default().min_level(4).max_level(3));
+
+        let (_, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(toc, "<ul></ul>");
+    }
+
+    #[test]
+    fn test_make_toc_section() {
+        let markdown = Markdown::default().transformer(Transformer::new().section(true));
+
+        let (output, toc) = markdown.execute_with_toc("# A\n\n## B\n\n# C");
+
+        assert_eq!(output, "<section><h1 id=\"a\">A</h1><section><h2 id=\"b\">B</h2></section></section><section><h1 id=\"c\">C</h1></section>");
+        assert_eq!(toc, "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li><li><a href=\"#c\">C</a></li></ul>");
+    }
+
+    #[test]
+    fn test_make_toc_id_collision() {
+        let input = "# Intro\n\n## Setup {#intro .x}\n\n# Intro\n\n# Setup\n\n";
+
+        let markdown = Markdown::default();
+
+        let (output, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(output, "<h1 id=\"intro-1\">Intro</h1><h2 class=\"x\" id=\"intro\">Setup</h2><h1 id=\"intro-2\">Intro</h1><h1 id=\"setup\">Setup</h1>");
+        assert_eq!(toc, "<ul><li><a href=\"#intro-1\">Intro</a><ul><li><a href=\"#intro\">Setup</a></li></ul></li><li><a href=\"#intro-2\">Intro</a></li><li><a href=\"#setup\">Setup</a></li></ul>");
+
+        let markdown = Markdown::default()
+            .toc_maker(TocMaker::default().id_collision(IdCollisionPolicy::PreferGenerated));
+
+        let (output, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(output, "<h1 id=\"intro\">Intro</h1><h2 class=\"x\" id=\"intro-2\">Setup</h2><h1 id=\"intro-1\">Intro</h1><h1 id=\"setup\">Setup</h1>");
+        assert_eq!(toc, "<ul><li><a href=\"#intro\">Intro</a><ul><li><a href=\"#intro-2\">Setup</a></li></ul></li><li><a href=\"#intro-1\">Intro</a></li><li><a href=\"#setup\">Setup</a></li></ul>");
+
+        let tokens = Markdown::lex(input);
+        let tree = markdown.parser.parse(input, tokens);
+        let mut document = markdown.transformer.transform(tree);
+
+        let (_, collisions) = TocMaker::default()
+            .id_collision(IdCollisionPolicy::Report)
+            .make_toc_with_collisions(&mut document);
+
+        assert_eq!(
+            collisions,
+            vec![IdCollision {
+                id: "intro".into(),
+                text: "Intro".into(),
+            }]
+        );
+        assert_eq!(markdown.stringifier.stringify(document), "<h1 id=\"intro\">Intro</h1><h2 class=\"x\" id=\"intro\">Setup</h2><h1 id=\"intro-1\">Intro</h1><h1 id=\"setup\">Setup</h1>");
+    }
+
+    #[test]
+    fn test_config_from_str() {
+        for list_type in [ListType::Unordered, ListType::Ordered] {
+            assert_eq!(list_type.to_string().parse(), Ok(list_type));
+        }
+
+        for policy in [
+            IdCollisionPolicy::PreferExplicit,
+            IdCollisionPolicy::PreferGenerated,
+            IdCollisionPolicy::Report,
+        ] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+
+        assert!("explicit".parse::<IdCollisionPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello World"), "hello-world");
+        assert_eq!(slugify("  Trim me  "), "trim-me");
+        assert_eq!(slugify("C++ & Rust: a_b"), "c--rust-a_b");
+        assert_eq!(slugify("Ünïcödé Straße"), "ünïcödé-straße");
+        assert_eq!(slugify("见出し１"), "见出し１");
+        assert_eq!(slugify("Cafe\u{301} हिन्दी"), "cafe\u{301}-हिन्दी");
+        assert_eq!(slugify("👍🏽 Ok"), "-ok");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn test_slug_style() {
+        let transliterate = SlugStyle::Transliterate.slugifier();
+
+        assert_eq!(
+            transliterate("Ĳssel Straße Ελληνικά Москва"),
+            "ijssel-strasse-ellinika-moskva"
+        );
+        assert_eq!(
+            transliterate("っきゃ ちゃっちゃ ファイル ウィキ ティー ヴァ しぇ コーヒー"),
+            "kkya-chatcha-fairu-wiki-ti-va-she-kohi"
+        );
+        assert_eq!(transliterate("서울 한국어"), "seoul-hangukeo");
+        assert_eq!(
+            transliterate("Cafe\u{301} हिन्दी 1２"),
+            "cafe-%E0%A4%B9%E0%A4%BF%E0%A4%A8%E0%A5%8D%E0%A4%A6%E0%A5%80-1%EF%BC%92"
+        );
+
+        let percent_encode = SlugStyle::PercentEncode.slugifier();
+
+        assert_eq!(percent_encode("Ünï code"), "%C3%BCn%C3%AF-code");
+        assert_eq!(SlugStyle::KeepUnicode.slugifier()("Ünï"), "ünï");
+
+        for style in [
+            SlugStyle::KeepUnicode,
+            SlugStyle::Transliterate,
+            SlugStyle::PercentEncode,
+        ] {
+            assert_eq!(style.to_string().parse(), Ok(style));
+        }
+
+        assert!("ascii".parse::<SlugStyle>().is_err());
+    }
+
+    #[test]
+    fn test_make_toc_slug_dedupe() {
+        let input = "# Hello World\n\n# hello world\n\n# Hello-World\n\n# Hello World\n\n";
+
+        let markdown = Markdown::default();
+
+        let (output, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(output, "<h1 id=\"hello-world\">Hello World</h1><h1 id=\"hello-world-1\">hello world</h1><h1 id=\"hello-world-2\">Hello-World</h1><h1 id=\"hello-world-3\">Hello World</h1>");
+        assert_eq!(toc, "<ul><li><a href=\"#hello-world\">Hello World</a></li><li><a href=\"#hello-world-1\">hello world</a></li><li><a href=\"#hello-world-2\">Hello-World</a></li><li><a href=\"#hello-world-3\">Hello World</a></li></ul>");
+    }
+
+    #[test]
+    fn test_used_ids() {
+        let mut used = UsedIds::from(HashSet::from(["a-2".to_string()]));
+
+        let ids = ["a", "a", "a-1", "a", "a", "", ""].map(|id| used.dedupe(id.to_string()));
+
+        assert_eq!(
+            ids,
+            ["a", "a-1", "a-1-1", "a-3", "a-4", "section", "section-1"]
+        );
+    }
+
+    #[test]
+    fn test_make_toc_empty_slug() {
+        let input = "# !!!\n\n# ???\n\n# Section\n\n";
+
+        let (output, toc) = Markdown::default().execute_with_toc(input);
+
+        assert_eq!(output, "<h1 id=\"section\">!!!</h1><h1 id=\"section-1\">???</h1><h1 id=\"section-2\">Section</h1>");
+        assert_eq!(toc, "<ul><li><a href=\"#section\">!!!</a></li><li><a href=\"#section-1\">???</a></li><li><a href=\"#section-2\">Section</a></li></ul>");
+    }
+
+    #[test]
+    fn test_make_toc_numbering() {
+        let markdown = Markdown::default().toc_maker(
+            TocMaker::default()
+                .numbering(Numbering::default().suffix("."))
+                .list_type(ListType::Ordered),
+        );
+
+        let (_, toc) = markdown.execute_with_toc("## A\n\n# B\n\n### C\n\n## D\n\n### E\n\n# F");
+
+        assert_eq!(
+            toc,
+            concat![
+                "<ol><li><a href=\"#a\">1. A</a></li>",
+                "<li><a href=\"#b\">2. B</a><ol>",
+                "<li><a href=\"#c\">2.1. C</a></li>",
+                "<li><a href=\"#d\">2.2. D</a><ol><li><a href=\"#e\">2.2.1. E</a></li></ol></li>",
+                "</ol></li>",
+                "<li><a href=\"#f\">3. F</a></li></ol>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outline() {
+        let markdown = Markdown::default()
+            .transformer(
+                Transformer::new()
+                    .section(true)
+                    .heading_anchor(AnchorPosition::After)
+                    .toc_placeholder(TocMaker::default().heading(ElementTag::H2)),
+            )
+            .toc_maker(TocMaker::default().min_level(2));
+
+        let input = "# Same\n\n[TOC]\n\n## Same\n\n> ### *Same*\n\n#### Deep";
+
+        let outline = markdown
+            .outline(input)
+            .into_iter()
+            .map(|heading| {
+                (
+                    heading.level,
+                    heading.text,
+                    heading.id,
+                    &input[heading.span],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outline,
+            vec![
+                (1, "Same".into(), Some("same".into()), "# Same"),
+                (2, "Same".into(), Some("same-1".into()), "## Same"),
+                // Headlines in blockquotes are not in the table of contents.
+                (3, "Same".into(), None, "### *Same*"),
+                (4, "Deep".into(), Some("deep".into()), "#### Deep"),
+            ]
+        );
+
+        // The ids match the table of contents of the same document.
+        let (_, toc) = markdown.execute_with_toc(input);
+
+        assert!(toc.contains("href=\"#same-1\"") && !toc.contains("#deep"));
+
+        let plain = Markdown::default().toc_maker(TocMaker::default().max_level(2));
+
+        assert_eq!(
+            plain
+                .outline("### Same\n\n## Same")
+                .into_iter()
+                .map(|heading| heading.id)
+                .collect::<Vec<_>>(),
+            vec![None, Some("same".into())]
+        );
+        assert_eq!(plain.outline(""), vec![]);
     }
 }
@@ -1,20 +1,399 @@
 //! Transformer of Markdown tree to HTML tree.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, fmt, ops::Range, sync::Arc};
 
-use crate::model::{html::*, tree::*};
+use crate::{
+    layer::{
+        parser::config::LineBreakStyle,
+        toc::{config::slugify, TocMaker},
+    },
+    model::{
+        counter::{Counter, LevelCounters, Numbering},
+        html::*,
+        messages::Messages,
+        tree::*,
+    },
+    unicode::is_mark,
+};
+
+use self::config::{AnchorPosition, LineWrap, TextDirection};
 
 /// The struct to transform Markdown tree to HTML tree.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Transformer {
-    #[allow(dead_code)]
-    section: bool,
+    /// Wrap each headline and its following content into a `<section>`.
+    /// Default is `false`. This works best with a headline ending other than
+    /// [`HeadlineEnding::HardBreak`](crate::layer::parser::config::HeadlineEnding).
+    pub section: bool,
+    /// Convert quotes, dashes and ellipses in text into typographic
+    /// characters. Default is `false`.
+    pub smart_punctuation: bool,
+    /// How to write line breaks which are not marked as hard breaks. Default
+    /// is [`LineWrap::Keep`].
+    pub line_wrap: LineWrap,
+    /// The style of line breaks of the parser, to tell hard breaks from
+    /// wrapped lines with [`LineWrap::Join`]. Default is
+    /// [`LineBreakStyle::HardBreak`], which is the default of the parser.
+    pub line_break_style: LineBreakStyle,
+    /// Keep the number of the first item of ordered lists as the `start`
+    /// attribute. If this is `false`, ordered lists are numbered from 1.
+    /// Default is `true`.
+    pub list_start: bool,
+    /// Classes added to every element with the tag. Default is empty.
+    pub tag_classes: BTreeMap<ElementTag, Vec<Cow<'static, str>>>,
+    /// Attributes added to every element with the tag. Default is empty.
+    pub tag_attrs: BTreeMap<ElementTag, Vec<Attr>>,
+    /// The `rel` attribute of external links. Default is `None`.
+    pub external_rel: Option<String>,
+    /// The `target` attribute of external links. Default is `None`.
+    pub external_target: Option<String>,
+    /// Hosts whose links are not external. Default is empty.
+    pub internal_hosts: Vec<String>,
+    /// Give ids to all headlines which do not have one. Default is `false`.
+    pub heading_ids: bool,
+    /// Make a blockquote whose last line starts with `-- ` into a figure with
+    /// the rest of the line as the caption. Default is `false`.
+    pub quote_citation: bool,
+    /// Add the class `blockquote-depth-N` to blockquotes, where `N` is the
+    /// number of blockquotes containing it and itself. Default is `false`.
+    pub quote_depth_class: bool,
+    /// Wrap code blocks into `<div class="code-block">` and each line of
+    /// their code into `<span class="line">`. Default is `false`.
+    pub code_block_wrapper: bool,
+    /// Put an empty `<button class="copy-button">` before the code of
+    /// blocks wrapped by [`code_block_wrapper`](Self::code_block_wrapper).
+    /// Default is `false`.
+    pub copy_button: bool,
+    /// The function to make an id from the text of a headline. Default is
+    /// [`slugify`].
+    pub slugifier: fn(&str) -> String,
+    /// Add a link to each headline with an id, pointing to the headline
+    /// itself. Default is `None`, which adds no links.
+    pub heading_anchor: Option<AnchorPosition>,
+    /// The text of the links of [`heading_anchor`](Self::heading_anchor).
+    /// Default is `¶`.
+    pub anchor_symbol: String,
+    /// Prefix headlines with hierarchical numbers such as `1.2`. Default is
+    /// `None`, which does not number headlines.
+    pub heading_numbering: Option<Numbering>,
+    /// The level of the highest headlines numbered by
+    /// [`heading_numbering`](Self::heading_numbering). Default is 1.
+    pub numbering_start: u8,
+    /// Replace paragraphs of only `[TOC]` or `[[toc]]` with the table of
+    /// contents made by this maker. Default is `None`, which keeps them as
+    /// text.
+    pub toc_placeholder: Option<TocMaker>,
+    /// The resolver of the URLs of wiki links. Default is `None`, which uses
+    /// the target as the URL.
+    pub wiki_resolver: Option<Arc<dyn WikiResolver>>,
+    /// The resolver of the URLs of hashtags and mentions. Default is `None`,
+    /// which writes them without links.
+    pub tag_resolver: Option<Arc<dyn TagResolver>>,
+    /// The lookup of emoji shortcodes such as `:smile:`. Default is `None`,
+    /// which keeps shortcodes as text.
+    pub emoji: Option<Arc<dyn EmojiLookup>>,
+    /// Put a headline of [`Messages::footnotes_label`] at the start of the
+    /// section of inline footnotes. Default is `None`, which puts no
+    /// headline.
+    pub footnotes_heading: Option<ElementTag>,
+    /// The messages for generated text, such as the links from footnotes
+    /// back to their references. Messages set with
+    /// [`Markdown::messages`](crate::Markdown::messages) are used instead of
+    /// these.
+    pub messages: Messages,
+    /// How to mark the direction of the text of blocks with `dir`. Default
+    /// is [`TextDirection::Off`].
+    pub text_direction: TextDirection,
+    /// Normalize text to NFC. Default is `false`.
+    #[cfg(feature = "nfc")]
+    pub nfc: bool,
+}
+
+/// The trait to resolve the target of a wiki link such as `[[Page]]` into a
+/// URL.
+///
+/// This is implemented for closures which take the target and return the
+/// URL.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let pages = ["Home", "Usage"];
+///
+/// let transformer = Transformer::new().wiki_resolver(move |target: &str| {
+///     pages
+///         .contains(&target)
+///         .then(|| format!("/wiki/{}", target.to_lowercase()))
+/// });
+///
+/// let markdown = Markdown::default()
+///     .parser(Parser::default().wiki_links(true))
+///     .transformer(transformer);
+///
+/// assert_eq!(
+///     markdown.execute("[[Home]] [[Missing|gone]]"),
+///     "<p><a class=\"wiki-link\" href=\"/wiki/home\">Home</a> gone</p>"
+/// );
+/// ```
+pub trait WikiResolver: Send + Sync {
+    /// Get the URL of the target, or `None` if the page does not exist. A
+    /// link which is not resolved is written as its label without a link.
+    fn resolve(&self, target: &str) -> Option<String>;
+}
+
+impl<F> WikiResolver for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync,
+{
+    fn resolve(&self, target: &str) -> Option<String> {
+        self(target)
+    }
+}
+
+/// The trait to resolve a hashtag or a mention into a URL.
+///
+/// This is implemented for closures which take the kind and the name, and
+/// return the URL.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let transformer = Transformer::new().tag_resolver(|kind, name: &str| match kind {
+///     TagKind::Hashtag => Some(format!("/tags/{name}")),
+///     TagKind::Mention => None,
+/// });
+///
+/// let markdown = Markdown::default()
+///     .parser(Parser::default().hashtags(true).mentions(true))
+///     .transformer(transformer);
+///
+/// assert_eq!(
+///     markdown.execute("#news @bob"),
+///     "<p><a class=\"tag\" href=\"/tags/news\">#news</a> <span class=\"mention\">@bob</span></p>"
+/// );
+/// ```
+pub trait TagResolver: Send + Sync {
+    /// Get the URL of the hashtag or the mention, or `None` to write it
+    /// without a link.
+    fn resolve(&self, kind: TagKind, name: &str) -> Option<String>;
+}
+
+impl<F> TagResolver for F
+where
+    F: Fn(TagKind, &str) -> Option<String> + Send + Sync,
+{
+    fn resolve(&self, kind: TagKind, name: &str) -> Option<String> {
+        self(kind, name)
+    }
+}
+
+/// The trait to look up the emoji of a shortcode such as `smile`, without
+/// colons.
+///
+/// This is implemented for closures which take the shortcode and return the
+/// emoji. With the `emoji` feature,
+/// [`emoji::Shortcodes`](crate::emoji::Shortcodes) looks up a bundled table.
+pub trait EmojiLookup: Send + Sync {
+    /// Get the emoji of the shortcode, or `None` to keep it as text.
+    fn emoji(&self, shortcode: &str) -> Option<String>;
+}
+
+impl<F> EmojiLookup for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync,
+{
+    fn emoji(&self, shortcode: &str) -> Option<String> {
+        self(shortcode)
+    }
+}
+
+impl fmt::Debug for Transformer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Transformer");
+
+        debug
+            .field("section", &self.section)
+            .field("smart_punctuation", &self.smart_punctuation)
+            .field("line_wrap", &self.line_wrap)
+            .field("line_break_style", &self.line_break_style)
+            .field("list_start", &self.list_start)
+            .field("tag_classes", &self.tag_classes)
+            .field("tag_attrs", &self.tag_attrs)
+            .field("external_rel", &self.external_rel)
+            .field("external_target", &self.external_target)
+            .field("internal_hosts", &self.internal_hosts)
+            .field("heading_ids", &self.heading_ids)
+            .field("quote_citation", &self.quote_citation)
+            .field("quote_depth_class", &self.quote_depth_class)
+            .field("code_block_wrapper", &self.code_block_wrapper)
+            .field("copy_button", &self.copy_button)
+            .field("slugifier", &self.slugifier)
+            .field("heading_anchor", &self.heading_anchor)
+            .field("anchor_symbol", &self.anchor_symbol)
+            .field("heading_numbering", &self.heading_numbering)
+            .field("numbering_start", &self.numbering_start)
+            .field("toc_placeholder", &self.toc_placeholder)
+            .field("wiki_resolver", &self.wiki_resolver.is_some())
+            .field("tag_resolver", &self.tag_resolver.is_some())
+            .field("emoji", &self.emoji.is_some())
+            .field("footnotes_heading", &self.footnotes_heading)
+            .field("messages", &self.messages)
+            .field("text_direction", &self.text_direction);
+
+        #[cfg(feature = "nfc")]
+        debug.field("nfc", &self.nfc);
+
+        debug.finish()
+    }
 }
 
-#[allow(clippy::derivable_impls)]
 impl Default for Transformer {
     fn default() -> Self {
-        Self { section: false }
+        Self {
+            section: false,
+            smart_punctuation: false,
+            line_wrap: LineWrap::Keep,
+            line_break_style: LineBreakStyle::HardBreak,
+            list_start: true,
+            tag_classes: BTreeMap::new(),
+            tag_attrs: BTreeMap::new(),
+            external_rel: None,
+            external_target: None,
+            internal_hosts: vec![],
+            heading_ids: false,
+            quote_citation: false,
+            quote_depth_class: false,
+            code_block_wrapper: false,
+            copy_button: false,
+            slugifier: slugify,
+            heading_anchor: None,
+            anchor_symbol: "¶".to_string(),
+            heading_numbering: None,
+            numbering_start: 1,
+            toc_placeholder: None,
+            wiki_resolver: None,
+            tag_resolver: None,
+            emoji: None,
+            footnotes_heading: None,
+            messages: Messages::default(),
+            text_direction: TextDirection::Off,
+            #[cfg(feature = "nfc")]
+            nfc: false,
+        }
+    }
+}
+
+pub mod config {
+    //! Configuration options for transforming Markdown tree.
+
+    use std::{fmt, str::FromStr};
+
+    use crate::error::ParseConfigError;
+
+    /// Where the anchor link is put in a headline.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum AnchorPosition {
+        /// Before the text of the headline.
+        Before,
+        /// After the text of the headline.
+        After,
+    }
+
+    impl FromStr for AnchorPosition {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "before" => Ok(Self::Before),
+                "after" => Ok(Self::After),
+                _ => Err(ParseConfigError::new("AnchorPosition", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for AnchorPosition {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Before => f.write_str("before"),
+                Self::After => f.write_str("after"),
+            }
+        }
+    }
+
+    /// How to write line breaks which are not marked as hard breaks.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LineWrap {
+        /// Write line breaks as the parser made them, by its
+        /// [`LineBreakStyle`](crate::layer::parser::config::LineBreakStyle).
+        Keep,
+        /// Join the lines with a space.
+        Join,
+    }
+
+    impl FromStr for LineWrap {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "keep" => Ok(Self::Keep),
+                "join" => Ok(Self::Join),
+                _ => Err(ParseConfigError::new("LineWrap", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for LineWrap {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Keep => f.write_str("keep"),
+                Self::Join => f.write_str("join"),
+            }
+        }
+    }
+
+    /// How to mark the direction of the text of blocks, such as paragraphs,
+    /// headlines, list items and table cells.
+    #[non_exhaustive]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TextDirection {
+        /// Do not mark the direction.
+        Off,
+        /// Detect the direction of each block by its first letter, and write
+        /// `dir="rtl"` or `dir="ltr"` if it differs from the direction of
+        /// the block around it. The document is left-to-right, so a document
+        /// without right-to-left text is unchanged.
+        Detect,
+        /// Write `dir="auto"`, so that the browser detects the direction of
+        /// each block.
+        Auto,
+    }
+
+    impl FromStr for TextDirection {
+        type Err = ParseConfigError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "off" => Ok(Self::Off),
+                "detect" => Ok(Self::Detect),
+                "auto" => Ok(Self::Auto),
+                _ => Err(ParseConfigError::new("TextDirection", s)),
+            }
+        }
+    }
+
+    impl fmt::Display for TextDirection {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Off => f.write_str("off"),
+                Self::Detect => f.write_str("detect"),
+                Self::Auto => f.write_str("auto"),
+            }
+        }
     }
 }
 
@@ -24,141 +403,2229 @@ impl Transformer {
         Self::default()
     }
 
-    #[allow(dead_code)]
-    fn section(mut self, section: bool) -> Self {
+    /// Set whether to wrap headlines into sections.
+    ///
+    /// Each headline and the blocks after it are wrapped into a `<section>`
+    /// up to the next headline of the same or a higher level. Sections of
+    /// lower level headlines are nested.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().section(true));
+    ///
+    /// let html = markdown.execute("Intro\n\n# A\n\n## B\n\nText\n\n# C");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<p>Intro</p>",
+    ///         "<section><h1>A</h1><section><h2>B</h2><p>Text</p></section></section>",
+    ///         "<section><h1>C</h1></section>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn section(mut self, section: bool) -> Self {
         self.section = section;
         self
     }
-}
 
-impl Transformer {
-    /// Transform Markdown tree to HTML tree.
-    pub fn transform<'a>(&self, tree: MarkdownTree<'a>) -> DocumentNode<'a> {
-        DocumentNode {
-            root: self.block_tree(tree.root),
-        }
+    /// Set whether to convert punctuation into typographic characters.
+    ///
+    /// `"` and `'` become curly quotes, `--` becomes an en dash, `---` becomes
+    /// an em dash, and `...` becomes an ellipsis. Escaped characters are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().smart_punctuation(true));
+    ///
+    /// let html = markdown.execute("\"Don't\" -- wait...");
+    ///
+    /// assert_eq!(html, "<p>\u{201c}Don\u{2019}t\u{201d} \u{2013} wait\u{2026}</p>");
+    /// ```
+    pub fn smart_punctuation(mut self, smart_punctuation: bool) -> Self {
+        self.smart_punctuation = smart_punctuation;
+        self
     }
 
-    fn block_tree<'a>(&self, tree: BlockTree<'a>) -> Vec<Node<'a>> {
-        tree.root
-            .into_iter()
-            .map(|item| self.block_item(item))
-            .collect()
+    /// Set how to write line breaks which are not marked as hard breaks.
+    ///
+    /// With [`LineWrap::Join`], the lines of text wrapped at a width are
+    /// joined with spaces, whatever paragraph ending the parser uses. A line
+    /// which ends with `\` or at least two spaces still ends with `<br>`,
+    /// and the marker is removed. Set
+    /// [`line_break_style`](Transformer::line_break_style) to the style of the
+    /// parser if it is not the default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().line_wrap(LineWrap::Join));
+    ///
+    /// let html = markdown.execute("Text wrapped \nat a width,\\\nwith a break  \nkept");
+    ///
+    /// assert_eq!(html, "<p>Text wrapped at a width,<br>with a break<br>kept</p>");
+    /// ```
+    pub fn line_wrap(mut self, line_wrap: LineWrap) -> Self {
+        self.line_wrap = line_wrap;
+        self
     }
 
-    fn block_item<'a>(&self, item: BlockItem<'a>) -> Node<'a> {
-        match item {
-            BlockItem::Paragraph(tree) => self.paragraph(tree),
-            BlockItem::Headline(level, tree) => self.headline(level, tree),
-            BlockItem::BulletList(tree) => self.bullet_list(tree),
-            BlockItem::OrderedList(tree) => self.ordered_list(tree),
-            BlockItem::BlockQuote(tree) => self.blockquote(tree),
-            BlockItem::Container(_, _) => todo!(),
+    /// Set the style of line breaks of the parser.
+    ///
+    /// [`Markdown::from_options`](crate::Markdown::from_options) sets it from
+    /// the parser options.
+    pub fn line_break_style(mut self, style: LineBreakStyle) -> Self {
+        self.line_break_style = style;
+        self
+    }
+
+    /// Set whether to keep the number of the first item of ordered lists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "3. a\n4. b";
+    ///
+    /// let html = Markdown::default().execute(input);
+    ///
+    /// assert_eq!(html, "<ol start=\"3\"><li>a</li><li>b</li></ol>");
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().list_start(false));
+    ///
+    /// assert_eq!(markdown.execute(input), "<ol><li>a</li><li>b</li></ol>");
+    /// ```
+    pub fn list_start(mut self, list_start: bool) -> Self {
+        self.list_start = list_start;
+        self
+    }
+
+    /// Add classes to every element with the tag.
+    ///
+    /// Classes are separated by white spaces, and are added after the classes
+    /// given by attributes in the markdown. Classes of a `&'static str` are
+    /// not copied for each element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(
+    ///     Transformer::new()
+    ///         .class_for(ElementTag::H1, "text-2xl font-bold")
+    ///         .attr_for(ElementTag::Blockquote, "role", "note"),
+    /// );
+    ///
+    /// let html = markdown.execute("# Title {.x}\n\n> Quote");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<h1 class=\"x text-2xl font-bold\">Title</h1><blockquote role=\"note\"><p>Quote</p></blockquote>"
+    /// );
+    /// ```
+    pub fn class_for(mut self, tag: ElementTag, class: impl Into<Cow<'static, str>>) -> Self {
+        let classes = self.tag_classes.entry(tag).or_default();
+
+        match class.into() {
+            Cow::Borrowed(class) => classes.extend(class.split_whitespace().map(Cow::Borrowed)),
+            Cow::Owned(class) => classes.extend(
+                class
+                    .split_whitespace()
+                    .map(|class| Cow::Owned(class.to_string())),
+            ),
         }
+
+        self
     }
 
-    fn paragraph<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::P,
-            children: self.inline_tree(tree),
-            ..Default::default()
-        })
+    /// Add an attribute to every element with the tag.
+    ///
+    /// Setting the same key again replaces the value. An element which
+    /// already has the attribute, e.g. by `{key=value}` in the document,
+    /// keeps its own value.
+    pub fn attr_for(
+        mut self,
+        tag: ElementTag,
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let attrs = self.tag_attrs.entry(tag).or_default();
+        let key = key.into();
+
+        match attrs.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value.into(),
+            None => attrs.push((key, value.into())),
+        }
+
+        self
     }
 
-    fn headline<'a>(&self, level: u8, tree: InlineTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::headline(level).unwrap(),
-            children: self.inline_tree(tree),
-            ..Default::default()
-        })
+    /// Set whether to give ids to all headlines which do not have one.
+    ///
+    /// Ids are made and deduplicated as [`TocMaker`] does, so
+    /// [`Markdown::execute`](crate::Markdown::execute) gives anchors without
+    /// a table of contents. The table of contents uses these ids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().heading_ids(true));
+    ///
+    /// let input = "# Intro\n\n## Intro\n\n### Usage {#intro}";
+    ///
+    /// assert_eq!(
+    ///     markdown.execute(input),
+    ///     "<h1 id=\"intro-1\">Intro</h1><h2 id=\"intro-2\">Intro</h2><h3 id=\"intro\">Usage</h3>"
+    /// );
+    ///
+    /// let (html, _) = markdown.execute_with_toc(input);
+    ///
+    /// assert_eq!(html, markdown.execute(input));
+    /// ```
+    pub fn heading_ids(mut self, heading_ids: bool) -> Self {
+        self.heading_ids = heading_ids;
+        self
     }
 
-    fn bullet_list<'a>(&self, tree: ListTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Ul,
-            children: self.list_tree(tree),
-            ..Default::default()
-        })
+    /// Make a blockquote whose last line starts with `-- ` or `— ` into a
+    /// `<figure>`, with the rest of the line in a `<figcaption>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().quote_citation(true));
+    ///
+    /// let html = markdown.execute("> Simple is better.\n> -- *Zen*");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<figure><blockquote><p>Simple is better.</p></blockquote>",
+    ///         "<figcaption><em>Zen</em></figcaption></figure>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn quote_citation(mut self, quote_citation: bool) -> Self {
+        self.quote_citation = quote_citation;
+        self
     }
 
-    fn ordered_list<'a>(&self, tree: ListTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Ol,
-            children: self.list_tree(tree),
-            ..Default::default()
-        })
+    /// Add the class `blockquote-depth-N` to blockquotes, where `N` starts
+    /// from 1 at the outermost blockquote. This helps to style deep quotes
+    /// such as replies in email threads.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().quote_depth_class(true));
+    ///
+    /// let html = markdown.execute("> > a");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<blockquote class=\"blockquote-depth-1\">",
+    ///         "<blockquote class=\"blockquote-depth-2\"><p>a</p></blockquote>",
+    ///         "</blockquote>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn quote_depth_class(mut self, quote_depth_class: bool) -> Self {
+        self.quote_depth_class = quote_depth_class;
+        self
     }
 
-    fn blockquote<'a>(&self, tree: BlockTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Blockquote,
-            children: self.block_tree(tree),
-            ..Default::default()
-        })
+    /// Wrap code blocks into `<div class="code-block">` and each line of
+    /// their code into `<span class="line">`, so that a stylesheet can
+    /// number the lines with a CSS counter. The newlines stay between the
+    /// spans, so the text of the code is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().code_block_wrapper(true).copy_button(true));
+    ///
+    /// let html = markdown.execute("```sh\ncd a\nls\n```");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<div class=\"code-block\"><button class=\"copy-button\" type=\"button\"></button>",
+    ///         "<pre><code class=\"language-sh\"><span class=\"line\">cd a</span>\n",
+    ///         "<span class=\"line\">ls</span>\n</code></pre></div>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn code_block_wrapper(mut self, code_block_wrapper: bool) -> Self {
+        self.code_block_wrapper = code_block_wrapper;
+        self
+    }
+
+    /// Set whether to put an empty `<button class="copy-button">` into the
+    /// wrappers of [`code_block_wrapper`](Self::code_block_wrapper), for a
+    /// script to make it copy the code without looking for code blocks.
+    pub fn copy_button(mut self, copy_button: bool) -> Self {
+        self.copy_button = copy_button;
+        self
+    }
+
+    /// Set the function to make an id from the text of a headline for
+    /// [`heading_ids`](Self::heading_ids).
+    pub fn slugifier(mut self, slugifier: fn(&str) -> String) -> Self {
+        self.slugifier = slugifier;
+        self
+    }
+
+    /// Add a link with the class `anchor` to each headline, like the
+    /// permalinks shown on hover by many documentation sites.
+    ///
+    /// Headlines are given ids as with [`heading_ids`](Self::heading_ids),
+    /// and the link points to the id. The link is not a part of the text of
+    /// the table of contents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(
+    ///     Transformer::new()
+    ///         .heading_anchor(AnchorPosition::After)
+    ///         .anchor_symbol("#"),
+    /// );
+    ///
+    /// let (html, toc) = markdown.execute_with_toc("# Hello world");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     "<h1 id=\"hello-world\">Hello world<a class=\"anchor\" href=\"#hello-world\">#</a></h1>"
+    /// );
+    ///
+    /// assert_eq!(
+    ///     toc,
+    ///     "<ul><li><a href=\"#hello-world\">Hello world</a></li></ul>"
+    /// );
+    /// ```
+    pub fn heading_anchor(mut self, position: AnchorPosition) -> Self {
+        self.heading_anchor = Some(position);
+        self
+    }
+
+    /// Set the text of the links of [`heading_anchor`](Self::heading_anchor).
+    pub fn anchor_symbol(mut self, symbol: &str) -> Self {
+        self.anchor_symbol = symbol.to_string();
+        self
+    }
+
+    /// Prefix headlines with hierarchical numbers such as `1.2`, written in a
+    /// `<span class="heading-number">` before the text.
+    ///
+    /// The numbers follow the nesting of headlines like
+    /// [`TocMaker::numbering`], counting from
+    /// [`numbering_start`](Self::numbering_start). Like the entries of the
+    /// table of contents, only headlines at the top level or in sections are
+    /// numbered. The table of contents shows the numbers as a part of the
+    /// text, so it should not number its entries again. Ids are made from the
+    /// text without the numbers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(
+    ///     Transformer::new()
+    ///         .heading_numbering(Numbering::default().separator("-"))
+    ///         .numbering_start(2),
+    /// );
+    ///
+    /// let (html, toc) = markdown.execute_with_toc("# Spec\n\n## Scope\n\n### Terms\n\n## Usage");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<h1 id=\"spec\">Spec</h1>",
+    ///         "<h2 id=\"scope\"><span class=\"heading-number\">1</span> Scope</h2>",
+    ///         "<h3 id=\"terms\"><span class=\"heading-number\">1-1</span> Terms</h3>",
+    ///         "<h2 id=\"usage\"><span class=\"heading-number\">2</span> Usage</h2>",
+    ///     ]
+    /// );
+    /// assert!(toc.contains("<a href=\"#terms\">1-1 Terms</a>"));
+    /// ```
+    pub fn heading_numbering(mut self, numbering: Numbering) -> Self {
+        self.heading_numbering = Some(numbering);
+        self
+    }
+
+    /// Set the level of the highest headlines numbered by
+    /// [`heading_numbering`](Self::heading_numbering). Higher headlines, such
+    /// as the title of the document, are not numbered.
+    pub fn numbering_start(mut self, level: u8) -> Self {
+        self.numbering_start = level;
+        self
+    }
+
+    /// Replace paragraphs of only `[TOC]` or `[[toc]]` with the table of
+    /// contents, so the author decides where it appears. The marker is
+    /// compared without case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().toc_placeholder(TocMaker::default().min_level(2)));
+    ///
+    /// let html = markdown.execute("# Guide\n\n[TOC]\n\n## Install\n\n## Usage");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<h1>Guide</h1>",
+    ///         "<ul><li><a href=\"#install\">Install</a></li><li><a href=\"#usage\">Usage</a></li></ul>",
+    ///         "<h2 id=\"install\">Install</h2><h2 id=\"usage\">Usage</h2>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn toc_placeholder(mut self, toc_maker: TocMaker) -> Self {
+        self.toc_placeholder = Some(toc_maker);
+        self
+    }
+
+    /// Set the resolver of the URLs of wiki links, which are parsed if
+    /// [`Parser::wiki_links`](crate::layer::parser::Parser::wiki_links) is
+    /// enabled.
+    pub fn wiki_resolver(mut self, resolver: impl WikiResolver + 'static) -> Self {
+        self.wiki_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Set the resolver of the URLs of hashtags and mentions, which are
+    /// parsed if [`Parser::hashtags`](crate::layer::parser::Parser::hashtags)
+    /// or [`Parser::mentions`](crate::layer::parser::Parser::mentions) is
+    /// enabled.
+    ///
+    /// Hashtags have the class `tag` and mentions have the class `mention`.
+    /// They are `<a>` if they are resolved, or `<span>` otherwise.
+    pub fn tag_resolver(mut self, resolver: impl TagResolver + 'static) -> Self {
+        self.tag_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Replace shortcodes such as `:smile:` in text with emoji.
+    ///
+    /// A shortcode is letters, digits, `_`, `+` and `-` between colons.
+    /// Shortcodes which the lookup does not know are kept as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let transformer = Transformer::new().emoji(|shortcode: &str| {
+    ///     (shortcode == "crab").then(|| "🦀".to_string())
+    /// });
+    ///
+    /// let markdown = Markdown::default().transformer(transformer);
+    ///
+    /// assert_eq!(markdown.execute("Hi :crab: at 10:30:00 :cat:"), "<p>Hi 🦀 at 10:30:00 :cat:</p>");
+    /// ```
+    ///
+    /// With the `emoji` feature, use the bundled table:
+    ///
+    /// ```
+    /// # #[cfg(feature = "emoji")]
+    /// # {
+    /// use note_mark::{emoji::Shortcodes, prelude::*};
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().emoji(Shortcodes));
+    ///
+    /// assert_eq!(markdown.execute(":tada: :+1:"), "<p>🎉 👍</p>");
+    /// # }
+    /// ```
+    pub fn emoji(mut self, lookup: impl EmojiLookup + 'static) -> Self {
+        self.emoji = Some(Arc::new(lookup));
+        self
+    }
+
+    /// Put a headline at the start of the section of inline footnotes.
+    ///
+    /// The text of the headline is [`Messages::footnotes_label`]. It is
+    /// added after headlines are numbered, so it is not numbered, but it gets
+    /// an id and is in the table of contents like other headlines.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let messages = Messages {
+    ///     footnotes_label: "Notes".to_string(),
+    ///     footnote_backref: "\u{2191}".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let markdown = Markdown::default()
+    ///     .parser(Parser::default().inline_footnotes(true))
+    ///     .transformer(Transformer::new().footnotes_heading(ElementTag::H2))
+    ///     .messages(messages);
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("a^[b]"),
+    ///     concat![
+    ///         "<p>a<sup class=\"footnote-ref\"><a id=\"fnref-1\" href=\"#fn-1\">1</a></sup></p>",
+    ///         "<section class=\"footnotes\"><h2>Notes</h2><ol><li id=\"fn-1\">b ",
+    ///         "<a class=\"footnote-backref\" href=\"#fnref-1\">\u{2191}</a></li></ol></section>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn footnotes_heading(mut self, tag: ElementTag) -> Self {
+        self.footnotes_heading = Some(tag);
+        self
+    }
+
+    /// Set the messages for generated text.
+    pub fn messages(mut self, messages: Messages) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Set how to mark the direction of the text of blocks.
+    ///
+    /// With [`TextDirection::Detect`], the direction of a block is the one
+    /// of its first letter, as browsers do for `dir="auto"`, and letters of
+    /// right-to-left scripts such as Hebrew and Arabic are found by their
+    /// Unicode blocks. A block with an explicit `dir` attribute keeps it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().text_direction(TextDirection::Detect));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("- שלום world\n  - hello עולם\n- 12 hi"),
+    ///     concat![
+    ///         "<ul><li dir=\"rtl\">שלום world<ul><li dir=\"ltr\">hello עולם</li></ul></li>",
+    ///         "<li>12 hi</li></ul>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn text_direction(mut self, text_direction: TextDirection) -> Self {
+        self.text_direction = text_direction;
+        self
+    }
+
+    /// Set whether to normalize text to NFC.
+    ///
+    /// A letter followed by combining marks, such as `e` and U+0301, becomes
+    /// the precomposed letter `é`, so text typed in either form renders the
+    /// same, and so do the ids of headlines and the names of hashtags. Each
+    /// run of text is normalized on its own, so a mark after markup such as
+    /// `*e*\u{301}` is kept apart. The ranges of the input are not changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .transformer(Transformer::new().heading_ids(true).nfc(true));
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("# Cafe\u{301}"),
+    ///     "<h1 id=\"caf\u{e9}\">Caf\u{e9}</h1>"
+    /// );
+    /// ```
+    #[cfg(feature = "nfc")]
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Set the `rel` attribute of external links.
+    ///
+    /// A link is external if its URL has a host, such as `https://host/` or
+    /// `//host/`, which is not one of [`internal_hosts`](Self::internal_hosts).
+    /// Relative links and links without a host such as `mailto:` are
+    /// internal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let transformer = Transformer::new()
+    ///     .external_rel("nofollow noopener")
+    ///     .external_target("_blank")
+    ///     .internal_host("example.com");
+    ///
+    /// let markdown = Markdown::default().transformer(transformer);
+    ///
+    /// let html = markdown.execute("[a](https://rust-lang.org) [b](/docs) [c](https://example.com/x)");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<p><a href=\"https://rust-lang.org\" rel=\"nofollow noopener\" target=\"_blank\">a</a> ",
+    ///         "<a href=\"/docs\">b</a> <a href=\"https://example.com/x\">c</a></p>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn external_rel(mut self, rel: &str) -> Self {
+        self.external_rel = Some(rel.to_string());
+        self
+    }
+
+    /// Set the `target` attribute of external links.
+    pub fn external_target(mut self, target: &str) -> Self {
+        self.external_target = Some(target.to_string());
+        self
+    }
+
+    /// Add a host whose links are not external. The host is compared without
+    /// case, and subdomains are not included.
+    pub fn internal_host(mut self, host: &str) -> Self {
+        self.internal_hosts.push(host.to_lowercase());
+        self
+    }
+
+    /// Check if the URL points to a host other than the internal hosts.
+    fn is_external(&self, url: &str) -> bool {
+        let scheme_len = url
+            .find(':')
+            .filter(|&index| {
+                url[..index].starts_with(|c: char| c.is_ascii_alphabetic())
+                    && url[..index]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            })
+            .map_or(0, |index| index + 1);
+
+        let Some(rest) = url[scheme_len..].strip_prefix("//") else {
+            return false;
+        };
+
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = match host.rsplit_once(':') {
+            Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+            _ => host,
+        };
+
+        !host.is_empty()
+            && !self
+                .internal_hosts
+                .iter()
+                .any(|internal| internal.eq_ignore_ascii_case(host))
+    }
+}
+
+impl Transformer {
+    /// Transform Markdown tree to HTML tree.
+    pub fn transform<'a>(&self, tree: MarkdownTree<'a>) -> DocumentNode<'a> {
+        let mut definitions = Definitions::default();
+        definitions.collect(&tree.root.root);
+
+        self.transform_with(tree, &definitions)
+    }
+
+    /// Transform Markdown tree to HTML tree with the definitions of the whole
+    /// document, which may be parsed in several trees.
+    pub(crate) fn transform_with<'a>(
+        &self,
+        tree: MarkdownTree<'a>,
+        definitions: &Definitions,
+    ) -> DocumentNode<'a> {
+        self.document(self.transform_nodes(tree, definitions))
+    }
+
+    /// Transform Markdown tree to the nodes of a part of a document, which
+    /// are made a document by [`document`](Self::document) after the parts
+    /// are joined.
+    pub(crate) fn transform_nodes<'a>(
+        &self,
+        tree: MarkdownTree<'a>,
+        definitions: &Definitions,
+    ) -> Vec<Node<'a>> {
+        let mut root = self.block_tree(tree.root);
+
+        if !definitions.abbreviations.is_empty() {
+            abbreviate(&mut root, &definitions.abbreviations);
+        }
+
+        if self.quote_depth_class {
+            add_quote_depths(&mut root);
+        }
+
+        self.inject_attributes(&mut root);
+
+        root
+    }
+
+    /// Make a document from transformed nodes, wrapping sections and giving
+    /// ids to headlines.
+    ///
+    /// These need the whole document, so chunks transformed separately are
+    /// joined before this.
+    pub(crate) fn document<'a>(&self, nodes: Vec<Node<'a>>) -> DocumentNode<'a> {
+        let mut document = DocumentNode {
+            root: self.sections(nodes),
+        };
+
+        let mut footnotes = vec![];
+
+        take_footnotes(&mut document.root, &mut footnotes);
+
+        if let Some(numbering) = &self.heading_numbering {
+            number_headlines(
+                &mut document.root,
+                numbering,
+                self.numbering_start,
+                &mut LevelCounters::default(),
+            );
+        }
+
+        // The section of footnotes is added after headlines are numbered, so
+        // its headline is not numbered.
+        if !footnotes.is_empty() {
+            document.root.push(self.footnote_section(footnotes));
+        }
+
+        if self.heading_ids || self.heading_anchor.is_some() {
+            TocMaker::default()
+                .min_level(1)
+                .max_level(6)
+                .slugifier(self.slugifier)
+                .assign_ids(&mut document);
+        }
+
+        if let Some(toc_maker) = &self.toc_placeholder {
+            if has_toc_placeholder(&document.root) {
+                let toc = toc_maker.make_toc(&mut document);
+
+                replace_toc_placeholder(&mut document.root, &toc.root);
+            }
+        }
+
+        if let Some(position) = self.heading_anchor {
+            self.add_anchors(&mut document.root, position);
+        }
+
+        // The generated blocks, such as footnotes and the table of contents,
+        // are marked too.
+        add_directions(&mut document.root, self.text_direction);
+
+        document
     }
 
-    fn list_tree<'a>(&self, tree: ListTree<'a>) -> Vec<Node<'a>> {
-        tree.root
+    /// Make the section of footnotes, whose items link back to the references.
+    fn footnote_section<'a>(&self, footnotes: Vec<Vec<Node<'a>>>) -> Node<'a> {
+        let items = footnotes
             .into_iter()
-            .map(|item| {
-                let mut nodes = self.inline_tree(item.name);
+            .enumerate()
+            .map(|(index, mut children)| {
+                let number = index + 1;
 
-                item.children
-                    .into_iter()
-                    .map(|item| self.block_item(item))
-                    .for_each(|node| nodes.push(node));
+                children.push(Node::Text(TextNode { text: " ".into() }));
+                children.push(Node::Element(ElementNode {
+                    tag: ElementTag::A,
+                    class: vec!["footnote-backref".into()],
+                    href: Some(format!("#fnref-{number}")),
+                    children: vec![Node::Text(TextNode {
+                        text: self.messages.footnote_backref.clone().into(),
+                    })],
+                    ..Default::default()
+                }));
 
                 Node::Element(ElementNode {
                     tag: ElementTag::Li,
-                    children: nodes,
+                    id: Some(format!("fn-{number}")),
+                    children,
                     ..Default::default()
                 })
             })
-            .collect()
+            .collect();
+
+        let mut children = vec![];
+
+        if let Some(tag) = self.footnotes_heading {
+            children.push(Node::Element(ElementNode {
+                tag,
+                children: vec![Node::Text(TextNode {
+                    text: self.messages.footnotes_label.clone().into(),
+                })],
+                ..Default::default()
+            }));
+        }
+
+        children.push(Node::Element(ElementNode {
+            tag: ElementTag::Ol,
+            children: items,
+            ..Default::default()
+        }));
+
+        Node::Element(ElementNode {
+            tag: ElementTag::Section,
+            class: vec!["footnotes".into()],
+            children,
+            ..Default::default()
+        })
     }
 
-    fn inline_tree<'a>(&self, tree: InlineTree<'a>) -> Vec<Node<'a>> {
-        tree.root
-            .into_iter()
-            .map(|item| self.inline_item(item))
-            .collect()
+    /// Add an anchor link to each headline with an id in the nodes and their
+    /// descendants.
+    fn add_anchors(&self, nodes: &mut [Node], position: AnchorPosition) {
+        visit_elements_mut(nodes, |element| {
+            let Some(id) = element
+                .id
+                .as_ref()
+                .filter(|_| element.tag.get_headline_level().is_some())
+            else {
+                return true;
+            };
+
+            let anchor = Node::Element(ElementNode {
+                tag: ElementTag::A,
+                class: vec!["anchor".into()],
+                href: Some(format!("#{id}")),
+                children: vec![Node::Text(TextNode {
+                    text: self.anchor_symbol.clone().into(),
+                })],
+                ..Default::default()
+            });
+
+            match position {
+                AnchorPosition::Before => element.children.insert(0, anchor),
+                AnchorPosition::After => element.children.push(anchor),
+            }
+
+            false
+        });
+    }
+
+    /// Add classes and attributes of [`tag_classes`](Self::tag_classes) and
+    /// [`tag_attrs`](Self::tag_attrs) to the nodes and their descendants.
+    fn inject_attributes(&self, nodes: &mut [Node]) {
+        if self.tag_classes.is_empty() && self.tag_attrs.is_empty() {
+            return;
+        }
+
+        visit_elements_mut(nodes, |element| {
+            self.inject_element_attributes(element);
+            true
+        });
+    }
+
+    fn inject_element_attributes(&self, element: &mut ElementNode) {
+        if let Some(classes) = self.tag_classes.get(&element.tag) {
+            for class in classes {
+                element.add_class(class.clone());
+            }
+        }
+
+        // Attributes written in the document win over the configured ones.
+        if let Some(attrs) = self.tag_attrs.get(&element.tag) {
+            for (key, value) in attrs {
+                match key.as_ref() {
+                    _ if element.has_attr(key) => {}
+                    "class" | "id" | "href" => element.set_attr(key, value),
+                    _ => element.attrs.push((key.clone(), value.clone())),
+                }
+            }
+        }
+    }
+
+    /// Wrap headlines and their following nodes into sections if
+    /// [`section`](Self::section) is enabled.
+    fn sections<'a>(&self, nodes: Vec<Node<'a>>) -> Vec<Node<'a>> {
+        if !self.section {
+            return nodes;
+        }
+
+        // The levels and nodes of open sections. The first one is the root.
+        let mut stack: Vec<(u8, Vec<Node<'a>>)> = vec![(0, vec![])];
+
+        let close = |stack: &mut Vec<(u8, Vec<Node<'a>>)>| {
+            let (_, children) = stack.pop().unwrap();
+
+            let mut section = ElementNode {
+                tag: ElementTag::Section,
+                children,
+                ..Default::default()
+            };
+
+            self.inject_element_attributes(&mut section);
+
+            stack.last_mut().unwrap().1.push(Node::Element(section));
+        };
+
+        for node in nodes {
+            let level = match &node {
+                Node::Element(element) => element.tag.get_headline_level(),
+                Node::Text(_) | Node::Comment(_) => None,
+            };
+
+            match level {
+                Some(level) => {
+                    while stack.last().unwrap().0 >= level {
+                        close(&mut stack);
+                    }
+
+                    stack.push((level, vec![node]));
+                }
+                None => stack.last_mut().unwrap().1.push(node),
+            }
+        }
+
+        while stack.len() > 1 {
+            close(&mut stack);
+        }
+
+        stack.pop().unwrap().1
+    }
+
+    fn block_tree<'a>(&self, tree: BlockTree<'a>) -> Vec<Node<'a>> {
+        self.block_items(tree.into_root())
+    }
+
+    /// Transform block items, putting the blocks of includes in their place
+    /// and leaving out definitions.
+    ///
+    /// Blockquotes, containers, lists and includes are opened on a stack instead of
+    /// recursing, so deeply nested blocks do not overflow the call stack.
+    fn block_items<'a>(&self, items: Vec<BlockItem<'a>>) -> Vec<Node<'a>> {
+        let mut stack = vec![BlockFrame {
+            items: items.into_iter(),
+            nodes: vec![],
+            closing: None,
+        }];
+
+        loop {
+            let frame = stack.last_mut().unwrap();
+
+            let Some(mut item) = frame.items.next() else {
+                let frame = stack.pop().unwrap();
+
+                let Some(closing) = frame.closing else {
+                    return frame.nodes;
+                };
+
+                if let Some(frame) = self.close_block(frame.nodes, closing, &mut stack) {
+                    stack.push(frame);
+                }
+
+                continue;
+            };
+
+            let mut attributes = vec![];
+
+            while let BlockItem::Attributed(attrs, inner) = item {
+                attributes.push(attrs);
+                item = *inner;
+            }
+
+            let opened = match item {
+                BlockItem::Include(_, tree) => Some(BlockFrame {
+                    items: tree.into_root().into_iter(),
+                    nodes: vec![],
+                    closing: Some(Closing::Include),
+                }),
+                BlockItem::Abbreviation(..) => continue,
+                BlockItem::BlockQuote(mut tree) => {
+                    let caption = match self.quote_citation {
+                        true => take_citation(&mut tree),
+                        false => None,
+                    };
+
+                    Some(BlockFrame {
+                        items: tree.into_root().into_iter(),
+                        nodes: vec![],
+                        closing: Some(Closing::BlockQuote(caption, attributes)),
+                    })
+                }
+                BlockItem::Container(names, tree) => Some(BlockFrame {
+                    items: tree.into_root().into_iter(),
+                    nodes: vec![],
+                    closing: Some(Closing::Container(names, attributes)),
+                }),
+                BlockItem::BulletList(tree) => {
+                    let list = ElementNode {
+                        tag: ElementTag::Ul,
+                        ..Default::default()
+                    };
+
+                    self.open_list(list, tree, attributes, &mut frame.nodes)
+                }
+                BlockItem::OrderedList(start, marker, tree) => {
+                    let list = self.ordered_list(start, marker);
+
+                    self.open_list(list, tree, attributes, &mut frame.nodes)
+                }
+                item => {
+                    let node = Self::with_attributes(self.block_item(item), attributes);
+
+                    frame.nodes.push(node);
+                    None
+                }
+            };
+
+            stack.extend(opened);
+        }
+    }
+
+    /// Put the nodes of a closed block into its parent, and open the next
+    /// item if the block is a list item.
+    fn close_block<'a>(
+        &self,
+        nodes: Vec<Node<'a>>,
+        closing: Closing<'a>,
+        stack: &mut [BlockFrame<'a>],
+    ) -> Option<BlockFrame<'a>> {
+        let parent = &mut stack.last_mut().unwrap().nodes;
+
+        match closing {
+            Closing::Include => parent.extend(nodes),
+            Closing::BlockQuote(caption, attributes) => {
+                let node = Self::with_attributes(self.blockquote(nodes, caption), attributes);
+
+                parent.push(node);
+            }
+            Closing::Container(names, attributes) => {
+                let container = ElementNode {
+                    tag: ElementTag::Div,
+                    class: names.into_iter().map(Cow::Owned).collect(),
+                    children: nodes,
+                    ..Default::default()
+                };
+
+                parent.push(Self::with_attributes(Node::Element(container), attributes));
+            }
+            Closing::ListItem(mut list, mut rest, attributes) => {
+                list.children.push(Node::Element(ElementNode {
+                    tag: ElementTag::Li,
+                    children: nodes,
+                    ..Default::default()
+                }));
+
+                match rest.next() {
+                    Some(item) => return Some(self.list_item(list, item, rest, attributes)),
+                    None => parent.push(Self::with_attributes(Node::Element(list), attributes)),
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Open the first item of a list, or put the list into the nodes if it
+    /// has no items.
+    fn open_list<'a>(
+        &self,
+        list: ElementNode<'a>,
+        tree: ListTree<'a>,
+        attributes: Vec<Attributes>,
+        nodes: &mut Vec<Node<'a>>,
+    ) -> Option<BlockFrame<'a>> {
+        let mut rest = tree.into_root().into_iter();
+
+        match rest.next() {
+            Some(item) => Some(self.list_item(list, item, rest, attributes)),
+            None => {
+                nodes.push(Self::with_attributes(Node::Element(list), attributes));
+                None
+            }
+        }
+    }
+
+    /// Open a list item, whose label comes before its blocks.
+    fn list_item<'a>(
+        &self,
+        list: ElementNode<'a>,
+        item: ListItem<'a>,
+        rest: std::vec::IntoIter<ListItem<'a>>,
+        attributes: Vec<Attributes>,
+    ) -> BlockFrame<'a> {
+        BlockFrame {
+            items: item.children.into_iter(),
+            nodes: self.inline_tree(item.name),
+            closing: Some(Closing::ListItem(list, rest, attributes)),
+        }
+    }
+
+    /// Transform a block item which has no child blocks.
+    fn block_item<'a>(&self, item: BlockItem<'a>) -> Node<'a> {
+        match item {
+            BlockItem::Paragraph(tree) => self.paragraph(tree),
+            BlockItem::Headline(level, tree) => self.headline(level, tree),
+            BlockItem::Comment(text) => Node::Comment(CommentNode { text }),
+            BlockItem::Table(table) => self.table(table),
+            BlockItem::CodeBlock(code) => self.code_block(code),
+            BlockItem::BulletList(_)
+            | BlockItem::OrderedList(..)
+            | BlockItem::BlockQuote(_)
+            | BlockItem::Container(..)
+            | BlockItem::Attributed(..)
+            | BlockItem::Include(..)
+            | BlockItem::Abbreviation(..) => {
+                unreachable!("nested blocks and definitions are handled by block_items")
+            }
+        }
+    }
+
+    /// Give the attributes to the node if it is an element, the innermost
+    /// first.
+    fn with_attributes<'a>(mut node: Node<'a>, attributes: Vec<Attributes>) -> Node<'a> {
+        for attributes in attributes.into_iter().rev() {
+            Self::set_attributes(&mut node, attributes);
+        }
+
+        node
+    }
+
+    /// Give the attributes to the node if it is an element.
+    fn set_attributes(node: &mut Node, attributes: Attributes) {
+        if let Node::Element(element) = node {
+            if let Some(id) = attributes.id {
+                element.id = Some(id);
+            }
+
+            for class in attributes.classes {
+                element.add_class(class);
+            }
+
+            for (key, value) in &attributes.attrs {
+                element.set_attr(key, value);
+            }
+        }
+    }
+
+    fn paragraph<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::P,
+            children: self.inline_tree(tree),
+            ..Default::default()
+        })
+    }
+
+    fn headline<'a>(&self, level: u8, tree: InlineTree<'a>) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::headline(level).unwrap(),
+            children: self.inline_tree(tree),
+            ..Default::default()
+        })
+    }
+
+    /// Make an `<ol>` without its items.
+    fn ordered_list<'a>(&self, start: u64, marker: ListMarker) -> ElementNode<'a> {
+        let mut attrs = match self.list_start && start != 1 {
+            true => vec![("start".into(), start.to_string().into())],
+            false => vec![],
+        };
+
+        let list_type = match marker.counter {
+            Counter::LowerAlpha => Some("a"),
+            Counter::UpperAlpha => Some("A"),
+            Counter::LowerRoman => Some("i"),
+            Counter::UpperRoman => Some("I"),
+            _ => None,
+        };
+
+        if let Some(list_type) = list_type {
+            attrs.push(("type".into(), list_type.into()));
+        }
+
+        ElementNode {
+            tag: ElementTag::Ol,
+            attrs,
+            ..Default::default()
+        }
+    }
+
+    fn table<'a>(&self, table: TableTree<'a>) -> Node<'a> {
+        let TableTree { aligns, head, rows } = table;
+
+        // Places merged into other cells have no elements.
+        let row = |tag: ElementTag, cells: Vec<TableCell<'a>>| {
+            let children = cells
+                .into_iter()
+                .zip(&aligns)
+                .filter_map(|(cell, align)| {
+                    let TableCell::Cell {
+                        tree,
+                        colspan,
+                        rowspan,
+                    } = cell
+                    else {
+                        return None;
+                    };
+
+                    let align = match align {
+                        Alignment::None => None,
+                        Alignment::Left => Some("left"),
+                        Alignment::Center => Some("center"),
+                        Alignment::Right => Some("right"),
+                    };
+
+                    let mut attrs = align
+                        .map(|align| ("align".into(), align.into()))
+                        .into_iter()
+                        .collect::<Vec<_>>();
+
+                    if colspan > 1 {
+                        attrs.push(("colspan".into(), colspan.to_string().into()));
+                    }
+
+                    if rowspan > 1 {
+                        attrs.push(("rowspan".into(), rowspan.to_string().into()));
+                    }
+
+                    Some(Node::Element(ElementNode {
+                        tag,
+                        attrs,
+                        children: self.inline_tree(tree),
+                        ..Default::default()
+                    }))
+                })
+                .collect();
+
+            Node::Element(ElementNode {
+                tag: ElementTag::Tr,
+                children,
+                ..Default::default()
+            })
+        };
+
+        let mut children = vec![Node::Element(ElementNode {
+            tag: ElementTag::Thead,
+            children: vec![row(ElementTag::Th, head)],
+            ..Default::default()
+        })];
+
+        // An empty `<tbody>` is left out like GFM.
+        if !rows.is_empty() {
+            children.push(Node::Element(ElementNode {
+                tag: ElementTag::Tbody,
+                children: rows
+                    .into_iter()
+                    .map(|cells| row(ElementTag::Td, cells))
+                    .collect(),
+                ..Default::default()
+            }));
+        }
+
+        Node::Element(ElementNode {
+            tag: ElementTag::Table,
+            children,
+            ..Default::default()
+        })
+    }
+
+    /// Make a code block of `<pre>` and `<code>`. The language is the class
+    /// `language-*` of the code, and the other words of the info string are
+    /// its `data-*` attributes, except for `caption` which makes a figure of
+    /// the block. The block is wrapped by
+    /// [`code_block_wrapper`](Self::code_block_wrapper) inside the figure.
+    fn code_block<'a>(&self, code: CodeBlock<'a>) -> Node<'a> {
+        let mut caption = None;
+        let mut attrs = vec![];
+
+        for (key, value) in code.info {
+            match key.as_str() {
+                "caption" if caption.is_none() => caption = Some(value),
+                _ => attrs.push((Cow::Owned(format!("data-{key}")), Cow::Owned(value))),
+            }
+        }
+
+        let pre = Node::Element(ElementNode {
+            tag: ElementTag::Pre,
+            children: vec![Node::Element(ElementNode {
+                tag: ElementTag::Code,
+                class: code
+                    .lang
+                    .map(|lang| Cow::Owned(format!("language-{lang}")))
+                    .into_iter()
+                    .collect(),
+                attrs,
+                children: match self.code_block_wrapper {
+                    true => Self::code_lines(code.text),
+                    false => vec![Node::Text(TextNode { text: code.text })],
+                },
+                ..Default::default()
+            })],
+            ..Default::default()
+        });
+
+        let pre = match self.code_block_wrapper {
+            true => {
+                let button = Node::Element(ElementNode {
+                    tag: ElementTag::Button,
+                    class: vec![Cow::Borrowed("copy-button")],
+                    attrs: vec![(Cow::Borrowed("type"), Cow::Borrowed("button"))],
+                    ..Default::default()
+                });
+
+                Node::Element(ElementNode {
+                    tag: ElementTag::Div,
+                    class: vec![Cow::Borrowed("code-block")],
+                    children: self
+                        .copy_button
+                        .then_some(button)
+                        .into_iter()
+                        .chain([pre])
+                        .collect(),
+                    ..Default::default()
+                })
+            }
+            false => pre,
+        };
+
+        let Some(caption) = caption else {
+            return pre;
+        };
+
+        let caption = Node::Element(ElementNode {
+            tag: ElementTag::Figcaption,
+            children: vec![self.text(self.normalize(Cow::Owned(caption)))],
+            ..Default::default()
+        });
+
+        Node::Element(ElementNode {
+            tag: ElementTag::Figure,
+            children: vec![pre, caption],
+            ..Default::default()
+        })
+    }
+
+    /// Split code into a `<span class="line">` for each line, with the
+    /// newlines between them.
+    fn code_lines(text: Cow<'_, str>) -> Vec<Node<'_>> {
+        let slice = |range: Range<usize>| -> Node<'_> {
+            Node::Text(TextNode {
+                text: match &text {
+                    Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+                    Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+                },
+            })
+        };
+
+        let mut nodes = vec![];
+        let mut start = 0;
+
+        while start < text.len() {
+            let end = text[start..]
+                .find('\n')
+                .map_or(text.len(), |index| start + index);
+
+            nodes.push(Node::Element(ElementNode {
+                tag: ElementTag::Span,
+                class: vec![Cow::Borrowed("line")],
+                children: vec![slice(start..end)],
+                ..Default::default()
+            }));
+
+            if end < text.len() {
+                nodes.push(Node::Text(TextNode {
+                    text: Cow::Borrowed("\n"),
+                }));
+            }
+
+            start = end + 1;
+        }
+
+        nodes
+    }
+
+    /// Make a blockquote of the transformed blocks, in a figure if it has a
+    /// caption.
+    fn blockquote<'a>(&self, children: Vec<Node<'a>>, caption: Option<InlineTree<'a>>) -> Node<'a> {
+        let blockquote = Node::Element(ElementNode {
+            tag: ElementTag::Blockquote,
+            children,
+            ..Default::default()
+        });
+
+        let Some(caption) = caption else {
+            return blockquote;
+        };
+
+        let caption = Node::Element(ElementNode {
+            tag: ElementTag::Figcaption,
+            children: self.inline_tree(caption),
+            ..Default::default()
+        });
+
+        Node::Element(ElementNode {
+            tag: ElementTag::Figure,
+            children: vec![blockquote, caption],
+            ..Default::default()
+        })
+    }
+
+    fn inline_tree<'a>(&self, mut tree: InlineTree<'a>) -> Vec<Node<'a>> {
+        if self.line_wrap == LineWrap::Join {
+            join_lines(&mut tree, self.line_break_style);
+        }
+
+        tree.into_root()
+            .into_iter()
+            .map(|item| self.inline_item(item))
+            .collect()
+    }
+
+    fn inline_item<'a>(&self, item: InlineItem<'a>) -> Node<'a> {
+        match item {
+            InlineItem::Text(text) => {
+                let text = match &self.emoji {
+                    Some(lookup) => emoji(text, lookup.as_ref()),
+                    None => text,
+                };
+
+                let text = self.normalize(text);
+
+                if self.smart_punctuation {
+                    self.text(smart_punctuation(text))
+                } else {
+                    self.text(text)
+                }
+            }
+            InlineItem::Escaped(text) => self.text(self.normalize(text)),
+            InlineItem::Italic(tree) => self.italic(tree),
+            InlineItem::Strong(tree) => self.strong(tree),
+            InlineItem::Highlight(tree) => self.highlight(tree),
+            InlineItem::Link { url, title, tree } => Node::Element(self.link(url, title, tree)),
+            InlineItem::Span(tree) => Node::Element(ElementNode {
+                tag: ElementTag::Span,
+                children: self.inline_tree(tree),
+                ..Default::default()
+            }),
+            InlineItem::Attributed(attributes, item) => {
+                let mut node = self.inline_item(*item);
+
+                Self::set_attributes(&mut node, attributes);
+
+                node
+            }
+            InlineItem::WikiLink { target, label } => self.wiki_link(target, label),
+            InlineItem::Tag { kind, name } => self.tag(kind, name),
+            InlineItem::Break => self.r#break(),
+            InlineItem::SoftBreak => self.text("\n".into()),
+            InlineItem::Comment(text) => Node::Comment(CommentNode { text }),
+            // The note stays in place until the whole document is made, where
+            // it is numbered and moved to the end.
+            InlineItem::Footnote(tree) => Node::Element(ElementNode {
+                tag: ElementTag::Sup,
+                class: vec![FOOTNOTE_REF_CLASS.into()],
+                children: self.inline_tree(tree),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn text<'a>(&self, text: Cow<'a, str>) -> Node<'a> {
+        Node::Text(TextNode { text })
+    }
+
+    /// Normalize the text of the input to NFC if it is set.
+    fn normalize<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        #[cfg(feature = "nfc")]
+        if self.nfc {
+            return crate::unicode::nfc(text);
+        }
+
+        text
+    }
+
+    fn italic<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::Em,
+            children: self.inline_tree(tree),
+            ..Default::default()
+        })
+    }
+
+    fn strong<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::Strong,
+            children: self.inline_tree(tree),
+            ..Default::default()
+        })
+    }
+
+    fn highlight<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::Mark,
+            children: self.inline_tree(tree),
+            ..Default::default()
+        })
+    }
+
+    fn link<'a>(
+        &self,
+        url: Cow<'a, str>,
+        title: Option<Cow<'a, str>>,
+        tree: InlineTree<'a>,
+    ) -> ElementNode<'a> {
+        let mut attrs = vec![];
+
+        if let Some(title) = title {
+            attrs.push(("title".into(), title.into_owned().into()));
+        }
+
+        if self.is_external(&url) {
+            if let Some(rel) = &self.external_rel {
+                attrs.push(("rel".into(), rel.clone().into()));
+            }
+
+            if let Some(target) = &self.external_target {
+                attrs.push(("target".into(), target.clone().into()));
+            }
+        }
+
+        ElementNode {
+            tag: ElementTag::A,
+            href: Some(url.into_owned()),
+            attrs,
+            children: self.inline_tree(tree),
+            ..Default::default()
+        }
+    }
+
+    fn wiki_link<'a>(&self, target: Cow<'a, str>, label: Option<Cow<'a, str>>) -> Node<'a> {
+        let url = match &self.wiki_resolver {
+            Some(resolver) => resolver.resolve(&target),
+            None => Some(target.to_string()),
+        };
+
+        let label = self.text(label.unwrap_or(target));
+
+        let Some(url) = url else {
+            return label;
+        };
+
+        let mut element = self.link(url.into(), None, InlineTree { root: vec![] });
+
+        element.add_class("wiki-link");
+        element.children.push(label);
+
+        Node::Element(element)
+    }
+
+    fn tag<'a>(&self, kind: TagKind, name: Cow<'a, str>) -> Node<'a> {
+        let name = self.normalize(name);
+        let url = self
+            .tag_resolver
+            .as_ref()
+            .and_then(|resolver| resolver.resolve(kind, &name));
+
+        let text = self.text(format!("{}{name}", kind.marker()).into());
+
+        let mut element = match url {
+            Some(url) => self.link(url.into(), None, InlineTree { root: vec![] }),
+            None => ElementNode {
+                tag: ElementTag::Span,
+                ..Default::default()
+            },
+        };
+
+        element.add_class(match kind {
+            TagKind::Hashtag => "tag",
+            TagKind::Mention => "mention",
+        });
+        element.children.push(text);
+
+        Node::Element(element)
+    }
+
+    fn r#break<'a>(&self) -> Node<'a> {
+        Node::Element(ElementNode {
+            tag: ElementTag::Br,
+            ..Default::default()
+        })
+    }
+}
+
+/// Replace the line breaks in the tree which are not hard breaks with spaces.
+///
+/// With [`LineBreakStyle::HardBreak`], every line break is a
+/// [`InlineItem::Break`], and the hard ones are told by `\` or at least two
+/// spaces at the end of the line, which are removed.
+fn join_lines(tree: &mut InlineTree, style: LineBreakStyle) {
+    for index in 0..tree.root.len() {
+        match tree.root[index] {
+            InlineItem::SoftBreak => {}
+            InlineItem::Break if style == LineBreakStyle::HardBreak => {
+                let mut hard = false;
+
+                if let Some(InlineItem::Text(text)) =
+                    index.checked_sub(1).map(|index| &mut tree.root[index])
+                {
+                    let trimmed = text.trim_end_matches(' ');
+
+                    let len = match trimmed.strip_suffix('\\') {
+                        Some(rest) => {
+                            hard = true;
+                            rest.len()
+                        }
+                        None => {
+                            hard = text.len() - trimmed.len() >= 2;
+                            trimmed.len()
+                        }
+                    };
+
+                    match text {
+                        Cow::Borrowed(text) => *text = &text[..len],
+                        Cow::Owned(text) => text.truncate(len),
+                    }
+                }
+
+                if hard {
+                    continue;
+                }
+            }
+            _ => continue,
+        }
+
+        tree.root[index] = InlineItem::Text(" ".into());
+    }
+}
+
+/// Replace shortcodes between colons with the emoji of the lookup.
+///
+/// A colon which ends an unknown shortcode can start the next one, so
+/// `a:b:smile:` has the shortcode `smile`.
+fn emoji<'a>(text: Cow<'a, str>, lookup: &dyn EmojiLookup) -> Cow<'a, str> {
+    if !text.contains(':') {
+        return text;
+    }
+
+    let is_shortcode_char = |c: char| c.is_ascii_alphanumeric() || "_+-".contains(c);
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = &text[..];
+    let mut replaced = false;
+
+    while let Some(start) = rest.find(':') {
+        let after = &rest[start + 1..];
+        let len = after.find(|c| !is_shortcode_char(c)).unwrap_or(after.len());
+
+        let found = (len > 0 && after[len..].starts_with(':'))
+            .then(|| lookup.emoji(&after[..len]))
+            .flatten();
+
+        match found {
+            Some(emoji) => {
+                output.push_str(&rest[..start]);
+                output.push_str(&emoji);
+                rest = &after[len + 1..];
+                replaced = true;
+            }
+            None => {
+                output.push_str(&rest[..start + 1 + len]);
+                rest = &after[len..];
+            }
+        }
+    }
+
+    if !replaced {
+        return text;
+    }
+
+    output.push_str(rest);
+
+    Cow::Owned(output)
+}
+
+/// Convert quotes, dashes and ellipses into typographic characters.
+///
+/// A quote is an opening quote if it is at the start of the text or after a
+/// white space or an opening bracket, and it is followed by a non-space
+/// character.
+fn smart_punctuation(text: Cow<str>) -> Cow<str> {
+    if !text.contains(['"', '\'', '-', '.']) {
+        return text;
+    }
+
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len());
+    let mut index = 0;
+
+    while index < chars.len() {
+        let run = |c: char| chars[index..].iter().take_while(|&&x| x == c).count();
+
+        match chars[index] {
+            quote @ ('"' | '\'') => {
+                let after_space = match index.checked_sub(1) {
+                    Some(i) => chars[i].is_whitespace() || "([{".contains(chars[i]),
+                    None => true,
+                };
+                let opening =
+                    after_space && chars.get(index + 1).is_some_and(|c| !c.is_whitespace());
+
+                output.push(match (quote, opening) {
+                    ('"', true) => '\u{201c}',
+                    ('"', false) => '\u{201d}',
+                    (_, true) => '\u{2018}',
+                    (_, false) => '\u{2019}',
+                });
+                index += 1;
+            }
+            '-' if run('-') >= 2 => {
+                let len = run('-');
+
+                // `---` is an em dash and `--` is an en dash. Longer runs
+                // are split into em dashes first.
+                for _ in 0..len / 3 {
+                    output.push('\u{2014}');
+                }
+                match len % 3 {
+                    1 => output.push('-'),
+                    2 => output.push('\u{2013}'),
+                    _ => {}
+                }
+                index += len;
+            }
+            '.' if run('.') >= 3 => {
+                output.push('\u{2026}');
+                index += 3;
+            }
+            c => {
+                output.push(c);
+                index += 1;
+            }
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+/// Remove the last line of the blockquote if it starts with `-- ` or `— `,
+/// and return the rest of the line.
+fn take_citation<'a>(tree: &mut BlockTree<'a>) -> Option<InlineTree<'a>> {
+    // The paragraph can have attributes, such as its source position.
+    fn last_paragraph<'t, 'a>(tree: &'t mut BlockTree<'a>) -> Option<&'t mut InlineTree<'a>> {
+        match tree.root.last_mut()? {
+            BlockItem::Paragraph(paragraph) => Some(paragraph),
+            BlockItem::Attributed(_, item) => match item.as_mut() {
+                BlockItem::Paragraph(paragraph) => Some(paragraph),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    let paragraph = last_paragraph(tree)?;
+
+    let start = paragraph
+        .root
+        .iter()
+        .rposition(|item| matches!(item, InlineItem::Break | InlineItem::SoftBreak))
+        .map_or(0, |index| index + 1);
+
+    // A blockquote of only the citation is left as is.
+    if start == 0 && tree.root.len() == 1 {
+        return None;
+    }
+
+    let paragraph = last_paragraph(tree)?;
+
+    let Some(InlineItem::Text(text)) = paragraph.root.get_mut(start) else {
+        return None;
+    };
+
+    let prefix = ["-- ", "— "]
+        .into_iter()
+        .find(|prefix| text.starts_with(prefix))?;
+
+    *text = match text {
+        Cow::Borrowed(text) => Cow::Borrowed(text[prefix.len()..].trim_start()),
+        Cow::Owned(text) => Cow::Owned(text[prefix.len()..].trim_start().to_string()),
+    };
+
+    let caption = paragraph.root.split_off(start);
+
+    // Remove the break before the caption, and the paragraph if it is empty.
+    paragraph.root.pop();
+
+    if paragraph.root.is_empty() {
+        tree.root.pop();
+    }
+
+    Some(InlineTree { root: caption })
+}
+
+/// A block of [`Transformer::block_items`] whose children are being
+/// transformed.
+struct BlockFrame<'a> {
+    /// The child items left.
+    items: std::vec::IntoIter<BlockItem<'a>>,
+    /// The nodes of the child items transformed so far.
+    nodes: Vec<Node<'a>>,
+    /// How the nodes are put into the parent block. The root has none.
+    closing: Option<Closing<'a>>,
+}
+
+/// The enum to represent how the nodes of a closed block are put into its
+/// parent.
+enum Closing<'a> {
+    /// The nodes of an include are put in its place.
+    Include,
+    /// A blockquote with its caption and attributes.
+    BlockQuote(Option<InlineTree<'a>>, Vec<Attributes>),
+    /// A container with its names, which become classes, and attributes.
+    Container(Vec<String>, Vec<Attributes>),
+    /// An item of a list, with the items after it and the attributes of the
+    /// list.
+    ListItem(
+        ElementNode<'a>,
+        std::vec::IntoIter<ListItem<'a>>,
+        Vec<Attributes>,
+    ),
+}
+
+/// The definitions in a document, which are collected before it is
+/// transformed because they apply to the text before them too.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Definitions {
+    /// The abbreviations and their expansions, the longest first.
+    abbreviations: Vec<(String, String)>,
+}
+
+impl Definitions {
+    /// Add the definitions in the items and their descendants. The first
+    /// definition of a name is kept.
+    pub(crate) fn collect(&mut self, items: &[BlockItem]) {
+        let mut stack = vec![items.iter()];
+
+        while let Some(items) = stack.last_mut() {
+            let Some(item) = items.next() else {
+                stack.pop();
+                continue;
+            };
+
+            match item {
+                BlockItem::Abbreviation(abbreviation, expansion) => {
+                    if !self
+                        .abbreviations
+                        .iter()
+                        .any(|(name, _)| name == abbreviation)
+                    {
+                        self.abbreviations
+                            .push((abbreviation.to_string(), expansion.to_string()));
+                    }
+                }
+                BlockItem::BulletList(tree) | BlockItem::OrderedList(_, _, tree) => {
+                    // The items are pushed last first to be collected in
+                    // document order.
+                    for item in tree.root.iter().rev() {
+                        stack.push(item.children.iter());
+                    }
+                }
+                BlockItem::BlockQuote(tree)
+                | BlockItem::Container(_, tree)
+                | BlockItem::Include(_, tree) => stack.push(tree.root.iter()),
+                BlockItem::Attributed(_, item) => stack.push(std::slice::from_ref(&**item).iter()),
+                BlockItem::Paragraph(_)
+                | BlockItem::Headline(..)
+                | BlockItem::Comment(_)
+                | BlockItem::Table(_)
+                | BlockItem::CodeBlock(_) => {}
+            }
+        }
+
+        // The longest abbreviation is tried first, so `HTML5` wins over
+        // `HTML`.
+        self.abbreviations
+            .sort_by_key(|(abbreviation, _)| std::cmp::Reverse(abbreviation.len()));
+    }
+}
+
+/// Wrap the abbreviations in the text of the nodes and their descendants in
+/// `<abbr>` with the expansion as the title.
+fn abbreviate<'a>(nodes: &mut Vec<Node<'a>>, abbreviations: &[(String, String)]) {
+    abbreviate_text(nodes, abbreviations);
+
+    // The expansions are not abbreviated again, and code is kept as it is.
+    visit_elements_mut(nodes, |element| {
+        if matches!(element.tag, ElementTag::Abbr | ElementTag::Pre) {
+            return false;
+        }
+
+        abbreviate_text(&mut element.children, abbreviations);
+        true
+    });
+}
+
+/// Wrap the abbreviations in the text nodes of the nodes, not in their
+/// descendants.
+fn abbreviate_text<'a>(nodes: &mut Vec<Node<'a>>, abbreviations: &[(String, String)]) {
+    let mut index = 0;
+
+    while index < nodes.len() {
+        match &mut nodes[index] {
+            Node::Text(text) => {
+                let found = find_abbreviations(&text.text, abbreviations);
+
+                if !found.is_empty() {
+                    let text = std::mem::take(&mut text.text);
+                    let split = split_abbreviations(text, found);
+                    let len = split.len();
+
+                    nodes.splice(index..index + 1, split);
+                    index += len;
+                    continue;
+                }
+            }
+            Node::Element(_) | Node::Comment(_) => {}
+        }
+
+        index += 1;
+    }
+}
+
+/// Find the abbreviations which are whole words in the text, and their
+/// expansions.
+fn find_abbreviations<'d>(
+    text: &str,
+    abbreviations: &'d [(String, String)],
+) -> Vec<(Range<usize>, &'d str)> {
+    let is_word =
+        |c: Option<char>| matches!(c, Some(c) if c.is_alphanumeric() || c == '_' || is_mark(c));
+
+    let mut found = vec![];
+    let mut index = 0;
+
+    while let Some(c) = text[index..].chars().next() {
+        if !is_word(text[..index].chars().next_back()) {
+            let rest = &text[index..];
+
+            if let Some((abbreviation, expansion)) =
+                abbreviations.iter().find(|(abbreviation, _)| {
+                    rest.starts_with(abbreviation.as_str())
+                        && !is_word(rest[abbreviation.len()..].chars().next())
+                })
+            {
+                found.push((index..index + abbreviation.len(), expansion.as_str()));
+                index += abbreviation.len();
+                continue;
+            }
+        }
+
+        index += c.len_utf8();
+    }
+
+    found
+}
+
+/// Split the text into text nodes and `<abbr>` elements of the found
+/// abbreviations.
+fn split_abbreviations<'a>(text: Cow<'a, str>, found: Vec<(Range<usize>, &str)>) -> Vec<Node<'a>> {
+    let slice = |range: Range<usize>| -> Cow<'a, str> {
+        match &text {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+            Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+        }
+    };
+
+    let mut nodes = vec![];
+    let mut start = 0;
+
+    for (range, expansion) in found {
+        if start < range.start {
+            nodes.push(Node::Text(TextNode {
+                text: slice(start..range.start),
+            }));
+        }
+
+        start = range.end;
+
+        nodes.push(Node::Element(ElementNode {
+            tag: ElementTag::Abbr,
+            attrs: vec![("title".into(), expansion.to_string().into())],
+            children: vec![Node::Text(TextNode { text: slice(range) })],
+            ..Default::default()
+        }));
+    }
+
+    if start < text.len() {
+        nodes.push(Node::Text(TextNode {
+            text: slice(start..text.len()),
+        }));
+    }
+
+    nodes
+}
+
+/// The class of the numbers of
+/// [`Transformer::heading_numbering`].
+const HEADING_NUMBER_CLASS: &str = "heading-number";
+
+/// Prefix the headlines from the level in the nodes and in their sections
+/// with their numbers.
+fn number_headlines(
+    nodes: &mut [Node],
+    numbering: &Numbering,
+    start: u8,
+    counters: &mut LevelCounters,
+) {
+    for node in nodes {
+        let Node::Element(element) = node else {
+            continue;
+        };
+
+        match element.tag.get_headline_level() {
+            Some(level) if level >= start => {
+                let number = numbering.format(&counters.next(level));
+
+                element.children.splice(
+                    0..0,
+                    [
+                        Node::Element(ElementNode {
+                            tag: ElementTag::Span,
+                            class: vec![HEADING_NUMBER_CLASS.into()],
+                            children: vec![Node::Text(TextNode {
+                                text: number.into(),
+                            })],
+                            ..Default::default()
+                        }),
+                        Node::Text(TextNode { text: " ".into() }),
+                    ],
+                );
+            }
+            None if element.tag == ElementTag::Section => {
+                number_headlines(&mut element.children, numbering, start, counters);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The class of the references to inline footnotes.
+const FOOTNOTE_REF_CLASS: &str = "footnote-ref";
+
+/// Replace the inline footnotes in the nodes and their descendants with
+/// links to their numbers, and take their contents in document order.
+///
+/// A footnote in a footnote is numbered right after the outer one.
+fn take_footnotes<'a>(nodes: &mut [Node<'a>], footnotes: &mut Vec<Vec<Node<'a>>>) {
+    visit_elements_mut(nodes, |element| {
+        if element.tag != ElementTag::Sup
+            || !element
+                .class
+                .iter()
+                .any(|class| class == FOOTNOTE_REF_CLASS)
+        {
+            return true;
+        }
+
+        let number = footnotes.len() + 1;
+
+        let link = Node::Element(ElementNode {
+            tag: ElementTag::A,
+            id: Some(format!("fnref-{number}")),
+            href: Some(format!("#fn-{number}")),
+            children: vec![Node::Text(TextNode {
+                text: number.to_string().into(),
+            })],
+            ..Default::default()
+        });
+
+        let mut content = std::mem::replace(&mut element.children, vec![link]);
+
+        footnotes.push(vec![]);
+
+        // Only a footnote in a footnote recurses, which is bounded by the
+        // nesting of inline items.
+        take_footnotes(&mut content, footnotes);
+
+        footnotes[number - 1] = content;
+
+        false
+    });
+}
+
+/// Check if the node is a number of
+/// [`Transformer::heading_numbering`].
+pub(crate) fn is_heading_number(node: &Node) -> bool {
+    matches!(node, Node::Element(element)
+        if element.tag == ElementTag::Span
+            && element.class.iter().any(|class| class == HEADING_NUMBER_CLASS))
+}
+
+/// Add the class of the depth to blockquotes in the nodes and their
+/// descendants. `depth` is the number of blockquotes containing the nodes.
+fn add_quote_depths(nodes: &mut [Node]) {
+    // The nodes left at each level, and the number of blockquotes around them.
+    let mut stack = vec![(nodes.iter_mut(), 0)];
+
+    while let Some((nodes, depth)) = stack.last_mut() {
+        let depth = *depth;
+
+        let Some(node) = nodes.next() else {
+            stack.pop();
+            continue;
+        };
+
+        let Node::Element(element) = node else {
+            continue;
+        };
+
+        let depth = match element.tag {
+            ElementTag::Blockquote => {
+                element.add_class(format!("blockquote-depth-{}", depth + 1));
+                depth + 1
+            }
+            _ => depth,
+        };
+
+        stack.push((element.children.iter_mut(), depth));
+    }
+}
+
+/// Mark the direction of the text of blocks in the nodes and their
+/// descendants with `dir`.
+fn add_directions(nodes: &mut [Node], text_direction: TextDirection) {
+    if text_direction == TextDirection::Off {
+        return;
+    }
+
+    // The nodes left at each level, and whether the text around them is
+    // right-to-left.
+    let mut stack = vec![(nodes.iter_mut(), false)];
+
+    while let Some((nodes, rtl)) = stack.last_mut() {
+        let mut rtl = *rtl;
+
+        let Some(node) = nodes.next() else {
+            stack.pop();
+            continue;
+        };
+
+        let Node::Element(element) = node else {
+            continue;
+        };
+
+        let is_text_block = matches!(
+            element.tag,
+            ElementTag::P
+                | ElementTag::Li
+                | ElementTag::Th
+                | ElementTag::Td
+                | ElementTag::Figcaption
+        ) || element.tag.get_headline_level().is_some();
+
+        if let Some((_, dir)) = element.attrs.iter().find(|(key, _)| key == "dir") {
+            // An explicit direction is kept, and is the one around its
+            // descendants unless it is `auto`.
+            match dir.as_ref() {
+                "rtl" => rtl = true,
+                "ltr" => rtl = false,
+                _ => {}
+            }
+        } else if is_text_block {
+            match text_direction {
+                TextDirection::Auto => element.set_attr("dir", "auto"),
+                _ => {
+                    let text = get_text(&element.children);
+
+                    if let Some(is_rtl) = first_strong_rtl(&text).filter(|&is_rtl| is_rtl != rtl) {
+                        element.set_attr("dir", if is_rtl { "rtl" } else { "ltr" });
+                        rtl = is_rtl;
+                    }
+                }
+            }
+        }
+
+        stack.push((element.children.iter_mut(), rtl));
+    }
+}
+
+/// Get whether the first letter of the text is of a right-to-left script,
+/// or `None` if the text has no letters.
+fn first_strong_rtl(text: &str) -> Option<bool> {
+    text.chars().find(|c| c.is_alphabetic()).map(|c| {
+        matches!(
+            c,
+            // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan and Mandaic.
+            '\u{0590}'..='\u{08FF}'
+                // Their presentation forms.
+                | '\u{FB1D}'..='\u{FDFF}'
+                | '\u{FE70}'..='\u{FEFF}'
+                // Right-to-left scripts of the supplementary planes.
+                | '\u{10800}'..='\u{10FFF}'
+                | '\u{1E800}'..='\u{1EFFF}'
+        )
+    })
+}
+
+/// Check if the element is a paragraph of only `[TOC]` or `[[toc]]`.
+fn is_toc_placeholder(element: &ElementNode) -> bool {
+    element.tag == ElementTag::P && {
+        let text = get_text(&element.children);
+        let text = text.trim();
+
+        text.eq_ignore_ascii_case("[toc]") || text.eq_ignore_ascii_case("[[toc]]")
     }
+}
 
-    fn inline_item<'a>(&self, item: InlineItem<'a>) -> Node<'a> {
-        match item {
-            InlineItem::Text(text) => self.text(text),
-            InlineItem::Italic(tree) => self.italic(tree),
-            InlineItem::Strong(tree) => self.strong(tree),
-            InlineItem::Break => self.r#break(),
+/// Check if the nodes or their descendants have a TOC placeholder.
+pub(crate) fn has_toc_placeholder(nodes: &[Node]) -> bool {
+    let mut found = false;
+
+    walk(nodes, &mut |event| {
+        if let WalkEvent::Enter(element) = event {
+            found = found || is_toc_placeholder(element);
         }
-    }
+    });
 
-    fn text<'a>(&self, text: Cow<'a, str>) -> Node<'a> {
-        Node::Text(TextNode { text })
-    }
+    found
+}
 
-    fn italic<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Em,
-            children: self.inline_tree(tree),
-            ..Default::default()
-        })
-    }
+/// Replace the TOC placeholders in the nodes and their descendants with the
+/// table of contents.
+fn replace_toc_placeholder<'a>(nodes: &mut Vec<Node<'a>>, toc: &[Node<'a>]) {
+    fn replace<'a>(nodes: &mut Vec<Node<'a>>, toc: &[Node<'a>]) {
+        let mut index = 0;
 
-    fn strong<'a>(&self, tree: InlineTree<'a>) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Strong,
-            children: self.inline_tree(tree),
-            ..Default::default()
-        })
-    }
+        while index < nodes.len() {
+            if matches!(&nodes[index], Node::Element(element) if is_toc_placeholder(element)) {
+                nodes.splice(index..index + 1, toc.iter().cloned());
+                index += toc.len();
+                continue;
+            }
 
-    fn r#break<'a>(&self) -> Node<'a> {
-        Node::Element(ElementNode {
-            tag: ElementTag::Br,
-            ..Default::default()
-        })
+            index += 1;
+        }
     }
+
+    replace(nodes, toc);
+
+    visit_elements_mut(nodes, |element| {
+        replace(&mut element.children, toc);
+        true
+    });
 }
 
 #[cfg(test)]
@@ -256,7 +2723,7 @@ mod tests {
                                 root: vec![InlineItem::Text(Cow::Borrowed("World"))],
                             },
                             children: vec![
-                                BlockItem::OrderedList(ListTree {
+                                BlockItem::OrderedList(1, ListMarker::default(), ListTree {
                                     root: vec![
                                         ListItem {
                                             name: InlineTree {
@@ -389,4 +2856,591 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_smart_punctuation() {
+        let convert = |text: &str| smart_punctuation(Cow::Borrowed(text)).into_owned();
+
+        assert_eq!(
+            convert("\"a\" 'b' it's"),
+            "\u{201c}a\u{201d} \u{2018}b\u{2019} it\u{2019}s"
+        );
+        assert_eq!(convert("(\"a\")"), "(\u{201c}a\u{201d})");
+        assert_eq!(
+            convert("a - b -- c --- d ----"),
+            "a - b \u{2013} c \u{2014} d \u{2014}-"
+        );
+        assert_eq!(convert("a.. b.... c"), "a.. b\u{2026}. c");
+        assert!(matches!(
+            smart_punctuation(Cow::Borrowed("plain")),
+            Cow::Borrowed(_)
+        ));
+
+        let markdown =
+            crate::Markdown::default().transformer(Transformer::new().smart_punctuation(true));
+
+        assert_eq!(
+            markdown.execute("*\"a*\" a\\...b \\---"),
+            "<p><em>\u{201c}a</em>\u{201d} a...b -\u{2013}</p>"
+        );
+    }
+
+    #[test]
+    fn test_line_wrap() {
+        use crate::layer::parser::Parser;
+
+        let input = "a  \nb\\\nc \n*d*\ne\\ \n\\\nf";
+
+        let markdown =
+            crate::Markdown::default().transformer(Transformer::new().line_wrap(LineWrap::Join));
+
+        assert_eq!(
+            markdown.execute(input),
+            "<p>a<br>b<br>c <em>d</em> e<br><br>f</p>"
+        );
+
+        let markdown = markdown
+            .parser(Parser::new().line_break_style(LineBreakStyle::SoftBreak))
+            .transformer(
+                Transformer::new()
+                    .line_wrap(LineWrap::Join)
+                    .line_break_style(LineBreakStyle::SoftBreak),
+            );
+
+        assert_eq!(
+            markdown.execute(input),
+            "<p>a<br>b<br>c <em>d</em> e<br><br>f</p>"
+        );
+
+        let markdown = markdown.transformer(Transformer::new());
+
+        assert_eq!(markdown.execute("a\nb"), "<p>a\nb</p>");
+    }
+
+    #[test]
+    fn test_section() {
+        let markdown = crate::Markdown::default().transformer(Transformer::new().section(true));
+
+        assert_eq!(
+            markdown.execute("### A\n\n# B\n\n> ## C\n\n#### D\n\n## E"),
+            concat![
+                "<section><h3>A</h3></section>",
+                "<section><h1>B</h1><blockquote><h2>C</h2></blockquote>",
+                "<section><h4>D</h4></section>",
+                "<section><h2>E</h2></section></section>",
+            ]
+        );
+        assert_eq!(markdown.execute("Text"), "<p>Text</p>");
+    }
+
+    #[test]
+    fn test_heading_ids() {
+        let markdown = crate::Markdown::default().transformer(
+            Transformer::new()
+                .section(true)
+                .heading_ids(true)
+                .slugifier(|text| text.to_uppercase()),
+        );
+
+        let input = "# a\n\n> # b\n\n## a\n\n#### c";
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<section><h1 id=\"A\">a</h1><blockquote><h1>b</h1></blockquote>",
+                "<section><h2 id=\"A-1\">a</h2><section><h4 id=\"C\">c</h4></section>",
+                "</section></section>",
+            ]
+        );
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(markdown.execute_parallel(input), markdown.execute(input));
+    }
+
+    #[test]
+    fn test_heading_anchor() {
+        let markdown = crate::Markdown::default()
+            .transformer(Transformer::new().heading_anchor(AnchorPosition::Before));
+
+        let input = "# a\n\n> ## b {#b}\n\n## c {#x}";
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<h1 id=\"a\"><a class=\"anchor\" href=\"#a\">¶</a>a</h1>",
+                "<blockquote><h2 id=\"b\"><a class=\"anchor\" href=\"#b\">¶</a>b</h2></blockquote>",
+                "<h2 id=\"x\"><a class=\"anchor\" href=\"#x\">¶</a>c</h2>",
+            ]
+        );
+
+        let (_, toc) = markdown.execute_with_toc(input);
+
+        assert_eq!(
+            toc,
+            "<ul><li><a href=\"#a\">a</a><ul><li><a href=\"#x\">c</a></li></ul></li></ul>"
+        );
+
+        assert_eq!("after".parse(), Ok(AnchorPosition::After));
+        assert_eq!(AnchorPosition::Before.to_string(), "before");
+    }
+
+    #[test]
+    fn test_toc_placeholder() {
+        let markdown = crate::Markdown::default().transformer(
+            Transformer::new()
+                .section(true)
+                .heading_anchor(AnchorPosition::After)
+                .toc_placeholder(TocMaker::default()),
+        );
+
+        let input = "# A\n\n[[TOC]]\n\n## B\n\n> [toc]\n\n[TOC] here";
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<section><h1 id=\"a\">A<a class=\"anchor\" href=\"#a\">¶</a></h1>",
+                "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li></ul>",
+                "<section><h2 id=\"b\">B<a class=\"anchor\" href=\"#b\">¶</a></h2>",
+                "<blockquote><ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li></ul></blockquote>",
+                "<p>[TOC] here</p></section></section>",
+            ]
+        );
+
+        assert_eq!(crate::Markdown::default().execute("[TOC]"), "<p>[TOC]</p>");
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(markdown.execute_parallel(input), markdown.execute(input));
+    }
+
+    #[test]
+    fn test_emoji() {
+        let lookup = |shortcode: &str| match shortcode {
+            "smile" => Some("😄".to_string()),
+            "+1" => Some("👍".to_string()),
+            _ => None,
+        };
+
+        let markdown = crate::Markdown::default()
+            .transformer(Transformer::new().emoji(lookup).smart_punctuation(true));
+
+        assert_eq!(
+            markdown.execute("a:b:smile: ::+1:: \\:smile: :smile -- :+1:"),
+            "<p>a:b😄 :👍: :smile: :smile \u{2013} 👍</p>"
+        );
+
+        assert_eq!(emoji(":x:".into(), &lookup), Cow::<str>::Borrowed(":x:"));
+    }
+
+    #[test]
+    fn test_tag_attributes() {
+        let markdown = crate::Markdown::default().transformer(
+            Transformer::new()
+                .section(true)
+                .class_for(ElementTag::Section, "slide")
+                .class_for(ElementTag::Li, "item")
+                .class_for(ElementTag::Li, " a  b ")
+                .attr_for(ElementTag::Em, "data-x", "1"),
+        );
+
+        assert_eq!(
+            markdown.execute("# A\n\n- *x*\n  - y"),
+            concat![
+                "<section class=\"slide\"><h1>A</h1><ul>",
+                "<li class=\"item a b\"><em data-x=\"1\">x</em>",
+                "<ul><li class=\"item a b\">y</li></ul></li>",
+                "</ul></section>",
+            ]
+        );
+
+        let markdown = crate::Markdown::default().transformer(
+            Transformer::new()
+                .class_for(ElementTag::H1, "x y")
+                .attr_for(ElementTag::H1, "role", "a")
+                .attr_for(ElementTag::H1, "role", "x")
+                .attr_for(ElementTag::H1, "id", "z"),
+        );
+
+        assert_eq!(
+            markdown.execute("# A"),
+            "<h1 class=\"x y\" id=\"z\" role=\"x\">A</h1>"
+        );
+        assert_eq!(
+            markdown.execute("# A {#b .y role=y}"),
+            "<h1 class=\"y x\" id=\"b\" role=\"y\">A</h1>"
+        );
+    }
+
+    #[test]
+    fn test_external_links() {
+        let transformer = Transformer::new()
+            .external_rel("nofollow")
+            .internal_host("Example.com");
+
+        for (url, external) in [
+            ("https://rust-lang.org", true),
+            ("HTTP://user@rust-lang.org:8080/a?b#c", true),
+            ("//cdn.rust-lang.org/x", true),
+            ("https://example.com/x", false),
+            ("https://EXAMPLE.com:443", false),
+            ("https://sub.example.com", true),
+            ("/docs/a.html", false),
+            ("docs/a:b", false),
+            ("#top", false),
+            ("mailto:a@rust-lang.org", false),
+            ("https:///x", false),
+        ] {
+            assert_eq!(transformer.is_external(url), external, "{url}");
+        }
+
+        let markdown = crate::Markdown::default().transformer(
+            transformer
+                .external_target("_blank")
+                .attr_for(ElementTag::A, "rel", "me"),
+        );
+
+        assert_eq!(
+            markdown.execute("[*a*](//x.org \"T\") [b](/b)"),
+            concat![
+                "<p><a href=\"//x.org\" title=\"T\" rel=\"nofollow\" target=\"_blank\"><em>a</em></a> ",
+                "<a href=\"/b\" rel=\"me\">b</a></p>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_start() {
+        let markdown = crate::Markdown::default();
+
+        assert_eq!(
+            markdown.execute("2. a\n  05. b\n  6. c\n3. d\n\nf\n\n1. e"),
+            concat![
+                "<ol start=\"2\"><li>a<ol start=\"5\"><li>b</li><li>c</li></ol></li><li>d</li></ol>",
+                "<p>f</p><ol><li>e</li></ol>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quote_citation() {
+        let markdown = crate::Markdown::default().transformer(
+            Transformer::new()
+                .quote_citation(true)
+                .quote_depth_class(true),
+        );
+
+        assert_eq!(
+            markdown.execute("> a\n> b\n>\n> — **C**, 2020\n\n> -- only"),
+            concat![
+                "<figure><blockquote class=\"blockquote-depth-1\"><p>a<br>b</p></blockquote>",
+                "<figcaption><strong>C</strong>, 2020</figcaption></figure>",
+                "<blockquote class=\"blockquote-depth-1\"><p>-- only</p></blockquote>",
+            ]
+        );
+
+        assert_eq!(
+            markdown.execute("> a\n>\n> > b\n> > -- B\n>\n> -- A"),
+            concat![
+                "<figure><blockquote class=\"blockquote-depth-1\"><p>a</p>",
+                "<figure><blockquote class=\"blockquote-depth-2\"><p>b</p></blockquote>",
+                "<figcaption>B</figcaption></figure></blockquote>",
+                "<figcaption>A</figcaption></figure>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        use crate::layer::stringifier::Stringifier;
+
+        const DEPTH: usize = 100_000;
+
+        let deep = || {
+            let mut item = BlockItem::Paragraph(InlineTree {
+                root: vec![InlineItem::Text(Cow::Borrowed("a"))],
+            });
+
+            for depth in 0..DEPTH {
+                item = match depth % 2 {
+                    0 => BlockItem::BlockQuote(BlockTree { root: vec![item] }),
+                    _ => BlockItem::BulletList(ListTree {
+                        root: vec![ListItem {
+                            name: InlineTree { root: vec![] },
+                            children: vec![item],
+                        }],
+                    }),
+                };
+            }
+
+            MarkdownTree {
+                root: BlockTree { root: vec![item] },
+            }
+        };
+
+        // Dropping the trees does not recurse.
+        drop(deep());
+
+        let tree = deep();
+
+        let document = Transformer::new()
+            .quote_depth_class(true)
+            .class_for(ElementTag::P, "text")
+            .transform(tree);
+
+        let mut html = String::new();
+
+        Stringifier::new().stringify_into(&document, &mut html);
+
+        assert_eq!(document.depth(), DEPTH / 2 * 3 + 2);
+        assert!(html.starts_with("<ul><li><blockquote class=\"blockquote-depth-1\">"));
+        assert!(
+            html.contains("<blockquote class=\"blockquote-depth-50000\"><p class=\"text\">a</p>")
+        );
+    }
+
+    #[test]
+    fn test_container() {
+        let paragraph = |text| {
+            BlockItem::Paragraph(InlineTree {
+                root: vec![InlineItem::Text(Cow::Borrowed(text))],
+            })
+        };
+
+        let tree = MarkdownTree {
+            root: BlockTree {
+                root: vec![
+                    BlockItem::Container(
+                        vec!["note".to_string(), "wide".to_string()],
+                        BlockTree {
+                            root: vec![
+                                paragraph("a"),
+                                BlockItem::Container(vec![], BlockTree { root: vec![] }),
+                            ],
+                        },
+                    ),
+                    paragraph("b"),
+                ],
+            },
+        };
+
+        let document = Transformer::new().transform(tree);
+
+        assert_eq!(
+            crate::layer::stringifier::Stringifier::new().stringify(document),
+            "<div class=\"note wide\"><p>a</p><div></div></div><p>b</p>"
+        );
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let markdown = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().inline_footnotes(true))
+            .transformer(Transformer::new().section(true));
+
+        assert_eq!(
+            markdown.execute("# A\n\na^[b^[c]]\n\n- d^[e]"),
+            concat![
+                "<section><h1>A</h1><p>a<sup class=\"footnote-ref\">",
+                "<a id=\"fnref-1\" href=\"#fn-1\">1</a></sup></p>",
+                "<ul><li>d<sup class=\"footnote-ref\">",
+                "<a id=\"fnref-3\" href=\"#fn-3\">3</a></sup></li></ul></section>",
+                "<section class=\"footnotes\"><ol>",
+                "<li id=\"fn-1\">b<sup class=\"footnote-ref\">",
+                "<a id=\"fnref-2\" href=\"#fn-2\">2</a></sup> ",
+                "<a class=\"footnote-backref\" href=\"#fnref-1\">↩</a></li>",
+                "<li id=\"fn-2\">c <a class=\"footnote-backref\" href=\"#fnref-2\">↩</a></li>",
+                "<li id=\"fn-3\">e <a class=\"footnote-backref\" href=\"#fnref-3\">↩</a></li>",
+                "</ol></section>",
+            ]
+        );
+
+        // The headline of the footnotes is not numbered, but has an id.
+        let markdown = markdown.transformer(
+            Transformer::new()
+                .heading_numbering(Numbering::default())
+                .heading_ids(true)
+                .footnotes_heading(ElementTag::H2),
+        );
+
+        assert_eq!(
+            markdown.execute("# A\n\na^[b]"),
+            concat![
+                "<h1 id=\"a\"><span class=\"heading-number\">1</span> A</h1>",
+                "<p>a<sup class=\"footnote-ref\">",
+                "<a id=\"fnref-1\" href=\"#fn-1\">1</a></sup></p>",
+                "<section class=\"footnotes\"><h2 id=\"footnotes\">Footnotes</h2><ol>",
+                "<li id=\"fn-1\">b <a class=\"footnote-backref\" href=\"#fnref-1\">↩</a></li>",
+                "</ol></section>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_direction() {
+        let markdown = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().inline_footnotes(true))
+            .transformer(Transformer::new().text_direction(TextDirection::Detect));
+
+        assert_eq!(
+            markdown.execute("# مرحبا\n\n> 1 a\n\nשלום^[עולם]\n{dir=ltr}\n\n- a\n  - ב\n\n..."),
+            concat![
+                "<h1 dir=\"rtl\">مرحبا</h1><blockquote><p>1 a</p></blockquote>",
+                "<p dir=\"ltr\">שלום<sup class=\"footnote-ref\"><a id=\"fnref-1\" href=\"#fn-1\">1</a>",
+                "</sup></p><ul><li>a<ul><li dir=\"rtl\">ב</li></ul></li></ul><p>...</p>",
+                "<section class=\"footnotes\"><ol><li id=\"fn-1\" dir=\"rtl\">עולם ",
+                "<a class=\"footnote-backref\" href=\"#fnref-1\">↩</a></li></ol></section>",
+            ]
+        );
+
+        let markdown = markdown.transformer(Transformer::new().text_direction(TextDirection::Auto));
+
+        assert_eq!(
+            markdown.execute("# a\n\n> b"),
+            "<h1 dir=\"auto\">a</h1><blockquote><p dir=\"auto\">b</p></blockquote>"
+        );
+        assert_eq!("detect".parse(), Ok(TextDirection::Detect));
+        assert_eq!(TextDirection::Auto.to_string(), "auto");
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn test_nfc() {
+        let markdown = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().hashtags(true))
+            .transformer(Transformer::new().heading_ids(true).nfc(true));
+
+        assert_eq!(
+            markdown.execute("# Cafe\u{301}\n\n#cafe\u{301} *e*\u{301} 日本 😀"),
+            concat![
+                "<h1 id=\"caf\u{e9}\">Caf\u{e9}</h1><p><span class=\"tag\">#caf\u{e9}</span> ",
+                "<em>e</em>\u{301} 日本 😀</p>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let markdown = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().abbreviations(true));
+
+        let input = concat![
+            "# HTML5\n\n",
+            "- HTML and [HTML](/) in HTMLX, *HTML*\n",
+            "  *[HTML]: HyperText \"Markup\" Language\n\n",
+            "*[HTML5]: Version 5\n",
+            "*[HTML]: Ignored\n",
+            "*[]: empty\n",
+        ];
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<h1><abbr title=\"Version 5\">HTML5</abbr></h1><ul><li>",
+                "<abbr title=\"HyperText &quot;Markup&quot; Language\">HTML</abbr> and ",
+                "<a href=\"/\"><abbr title=\"HyperText &quot;Markup&quot; Language\">HTML</abbr></a> ",
+                "in HTMLX, <em><abbr title=\"HyperText &quot;Markup&quot; Language\">HTML</abbr></em>",
+                "</li></ul><p>*[]: empty</p>",
+            ]
+        );
+        assert_eq!(
+            markdown.reformat("*[HTML]:  Markup\nHTML"),
+            "*[HTML]: Markup\n\nHTML\n"
+        );
+        assert_eq!(
+            markdown.execute("HTML"),
+            "<p>HTML</p>",
+            "text without definitions is kept"
+        );
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(markdown.execute_parallel(input), markdown.execute(input));
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let markdown = crate::Markdown::default()
+            .parser(crate::layer::parser::Parser::new().abbreviations(true));
+
+        let input = concat![
+            "```rust,linenos,hl_lines=\"2-3\" caption=\"*HTML* code\"\n",
+            "let a = \"<b>\"; // HTML\n",
+            "```\n",
+            "{#main}\n\n",
+            "~~~\n",
+            "HTML\n",
+            "~~~\n\n",
+            "*[HTML]: Markup\n",
+        ];
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<figure id=\"main\"><pre><code class=\"language-rust\" data-linenos=\"\" ",
+                "data-hl_lines=\"2-3\">let a = &quot;&lt;b&gt;&quot;; // HTML\n</code></pre>",
+                "<figcaption>*<abbr title=\"Markup\">HTML</abbr>* code</figcaption></figure>",
+                "<pre><code>HTML\n</code></pre>",
+            ]
+        );
+
+        let markdown =
+            markdown.figure_list_maker(crate::layer::figures::FigureListMaker::default());
+
+        let (_, list) = markdown.execute_with_figures(input);
+
+        assert_eq!(list, "<ol><li><a href=\"#main\">*HTML* code</a></li></ol>");
+
+        let markdown =
+            crate::Markdown::default().transformer(Transformer::new().code_block_wrapper(true));
+
+        assert_eq!(
+            markdown.execute("```a caption=b\nx\n\ny\n```\n{#c}\n\n```\n```"),
+            concat![
+                "<figure id=\"c\"><div class=\"code-block\"><pre><code class=\"language-a\">",
+                "<span class=\"line\">x</span>\n<span class=\"line\"></span>\n",
+                "<span class=\"line\">y</span>\n</code></pre></div><figcaption>b</figcaption></figure>",
+                "<div class=\"code-block\"><pre><code></code></pre></div>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heading_numbering() {
+        let transformer = Transformer::new()
+            .heading_numbering(Numbering::default().suffix("."))
+            .section(true)
+            .heading_ids(true);
+        let markdown = crate::Markdown::default().transformer(transformer.clone());
+
+        let input = "## A\n\n#### B\n\n### C\n\n- ## D\n\n# E {#e}\n\n## A";
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<section><h2 id=\"a\"><span class=\"heading-number\">1.</span> A</h2>",
+                "<section><h4 id=\"b\"><span class=\"heading-number\">1.1.</span> B</h4></section>",
+                "<section><h3 id=\"c\"><span class=\"heading-number\">1.2.</span> C</h3>",
+                "<ul><li>## D</li></ul></section></section>",
+                "<section><h1 id=\"e\"><span class=\"heading-number\">2.</span> E</h1>",
+                "<section><h2 id=\"a-1\"><span class=\"heading-number\">2.1.</span> A</h2>",
+                "</section></section>",
+            ]
+        );
+
+        let markdown = crate::Markdown::default().transformer(
+            transformer
+                .heading_anchor(AnchorPosition::Before)
+                .numbering_start(3),
+        );
+
+        assert_eq!(
+            markdown.execute("## A\n\n### B"),
+            concat![
+                "<section><h2 id=\"a\"><a class=\"anchor\" href=\"#a\">¶</a>A</h2>",
+                "<section><h3 id=\"b\"><a class=\"anchor\" href=\"#b\">¶</a>",
+                "<span class=\"heading-number\">1.</span> B</h3></section></section>",
+            ]
+        );
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(markdown.execute_parallel(input), markdown.execute(input));
+    }
 }
@@ -16,13 +16,126 @@
 //!
 //! assert_eq!(html, "<h1>Hello, world!</h1><p>This is a paragraph.</p>");
 //! ```
+//!
+//! # Features
+//!
+//! - `cli`: Builds the `note-mark` binary, which converts files or the standard
+//!   input to HTML. Run `note-mark --help` for its options.
+//! - `emoji`: Provides a table of emoji shortcodes in the `emoji` module.
+//! - `nfc`: Provides [`Transformer::nfc`](layer::transformer::Transformer::nfc),
+//!   which normalizes text to NFC with unicode-normalization.
+//! - `parallel`: Provides [`Markdown::execute_parallel`], which parses top level
+//!   blocks in parallel with rayon, and
+//!   [`MarkdownBatch::render_parallel`](batch::MarkdownBatch::render_parallel).
+//! - `theme`: Provides a default stylesheet in the `theme` module.
+//! - `serde`: Implements `Serialize` and `Deserialize` for the options in the
+//!   `options` module, so they can be loaded from configuration files.
+//! - `wasm`: Provides bindings for JavaScript in the `wasm` module.
+//!
+//! # Performance
+//!
+//! Parsing takes time linear in the length of the input, except that the
+//! tokens of lists and blockquotes are copied once per nesting level. So the
+//! time and the peak memory are `O(n * d)` for nesting depth `d`. A list of
+//! 1 MB whose lines are each indented one level deeper takes about 750 MB
+//! with [`Markdown::untrusted`]. Lower
+//! [`Parser::max_block_nesting`](layer::parser::Parser::max_block_nesting)
+//! together with [`Parser::max_input_len`](layer::parser::Parser::max_input_len)
+//! where memory matters.
+//!
+//! The parser and the transformation of inline elements are recursive. Their
+//! depth is bounded by [`Parser::max_nesting`](layer::parser::Parser::max_nesting),
+//! which is 128 by default, so input such as thousands of `>` or nested
+//! links is rendered within the stack of a spawned thread. Raising the limit
+//! or removing it with `None` lets input nested deeply enough overflow the
+//! stack. Lists, blockquotes and the HTML document are transformed,
+//! stringified and dropped without recursion.
+//!
+//! The `parse` benchmark renders long prose, deep lists and pathological
+//! markup such as `****` and `[a](` at several sizes:
+//!
+//! ```sh
+//! cargo bench --bench parse
+//! ```
+//!
+//! # Thread safety
+//!
+//! [`Markdown`] and the configurations of all layers are `Send + Sync`, so
+//! one instance can serve the threads of a web server behind an `Arc` or a
+//! shared reference. Resolvers and other hooks must be `Send + Sync` too.
 
+pub mod batch;
+pub mod cache;
+#[cfg(feature = "emoji")]
+pub mod emoji;
+pub mod error;
+pub mod incremental;
 pub mod layer;
 pub mod model;
+pub mod options;
 pub mod prelude;
+#[cfg(feature = "theme")]
+pub mod theme;
+mod unicode;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::Error;
+
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
 
+use batch::MarkdownBatch;
+use error::{ConfigWarning, Diagnostic, DiagnosticKind};
+use incremental::IncrementalDocument;
+use layer::parser::config::{Comments, HeadlineEnding, IndentStyle};
 use layer::{
-    lexer::lex, parser::Parser, stringifier::Stringifier, toc::TocMaker, transformer::Transformer,
+    figures::FigureListMaker,
+    formatter::MarkdownStringifier,
+    lexer::lex,
+    metadata::{
+        config::{ExcerptRule, Title},
+        FrontMatter, MetadataExtractor,
+    },
+    parser::Parser,
+    stringifier::{Stringifier, TextStringifier},
+    toc::{Heading, TocMaker},
+    transformer::{config::AnchorPosition, has_toc_placeholder, Transformer},
+};
+use model::{
+    event::Event,
+    html::{DocumentNode, Node},
+    link::LinkRef,
+    messages::Messages,
+    stats::RenderStats,
+    token::Token,
+    tree::MarkdownTree,
+};
+use options::{FrontMatterHook, MarkdownOptions, Profile};
+
+// The configurations are shared by threads, so hooks are kept as
+// `Arc<dyn Trait>` of a `Send + Sync` trait or as `fn` pointers. This fails
+// to compile if a configuration stops being `Send + Sync`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<Markdown>();
+    assert_send_sync::<MarkdownOptions>();
+    assert_send_sync::<Parser>();
+    assert_send_sync::<Transformer>();
+    assert_send_sync::<Stringifier>();
+    assert_send_sync::<TextStringifier>();
+    assert_send_sync::<MarkdownStringifier>();
+    assert_send_sync::<TocMaker>();
+    assert_send_sync::<FigureListMaker>();
+    assert_send_sync::<MetadataExtractor>();
+    assert_send_sync::<Messages>();
+    assert_send_sync::<MarkdownBatch>();
+    assert_send_sync::<IncrementalDocument>();
+    assert_send_sync::<cache::RenderCache>();
+    assert_send_sync::<Error>();
 };
 
 /// Markdown parser and transformer.
@@ -46,8 +159,23 @@ pub struct Markdown {
     transformer: Transformer,
     /// Stringifier configuration.
     stringifier: Stringifier,
+    /// Plain text stringifier configuration.
+    text_stringifier: TextStringifier,
+    /// Markdown stringifier configuration.
+    markdown_stringifier: MarkdownStringifier,
     /// Table of contents maker configuration.
     toc_maker: TocMaker,
+    /// List of figures maker configuration.
+    figure_list_maker: FigureListMaker,
+    /// Metadata extractor configuration.
+    metadata: MetadataExtractor,
+    /// Messages for generated text, which override the messages of the
+    /// layers.
+    messages: Option<Messages>,
+    /// Whether front matter can override the options of its document.
+    front_matter_options: bool,
+    /// The function to change the options read from front matter.
+    front_matter_hook: Option<FrontMatterHook>,
 }
 
 impl Markdown {
@@ -56,6 +184,141 @@ impl Markdown {
         Self::default()
     }
 
+    /// Create a `Markdown` instance with safe settings for untrusted input.
+    ///
+    /// Text and attribute values are escaped, so HTML in the input is not
+    /// rendered. Attributes which can run scripts are dropped, and links are
+    /// limited to `http`, `https` and `mailto` URLs. Nesting is limited to 32
+    /// levels and the input is limited to 1 MiB, so deeply nested or huge
+    /// input cannot exhaust the stack or the memory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::untrusted();
+    ///
+    /// let html = markdown.execute("# <img src=x onerror=alert(1)>");
+    ///
+    /// assert_eq!(html, "<h1>&lt;img src=x onerror=alert(1)&gt;</h1>");
+    /// ```
+    pub fn untrusted() -> Self {
+        Self::default()
+            .parser(
+                Parser::new()
+                    .max_nesting(Some(32))
+                    .max_input_len(Some(1 << 20)),
+            )
+            .stringifier(
+                Stringifier::new()
+                    .escape(true)
+                    .sanitize(true)
+                    .url_schemes(&["http", "https", "mailto"]),
+            )
+    }
+
+    /// Create a `Markdown` instance with the settings of a profile, which
+    /// bundles the settings of all layers for a kind of content.
+    ///
+    /// Setting a layer afterwards replaces all of its settings, so single
+    /// settings are changed with [`MarkdownOptions::profile`] and
+    /// [`from_options`](Self::from_options).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{options::Profile, prelude::*};
+    ///
+    /// let markdown = Markdown::profile(Profile::Comments);
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("@ann <b>hi</b> [x](javascript:alert(1)) [y](https://a.example)"),
+    ///     concat![
+    ///         "<p><span class=\"mention\">@ann</span> &lt;b&gt;hi&lt;/b&gt; <a>x</a> ",
+    ///         "<a href=\"https://a.example\" rel=\"nofollow ugc noopener\">y</a></p>",
+    ///     ]
+    /// );
+    ///
+    /// let markdown = Markdown::profile(Profile::Notes);
+    ///
+    /// assert_eq!(
+    ///     markdown.execute("# Idea\n\n[[Inbox]] ==now== #todo"),
+    ///     concat![
+    ///         "<h1 id=\"idea\">Idea</h1><p><a class=\"wiki-link\" href=\"Inbox\">Inbox</a> ",
+    ///         "<mark>now</mark> <span class=\"tag\">#todo</span></p>",
+    ///     ]
+    /// );
+    /// ```
+    pub fn profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Comments => {
+                let markdown = Self::untrusted();
+                let parser = markdown
+                    .parser
+                    .clone()
+                    .mentions(true)
+                    .comments(Comments::Strip);
+
+                markdown
+                    .parser(parser)
+                    .transformer(Transformer::new().external_rel("nofollow ugc noopener"))
+            }
+            Profile::Notes => Self::default()
+                .parser(
+                    Parser::new()
+                        .front_matter(true)
+                        .wiki_links(true)
+                        .hashtags(true)
+                        .highlight(true)
+                        .abbreviations(true),
+                )
+                .transformer(Transformer::new().heading_ids(true).quote_citation(true)),
+            Profile::Docs => Self::default()
+                .parser(
+                    Parser::new()
+                        .front_matter(true)
+                        .fancy_lists(true)
+                        .highlight(true)
+                        .abbreviations(true)
+                        .comments(Comments::Strip),
+                )
+                .transformer(
+                    Transformer::new()
+                        .heading_anchor(AnchorPosition::After)
+                        .toc_placeholder(TocMaker::default())
+                        .smart_punctuation(true),
+                ),
+        }
+    }
+
+    /// Create a `Markdown` instance configured by the options.
+    ///
+    /// Options which are not set keep the default values, or the values of
+    /// the [`profile`](MarkdownOptions::profile) if it is set.
+    pub fn from_options(options: &MarkdownOptions) -> Self {
+        let base = options.profile.map(Self::profile).unwrap_or_default();
+
+        let toc_maker = options.toc.apply(base.toc_maker);
+
+        let mut transformer = options.transformer.apply(base.transformer);
+
+        if transformer.toc_placeholder.is_some() {
+            transformer.toc_placeholder = Some(toc_maker.clone());
+        }
+
+        let parser = options.parser.apply(base.parser);
+
+        transformer.line_break_style = parser.line_break_style;
+
+        Self::default()
+            .parser(parser)
+            .transformer(transformer)
+            .front_matter_options(options.front_matter_options.unwrap_or_default())
+            .stringifier(options.stringifier.apply(base.stringifier))
+            .toc_maker(toc_maker)
+    }
+
     /// Set the parser configuration.
     pub fn parser(mut self, parser: Parser) -> Self {
         self.parser = parser;
@@ -65,6 +328,7 @@ impl Markdown {
     /// Set the transformer configuration.
     pub fn transformer(mut self, transformer: Transformer) -> Self {
         self.transformer = transformer;
+        self.apply_messages();
         self
     }
 
@@ -74,122 +338,1736 @@ impl Markdown {
         self
     }
 
+    /// Set the plain text stringifier configuration.
+    pub fn text_stringifier(mut self, text_stringifier: TextStringifier) -> Self {
+        self.text_stringifier = text_stringifier;
+        self
+    }
+
+    /// Set the markdown stringifier configuration.
+    pub fn markdown_stringifier(mut self, markdown_stringifier: MarkdownStringifier) -> Self {
+        self.markdown_stringifier = markdown_stringifier;
+        self
+    }
+
     /// Set the table of contents maker configuration.
     pub fn toc_maker(mut self, toc_maker: TocMaker) -> Self {
         self.toc_maker = toc_maker;
         self
     }
+
+    /// Set the list of figures maker configuration.
+    pub fn figure_list_maker(mut self, figure_list_maker: FigureListMaker) -> Self {
+        self.figure_list_maker = figure_list_maker;
+        self
+    }
+
+    /// Set the metadata extractor configuration.
+    pub fn metadata(mut self, metadata: MetadataExtractor) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set the messages for generated text in all layers.
+    ///
+    /// These are used instead of the messages of each layer configuration,
+    /// regardless of the order of the builder calls.
+    pub fn messages(mut self, messages: Messages) -> Self {
+        self.messages = Some(messages);
+        self.apply_messages();
+        self
+    }
+
+    /// Put the messages into the transformer and its table of contents, which
+    /// are used by every method which renders.
+    fn apply_messages(&mut self) {
+        let Some(messages) = &self.messages else {
+            return;
+        };
+
+        self.transformer.messages = messages.clone();
+
+        if let Some(toc_maker) = &mut self.transformer.toc_placeholder {
+            toc_maker.messages = messages.clone();
+        }
+    }
+
+    /// Set whether front matter can override the options of its document,
+    /// such as `toc: true` or `highlight: false`.
+    ///
+    /// The keys are read by [`MarkdownOptions::from_front_matter`], changed
+    /// by the [`front_matter_hook`](Self::front_matter_hook) if it is set,
+    /// and applied over this configuration by
+    /// [`for_document`](Self::for_document). The methods which render a
+    /// document, such as [`execute`](Self::execute) and
+    /// [`execute_with_toc`](Self::execute_with_toc), and batches apply them.
+    /// Enable [`Parser::front_matter`] too so that the front matter is not
+    /// rendered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .parser(Parser::default().front_matter(true))
+    ///     .front_matter_options(true);
+    ///
+    /// let html = markdown.execute("---\nhighlight: true\n---\n==a==");
+    ///
+    /// assert_eq!(html, "<p><mark>a</mark></p>");
+    /// ```
+    pub fn front_matter_options(mut self, front_matter_options: bool) -> Self {
+        self.front_matter_options = front_matter_options;
+        self
+    }
+
+    /// Set the function to change the options read from front matter, such
+    /// as to veto some keys or to read keys of its own.
+    ///
+    /// It runs only if [`front_matter_options`](Self::front_matter_options)
+    /// is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default()
+    ///     .parser(Parser::default().front_matter(true))
+    ///     .front_matter_options(true)
+    ///     .front_matter_hook(|front_matter, options| {
+    ///         // Drafts never have a table of contents.
+    ///         if front_matter.get("draft") == Some("true") {
+    ///             options.transformer.toc_placeholder = None;
+    ///         }
+    ///         // A key of the site for typographic quotes.
+    ///         if front_matter.get("typography") == Some("fancy") {
+    ///             options.transformer.smart_punctuation = Some(true);
+    ///         }
+    ///     });
+    ///
+    /// let html = markdown.execute("---\ndraft: true\ntoc: true\ntypography: fancy\n---\n[TOC]\n\n\"a\"");
+    ///
+    /// assert_eq!(html, "<p>[TOC]</p><p>\u{201c}a\u{201d}</p>");
+    /// ```
+    pub fn front_matter_hook(mut self, hook: FrontMatterHook) -> Self {
+        self.front_matter_hook = Some(hook);
+        self
+    }
+
+    /// Get the configuration for the document, with the options in its front
+    /// matter applied if [`front_matter_options`](Self::front_matter_options)
+    /// is enabled.
+    ///
+    /// This is borrowed if the document changes no option. Entries which
+    /// cannot be parsed are skipped, and
+    /// [`validate_input`](Self::validate_input) reports them.
+    pub fn for_document(&self, input: &str) -> Cow<'_, Self> {
+        let Some(options) = self.document_options(input) else {
+            return Cow::Borrowed(self);
+        };
+
+        let toc_maker = options.toc.apply(self.toc_maker.clone());
+
+        // A placeholder which is already set keeps its own maker.
+        let placeholder = match &self.transformer.toc_placeholder {
+            Some(placeholder) => options.toc.apply(placeholder.clone()),
+            None => toc_maker.clone(),
+        };
+
+        let parser = options.parser.apply(self.parser.clone());
+        let mut transformer = options.transformer.apply(self.transformer.clone());
+
+        if transformer.toc_placeholder.is_some() {
+            transformer.toc_placeholder = Some(placeholder);
+        }
+
+        transformer.line_break_style = parser.line_break_style;
+
+        let mut markdown = Self {
+            parser,
+            transformer,
+            toc_maker,
+            // The options are applied, so the methods of the copy use it as
+            // it is.
+            front_matter_options: false,
+            ..self.clone()
+        };
+
+        // The placeholder may be made from the maker of this configuration.
+        markdown.apply_messages();
+
+        Cow::Owned(markdown)
+    }
+
+    /// Read the options of the document from its front matter, if they are
+    /// enabled and change anything.
+    fn document_options(&self, input: &str) -> Option<MarkdownOptions> {
+        if !self.front_matter_options {
+            return None;
+        }
+
+        let (front_matter, _) = FrontMatter::parse(input)?;
+
+        let (mut options, _) = MarkdownOptions::from_front_matter(&front_matter);
+
+        if let Some(hook) = self.front_matter_hook {
+            hook(&front_matter, &mut options);
+        }
+
+        (options != MarkdownOptions::default()).then_some(options)
+    }
 }
 
 impl Markdown {
-    /// Execute the markdown parser.
-    pub fn execute(&self, input: &str) -> String {
-        let tokens = lex(input);
-        let tree = self.parser.parse(input, tokens);
-        let document = self.transformer.transform(tree);
-        self.stringifier.stringify(document)
+    /// Check the configuration for conflicting or likely misconfigured
+    /// settings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// assert_eq!(Markdown::default().validate(), vec![]);
+    ///
+    /// let markdown = Markdown::default()
+    ///     .parser(Parser::default().list_indent_style(IndentStyle::Space(0)))
+    ///     .toc_maker(TocMaker::default().min_level(3).max_level(2));
+    ///
+    /// assert_eq!(
+    ///     markdown.validate(),
+    ///     vec![
+    ///         ConfigWarning::ZeroIndentWidth,
+    ///         ConfigWarning::TocLevelRange { min: 3, max: 2 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<ConfigWarning> {
+        let mut warnings = vec![];
+
+        if matches!(
+            (self.parser.list_indent_style, self.parser.tab_stop),
+            (IndentStyle::Space(0), _) | (IndentStyle::Both, 0)
+        ) {
+            warnings.push(ConfigWarning::ZeroIndentWidth);
+        }
+
+        let (min, max) = (self.toc_maker.min_level, self.toc_maker.max_level);
+
+        if min > max || min == 0 || max > 6 {
+            warnings.push(ConfigWarning::TocLevelRange { min, max });
+        }
+
+        if (self.stringifier.sanitize || self.stringifier.url_schemes.is_some())
+            && !self.stringifier.escape
+        {
+            warnings.push(ConfigWarning::SanitizeWithoutEscape);
+        }
+
+        if self.metadata.title_chain.is_empty() {
+            warnings.push(ConfigWarning::EmptyTitleChain);
+        }
+
+        if self.transformer.section && self.parser.headline_ending == HeadlineEnding::HardBreak {
+            warnings.push(ConfigWarning::SectionWithHardBreakHeadline);
+        }
+
+        warnings
     }
 
-    /// Execute the markdown parser and generate the table of contents.
+    /// Check the configuration like [`validate`](Self::validate), and also
+    /// check if it suits the input.
     ///
     /// # Example
     ///
     /// ```
     /// use note_mark::prelude::*;
     ///
-    /// let markdown = Markdown::default();
-    ///
-    /// let input = concat![
-    ///     "# Headline1-1\n\n",
-    ///     "# Headline1-2\n\n",
-    ///     "## Headline2-1\n\n",
-    ///     "## Headline2-2\n\n",
-    ///     "# Headline1-3\n\n",
-    /// ];
+    /// let markdown = Markdown::default().parser(Parser::default().list_indent_style(IndentStyle::Tab));
     ///
-    /// let (html, toc) = markdown.execute_with_toc(input);
+    /// let warnings = markdown.validate_input("- a\n\t- b\n  - c\n");
     ///
-    /// assert_eq!(toc, "<ul><li><a href=\"#Headline1-1\">Headline1-1</a></li><li><a href=\"#Headline1-2\">Headline1-2</a><ul><li><a href=\"#Headline2-1\">Headline2-1</a></li><li><a href=\"#Headline2-2\">Headline2-2</a></li></ul></li><li><a href=\"#Headline1-3\">Headline1-3</a></li></ul>");
+    /// assert_eq!(warnings, vec![ConfigWarning::IndentStyleMismatch { line: 3 }]);
     /// ```
-    /// ## Original output
+    pub fn validate_input(&self, input: &str) -> Vec<ConfigWarning> {
+        let mut warnings = self.validate();
+
+        let counted = |c: char| match self.parser.list_indent_style {
+            IndentStyle::Space(_) => c == ' ',
+            IndentStyle::Tab => c == '\t',
+            _ => c == ' ' || c == '\t',
+        };
+
+        let mismatch = input.lines().position(|line| {
+            let content = line.trim_start_matches([' ', '\t']);
+            let indent = &line[..line.len() - content.len()];
+
+            let digits = content.chars().take_while(char::is_ascii_digit).count();
+            let item = content.starts_with("- ")
+                || (digits > 0
+                    && [". ", ") "]
+                        .iter()
+                        .any(|marker| content[digits..].starts_with(marker)));
+
+            item && !indent.chars().all(counted)
+        });
+
+        if let Some(index) = mismatch {
+            warnings.push(ConfigWarning::IndentStyleMismatch { line: index + 1 });
+        }
+
+        if let Some((front_matter, _)) =
+            FrontMatter::parse(input).filter(|_| self.front_matter_options)
+        {
+            let (_, errors) = MarkdownOptions::from_front_matter(&front_matter);
+
+            warnings.extend(errors.into_iter().map(|(key, error)| {
+                ConfigWarning::InvalidFrontMatterOption {
+                    key: key.to_string(),
+                    error,
+                }
+            }));
+        }
+
+        warnings
+    }
+
+    /// Split the input into tokens, which the parser reads.
+    ///
+    /// This is the same as [`lex`], and is here for tools which build their
+    /// own parsers or linters on the tokens.
+    ///
+    /// # Example
     ///
-    /// ```html
-    /// <h1 id="Headline1-1">Headline1-1</h1>
-    /// <h1 id="Headline1-2">Headline1-2</h1>
-    /// <h2 id="Headline2-1">Headline2-1</h2>
-    /// <h2 id="Headline2-2">Headline2-2</h2>
-    /// <h1 id="Headline1-3">Headline1-3</h1>
     /// ```
+    /// use note_mark::prelude::*;
     ///
-    /// ## Toc output
+    /// let stars = Markdown::lex("**a** *b*")
+    ///     .filter(|token| token.kind == TokenKind::Star)
+    ///     .count();
     ///
-    /// ```html
-    /// <ul>
-    ///     <li><a href="#Headline1-1">Headline1-1</a></li>
-    ///     <li>
-    ///         <a href="#Headline1-2">Headline1-2</a>
-    ///         <ul>
-    ///             <li><a href="#Headline2-1">Headline2-1</a></li>
-    ///             <li><a href="#Headline2-2">Headline2-2</a></li>
-    ///         </ul>
-    ///     </li>
-    ///     <li><a href="#Headline1-3">Headline1-3</a></li>
-    /// </ul>
+    /// assert_eq!(stars, 6);
     /// ```
-    pub fn execute_with_toc(&self, input: &str) -> (String, String) {
+    pub fn lex(input: &str) -> impl Iterator<Item = Token> + '_ {
+        lex(input)
+    }
+
+    /// Execute the markdown parser.
+    ///
+    /// The input is anything which is a string, such as `&str` or `String`.
+    pub fn execute(&self, input: impl AsRef<str>) -> String {
+        let input = input.as_ref();
+
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute(input);
+        }
+
         let tokens = lex(input);
-        let tree = self.parser.parse(input, tokens);
-        let mut document = self.transformer.transform(tree);
+        let (tree, _) = self.parse_for_html(input, tokens);
+        let document = self.transformer.transform(tree);
+        self.stringifier.stringify(document)
+    }
 
-        let toc = self.toc_maker.make_toc(&mut document);
+    /// Parse the input to render it as HTML, giving blocks their source
+    /// positions if the stringifier writes them.
+    fn parse_for_html<'a>(
+        &self,
+        input: &'a str,
+        tokens: impl Iterator<Item = Token>,
+    ) -> (MarkdownTree<'a>, Vec<Diagnostic>) {
+        match self.stringifier.source_positions {
+            true => self.parser.parse_with_positions(input, tokens, 1),
+            false => self.parser.parse_with_diagnostics(input, tokens),
+        }
+    }
+
+    /// Execute the markdown parser, and report markup which is rendered as
+    /// text because it is not closed, such as `**bold` or `[link](url`.
+    pub fn execute_with_diagnostics(&self, input: &str) -> (String, Vec<Diagnostic>) {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_with_diagnostics(input);
+        }
 
-        (
-            self.stringifier.stringify(document),
-            self.stringifier.stringify(toc),
-        )
+        let tokens = lex(input);
+        let (tree, diagnostics) = self.parse_for_html(input, tokens);
+        let document = self.transformer.transform(tree);
+        (self.stringifier.stringify(document), diagnostics)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Execute the markdown parser, and return an error instead of rendering
+    /// a degraded result.
+    ///
+    /// The errors are invalid settings, including options in the front
+    /// matter which cannot be parsed, input longer than
+    /// [`Parser::max_input_len`](layer::parser::Parser::max_input_len) or
+    /// [`Parser::max_tokens`](layer::parser::Parser::max_tokens), and
+    /// includes which are not resolved, include themselves or are nested too
+    /// deeply. Markup which is not closed or nested too deeply is rendered as
+    /// text as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{prelude::*, Error};
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().include_resolver(
+    ///     |target: &str| (target == "a").then(|| Inclusion::Markdown("*a*".into())),
+    /// ));
+    ///
+    /// assert_eq!(markdown.try_execute("![[a]]").unwrap(), "<p><em>a</em></p>");
+    /// assert_eq!(
+    ///     markdown.try_execute("![[b]]").unwrap_err().to_string(),
+    ///     "`b` is not found"
+    /// );
+    ///
+    /// let markdown = markdown.parser(Parser::default().list_indent_style(IndentStyle::Space(0)));
+    ///
+    /// assert!(matches!(
+    ///     markdown.try_execute("- a"),
+    ///     Err(Error::Config(ConfigWarning::ZeroIndentWidth))
+    /// ));
+    /// ```
+    pub fn try_execute(&self, input: &str) -> Result<String, Error> {
+        // Options in the front matter can make the settings invalid too.
+        let mut warnings = self
+            .validate_input(input)
+            .into_iter()
+            .chain(self.for_document(input).validate());
 
-    #[test]
-    fn test_markdown() {
-        let input = concat![
-            "# Hello World\n\n",
-            "This is **TEST**\n\n",
-            "## Goodbye\n",
-            "I'm happy\n\n",
-            "See you\n",
-            "again\n"
-        ];
+        if let Some(warning) = warnings.find(ConfigWarning::is_invalid) {
+            return Err(Error::Config(warning));
+        }
 
-        let output = Markdown::default().execute(input);
+        if let Some(max) = self.parser.max_input_len.filter(|&max| max < input.len()) {
+            return Err(Error::InputTooLong {
+                len: input.len(),
+                max,
+            });
+        }
 
-        assert_eq!(
-            &output,
-            "<h1>Hello World</h1><p>This is <strong>TEST</strong></p><h2>Goodbye<br>I'm happy</h2><p>See you<br>again</p>"
-        );
+        let (html, diagnostics) = self.execute_with_diagnostics(input);
+
+        for diagnostic in diagnostics {
+            match diagnostic.kind {
+                // The input is cut only by the tokens, since its length is
+                // checked above.
+                DiagnosticKind::InputTruncated => {
+                    return Err(Error::TooManyTokens {
+                        max: self.parser.max_tokens.unwrap_or_default(),
+                    });
+                }
+                ref kind if kind.is_include() => return Err(Error::Include(diagnostic)),
+                _ => {}
+            }
+        }
+
+        Ok(html)
     }
 
-    #[test]
-    fn test_markdown2() {
-        let input = concat![
-            "- Hello\n",
-            "- World\n",
-            "  - Change the **world**\n",
-            "  - Great!\n",
-            "    1. Yeah\n",
-            "    1. Wryyyyy\n",
-            "- End of the world\n"
-        ];
+    /// Execute the markdown parser like [`try_execute`](Self::try_execute),
+    /// and write the HTML to the writer.
+    ///
+    /// Nothing is written if there is an error other than writing.
+    pub fn try_execute_into(&self, input: &str, mut writer: impl io::Write) -> Result<(), Error> {
+        let html = self.try_execute(input)?;
 
-        let output = Markdown::default().execute(input);
+        writer.write_all(html.as_bytes())?;
 
-        assert_eq!(
-            &output,
-            "<ul><li>Hello</li><li>World<ul><li>Change the <strong>world</strong></li><li>Great!<ol><li>Yeah</li><li>Wryyyyy</li></ol></li></ul></li><li>End of the world</li></ul>")
+        Ok(())
+    }
+
+    /// Execute the markdown parser on the input read from the reader, such
+    /// as the standard input.
+    ///
+    /// The HTML is the same as [`execute`](Self::execute) of the whole
+    /// input, and the whole input is read into memory before it is parsed,
+    /// since links, footnotes and ids can depend on any part of it. The input
+    /// over [`Parser::max_input_len`](layer::parser::Parser::max_input_len)
+    /// is ignored, so it is not read, unless a
+    /// [`front_matter_hook`](Self::front_matter_hook) can change the limit.
+    /// The reader is read in chunks, so it does not need to be buffered.
+    ///
+    /// The error is [`Error::Read`] if the input cannot be read or it is not
+    /// UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().max_input_len(Some(9)));
+    ///
+    /// let html = markdown.execute_reader("# Hello\n\nworld".as_bytes()).unwrap();
+    ///
+    /// assert_eq!(html, "<h1>Hello</h1>");
+    /// ```
+    pub fn execute_reader(&self, reader: impl io::Read) -> Result<String, Error> {
+        let limit = match self.parser.max_input_len {
+            Some(max) if !self.front_matter_options || self.front_matter_hook.is_none() => {
+                // A byte over the limit tells whether the input is cut.
+                Some((max as u64).saturating_add(1))
+            }
+            _ => None,
+        };
+
+        let input = read_input(reader, limit).map_err(Error::Read)?;
+
+        Ok(self.execute(input))
+    }
+
+    /// Collect the links and wiki links of the input in document order, such
+    /// as to maintain a database of backlinks.
+    ///
+    /// Each link has its target, its plain text and its byte range in the
+    /// input. Wiki links are found only if
+    /// [`Parser::wiki_links`](layer::parser::Parser::wiki_links) is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{model::link::*, prelude::*};
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().wiki_links(true));
+    ///
+    /// let links = markdown.extract_links("See [the *docs*](/docs \"Docs\")\n\n- [[Home|home]]");
+    ///
+    /// assert_eq!(
+    ///     links,
+    ///     vec![
+    ///         LinkRef {
+    ///             kind: LinkKind::Link,
+    ///             target: "/docs".into(),
+    ///             title: Some("Docs".into()),
+    ///             text: "the docs".into(),
+    ///             span: 4..30,
+    ///         },
+    ///         LinkRef {
+    ///             kind: LinkKind::WikiLink,
+    ///             target: "Home".into(),
+    ///             title: None,
+    ///             text: "home".into(),
+    ///             span: 34..47,
+    ///         },
+    ///     ]
+    /// );
+    /// ```
+    pub fn extract_links(&self, input: &str) -> Vec<LinkRef> {
+        self.parser.extract_links(input, lex(input))
+    }
+
+    /// Execute the markdown parser and render plain text instead of HTML.
+    ///
+    /// This is useful for search indexing and previews.
+    pub fn execute_text(&self, input: &str) -> String {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_text(input);
+        }
+
+        let tokens = lex(input);
+        let tree = self.parser.parse(input, tokens);
+        let document = self.transformer.transform(tree);
+        self.text_stringifier.stringify(&document)
+    }
+
+    /// Parse the input and write it back as normalized markdown.
+    ///
+    /// With [`Parser::front_matter`], front matter is written back as it is
+    /// before the formatted document.
+    ///
+    /// Comments are kept even with [`Comments::Strip`], since the text after
+    /// a removed comment could start a block, such as `# a` after
+    /// `<!-- c --># a`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+    ///
+    /// assert_eq!(
+    ///     markdown.reformat("---\ntitle: x\n---\n#  A\n"),
+    ///     "---\ntitle: x\n---\n# A\n"
+    /// );
+    /// ```
+    pub fn reformat(&self, input: &str) -> String {
+        let parser = match self.parser.comments {
+            Comments::Strip => Cow::Owned(self.parser.clone().comments(Comments::Preserve)),
+            _ => Cow::Borrowed(&self.parser),
+        };
+
+        let tokens = lex(input);
+        let tree = parser.parse(input, tokens);
+
+        let front_matter = match self.parser.front_matter {
+            true => FrontMatter::parse(input).map_or("", |(_, len)| &input[..len]),
+            false => "",
+        };
+
+        front_matter.to_string() + &self.markdown_stringifier.stringify(&tree)
+    }
+
+    /// Execute the markdown parser and collect render statistics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let (html, stats) = markdown.execute_with_stats("# Hello, **world**!");
+    ///
+    /// assert_eq!(html, "<h1>Hello, <strong>world</strong>!</h1>");
+    /// assert_eq!(stats.tokens, 10);
+    /// assert_eq!(stats.nodes, 5);
+    /// assert_eq!(stats.depth, 3);
+    /// assert_eq!(stats.bytes, html.len());
+    /// ```
+    pub fn execute_with_stats(&self, input: &str) -> (String, RenderStats) {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_with_stats(input);
+        }
+
+        let tokens = lex(input).collect::<Vec<_>>();
+        let token_count = tokens.len();
+        let (tree, _) = self.parse_for_html(input, tokens.into_iter());
+        let document = self.transformer.transform(tree);
+
+        let nodes = document.node_count();
+        let depth = document.depth();
+
+        let output = self.stringifier.stringify(document);
+
+        let stats = RenderStats {
+            tokens: token_count,
+            nodes,
+            depth,
+            bytes: output.len(),
+        };
+
+        (output, stats)
+    }
+
+    /// Execute the markdown parser, parsing and transforming chunks of top
+    /// level blocks in parallel.
+    ///
+    /// The output is the same as [`execute`](Self::execute). This is faster
+    /// for large documents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let input = "# Hello\n\n- a\n- b\n\nworld\n".repeat(100);
+    ///
+    /// assert_eq!(markdown.execute_parallel(&input), markdown.execute(&input));
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn execute_parallel(&self, input: &str) -> String {
+        use crate::layer::transformer::Definitions;
+        use rayon::prelude::*;
+
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_parallel(input);
+        }
+
+        // The number of the first line of each chunk, for source positions.
+        let mut line = 1;
+        let mut end = 0;
+
+        let chunks = self
+            .parser
+            .split_blocks(input)
+            .into_iter()
+            .map(|range| {
+                line += input[end..range.start].matches('\n').count();
+                end = range.start;
+
+                (range, line)
+            })
+            .collect::<Vec<_>>();
+
+        let trees = chunks
+            .into_par_iter()
+            .map(|(range, line)| {
+                let parser = self.parser.for_chunk(range.start);
+                let input = &input[range];
+
+                match self.stringifier.source_positions {
+                    true => parser.parse_with_positions(input, lex(input), line).0,
+                    false => parser.parse(input, lex(input)),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Definitions apply to the whole document, so they are collected
+        // from all chunks before transforming any of them.
+        let mut definitions = Definitions::default();
+
+        for tree in &trees {
+            definitions.collect(&tree.root.root);
+        }
+
+        let nodes = trees
+            .into_par_iter()
+            .map(|tree| self.transformer.transform_nodes(tree, &definitions))
+            .collect::<Vec<_>>();
+
+        // Sections can span chunks, and ids, headline numbers and footnote
+        // numbers depend on the whole document, so they are made after
+        // joining chunks.
+        let document = self
+            .transformer
+            .document(nodes.into_iter().flatten().collect());
+
+        self.stringifier.stringify(document)
+    }
+
+    /// Parse the input into events of blocks and inlines.
+    ///
+    /// Chunks of top level blocks are parsed lazily, so consumers can render
+    /// the events as a stream without building a whole document.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{model::event::*, prelude::*};
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let mut events = markdown.events("# Hello\n\nworld\n");
+    ///
+    /// assert_eq!(events.next(), Some(Event::Start(Tag::Headline(1))));
+    /// assert_eq!(events.next(), Some(Event::Text("Hello".into())));
+    /// assert_eq!(events.next(), Some(Event::End(Tag::Headline(1))));
+    /// assert_eq!(events.next(), Some(Event::Start(Tag::Paragraph)));
+    /// ```
+    pub fn events<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Event<'a>> + 'a {
+        self.parser
+            .split_blocks(input)
+            .into_iter()
+            .flat_map(move |range| {
+                let parser = self.parser.for_chunk(range.start);
+                let input = &input[range];
+
+                parser.parse(input, lex(input)).into_events()
+            })
+    }
+
+    /// Parse the input into a document which can be edited and rendered again
+    /// without parsing the whole input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let mut document = markdown.incremental("# Hello\n\nworld\n");
+    ///
+    /// document.replace_range(0..1, "##");
+    ///
+    /// assert_eq!(document.render(), "<h2>Hello</h2><p>world</p>");
+    /// ```
+    pub fn incremental(&self, input: &str) -> IncrementalDocument {
+        IncrementalDocument::new(self.clone(), input)
+    }
+
+    /// Create a batch of documents which link to each other with this
+    /// configuration. See [`MarkdownBatch`].
+    pub fn batch(&self) -> MarkdownBatch {
+        MarkdownBatch::new(self.clone())
+    }
+
+    /// Extract the title of the document.
+    ///
+    /// The sources in [`MetadataExtractor::title_chain`] are tried in order,
+    /// and the returned title tells which source was used. Front matter is
+    /// read even if the parser does not skip it. The file name is used only
+    /// if it is given.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let title = markdown.title("---\ntitle: Notes\n---\n# Hello", None).unwrap();
+    ///
+    /// assert_eq!(title.text, "Notes");
+    /// assert_eq!(title.source, TitleSource::FrontMatter);
+    ///
+    /// let title = markdown.title("Hello, world! This is a note.", None).unwrap();
+    ///
+    /// assert_eq!(title.text, "Hello, world");
+    /// assert_eq!(title.source, TitleSource::FirstSentence);
+    /// ```
+    pub fn title(&self, input: &str, filename: Option<&str>) -> Option<Title> {
+        let (front_matter, body) = match FrontMatter::parse(input) {
+            Some((front_matter, len)) => (Some(front_matter), &input[len..]),
+            None => (None, input),
+        };
+
+        let tree = self.parser.parse(body, lex(body));
+        let document = self.transformer.transform(tree);
+
+        self.metadata
+            .title(front_matter.as_ref(), &document, filename)
+    }
+
+    /// Execute the markdown parser and extract the title of the document
+    /// from the same parse.
+    ///
+    /// The title is extracted like [`title`](Self::title). If
+    /// [`MetadataExtractor::drop_title_headline`] is set, the headline which
+    /// the title is taken from is not rendered. Front matter is read even if
+    /// the parser does not skip it, but it is rendered unless the parser
+    /// skips it.
+    pub fn execute_with_title(
+        &self,
+        input: &str,
+        filename: Option<&str>,
+    ) -> (String, Option<Title>) {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_with_title(input, filename);
+        }
+
+        let front_matter = FrontMatter::parse(input).map(|(front_matter, _)| front_matter);
+
+        let (tree, _) = self.parse_for_html(input, lex(input));
+        let mut document = self.transformer.transform(tree);
+
+        let title = self
+            .metadata
+            .take_title(front_matter.as_ref(), &mut document, filename);
+
+        (self.stringifier.stringify(document), title)
+    }
+
+    /// Render an excerpt of the document, such as a summary for a feed or an
+    /// index page.
+    ///
+    /// This returns `None` if the document has no paragraph for
+    /// [`ExcerptRule::FirstParagraph`](layer::metadata::config::ExcerptRule),
+    /// or no `<!-- more -->` line for `ExcerptRule::MoreMarker`. An excerpt
+    /// of `ExcerptRule::Words` which is cut ends with
+    /// [`Messages::ellipsis`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let input = "# Title\n\nThe *first* paragraph.\n\n<!-- more -->\n\nThe rest.";
+    ///
+    /// assert_eq!(
+    ///     markdown.excerpt(input, ExcerptRule::FirstParagraph).unwrap(),
+    ///     "<p>The <em>first</em> paragraph.</p>"
+    /// );
+    /// assert_eq!(
+    ///     markdown.excerpt(input, ExcerptRule::Words(3)).unwrap(),
+    ///     "<h1>Title</h1><p>The <em>first…</em></p>"
+    /// );
+    /// assert_eq!(
+    ///     markdown.excerpt(input, ExcerptRule::MoreMarker).unwrap(),
+    ///     "<h1>Title</h1><p>The <em>first</em> paragraph.</p>"
+    /// );
+    /// ```
+    pub fn excerpt(&self, input: &str, rule: ExcerptRule) -> Option<String> {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.excerpt(input, rule);
+        }
+
+        let input = match rule {
+            ExcerptRule::MoreMarker => &input[..MetadataExtractor::more_marker(input)?],
+            _ => input,
+        };
+
+        let (tree, _) = self.parse_for_html(input, lex(input));
+        let document = self.transformer.transform(tree);
+
+        let default = Messages::default();
+        let messages = self.messages.as_ref().unwrap_or(&default);
+
+        let excerpt = self.metadata.excerpt(document, rule, &messages.ellipsis)?;
+
+        Some(self.stringifier.stringify(excerpt))
+    }
+
+    /// Execute the markdown parser and generate the table of contents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// let input = concat![
+    ///     "# Headline1-1\n\n",
+    ///     "# Headline1-2\n\n",
+    ///     "## Headline2-1\n\n",
+    ///     "## Headline2-2\n\n",
+    ///     "# Headline1-3\n\n",
+    /// ];
+    ///
+    /// let (html, toc) = markdown.execute_with_toc(input);
+    ///
+    /// assert_eq!(toc, "<ul><li><a href=\"#headline1-1\">Headline1-1</a></li><li><a href=\"#headline1-2\">Headline1-2</a><ul><li><a href=\"#headline2-1\">Headline2-1</a></li><li><a href=\"#headline2-2\">Headline2-2</a></li></ul></li><li><a href=\"#headline1-3\">Headline1-3</a></li></ul>");
+    /// ```
+    /// ## Original output
+    ///
+    /// ```html
+    /// <h1 id="headline1-1">Headline1-1</h1>
+    /// <h1 id="headline1-2">Headline1-2</h1>
+    /// <h2 id="headline2-1">Headline2-1</h2>
+    /// <h2 id="headline2-2">Headline2-2</h2>
+    /// <h1 id="headline1-3">Headline1-3</h1>
+    /// ```
+    ///
+    /// ## Toc output
+    ///
+    /// ```html
+    /// <ul>
+    ///     <li><a href="#headline1-1">Headline1-1</a></li>
+    ///     <li>
+    ///         <a href="#headline1-2">Headline1-2</a>
+    ///         <ul>
+    ///             <li><a href="#headline2-1">Headline2-1</a></li>
+    ///             <li><a href="#headline2-2">Headline2-2</a></li>
+    ///         </ul>
+    ///     </li>
+    ///     <li><a href="#headline1-3">Headline1-3</a></li>
+    /// </ul>
+    /// ```
+    pub fn execute_with_toc(&self, input: &str) -> (String, String) {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_with_toc(input);
+        }
+
+        let tokens = lex(input);
+        let (tree, _) = self.parse_for_html(input, tokens);
+        let mut document = self.transformer.transform(tree);
+
+        let messages = self.messages.as_ref().unwrap_or(&self.toc_maker.messages);
+
+        let (toc, _) = self
+            .toc_maker
+            .make_toc_with_messages(&mut document, messages);
+
+        // The table of contents is a part of a page, so it is not wrapped in
+        // a document.
+        let mut toc_html = String::new();
+
+        self.stringifier.stringify_into(&toc, &mut toc_html);
+
+        (self.stringifier.stringify(document), toc_html)
+    }
+
+    /// Convert Markdown to HTML with a list of figures.
+    ///
+    /// Figures are `<figure>` elements with a `<figcaption>`, such as
+    /// blockquotes with a citation by
+    /// [`Transformer::quote_citation`]. Figures without an id are given one by
+    /// the [`FigureListMaker`], and the list links to them in document order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let markdown = Markdown::default().transformer(Transformer::new().quote_citation(true));
+    ///
+    /// let (html, figures) = markdown.execute_with_figures("> Simple is better.\n> -- Zen");
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     concat![
+    ///         "<figure id=\"figure-1\"><blockquote><p>Simple is better.</p></blockquote>",
+    ///         "<figcaption>Zen</figcaption></figure>",
+    ///     ]
+    /// );
+    /// assert_eq!(figures, "<ol><li><a href=\"#figure-1\">Zen</a></li></ol>");
+    /// ```
+    pub fn execute_with_figures(&self, input: &str) -> (String, String) {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.execute_with_figures(input);
+        }
+
+        let tokens = lex(input);
+        let (tree, _) = self.parse_for_html(input, tokens);
+        let mut document = self.transformer.transform(tree);
+
+        let messages = self
+            .messages
+            .as_ref()
+            .unwrap_or(&self.figure_list_maker.messages);
+        let list = self
+            .figure_list_maker
+            .make_list_with_messages(&mut document, messages);
+
+        // Like the table of contents, the list is a part of a page.
+        let mut list_html = String::new();
+
+        self.stringifier.stringify_into(&list, &mut list_html);
+
+        (self.stringifier.stringify(document), list_html)
+    }
+
+    /// Get the outline of the document, such as for a sidebar, breadcrumbs
+    /// or a search index.
+    ///
+    /// Headlines of all levels are returned in document order, including
+    /// those in lists and blockquotes. Ids are given by the
+    /// [`TocMaker`] like [`execute_with_toc`](Self::execute_with_toc), so they
+    /// match the anchors of the table of contents.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "# Guide\n\nText\n\n## *Install* {#setup}\n\n#### Notes";
+    ///
+    /// let outline = Markdown::default().outline(input);
+    ///
+    /// assert_eq!(
+    ///     outline,
+    ///     vec![
+    ///         Heading {
+    ///             level: 1,
+    ///             text: "Guide".into(),
+    ///             id: Some("guide".into()),
+    ///             span: 0..7,
+    ///         },
+    ///         Heading {
+    ///             level: 2,
+    ///             text: "Install".into(),
+    ///             id: Some("setup".into()),
+    ///             span: 15..36,
+    ///         },
+    ///         Heading {
+    ///             level: 4,
+    ///             text: "Notes".into(),
+    ///             id: None,
+    ///             span: 38..48,
+    ///         },
+    ///     ]
+    /// );
+    /// ```
+    pub fn outline(&self, input: &str) -> Vec<Heading> {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.outline(input);
+        }
+
+        let (tree, spans) = self.parser.parse_with_headlines(input, lex(input));
+
+        // The table of contents of a placeholder has a headline which is not
+        // in the input, so only its ids are given.
+        let mut transformer = self.transformer.clone();
+        let placeholder = transformer.toc_placeholder.take();
+
+        let mut document = transformer.transform(tree);
+
+        if let Some(toc_maker) = placeholder {
+            if has_toc_placeholder(&document.root) {
+                toc_maker.assign_ids(&mut document);
+            }
+        }
+
+        self.toc_maker.outline(&mut document, spans)
+    }
+
+    /// Render only the section under the headline which has the id, such as
+    /// to embed a part of a document into another page.
+    ///
+    /// The section is the headline and the blocks after it until the next
+    /// headline of the same or a higher level. The id is the one in the
+    /// [`outline`](Self::outline) and the table of contents. The section is
+    /// rendered as it is in [`execute`](Self::execute), so the headline has
+    /// an id only if the transformer gives it one. `None` is returned if no
+    /// headline has the id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let input = "# Guide\n\n## Installation\n\nRun it.\n\n### Linux\n\nEasy.\n\n## Usage";
+    ///
+    /// let markdown = Markdown::default();
+    ///
+    /// assert_eq!(
+    ///     markdown.section(input, "installation").unwrap(),
+    ///     "<h2>Installation</h2><p>Run it.</p><h3>Linux</h3><p>Easy.</p>"
+    /// );
+    /// assert_eq!(markdown.section(input, "missing"), None);
+    /// ```
+    pub fn section(&self, input: &str, id: &str) -> Option<String> {
+        if let Cow::Owned(markdown) = self.for_document(input) {
+            return markdown.section(input, id);
+        }
+
+        let (tree, _) = self.parse_for_html(input, lex(input));
+        let document = self.transformer.transform(tree);
+
+        let path = self.toc_maker.find_headline(&document, id)?;
+
+        let (last, parents) = path.split_last()?;
+
+        let mut nodes = document.into_root();
+
+        for &index in parents {
+            let Node::Element(element) = nodes.swap_remove(index) else {
+                unreachable!()
+            };
+
+            nodes = element.children;
+        }
+
+        let level = |node: &Node| match node {
+            Node::Element(element) => element.tag.get_headline_level(),
+            Node::Text(_) | Node::Comment(_) => None,
+        };
+
+        let start = level(&nodes[*last])?;
+
+        let root = nodes
+            .into_iter()
+            .skip(*last)
+            .enumerate()
+            .take_while(|(index, node)| match level(node) {
+                Some(level) => *index == 0 || level > start,
+                None => true,
+            })
+            .map(|(_, node)| node)
+            .collect();
+
+        Some(self.stringifier.stringify(DocumentNode { root }))
+    }
+}
+
+/// Read the input as UTF-8, up to the limit in bytes if any.
+///
+/// A character which the limit cuts is dropped, since the input is cut before
+/// it anyway.
+fn read_input(reader: impl io::Read, limit: Option<u64>) -> io::Result<String> {
+    let mut bytes = vec![];
+
+    reader
+        .take(limit.unwrap_or(u64::MAX))
+        .read_to_end(&mut bytes)?;
+
+    let cut = limit == Some(bytes.len() as u64);
+
+    let bytes = match std::str::from_utf8(&bytes) {
+        Ok(_) => bytes,
+        Err(error) if cut && error.error_len().is_none() => {
+            let len = error.valid_up_to();
+
+            bytes.truncate(len);
+            bytes
+        }
+        Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+    };
+
+    String::from_utf8(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::html::ElementTag;
+
+    #[test]
+    fn test_markdown() {
+        let input = concat![
+            "# Hello World\n\n",
+            "This is **TEST**\n\n",
+            "## Goodbye\n",
+            "I'm happy\n\n",
+            "See you\n",
+            "again\n"
+        ];
+
+        let output = Markdown::default().execute(input);
+
+        assert_eq!(
+            &output,
+            "<h1>Hello World</h1><p>This is <strong>TEST</strong></p><h2>Goodbye<br>I'm happy</h2><p>See you<br>again</p>"
+        );
+    }
+
+    #[test]
+    fn test_markdown2() {
+        let input = concat![
+            "- Hello\n",
+            "- World\n",
+            "  - Change the **world**\n",
+            "  - Great!\n",
+            "    1. Yeah\n",
+            "    1. Wryyyyy\n",
+            "- End of the world\n"
+        ];
+
+        let output = Markdown::default().execute(input);
+
+        assert_eq!(
+            &output,
+            "<ul><li>Hello</li><li>World<ul><li>Change the <strong>world</strong></li><li>Great!<ol><li>Yeah</li><li>Wryyyyy</li></ol></li></ul></li><li>End of the world</li></ul>")
+    }
+
+    #[test]
+    fn test_markdown_stats() {
+        let input = concat!["- AAA\n", "  - BBB\n", "    - *CCC*\n", "\n", "End\n",];
+
+        let markdown = Markdown::default();
+
+        let (output, stats) = markdown.execute_with_stats(input);
+
+        assert_eq!(output, markdown.execute(input));
+        assert_eq!(stats.bytes, output.len());
+        assert_eq!(stats.nodes, 12);
+        assert_eq!(stats.depth, 8);
+
+        let (output, stats) = markdown.execute_with_stats("");
+
+        assert_eq!(output, "");
+        assert_eq!(stats, RenderStats::default());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_markdown_parallel() {
+        let input = concat![
+            "# Hello World\n\n",
+            "This is **TEST**\n\n",
+            "- AAA\n",
+            "  - BBB\n\n",
+            "- CCC\n\n",
+            "> Ok!\n",
+            ">\n",
+            "End\n",
+        ]
+        .repeat(50);
+
+        let markdown = Markdown::default();
+
+        assert_eq!(markdown.execute_parallel(&input), markdown.execute(&input));
+
+        let markdown = markdown.stringifier(Stringifier::new().format(true));
+
+        assert_eq!(markdown.execute_parallel(&input), markdown.execute(&input));
+        assert_eq!(markdown.execute_parallel(""), "");
+
+        let markdown = Markdown::default().transformer(Transformer::new().section(true));
+
+        assert_eq!(markdown.execute_parallel(&input), markdown.execute(&input));
+    }
+
+    #[test]
+    fn test_markdown_untrusted() {
+        let input = concat![
+            "# <script>alert(1)</script> {#a\" onclick=x .b style=\"color:red\"}\n\n",
+            "- \"quoted\" & <b>bold</b> {href=javascript:alert(1) title=\"a&b\"}\n",
+        ];
+
+        let output = Markdown::untrusted().execute(input);
+
+        assert_eq!(
+            output,
+            "<h1>&lt;script&gt;alert(1)&lt;/script&gt; {#a&quot; onclick=x .b style=&quot;color:red&quot;}</h1><ul><li>&quot;quoted&quot; &amp; &lt;b&gt;bold&lt;/b&gt; {href=javascript:alert(1) title=&quot;a&amp;b&quot;}</li></ul>"
+        );
+
+        let output =
+            Markdown::untrusted().execute("# A {.b onclick=x href=javascript:y title=\"a&b\"}");
+
+        assert_eq!(output, "<h1 class=\"b\" title=\"a&amp;b\">A</h1>");
+
+        let output = Markdown::untrusted().execute(&("> ".repeat(100_000) + "a"));
+
+        assert_eq!(output.matches("<blockquote>").count(), 32);
+
+        let output = Markdown::untrusted().execute("a".repeat((1 << 20) + 10));
+
+        assert_eq!(output.len(), (1 << 20) + "<p></p>".len());
+    }
+
+    #[test]
+    fn test_markdown_profile() {
+        for profile in [Profile::Comments, Profile::Notes, Profile::Docs] {
+            assert_eq!(Markdown::profile(profile).validate(), vec![]);
+            assert_eq!(profile.to_string().parse(), Ok(profile));
+        }
+
+        let input = "---\ntitle: A\n---\n# Guide\n\n[TOC]\n\n## \"Use\" HTML\n\n*[HTML]: Markup";
+
+        assert_eq!(
+            Markdown::profile(Profile::Docs).execute(input),
+            concat![
+                "<h1 id=\"guide\">Guide<a class=\"anchor\" href=\"#guide\">¶</a></h1>",
+                "<ul><li><a href=\"#guide\">Guide</a><ul><li><a href=\"#use-html\">“Use” HTML</a>",
+                "</li></ul></li></ul><h2 id=\"use-html\">“Use” ",
+                "<abbr title=\"Markup\">HTML</abbr><a class=\"anchor\" href=\"#use-html\">¶</a></h2>",
+            ]
+        );
+
+        let options = MarkdownOptions::new()
+            .profile(Profile::Comments)
+            .stringifier(options::StringifierOptions {
+                escape: Some(false),
+                ..Default::default()
+            });
+
+        assert_eq!(
+            Markdown::from_options(&options).execute("<b>hi</b> @a<!-- x -->"),
+            "<p><b>hi</b> <span class=\"mention\">@a</span></p>"
+        );
+    }
+
+    #[test]
+    fn test_front_matter_options() {
+        let input = "---\ntoc: true\ntoc_max_level: 1\nsection: x\n---\n[TOC]\n\n# A\n\n## B";
+
+        let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+
+        assert_eq!(markdown.execute(input), "<p>[TOC]</p><h1>A</h1><h2>B</h2>");
+        assert!(matches!(markdown.for_document(input), Cow::Borrowed(_)));
+
+        let markdown = markdown.front_matter_options(true);
+
+        assert_eq!(
+            markdown.execute(input),
+            "<ul><li><a href=\"#a\">A</a></li></ul><h1 id=\"a\">A</h1><h2>B</h2>"
+        );
+        assert!(matches!(markdown.for_document("# A"), Cow::Borrowed(_)));
+        assert!(matches!(
+            markdown.for_document("---\ntitle: A\n---\n"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            markdown.validate_input(input),
+            vec![ConfigWarning::InvalidFrontMatterOption {
+                key: "section".to_string(),
+                error: error::ParseConfigError::new("bool", "x"),
+            }]
+        );
+
+        // A placeholder which is configured keeps its maker, with the levels
+        // of the document.
+        let markdown =
+            markdown.transformer(Transformer::new().toc_placeholder(
+                TocMaker::default().list_type(layer::toc::config::ListType::Ordered),
+            ));
+
+        assert_eq!(
+            markdown.execute(input),
+            "<ol><li><a href=\"#a\">A</a></li></ol><h1 id=\"a\">A</h1><h2>B</h2>"
+        );
+
+        let batch = markdown.batch().document("a.md", input);
+
+        assert!(batch.render().documents[0].html.starts_with("<ol>"));
+
+        let options = MarkdownOptions {
+            front_matter_options: Some(true),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Markdown::from_options(&options).execute("---\nhighlight: true\n---\n==a=="),
+            "<p>---<br>highlight: true<br>---<br><mark>a</mark></p>"
+        );
+    }
+
+    #[test]
+    fn test_front_matter_chunks() {
+        let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+
+        // Only the first chunk can have front matter.
+        for input in [
+            "a\n\n---\nx\n---\n# H\n",
+            "---\nt: 1\n---\na\n\n---\nx\n---\n",
+        ] {
+            let expected = markdown.execute(input);
+
+            assert_eq!(markdown.incremental(input).render(), expected);
+            assert_eq!(
+                markdown.events(input).collect::<Vec<_>>(),
+                markdown.parser.parse(input, lex(input)).into_events()
+            );
+
+            #[cfg(feature = "parallel")]
+            assert_eq!(markdown.execute_parallel(input), expected);
+        }
+
+        assert_eq!(
+            markdown.execute("a\n\n---\nx\n---\n# H\n"),
+            "<p>a</p><p>---<br>x<br>---<br># H</p>"
+        );
+
+        // An edit can move a chunk to or from the start.
+        let mut document = markdown.incremental("a\n\n---\nx\n---\n# H\n");
+
+        document.replace_range(0..3, "");
+
+        assert_eq!(document.render(), "<h1>H</h1>");
+
+        document.replace_range(0..0, "b\n\n");
+
+        assert_eq!(document.render(), markdown.execute(document.source()));
+    }
+
+    #[test]
+    fn test_messages() {
+        let messages = Messages {
+            toc_label: "Inhalt".to_string(),
+            footnote_backref: "^".to_string(),
+            ..Default::default()
+        };
+        let parser = Parser::new().inline_footnotes(true).front_matter(true);
+        let transformer =
+            Transformer::new().toc_placeholder(TocMaker::default().heading(ElementTag::H2));
+
+        // The messages are used regardless of the order of the builder calls.
+        for markdown in [
+            Markdown::default()
+                .parser(parser.clone())
+                .transformer(transformer.clone())
+                .messages(messages.clone()),
+            Markdown::default()
+                .parser(parser.clone())
+                .messages(messages.clone())
+                .transformer(transformer),
+        ] {
+            assert_eq!(
+                markdown.execute("[TOC]\n\n# A\n\nb^[c]"),
+                concat![
+                    "<h2>Inhalt</h2><ul><li><a href=\"#a\">A</a></li></ul><h1 id=\"a\">A</h1>",
+                    "<p>b<sup class=\"footnote-ref\"><a id=\"fnref-1\" href=\"#fn-1\">1</a></sup></p>",
+                    "<section class=\"footnotes\"><ol><li id=\"fn-1\">c ",
+                    "<a class=\"footnote-backref\" href=\"#fnref-1\">^</a></li></ol></section>",
+                ]
+            );
+        }
+
+        // The placeholder of front matter is made from the maker of the table
+        // of contents.
+        let markdown = Markdown::default()
+            .parser(parser)
+            .toc_maker(TocMaker::default().heading(ElementTag::H2))
+            .front_matter_options(true)
+            .messages(messages);
+
+        assert_eq!(
+            markdown.execute("---\ntoc: true\n---\n[TOC]\n\n# A"),
+            "<h2>Inhalt</h2><ul><li><a href=\"#a\">A</a></li></ul><h1 id=\"a\">A</h1>"
+        );
+    }
+
+    #[test]
+    fn test_markdown_validate() {
+        assert_eq!(Markdown::untrusted().validate(), vec![]);
+
+        let markdown = Markdown::default()
+            .stringifier(Stringifier::new().sanitize(true))
+            .metadata(MetadataExtractor::new().title_chain(&[]));
+
+        assert_eq!(
+            markdown.validate(),
+            vec![
+                ConfigWarning::SanitizeWithoutEscape,
+                ConfigWarning::EmptyTitleChain
+            ]
+        );
+
+        let markdown = Markdown::default();
+
+        assert_eq!(markdown.validate_input("- a\n  - b\n    1. c"), vec![]);
+        assert_eq!(
+            markdown.validate_input("text\n\t- a\n\t1. b"),
+            vec![ConfigWarning::IndentStyleMismatch { line: 2 }]
+        );
+
+        let markdown = Markdown::default().transformer(Transformer::new().section(true));
+
+        assert_eq!(
+            markdown.validate(),
+            vec![ConfigWarning::SectionWithHardBreakHeadline]
+        );
+
+        let markdown = markdown.parser(Parser::new().headline_ending(HeadlineEnding::SoftBreak));
+
+        assert_eq!(markdown.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_markdown_escaped() {
+        let output = Markdown::default().execute("a\\*b\\*c \\# d\n\n\\1. e");
+
+        assert_eq!(output, "<p>a*b*c # d</p><p>\\1. e</p>");
+
+        let output = Markdown::default().execute("1 + 1 = $2 <b> ~a_b! \\_\\$");
+
+        assert_eq!(output, "<p>1 + 1 = $2 <b> ~a_b! _$</p>");
+
+        // Every ASCII punctuation character can be escaped.
+        let punctuation = (b'!'..=b'~')
+            .map(char::from)
+            .filter(char::is_ascii_punctuation)
+            .collect::<String>();
+
+        let input = punctuation
+            .chars()
+            .map(|c| format!("\\{c}"))
+            .collect::<String>();
+
+        assert_eq!(
+            Markdown::default().execute(&input),
+            format!("<p>{punctuation}</p>")
+        );
+
+        let markdown = Markdown::default().parser(
+            Parser::new()
+                .wiki_links(true)
+                .hashtags(true)
+                .mentions(true)
+                .highlight(true),
+        );
+
+        let input = concat![
+            "\\*a\\* *b\\*c* \\[d](e) [f\\]](g\\)\\\\ \"h\\!\") ",
+            "[[i\\|j|k\\]]] \\#l \\@m \\==n== \\a\\"
+        ];
+
+        assert_eq!(
+            markdown.execute(input),
+            concat![
+                "<p>*a* <em>b*c</em> [d](e) <a href=\"g)\\\" title=\"h!\">f]</a> ",
+                "<a class=\"wiki-link\" href=\"i|j\">k]</a> #l @m ==n== \\a\\</p>"
+            ]
+        );
+
+        let reformatted = markdown.reformat(input);
+
+        assert_eq!(markdown.execute(&reformatted), markdown.execute(input));
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let markdown = std::sync::Arc::new(
+            Markdown::default()
+                .parser(Parser::new().wiki_links(true))
+                .transformer(
+                    Transformer::new()
+                        .heading_ids(true)
+                        .wiki_resolver(|target: &str| Some(format!("/{target}"))),
+                ),
+        );
+
+        let input = "# Title\n\nSee [[Page]].";
+        let expected = markdown.execute(input);
+
+        let handles = (0..4)
+            .map(|_| {
+                let markdown = markdown.clone();
+
+                std::thread::spawn(move || markdown.execute(input))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+
+        assert!(expected.contains("href=\"/Page\""));
+    }
+
+    #[test]
+    fn test_try_execute() {
+        struct Broken;
+
+        impl io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let markdown = Markdown::default()
+            .parser(Parser::new().max_include_depth(1).include_resolver(
+                |target: &str| match target {
+                    "a" => Some(layer::parser::Inclusion::Markdown("![[b]]".into())),
+                    "b" => Some(layer::parser::Inclusion::Markdown("b".into())),
+                    _ => None,
+                },
+            ))
+            .front_matter_options(true);
+
+        let mut output = vec![];
+
+        markdown.try_execute_into("# **a", &mut output).unwrap();
+
+        assert_eq!(output, b"<h1>**a</h1>");
+
+        let error = markdown.try_execute_into("![[a]]", Broken).unwrap_err();
+
+        assert!(matches!(&error, Error::Include(diagnostic) if diagnostic.span == (0..6)));
+        assert_eq!(error.to_string(), "`b` is included too deeply");
+
+        let error = markdown.try_execute_into("a", Broken).unwrap_err();
+
+        assert!(matches!(&error, Error::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = markdown
+            .try_execute("---\ntoc_min_level: 4\ntoc_max_level: 2\n---\n")
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Config(ConfigWarning::TocLevelRange { min: 4, max: 2 })
+        ));
+
+        let error = markdown
+            .try_execute("---\nsection: maybe\n---\n")
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid configuration: front matter option `section` is ignored: invalid value `maybe` for bool"
+        );
+
+        let markdown = markdown.toc_maker(TocMaker::default().min_level(0));
+
+        assert!(matches!(markdown.try_execute("a"), Err(Error::Config(_))));
+
+        let markdown = Markdown::default().parser(
+            Parser::new()
+                .max_tokens(Some(3))
+                .max_inline_nesting(Some(0)),
+        );
+
+        assert_eq!(markdown.try_execute("*a*").unwrap(), "<p>*a*</p>");
+        assert!(matches!(
+            markdown.try_execute("*a* b"),
+            Err(Error::TooManyTokens { max: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_execute_reader() {
+        struct Broken;
+
+        impl io::Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+
+        let input = "# A\n\n[b][c] あい\n\n[c]: /d";
+        let markdown = Markdown::default();
+
+        assert_eq!(
+            markdown.execute_reader(input.as_bytes()).unwrap(),
+            markdown.execute(input)
+        );
+
+        // The limit may cut a character.
+        for max in 0..=input.len() {
+            let markdown = Markdown::default().parser(Parser::new().max_input_len(Some(max)));
+
+            assert_eq!(
+                markdown.execute_reader(input.as_bytes()).unwrap(),
+                markdown.execute(input)
+            );
+        }
+
+        // The input over the limit is not read.
+        let markdown = Markdown::default().parser(Parser::new().max_input_len(Some(4)));
+
+        assert_eq!(
+            markdown.execute_reader(io::repeat(b'a')).unwrap(),
+            "<p>aaaa</p>"
+        );
+
+        let error = markdown.execute_reader(&b"a\xff"[..]).unwrap_err();
+
+        assert!(matches!(&error, Error::Read(error) if error.kind() == io::ErrorKind::InvalidData));
+
+        let error = markdown.execute_reader(Broken).unwrap_err();
+
+        assert!(matches!(&error, Error::Read(error) if error.kind() == io::ErrorKind::BrokenPipe));
+        assert!(error.to_string().starts_with("cannot read the input: "));
+    }
+
+    #[test]
+    fn test_markdown_section() {
+        let input = "# A\n\n## B\n\nText\n\n- Item\n\n  ## B\n\n  In list\n- Next\n\n### C\n\n# D";
+
+        let markdown = Markdown::default();
+
+        assert_eq!(
+            markdown.section(input, "b").unwrap(),
+            concat![
+                "<h2>B</h2><p>Text</p>",
+                "<ul><li>Item<h2>B</h2><p>In list</p></li><li>Next</li></ul>",
+                "<h3>C</h3>",
+            ]
+        );
+        // Headlines in lists are not in the table of contents.
+        assert_eq!(markdown.section(input, "b-1"), None);
+        assert_eq!(markdown.section(input, "d").unwrap(), "<h1>D</h1>");
+        assert_eq!(markdown.section(input, "B"), None);
+
+        let markdown =
+            Markdown::default().transformer(Transformer::new().section(true).heading_ids(true));
+
+        assert_eq!(markdown.section(input, "c").unwrap(), "<h3 id=\"c\">C</h3>");
+        assert_eq!(
+            markdown.section("# A\n\n## B\n\nText\n\n# C", "a").unwrap(),
+            concat![
+                "<h1 id=\"a\">A</h1>",
+                "<section><h2 id=\"b\">B</h2><p>Text</p></section>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_list_blocks() {
+        let input = concat![
+            "- a\n",
+            "\n",
+            "  Second paragraph\n",
+            "  > quote\n",
+            "\n",
+            "  # Headline\n",
+            "- b\n",
+            "lazy\n",
+            "\n",
+            "Outside\n",
+        ];
+
+        assert_eq!(
+            Markdown::default().execute(input),
+            concat![
+                "<ul><li>a<p>Second paragraph<br>> quote</p><h1>Headline</h1></li>",
+                "<li>b<br>lazy</li></ul>",
+                "<p>Outside</p>",
+            ]
+        );
     }
 
     #[test]
@@ -0,0 +1,358 @@
+//! Counter styles for auto-numbering.
+//!
+//! Headlines, footnotes and figures which are numbered automatically format
+//! their numbers with a [`CounterStyle`]. Hierarchical numbers such as `1.2.3`
+//! are formatted with [`Numbering`], which
+//! [`TocMaker::numbering`](crate::layer::toc::TocMaker::numbering) uses to number
+//! the entries of the table of contents.
+
+use std::{fmt, str::FromStr, sync::Arc};
+
+use crate::error::ParseConfigError;
+
+/// The trait to format a counter value.
+///
+/// This is implemented for built-in styles in [`Counter`] and for closures.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let style = |value: usize| format!("({value})");
+///
+/// assert_eq!(style.format(3), "(3)");
+/// assert_eq!(Counter::UpperRoman.format(14), "XIV");
+/// ```
+pub trait CounterStyle: Send + Sync {
+    /// Format the value. Values start from 1.
+    fn format(&self, value: usize) -> String;
+}
+
+impl<F> CounterStyle for F
+where
+    F: Fn(usize) -> String + Send + Sync,
+{
+    fn format(&self, value: usize) -> String {
+        self(value)
+    }
+}
+
+/// Built-in counter styles.
+///
+/// Values which a style cannot represent, such as 0 in roman numerals, are
+/// formatted as decimal numbers.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Counter {
+    /// `1`, `2`, `3`, ...
+    #[default]
+    Decimal,
+    /// `i`, `ii`, `iii`, ...
+    LowerRoman,
+    /// `I`, `II`, `III`, ...
+    UpperRoman,
+    /// `a`, `b`, ..., `z`, `aa`, `ab`, ...
+    LowerAlpha,
+    /// `A`, `B`, ..., `Z`, `AA`, `AB`, ...
+    UpperAlpha,
+}
+
+impl CounterStyle for Counter {
+    fn format(&self, value: usize) -> String {
+        match self {
+            Self::Decimal => value.to_string(),
+            Self::LowerRoman => roman(value).to_lowercase(),
+            Self::UpperRoman => roman(value),
+            Self::LowerAlpha => alpha(value).to_lowercase(),
+            Self::UpperAlpha => alpha(value),
+        }
+    }
+}
+
+impl FromStr for Counter {
+    type Err = ParseConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(Self::Decimal),
+            "lower-roman" => Ok(Self::LowerRoman),
+            "upper-roman" => Ok(Self::UpperRoman),
+            "lower-alpha" => Ok(Self::LowerAlpha),
+            "upper-alpha" => Ok(Self::UpperAlpha),
+            _ => Err(ParseConfigError::new("Counter", s)),
+        }
+    }
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decimal => f.write_str("decimal"),
+            Self::LowerRoman => f.write_str("lower-roman"),
+            Self::UpperRoman => f.write_str("upper-roman"),
+            Self::LowerAlpha => f.write_str("lower-alpha"),
+            Self::UpperAlpha => f.write_str("upper-alpha"),
+        }
+    }
+}
+
+/// Format the value in upper roman numerals.
+fn roman(value: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    if value == 0 || value >= 4000 {
+        return value.to_string();
+    }
+
+    let mut rest = value;
+    let mut output = String::new();
+
+    for (number, numeral) in NUMERALS {
+        while rest >= number {
+            output.push_str(numeral);
+            rest -= number;
+        }
+    }
+
+    output
+}
+
+/// Format the value in upper letters.
+fn alpha(value: usize) -> String {
+    if value == 0 {
+        return value.to_string();
+    }
+
+    let mut rest = value;
+    let mut letters = vec![];
+
+    while rest > 0 {
+        rest -= 1;
+        letters.push((b'A' + (rest % 26) as u8) as char);
+        rest /= 26;
+    }
+
+    letters.into_iter().rev().collect()
+}
+
+/// The struct to format hierarchical numbers such as `1.2.3` or `II-b`.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let numbering = Numbering::default();
+///
+/// assert_eq!(numbering.format(&[1, 2, 3]), "1.2.3");
+///
+/// let numbering = Numbering::default()
+///     .styles(&[Counter::UpperRoman, Counter::LowerAlpha])
+///     .separator("-")
+///     .suffix(")");
+///
+/// assert_eq!(numbering.format(&[2, 2, 3]), "II-b-c)");
+/// ```
+#[derive(Clone)]
+pub struct Numbering {
+    /// The styles of each level. The last style is used for deeper levels.
+    /// Default is [`Counter::Decimal`] for all levels.
+    pub styles: Vec<Arc<dyn CounterStyle>>,
+    /// The separator between levels. Default is `.`.
+    pub separator: String,
+    /// The text after the number. Default is empty.
+    pub suffix: String,
+}
+
+impl fmt::Debug for Numbering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // A style is shown by how it formats 1, which tells the built-in
+        // styles apart.
+        let styles = self
+            .styles
+            .iter()
+            .map(|style| style.format(1))
+            .collect::<Vec<_>>();
+
+        f.debug_struct("Numbering")
+            .field("styles", &styles)
+            .field("separator", &self.separator)
+            .field("suffix", &self.suffix)
+            .finish()
+    }
+}
+
+impl Default for Numbering {
+    fn default() -> Self {
+        Self {
+            styles: vec![Arc::new(Counter::Decimal)],
+            separator: ".".to_string(),
+            suffix: String::new(),
+        }
+    }
+}
+
+impl Numbering {
+    /// Set the built-in styles of each level.
+    pub fn styles(mut self, styles: &[Counter]) -> Self {
+        self.styles = styles
+            .iter()
+            .map(|style| Arc::new(*style) as Arc<dyn CounterStyle>)
+            .collect();
+        self
+    }
+
+    /// Add a custom style for the next level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::prelude::*;
+    ///
+    /// let numbering = Numbering::default().style(|value: usize| format!("§{value}"));
+    ///
+    /// assert_eq!(numbering.format(&[1, 4]), "1.§4");
+    /// ```
+    pub fn style(mut self, style: impl CounterStyle + 'static) -> Self {
+        self.styles.push(Arc::new(style));
+        self
+    }
+
+    /// Set the separator between levels.
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Set the text after the number.
+    pub fn suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    /// Format the counters of each level.
+    pub fn format(&self, counters: &[usize]) -> String {
+        let mut output = counters
+            .iter()
+            .enumerate()
+            .map(
+                |(level, value)| match self.styles.get(level).or(self.styles.last()) {
+                    Some(style) => style.format(*value),
+                    None => value.to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+
+        output.push_str(&self.suffix);
+
+        output
+    }
+}
+
+/// The counters of hierarchical numbers of entries such as headlines, which
+/// follow the nesting of their levels.
+#[derive(Debug, Default)]
+pub(crate) struct LevelCounters {
+    /// The levels and counters of the entries which are open.
+    stack: Vec<(u8, usize)>,
+}
+
+impl LevelCounters {
+    /// Count an entry of the level, and get the counters of its number.
+    pub(crate) fn next(&mut self, level: u8) -> Vec<usize> {
+        // A closed deeper entry is a sibling if no entry of the same level is
+        // open, so the new entry follows its counter.
+        let mut closed = None;
+
+        while self.stack.last().is_some_and(|(open, _)| *open > level) {
+            closed = self.stack.pop();
+        }
+
+        match self.stack.last_mut() {
+            Some((open, count)) if *open == level => *count += 1,
+            _ => self
+                .stack
+                .push((level, closed.map_or(1, |(_, count)| count + 1))),
+        }
+
+        self.stack.iter().map(|(_, count)| *count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter() {
+        let format = |style: Counter, values: &[usize]| {
+            values
+                .iter()
+                .map(|value| style.format(*value))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            format(Counter::LowerRoman, &[0, 1, 4, 9, 14, 40, 1994, 3999, 4000]),
+            vec![
+                "0",
+                "i",
+                "iv",
+                "ix",
+                "xiv",
+                "xl",
+                "mcmxciv",
+                "mmmcmxcix",
+                "4000"
+            ]
+        );
+        assert_eq!(
+            format(Counter::UpperAlpha, &[0, 1, 26, 27, 52, 53, 702, 703]),
+            vec!["0", "A", "Z", "AA", "AZ", "BA", "ZZ", "AAA"]
+        );
+        assert_eq!(format(Counter::Decimal, &[0, 10]), vec!["0", "10"]);
+
+        for style in [
+            Counter::Decimal,
+            Counter::LowerRoman,
+            Counter::UpperRoman,
+            Counter::LowerAlpha,
+            Counter::UpperAlpha,
+        ] {
+            assert_eq!(style.to_string().parse(), Ok(style));
+        }
+    }
+
+    #[test]
+    fn test_numbering() {
+        let numbering = Numbering {
+            styles: vec![],
+            ..Default::default()
+        };
+
+        assert_eq!(numbering.format(&[1, 2]), "1.2");
+        assert_eq!(numbering.format(&[]), "");
+
+        let numbering = Numbering::default()
+            .styles(&[Counter::Decimal, Counter::LowerAlpha, Counter::LowerRoman])
+            .suffix(". ");
+
+        assert_eq!(numbering.format(&[3, 1, 4, 2]), "3.a.iv.ii. ");
+    }
+}
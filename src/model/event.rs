@@ -0,0 +1,393 @@
+//! Events of a markdown document.
+//!
+//! Events are a flat representation of [`MarkdownTree`], like the event
+//! stream of pulldown-cmark. Every [`Event::Start`] is followed by a matching
+//! [`Event::End`].
+
+use std::borrow::Cow;
+
+use super::tree::*;
+
+/// The enum to represent an event of a markdown document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// The start of an element.
+    Start(Tag),
+    /// The end of an element.
+    End(Tag),
+    /// Text. Escaped characters are text without the backslash.
+    Text(Cow<'a, str>),
+    /// A hard line break.
+    Break,
+    /// A soft line break.
+    SoftBreak,
+    /// An HTML comment, which is the text between `<!--` and `-->`.
+    Comment(Cow<'a, str>),
+}
+
+/// The enum to represent an element which contains other events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tag {
+    Paragraph,
+    Headline(u8),
+    BulletList,
+    /// An ordered list, the number of its first item and its marker.
+    OrderedList(u64, ListMarker),
+    ListItem,
+    BlockQuote,
+    Container(Vec<String>),
+    /// Attributes of the block or the inline item in it.
+    Attributed(Attributes),
+    Italic,
+    Strong,
+    Highlight,
+    Span,
+    Link {
+        url: String,
+        title: Option<String>,
+    },
+    /// A wiki link. The text in it is the label, or the target if there is
+    /// no label.
+    WikiLink {
+        target: String,
+    },
+    /// A hashtag or a mention. The text in it is the name with `#` or `@`.
+    Tag {
+        kind: TagKind,
+        name: String,
+    },
+    /// An inline footnote, which contains the text of the note.
+    Footnote,
+    /// A table and the alignments of its columns. This contains a
+    /// [`Tag::TableHead`] and [`Tag::TableRow`]s.
+    Table(Vec<Alignment>),
+    /// The header row of a table.
+    TableHead,
+    TableRow,
+    /// A cell of a table, and the numbers of the columns and the rows it
+    /// spans. Places merged into other cells have no events.
+    TableCell {
+        colspan: usize,
+        rowspan: usize,
+    },
+    /// A fenced code block, its language and the other words of its info
+    /// string. This contains the code as one [`Event::Text`].
+    CodeBlock {
+        lang: Option<String>,
+        info: Vec<(String, String)>,
+    },
+}
+
+impl<'a> MarkdownTree<'a> {
+    /// Convert into events in document order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{layer::lexer::lex, model::event::*, prelude::*};
+    ///
+    /// let input = "# Hello *world*";
+    ///
+    /// let events = Parser::default().parse(input, lex(input)).into_events();
+    ///
+    /// assert_eq!(
+    ///     events,
+    ///     vec![
+    ///         Event::Start(Tag::Headline(1)),
+    ///         Event::Text("Hello ".into()),
+    ///         Event::Start(Tag::Italic),
+    ///         Event::Text("world".into()),
+    ///         Event::End(Tag::Italic),
+    ///         Event::End(Tag::Headline(1)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn into_events(self) -> Vec<Event<'a>> {
+        let mut events = vec![];
+
+        for item in self.root.into_root() {
+            push_block(&mut events, item);
+        }
+
+        events
+    }
+}
+
+fn push_block<'a>(events: &mut Vec<Event<'a>>, item: BlockItem<'a>) {
+    let (tag, children) = match item {
+        BlockItem::Paragraph(tree) => (Tag::Paragraph, Children::Inline(tree)),
+        BlockItem::Headline(level, tree) => (Tag::Headline(level), Children::Inline(tree)),
+        BlockItem::BulletList(tree) => (Tag::BulletList, Children::List(tree)),
+        BlockItem::OrderedList(start, marker, tree) => {
+            (Tag::OrderedList(start, marker), Children::List(tree))
+        }
+        BlockItem::BlockQuote(tree) => (Tag::BlockQuote, Children::Block(tree.into_root())),
+        BlockItem::Container(names, tree) => {
+            (Tag::Container(names), Children::Block(tree.into_root()))
+        }
+        BlockItem::Attributed(attributes, item) => {
+            (Tag::Attributed(attributes), Children::Block(vec![*item]))
+        }
+        BlockItem::Comment(text) => {
+            events.push(Event::Comment(text));
+            return;
+        }
+        // Included blocks are in place of the include.
+        BlockItem::Include(_, tree) => {
+            for item in tree.into_root() {
+                push_block(events, item);
+            }
+            return;
+        }
+        BlockItem::Table(table) => {
+            push_table(events, table);
+            return;
+        }
+        BlockItem::CodeBlock(code) => {
+            let tag = Tag::CodeBlock {
+                lang: code.lang.map(Cow::into_owned),
+                info: code.info,
+            };
+
+            events.push(Event::Start(tag.clone()));
+            events.push(Event::Text(code.text));
+            events.push(Event::End(tag));
+            return;
+        }
+        // Definitions apply to the whole document, so they have no events.
+        BlockItem::Abbreviation(..) => return,
+    };
+
+    events.push(Event::Start(tag.clone()));
+
+    match children {
+        Children::Inline(tree) => push_inline(events, tree),
+        Children::Block(items) => {
+            for item in items {
+                push_block(events, item);
+            }
+        }
+        Children::List(tree) => {
+            for item in tree.into_root() {
+                events.push(Event::Start(Tag::ListItem));
+
+                push_inline(events, item.name);
+
+                for child in item.children {
+                    push_block(events, child);
+                }
+
+                events.push(Event::End(Tag::ListItem));
+            }
+        }
+    }
+
+    events.push(Event::End(tag));
+}
+
+fn push_table<'a>(events: &mut Vec<Event<'a>>, table: TableTree<'a>) {
+    let push_cells = |events: &mut Vec<Event<'a>>, cells: Vec<TableCell<'a>>| {
+        for cell in cells {
+            if let TableCell::Cell {
+                tree,
+                colspan,
+                rowspan,
+            } = cell
+            {
+                let tag = Tag::TableCell { colspan, rowspan };
+
+                events.push(Event::Start(tag.clone()));
+                push_inline(events, tree);
+                events.push(Event::End(tag));
+            }
+        }
+    };
+
+    let tag = Tag::Table(table.aligns);
+
+    events.push(Event::Start(tag.clone()));
+
+    events.push(Event::Start(Tag::TableHead));
+    push_cells(events, table.head);
+    events.push(Event::End(Tag::TableHead));
+
+    for row in table.rows {
+        events.push(Event::Start(Tag::TableRow));
+        push_cells(events, row);
+        events.push(Event::End(Tag::TableRow));
+    }
+
+    events.push(Event::End(tag));
+}
+
+fn push_inline<'a>(events: &mut Vec<Event<'a>>, tree: InlineTree<'a>) {
+    for item in tree.into_root() {
+        match item {
+            InlineItem::Text(text) | InlineItem::Escaped(text) => events.push(Event::Text(text)),
+            InlineItem::Italic(tree) => {
+                events.push(Event::Start(Tag::Italic));
+                push_inline(events, tree);
+                events.push(Event::End(Tag::Italic));
+            }
+            InlineItem::Strong(tree) => {
+                events.push(Event::Start(Tag::Strong));
+                push_inline(events, tree);
+                events.push(Event::End(Tag::Strong));
+            }
+            InlineItem::Highlight(tree) => {
+                events.push(Event::Start(Tag::Highlight));
+                push_inline(events, tree);
+                events.push(Event::End(Tag::Highlight));
+            }
+            InlineItem::Link { url, title, tree } => {
+                let tag = Tag::Link {
+                    url: url.into_owned(),
+                    title: title.map(Cow::into_owned),
+                };
+
+                events.push(Event::Start(tag.clone()));
+                push_inline(events, tree);
+                events.push(Event::End(tag));
+            }
+            InlineItem::Span(tree) => {
+                events.push(Event::Start(Tag::Span));
+                push_inline(events, tree);
+                events.push(Event::End(Tag::Span));
+            }
+            InlineItem::Attributed(attributes, item) => {
+                let tag = Tag::Attributed(attributes);
+
+                events.push(Event::Start(tag.clone()));
+                push_inline(events, InlineTree { root: vec![*item] });
+                events.push(Event::End(tag));
+            }
+            InlineItem::WikiLink { target, label } => {
+                let tag = Tag::WikiLink {
+                    target: target.to_string(),
+                };
+
+                events.push(Event::Start(tag.clone()));
+                events.push(Event::Text(label.unwrap_or(target)));
+                events.push(Event::End(tag));
+            }
+            InlineItem::Tag { kind, name } => {
+                let tag = Tag::Tag {
+                    kind,
+                    name: name.to_string(),
+                };
+
+                events.push(Event::Start(tag.clone()));
+                events.push(Event::Text(format!("{}{name}", kind.marker()).into()));
+                events.push(Event::End(tag));
+            }
+            InlineItem::Break => events.push(Event::Break),
+            InlineItem::SoftBreak => events.push(Event::SoftBreak),
+            InlineItem::Comment(text) => events.push(Event::Comment(text)),
+            InlineItem::Footnote(tree) => {
+                events.push(Event::Start(Tag::Footnote));
+                push_inline(events, tree);
+                events.push(Event::End(Tag::Footnote));
+            }
+        }
+    }
+}
+
+/// Children of a block item.
+enum Children<'a> {
+    Inline(InlineTree<'a>),
+    Block(Vec<BlockItem<'a>>),
+    List(ListTree<'a>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Markdown;
+
+    #[test]
+    fn test_events() {
+        let input = "- a {x}\n  1. \\*b\n\n> c\n> d\n\n# T {#t}";
+
+        let markdown = Markdown::default();
+
+        let events = markdown.events(input).collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Start(Tag::BulletList),
+                Event::Start(Tag::ListItem),
+                Event::Text("a {x}".into()),
+                Event::Start(Tag::OrderedList(1, ListMarker::default())),
+                Event::Start(Tag::ListItem),
+                Event::Text("*".into()),
+                Event::Text("b".into()),
+                Event::End(Tag::ListItem),
+                Event::End(Tag::OrderedList(1, ListMarker::default())),
+                Event::End(Tag::ListItem),
+                Event::End(Tag::BulletList),
+                Event::Start(Tag::BlockQuote),
+                Event::Start(Tag::Paragraph),
+                Event::Text("c".into()),
+                Event::Break,
+                Event::Text("d".into()),
+                Event::End(Tag::Paragraph),
+                Event::End(Tag::BlockQuote),
+                Event::Start(Tag::Attributed(Attributes {
+                    id: Some("t".into()),
+                    ..Default::default()
+                })),
+                Event::Start(Tag::Headline(1)),
+                Event::Text("T".into()),
+                Event::End(Tag::Headline(1)),
+                Event::End(Tag::Attributed(Attributes {
+                    id: Some("t".into()),
+                    ..Default::default()
+                })),
+            ]
+        );
+
+        let markdown = Markdown::default().parser(
+            crate::layer::parser::Parser::new().tables(crate::layer::parser::config::Tables::Piped),
+        );
+
+        let events = markdown.events("| a |\n| :-- |\n| b |").collect::<Vec<_>>();
+        let cell = Tag::TableCell {
+            colspan: 1,
+            rowspan: 1,
+        };
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Start(Tag::Table(vec![Alignment::Left])),
+                Event::Start(Tag::TableHead),
+                Event::Start(cell.clone()),
+                Event::Text("a".into()),
+                Event::End(cell.clone()),
+                Event::End(Tag::TableHead),
+                Event::Start(Tag::TableRow),
+                Event::Start(cell.clone()),
+                Event::Text("b".into()),
+                Event::End(cell),
+                Event::End(Tag::TableRow),
+                Event::End(Tag::Table(vec![Alignment::Left])),
+            ]
+        );
+
+        let events = markdown.events("```rust x=1\n*a*\n```").collect::<Vec<_>>();
+        let code = Tag::CodeBlock {
+            lang: Some("rust".to_string()),
+            info: vec![("x".to_string(), "1".to_string())],
+        };
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Start(code.clone()),
+                Event::Text("*a*\n".into()),
+                Event::End(code),
+            ]
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! This module contains the data structures used to represent an HTML.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, fmt};
+
+use super::stats::TextStats;
 
 /// The struct to represent an root HTML document.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,8 +12,254 @@ pub struct DocumentNode<'a> {
     pub root: Vec<Node<'a>>,
 }
 
+impl<'a> DocumentNode<'a> {
+    /// Take the nodes out of this document.
+    pub fn into_root(mut self) -> Vec<Node<'a>> {
+        std::mem::take(&mut self.root)
+    }
+
+    /// Count all nodes in this document.
+    pub fn node_count(&self) -> usize {
+        self.root.iter().map(|node| node.node_count()).sum()
+    }
+
+    /// Get the maximum nesting depth of this document.
+    ///
+    /// Top level nodes have depth 1. An empty document has depth 0.
+    pub fn depth(&self) -> usize {
+        self.root.iter().map(|node| node.depth()).max().unwrap_or(0)
+    }
+
+    /// Count the words and the characters of the text of this document, such
+    /// as to estimate its reading time.
+    ///
+    /// Block elements and line breaks separate words. Comments are not
+    /// counted.
+    pub fn text_stats(&self) -> TextStats {
+        self.text_stats_filtered(|_| true)
+    }
+
+    /// Count the words and the characters of the text of this document, but
+    /// not of the elements for which `filter` returns false and their
+    /// descendants.
+    ///
+    /// For example, code blocks are excluded by skipping [`ElementTag::Pre`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{layer::lexer::lex, model::html::ElementTag, prelude::*};
+    ///
+    /// let input = "Some text\n\n> A long quotation here";
+    ///
+    /// let document = Transformer::new().transform(Parser::new().parse(input, lex(input)));
+    ///
+    /// let stats = document.text_stats_filtered(|element| element.tag != ElementTag::Blockquote);
+    ///
+    /// assert_eq!(stats.words, 2);
+    /// ```
+    pub fn text_stats_filtered(&self, filter: impl Fn(&ElementNode) -> bool) -> TextStats {
+        let mut text = String::new();
+
+        push_text(&self.root, &filter, &mut text);
+
+        TextStats::of(&text)
+    }
+
+    /// Compare the structure of this document with another, such as the
+    /// expected document of a snapshot test.
+    ///
+    /// Children are compared by their positions, and attributes are compared
+    /// in the order of
+    /// [`Stringifier::stable`](crate::layer::stringifier::Stringifier::stable)
+    /// output, so classes and attributes set in another order are the same.
+    /// The differences are in document order, and are empty if the documents
+    /// are the same.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{layer::lexer::lex, model::html::*, prelude::*};
+    ///
+    /// fn document(input: &str) -> DocumentNode<'_> {
+    ///     Transformer::new().transform(Parser::new().parse(input, lex(input)))
+    /// }
+    ///
+    /// let diffs = document("# Title\n\nOld *text*").diff(&document("# Title\n\nNew *text*\n\nMore"));
+    ///
+    /// assert_eq!(
+    ///     diffs.iter().map(|diff| diff.to_string()).collect::<Vec<_>>(),
+    ///     vec!["1/0: text \"Old \" -> \"New \"", "2: added"]
+    /// );
+    /// ```
+    pub fn diff(&self, other: &DocumentNode) -> Vec<NodeDiff> {
+        let mut diffs = vec![];
+
+        diff_nodes(&self.root, &other.root, &mut vec![], &mut diffs);
+
+        diffs
+    }
+}
+
+/// A difference between two documents found by [`DocumentNode::diff`].
+///
+/// It is written like `1/0: text "a" -> "b"`, where `1/0` is the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDiff {
+    /// The indices of the children on the path to the node.
+    pub path: Vec<usize>,
+    /// The kind of the difference.
+    pub kind: DiffKind,
+}
+
+/// The kind of a [`NodeDiff`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The node is only in the other document.
+    Added,
+    /// The node is only in this document.
+    Removed,
+    /// The nodes are of different kinds, or elements of different tags.
+    Replaced,
+    /// The text of text nodes or comments is different.
+    Text { old: String, new: String },
+    /// An attribute of elements is different. Classes are compared as the
+    /// sorted `class` attribute.
+    Attr {
+        name: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl fmt::Display for NodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, child) in self.path.iter().enumerate() {
+            if index != 0 {
+                f.write_str("/")?;
+            }
+
+            write!(f, "{child}")?;
+        }
+
+        match &self.kind {
+            DiffKind::Added => f.write_str(": added"),
+            DiffKind::Removed => f.write_str(": removed"),
+            DiffKind::Replaced => f.write_str(": replaced"),
+            DiffKind::Text { old, new } => write!(f, ": text {old:?} -> {new:?}"),
+            DiffKind::Attr { name, old, new } => write!(f, ": {name} {old:?} -> {new:?}"),
+        }
+    }
+}
+
+/// Compare nodes by their positions, and push the differences.
+fn diff_nodes(old: &[Node], new: &[Node], path: &mut Vec<usize>, diffs: &mut Vec<NodeDiff>) {
+    for index in 0..old.len().max(new.len()) {
+        path.push(index);
+
+        let mut push = |kind| {
+            diffs.push(NodeDiff {
+                path: path.clone(),
+                kind,
+            })
+        };
+
+        match (old.get(index), new.get(index)) {
+            (Some(Node::Element(old)), Some(Node::Element(new))) if old.tag == new.tag => {
+                let (old_attrs, new_attrs) = (canonical_attrs(old), canonical_attrs(new));
+
+                let mut names = old_attrs.keys().chain(new_attrs.keys()).collect::<Vec<_>>();
+
+                names.sort_unstable();
+                names.dedup();
+
+                for name in names {
+                    let (old, new) = (old_attrs.get(name), new_attrs.get(name));
+
+                    if old != new {
+                        push(DiffKind::Attr {
+                            name: name.to_string(),
+                            old: old.cloned(),
+                            new: new.cloned(),
+                        });
+                    }
+                }
+
+                diff_nodes(&old.children, &new.children, path, diffs);
+            }
+            (
+                Some(Node::Text(TextNode { text: old })),
+                Some(Node::Text(TextNode { text: new })),
+            )
+            | (
+                Some(Node::Comment(CommentNode { text: old })),
+                Some(Node::Comment(CommentNode { text: new })),
+            ) => {
+                if old != new {
+                    push(DiffKind::Text {
+                        old: old.to_string(),
+                        new: new.to_string(),
+                    });
+                }
+            }
+            (Some(_), Some(_)) => push(DiffKind::Replaced),
+            (Some(_), None) => push(DiffKind::Removed),
+            (None, Some(_)) => push(DiffKind::Added),
+            (None, None) => unreachable!(),
+        }
+
+        path.pop();
+    }
+}
+
+/// Get the attributes of an element by their names, with the classes as the
+/// sorted `class` attribute.
+fn canonical_attrs<'b>(element: &'b ElementNode) -> BTreeMap<&'b str, String> {
+    let (class, attrs) = element.sorted_attrs();
+
+    let mut canonical = attrs
+        .into_iter()
+        .map(|(name, value)| (name, value.to_string()))
+        .collect::<BTreeMap<_, _>>();
+
+    if !class.is_empty() {
+        canonical.insert("class", class.join(" "));
+    }
+
+    canonical.extend(element.id.as_ref().map(|id| ("id", id.clone())));
+    canonical.extend(element.href.as_ref().map(|href| ("href", href.clone())));
+
+    canonical
+}
+
+/// Write the text of the nodes which pass the filter, separating blocks with
+/// spaces.
+fn push_text(nodes: &[Node], filter: &impl Fn(&ElementNode) -> bool, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Element(element) if !filter(element) => {}
+            Node::Element(element) => {
+                let separated = element.tag.is_block_item() || element.tag.is_void();
+
+                if separated {
+                    output.push(' ');
+                }
+
+                push_text(&element.children, filter, output);
+
+                if separated {
+                    output.push(' ');
+                }
+            }
+            Node::Text(text) => output.push_str(&text.text),
+            Node::Comment(_) => {}
+        }
+    }
+}
+
 /// The enum to represent an HTML element tag.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ElementTag {
     Div,
     Span,
@@ -26,9 +274,25 @@ pub enum ElementTag {
     Ol,
     Li,
     Blockquote,
+    Nav,
+    Section,
+    Figure,
+    Figcaption,
+    Table,
+    Thead,
+    Tbody,
+    Tr,
+    Th,
+    Td,
+    Pre,
+    Code,
     A,
     Strong,
     Em,
+    Mark,
+    Abbr,
+    Sup,
+    Button,
     Br,
 }
 
@@ -48,8 +312,26 @@ impl ElementTag {
                 | ElementTag::H4
                 | ElementTag::H5
                 | ElementTag::H6
+                | ElementTag::Nav
+                | ElementTag::Blockquote
+                | ElementTag::Section
+                | ElementTag::Figure
+                | ElementTag::Figcaption
+                | ElementTag::Table
+                | ElementTag::Thead
+                | ElementTag::Tbody
+                | ElementTag::Tr
+                | ElementTag::Th
+                | ElementTag::Td
+                | ElementTag::Pre
         )
     }
+
+    /// Whether this tag is a void element, which has no children and no end
+    /// tag.
+    pub fn is_void(&self) -> bool {
+        matches!(self, ElementTag::Br)
+    }
 }
 
 impl ElementTag {
@@ -85,6 +367,7 @@ impl ElementTag {
 pub enum Node<'a> {
     Element(ElementNode<'a>),
     Text(TextNode<'a>),
+    Comment(CommentNode<'a>),
 }
 
 impl Node<'_> {
@@ -92,36 +375,143 @@ impl Node<'_> {
     pub fn is_block_item(&self) -> bool {
         match self {
             Node::Element(element) => element.tag.is_block_item(),
-            Node::Text(_) => false,
+            Node::Text(_) | Node::Comment(_) => false,
+        }
+    }
+
+    /// Count this node and all its descendants.
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+
+        walk(std::slice::from_ref(self), &mut |event| {
+            if !matches!(event, WalkEvent::Leave(_)) {
+                count += 1;
+            }
+        });
+
+        count
+    }
+
+    /// Get the nesting depth of this node.
+    pub fn depth(&self) -> usize {
+        let (mut depth, mut max) = (0, 0);
+
+        walk(std::slice::from_ref(self), &mut |event| match event {
+            WalkEvent::Enter(_) => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            WalkEvent::Leave(_) => depth -= 1,
+            WalkEvent::Text(_) | WalkEvent::Comment(_) => max = max.max(depth + 1),
+        });
+
+        max
+    }
+}
+
+/// The enum to represent an event of walking nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkEvent<'b, 'a> {
+    /// Entering an element, before its children.
+    Enter(&'b ElementNode<'a>),
+    /// Leaving an element, after its children.
+    Leave(&'b ElementNode<'a>),
+    /// A text node.
+    Text(&'b TextNode<'a>),
+    /// A comment node.
+    Comment(&'b CommentNode<'a>),
+}
+
+/// Walk nodes and their descendants in document order.
+///
+/// This is the shared traversal of stringifiers. It keeps its own stack
+/// instead of recursing, so deeply nested nodes do not overflow the call
+/// stack.
+pub fn walk<'a, 'b>(nodes: &'b [Node<'a>], f: &mut impl FnMut(WalkEvent<'b, 'a>)) {
+    let mut stack: Vec<(std::slice::Iter<'b, Node<'a>>, Option<&'b ElementNode<'a>>)> =
+        vec![(nodes.iter(), None)];
+
+    while let Some((iter, _)) = stack.last_mut() {
+        match iter.next() {
+            Some(Node::Element(element)) => {
+                f(WalkEvent::Enter(element));
+                stack.push((element.children.iter(), Some(element)));
+            }
+            Some(Node::Text(text)) => f(WalkEvent::Text(text)),
+            Some(Node::Comment(comment)) => f(WalkEvent::Comment(comment)),
+            None => {
+                if let Some((_, Some(element))) = stack.pop() {
+                    f(WalkEvent::Leave(element));
+                }
+            }
+        }
+    }
+}
+
+/// Visit the elements in nodes and their descendants in document order,
+/// parents before their children.
+///
+/// The children of an element are visited after `f` returns `true` for it,
+/// so `f` may replace them first. Like [`walk`], this does not recurse.
+pub(crate) fn visit_elements_mut<'a>(
+    nodes: &mut [Node<'a>],
+    mut f: impl FnMut(&mut ElementNode<'a>) -> bool,
+) {
+    let mut stack = vec![nodes.iter_mut()];
+
+    while let Some(iter) = stack.last_mut() {
+        match iter.next() {
+            Some(Node::Element(element)) => {
+                if f(element) {
+                    stack.push(element.children.iter_mut());
+                }
+            }
+            Some(_) => {}
+            None => {
+                stack.pop();
+            }
         }
     }
 }
 
 /// Stringify a node.
 pub fn get_text(nodes: &[Node<'_>]) -> String {
-    nodes
-        .iter()
-        .map(|node| match node {
-            Node::Element(element) => get_text(&element.children),
-            Node::Text(text) => text.text.to_string(),
-        })
-        .collect::<Vec<_>>()
-        .join("")
+    let mut text = String::new();
+
+    walk(nodes, &mut |event| {
+        if let WalkEvent::Text(node) = event {
+            text.push_str(&node.text);
+        }
+    });
+
+    text
 }
 
+/// The name and the value of an attribute.
+pub type Attr = (Cow<'static, str>, Cow<'static, str>);
+
 /// The struct to represent an HTML element node.
+///
+/// Attributes are written in this order: `class`, `id`, `href`, and then
+/// [`attrs`](Self::attrs) in the order they are added. Use
+/// [`add_class`](Self::add_class) and [`set_attr`](Self::set_attr) to keep
+/// classes unique and each attribute written once.
+///
+/// Classes and attributes are [`Cow<'static, str>`](Cow), so the fixed names
+/// given by the transformer, such as `anchor` and `colspan`, are not
+/// allocated.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ElementNode<'a> {
     /// The tag of this element.
     pub tag: ElementTag,
     /// The id of this element.
-    pub id: Vec<String>,
-    /// The classes of this element.
-    pub class: Vec<String>,
+    pub id: Option<String>,
+    /// The classes of this element, without duplicates.
+    pub class: Vec<Cow<'static, str>>,
     /// The href of this element.
     pub href: Option<String>,
     /// The attributes of this element.
-    pub attrs: Vec<(String, String)>,
+    pub attrs: Vec<Attr>,
     /// The children of this element.
     pub children: Vec<Node<'a>>,
 }
@@ -130,7 +520,7 @@ impl Default for ElementNode<'_> {
     fn default() -> Self {
         Self {
             tag: ElementTag::Div,
-            id: vec![],
+            id: None,
             class: vec![],
             href: None,
             attrs: vec![],
@@ -139,8 +529,212 @@ impl Default for ElementNode<'_> {
     }
 }
 
+impl ElementNode<'_> {
+    /// Add a class if the element does not have it.
+    pub fn add_class(&mut self, class: impl Into<Cow<'static, str>>) {
+        let class = class.into();
+
+        if !self.class.contains(&class) {
+            self.class.push(class);
+        }
+    }
+
+    /// Get the classes sorted without duplicates, and the other attributes
+    /// sorted by name. The last value of a name is used.
+    ///
+    /// This is the order of attributes in
+    /// [`Stringifier::stable`](crate::layer::stringifier::Stringifier::stable)
+    /// output.
+    pub(crate) fn sorted_attrs(&self) -> (Vec<&str>, Vec<(&str, &str)>) {
+        let mut class = self.class.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+
+        class.sort_unstable();
+        class.dedup();
+
+        let mut attrs = Vec::<(&str, &str)>::new();
+
+        for (name, value) in &self.attrs {
+            match attrs.iter_mut().find(|(other, _)| other == name) {
+                Some(attr) => attr.1 = value,
+                None => attrs.push((name, value)),
+            }
+        }
+
+        attrs.sort_by_key(|(name, _)| *name);
+
+        (class, attrs)
+    }
+
+    /// Whether the element has the attribute, including `class`, `id` and
+    /// `href`.
+    pub fn has_attr(&self, key: &str) -> bool {
+        match key {
+            "class" => !self.class.is_empty(),
+            "id" => self.id.is_some(),
+            "href" => self.href.is_some(),
+            _ => self.attrs.iter().any(|(k, _)| k == key),
+        }
+    }
+
+    /// Set an attribute, replacing the old value.
+    ///
+    /// `id` and `href` are set to their fields, and the classes of `class`
+    /// are added to the existing classes.
+    pub fn set_attr(&mut self, key: &str, value: &str) {
+        match key {
+            "class" => value
+                .split_whitespace()
+                .for_each(|class| self.add_class(class.to_string())),
+            "id" => self.id = Some(value.to_string()),
+            "href" => self.href = Some(value.to_string()),
+            _ => match self.attrs.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => *v = value.to_string().into(),
+                None => self
+                    .attrs
+                    .push((key.to_string().into(), value.to_string().into())),
+            },
+        }
+    }
+}
+
+/// Drop the nodes and their descendants with a stack on the heap instead of
+/// recursion, so that dropping a deeply nested document cannot overflow the
+/// stack.
+///
+/// The children of an element are taken before the element is dropped, so
+/// dropping it does not recurse.
+fn drop_nodes(mut stack: Vec<Node<'_>>) {
+    while let Some(node) = stack.pop() {
+        if let Node::Element(mut element) = node {
+            stack.append(&mut element.children);
+        }
+    }
+}
+
+impl Drop for DocumentNode<'_> {
+    fn drop(&mut self) {
+        drop_nodes(std::mem::take(&mut self.root));
+    }
+}
+
 /// The struct to represent an HTML text node.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TextNode<'a> {
     pub text: Cow<'a, str>,
 }
+
+/// The struct to represent an HTML comment node.
+///
+/// The text is written between `<!--` and `-->`. `-->` and `--!>` in it are
+/// broken, so the text cannot close the comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentNode<'a> {
+    pub text: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_element_attrs() {
+        let mut element = ElementNode::default();
+
+        element.set_attr("class", "a b");
+        element.add_class("a");
+        element.set_attr("id", "x");
+        element.set_attr("id", "y");
+        element.set_attr("data-a", "1");
+        element.set_attr("data-a", "2");
+
+        assert_eq!(element.class, vec!["a", "b"]);
+        assert_eq!(element.id.as_deref(), Some("y"));
+        assert_eq!(element.attrs, vec![("data-a".into(), "2".into())]);
+        assert!(element.has_attr("id"));
+        assert!(!element.has_attr("href"));
+    }
+
+    #[test]
+    fn test_diff() {
+        let element = |tag, class: &[&str], attrs: &[(&str, &str)], children| {
+            let mut element = ElementNode {
+                tag,
+                children,
+                ..Default::default()
+            };
+
+            for class in class {
+                element.add_class(class.to_string());
+            }
+
+            for (name, value) in attrs {
+                element.set_attr(name, value);
+            }
+
+            Node::Element(element)
+        };
+
+        let text = |text| {
+            Node::Text(TextNode {
+                text: Cow::Borrowed(text),
+            })
+        };
+
+        let old = DocumentNode {
+            root: vec![
+                element(
+                    ElementTag::P,
+                    &["a", "b"],
+                    &[("id", "x"), ("lang", "en"), ("dir", "ltr")],
+                    vec![text("Same")],
+                ),
+                element(ElementTag::Ul, &[], &[("data-a", "1")], vec![text("a")]),
+                Node::Comment(CommentNode { text: "c".into() }),
+            ],
+        };
+
+        let same = DocumentNode {
+            root: vec![
+                element(
+                    ElementTag::P,
+                    &["b", "a"],
+                    &[("dir", "ltr"), ("lang", "en"), ("id", "x")],
+                    vec![text("Same")],
+                ),
+                element(ElementTag::Ul, &[], &[("data-a", "1")], vec![text("a")]),
+                Node::Comment(CommentNode { text: "c".into() }),
+            ],
+        };
+
+        assert_eq!(old.diff(&same), vec![]);
+
+        let new = DocumentNode {
+            root: vec![
+                element(
+                    ElementTag::P,
+                    &["a"],
+                    &[("id", "y"), ("href", "/")],
+                    vec![text("Same"), text("More")],
+                ),
+                element(ElementTag::Ol, &[], &[], vec![]),
+            ],
+        };
+
+        assert_eq!(
+            old.diff(&new)
+                .iter()
+                .map(|diff| diff.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "0: class Some(\"a b\") -> Some(\"a\")",
+                "0: dir Some(\"ltr\") -> None",
+                "0: href None -> Some(\"/\")",
+                "0: id Some(\"x\") -> Some(\"y\")",
+                "0: lang Some(\"en\") -> None",
+                "0/1: added",
+                "1: replaced",
+                "2: removed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Links found in a document.
+
+use std::ops::Range;
+
+/// The struct to represent a link in the input, such as `[text](url)` or
+/// `[[Page]]`.
+///
+/// Links are returned by
+/// [`Markdown::extract_links`](crate::Markdown::extract_links).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRef {
+    /// The kind of the link.
+    pub kind: LinkKind,
+    /// The destination of a link, or the target of a wiki link.
+    pub target: String,
+    /// The title of a link.
+    pub title: Option<String>,
+    /// The plain text of the link. This is the target of a wiki link without
+    /// a label.
+    pub text: String,
+    /// The byte range of the link in the input.
+    pub span: Range<usize>,
+}
+
+/// The kind of a [`LinkRef`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A link such as `[text](url)`.
+    Link,
+    /// A wiki link such as `[[Page]]` or `[[Page|label]]`.
+    WikiLink,
+}
@@ -0,0 +1,60 @@
+//! Messages for text generated by the layers.
+
+/// The struct to hold text which is generated by the layers rather than
+/// written in the document.
+///
+/// Override the fields to translate the output. These are all the labels
+/// of the table of contents, footnotes and figures, and
+/// [`Markdown::messages`](crate::Markdown::messages) sets them for all layers
+/// at once.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::prelude::*;
+///
+/// let messages = Messages {
+///     toc_label: "Inhaltsverzeichnis".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let markdown = Markdown::default()
+///     .messages(messages)
+///     .toc_maker(TocMaker::default().wrapper(ElementTag::Nav, &[]).heading(ElementTag::H2));
+///
+/// let (_, toc) = markdown.execute_with_toc("# A");
+///
+/// assert_eq!(toc, "<nav aria-label=\"Inhaltsverzeichnis\"><h2>Inhaltsverzeichnis</h2><ul><li><a href=\"#a\">A</a></li></ul></nav>");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Messages {
+    /// The label of the table of contents, used for the heading and the
+    /// `aria-label` of the wrapper. Default is `Table of contents`.
+    pub toc_label: String,
+    /// The mark put after an excerpt which is cut in the middle of the
+    /// document. Default is `…`.
+    pub ellipsis: String,
+    /// The headline of the section of inline footnotes, if
+    /// [`Transformer::footnotes_heading`](crate::layer::transformer::Transformer::footnotes_heading)
+    /// is set. Default is `Footnotes`.
+    pub footnotes_label: String,
+    /// The text of the links from footnotes back to their references.
+    /// Default is `↩`.
+    pub footnote_backref: String,
+    /// The word before the number of a figure in the list of figures, if
+    /// [`FigureListMaker::numbered`](crate::layer::figures::FigureListMaker::numbered)
+    /// is enabled. Default is `Figure`.
+    pub figure_label: String,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self {
+            toc_label: "Table of contents".to_string(),
+            ellipsis: "…".to_string(),
+            footnotes_label: "Footnotes".to_string(),
+            footnote_backref: "↩".to_string(),
+            figure_label: "Figure".to_string(),
+        }
+    }
+}
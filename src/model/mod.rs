@@ -1,6 +1,11 @@
 //! The model module contains the data structures used to parse and
 //! transform the markdown.
 
+pub mod counter;
+pub mod event;
 pub mod html;
+pub mod link;
+pub mod messages;
+pub mod stats;
 pub mod token;
 pub mod tree;
@@ -0,0 +1,132 @@
+//! Render and text statistics.
+
+/// The struct to represent counters collected while rendering a document.
+///
+/// This is useful to monitor the complexity of the content and to detect
+/// pathological documents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of tokens produced by the lexer.
+    pub tokens: usize,
+    /// The number of nodes in the HTML tree.
+    pub nodes: usize,
+    /// The maximum nesting depth of the HTML tree.
+    pub depth: usize,
+    /// The length of the output in bytes.
+    pub bytes: usize,
+}
+
+/// The struct to represent counts of the text of a document.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{layer::lexer::lex, prelude::*};
+///
+/// let input = "# Hello, *world*!\n\nこんにちは 2024-01-01";
+///
+/// let document = Transformer::new().transform(Parser::new().parse(input, lex(input)));
+///
+/// let stats = document.text_stats();
+///
+/// assert_eq!(stats.words, 8);
+/// assert_eq!(stats.characters, 27);
+/// assert_eq!(stats.reading_minutes(200), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStats {
+    /// The number of words. A CJK character is a word by itself, since
+    /// those languages do not put spaces between words. A run of other
+    /// characters without a letter or a digit, such as `-`, is not a word.
+    pub words: usize,
+    /// The number of characters which are not white spaces.
+    pub characters: usize,
+}
+
+impl TextStats {
+    /// Count the words and the characters of the text.
+    pub fn of(text: &str) -> Self {
+        Self {
+            words: word_ends(text).len(),
+            characters: text.chars().filter(|c| !c.is_whitespace()).count(),
+        }
+    }
+
+    /// Estimate the reading time in minutes at the words per minute, such as
+    /// 200 for English prose. It is rounded up, so text of a few words takes
+    /// a minute. Empty text takes no time.
+    pub fn reading_minutes(&self, words_per_minute: usize) -> usize {
+        self.words.div_ceil(words_per_minute.max(1))
+    }
+}
+
+/// Get the byte offsets of the ends of the words of the text, as counted by
+/// [`TextStats::words`].
+pub(crate) fn word_ends(text: &str) -> Vec<usize> {
+    let mut ends = vec![];
+
+    // The end of the current run of characters which are not white spaces
+    // or CJK characters, if it has a letter or a digit.
+    let mut word: Option<usize> = None;
+
+    for (index, c) in text.char_indices() {
+        if c.is_whitespace() || is_cjk(c) {
+            ends.extend(word.take());
+        }
+
+        if is_cjk(c) {
+            ends.push(index + c.len_utf8());
+        } else if !c.is_whitespace() && (word.is_some() || c.is_alphanumeric()) {
+            word = Some(index + c.len_utf8());
+        }
+    }
+
+    ends.extend(word);
+
+    ends
+}
+
+/// Check if the character is a CJK ideograph, kana or a Hangul syllable.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{ac00}'..='\u{d7af}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{20000}'..='\u{2fa1f}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_stats() {
+        for (text, words, characters) in [
+            ("", 0, 0),
+            ("  one two\tthree\n", 3, 11),
+            ("a - b — c", 3, 5),
+            ("日本語のtext", 5, 8),
+            ("漢字、かな。", 4, 6),
+            ("don't e-mail 3.14", 3, 15),
+        ] {
+            assert_eq!(
+                TextStats::of(text),
+                TextStats { words, characters },
+                "{text:?}"
+            );
+        }
+
+        let stats = TextStats {
+            words: 401,
+            characters: 0,
+        };
+
+        assert_eq!(stats.reading_minutes(200), 3);
+        assert_eq!(stats.reading_minutes(0), 401);
+        assert_eq!(TextStats::default().reading_minutes(200), 0);
+    }
+}
@@ -21,6 +21,78 @@ impl Token {
     pub fn range(&self) -> std::ops::Range<usize> {
         self.start..self.start + self.len
     }
+
+    /// Get the position of the start of the token.
+    pub fn position(&self, index: &LineIndex) -> Position {
+        index.position(self.start)
+    }
+}
+
+/// The struct to represent a position in the source.
+///
+/// Lines and columns start from 1. Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number.
+    pub line: usize,
+    /// The column number.
+    pub column: usize,
+}
+
+/// The struct to convert byte offsets in the source into positions.
+///
+/// The start of each line is found once when this is created, so each
+/// conversion is a binary search.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{layer::lexer::lex, prelude::*};
+///
+/// let input = "# Title\n\nあ *b*";
+///
+/// let index = LineIndex::new(input);
+///
+/// let star = lex(input).filter(|token| token.kind == TokenKind::Star).next().unwrap();
+///
+/// assert_eq!(star.position(&index), Position { line: 3, column: 3 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    input: &'a str,
+    /// The byte offsets of the start of each line.
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of the source.
+    pub fn new(input: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { input, starts }
+    }
+
+    /// Convert a byte offset into a position.
+    ///
+    /// An offset after the end is treated as the end, and an offset inside a
+    /// character is treated as the start of the character.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.input.len());
+
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+
+        Position {
+            line,
+            column: self.input[start..offset].chars().count() + 1,
+        }
+    }
 }
 
 /// The enum to represent a token kind.
@@ -34,6 +106,10 @@ pub enum TokenKind {
     Break,
     /// "\\"
     Backslash,
+    /// "\\" followed by an escapable character.
+    ///
+    /// The range contains both the backslash and the escaped character.
+    Escaped,
     /// "#"
     Pound,
     /// "*"
@@ -62,4 +138,60 @@ pub enum TokenKind {
     OpenBracket,
     /// "]"
     CloseBracket,
+    /// "!"
+    Exclamation,
+    /// "~"
+    Tilde,
+    /// "="
+    Equal,
+    /// "_"
+    Underscore,
+    /// "<"
+    Lt,
+    /// "+"
+    Plus,
+    /// "$"
+    Dollar,
+    /// "^"
+    Caret,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let input = "ab\r\n\nあい\n";
+        let index = LineIndex::new(input);
+
+        let positions = [0, 2, 4, 5, 6, 8, 9, 12, 13, 100]
+            .into_iter()
+            .map(|offset| {
+                let position = index.position(offset);
+                (position.line, position.column)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            positions,
+            vec![
+                (1, 1),
+                (1, 3),
+                (2, 1),
+                (3, 1),
+                (3, 1),
+                (3, 2),
+                (3, 2),
+                (4, 1),
+                (4, 1),
+                (4, 1)
+            ]
+        );
+
+        assert_eq!(
+            LineIndex::new("").position(0),
+            Position { line: 1, column: 1 }
+        );
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::borrow::Cow;
 
+use super::counter::Counter;
+
 /// The struct to represent a root markdown document.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MarkdownTree<'a> {
@@ -20,9 +22,91 @@ pub enum BlockItem<'a> {
     Paragraph(InlineTree<'a>),
     Headline(u8, InlineTree<'a>),
     BulletList(ListTree<'a>),
-    OrderedList(ListTree<'a>),
+    /// An ordered list, the number of its first item and its marker.
+    OrderedList(u64, ListMarker, ListTree<'a>),
     BlockQuote(BlockTree<'a>),
     Container(Vec<String>, BlockTree<'a>),
+    /// A block item with attributes given by an attribute block.
+    Attributed(Attributes, Box<BlockItem<'a>>),
+    /// A paragraph of only an HTML comment. This contains the text between
+    /// `<!--` and `-->`.
+    Comment(Cow<'a, str>),
+    /// The blocks of another document included by `![[target]]`, and the
+    /// target.
+    Include(Cow<'a, str>, BlockTree<'a>),
+    /// An abbreviation definition such as
+    /// `*[HTML]: HyperText Markup Language`, the abbreviation and its
+    /// expansion.
+    Abbreviation(Cow<'a, str>, Cow<'a, str>),
+    /// A table such as `| a | b |`.
+    Table(TableTree<'a>),
+    /// A fenced code block such as ` ```rust `.
+    CodeBlock(CodeBlock<'a>),
+}
+
+/// The struct to represent a fenced code block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock<'a> {
+    /// The language, the first word of the info string without `=`.
+    pub lang: Option<Cow<'a, str>>,
+    /// The other words of the info string, such as `linenos` or
+    /// `hl_lines="2-3"`, as keys and values. A word without `=` has an empty
+    /// value.
+    pub info: Vec<(String, String)>,
+    /// The content, each line of which ends with a line break.
+    pub text: Cow<'a, str>,
+}
+
+/// The struct to represent a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableTree<'a> {
+    /// The alignments of the columns.
+    pub aligns: Vec<Alignment>,
+    /// The cells of the header row.
+    pub head: Vec<TableCell<'a>>,
+    /// The body rows, which have as many cells as the header row.
+    pub rows: Vec<Vec<TableCell<'a>>>,
+}
+
+/// The enum to represent a place in a row of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableCell<'a> {
+    /// A cell, and the numbers of the columns and the rows it spans.
+    Cell {
+        tree: InlineTree<'a>,
+        colspan: usize,
+        rowspan: usize,
+    },
+    /// A place merged into the cell on its left by `||`.
+    MergedLeft,
+    /// A place merged into the cell above by `^^`.
+    MergedUp,
+}
+
+/// The alignment of a column of a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// `---`
+    #[default]
+    None,
+    /// `:--`
+    Left,
+    /// `:-:`
+    Center,
+    /// `--:`
+    Right,
+}
+
+/// The struct to represent attributes given by an attribute block such as
+/// `{#id .class key=value}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    /// The id of the element.
+    pub id: Option<String>,
+    /// The classes of the element.
+    pub classes: Vec<String>,
+    /// The other attributes of the element.
+    pub attrs: Vec<(String, String)>,
 }
 
 /// The struct to represent a list tree.
@@ -50,7 +134,469 @@ pub struct InlineTree<'a> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InlineItem<'a> {
     Text(Cow<'a, str>),
+    /// A character escaped by a backslash. This contains the character without
+    /// the backslash.
+    Escaped(Cow<'a, str>),
     Italic(InlineTree<'a>),
     Strong(InlineTree<'a>),
+    /// A highlight such as `==text==`.
+    Highlight(InlineTree<'a>),
+    /// A link such as `[text](url "title")`.
+    Link {
+        url: Cow<'a, str>,
+        title: Option<Cow<'a, str>>,
+        tree: InlineTree<'a>,
+    },
+    /// A text in brackets which is given attributes, such as
+    /// `[text]{.class}`.
+    Span(InlineTree<'a>),
+    /// An inline item with attributes given by an attribute block right after
+    /// it, such as `*text*{.class}`.
+    Attributed(Attributes, Box<InlineItem<'a>>),
+    /// A wiki link such as `[[target|label]]`.
+    WikiLink {
+        target: Cow<'a, str>,
+        label: Option<Cow<'a, str>>,
+    },
     Break,
+    /// A hashtag such as `#rust` or a mention such as `@alice`. The name does
+    /// not contain `#` or `@`.
+    Tag {
+        kind: TagKind,
+        name: Cow<'a, str>,
+    },
+    /// A line break which is not a hard break.
+    SoftBreak,
+    /// An HTML comment. This contains the text between `<!--` and `-->`.
+    Comment(Cow<'a, str>),
+    /// An inline footnote such as `^[text]`.
+    Footnote(InlineTree<'a>),
+}
+
+/// The kind of [`InlineItem::Tag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagKind {
+    /// `#name`
+    Hashtag,
+    /// `@name`
+    Mention,
+}
+
+impl TagKind {
+    /// Get the character before the name.
+    pub fn marker(&self) -> char {
+        match self {
+            Self::Hashtag => '#',
+            Self::Mention => '@',
+        }
+    }
+}
+
+/// The marker of the items of an ordered list, such as `1.` or `a)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListMarker {
+    /// How the items are numbered. Only [`Counter::Decimal`], the alphabetic
+    /// and the roman counters are parsed.
+    pub counter: Counter,
+    /// The character after the number.
+    pub delimiter: ListDelimiter,
+}
+
+/// The character after the number of an ordered list item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ListDelimiter {
+    /// `.`
+    #[default]
+    Period,
+    /// `)`
+    Paren,
+}
+
+impl ListDelimiter {
+    /// Get the character of the delimiter.
+    pub fn char(&self) -> char {
+        match self {
+            Self::Period => '.',
+            Self::Paren => ')',
+        }
+    }
+}
+
+/// An item of a markdown tree waiting to be dropped by [`drop_items`].
+enum Item<'a> {
+    Block(BlockItem<'a>),
+    Inline(InlineItem<'a>),
+}
+
+/// Drop the items and their descendants with a stack on the heap instead of
+/// recursion, so that dropping a deeply nested tree cannot overflow the
+/// stack.
+///
+/// The trees of an item are emptied before the item is dropped, so their own
+/// `Drop` does nothing more.
+fn drop_items(mut stack: Vec<Item<'_>>) {
+    while let Some(item) = stack.pop() {
+        match item {
+            Item::Block(item) => match item {
+                BlockItem::Paragraph(tree) | BlockItem::Headline(_, tree) => {
+                    stack.extend(tree.into_root().into_iter().map(Item::Inline))
+                }
+                BlockItem::BulletList(tree) | BlockItem::OrderedList(_, _, tree) => {
+                    list_items(&mut stack, tree.into_root())
+                }
+                BlockItem::BlockQuote(tree)
+                | BlockItem::Container(_, tree)
+                | BlockItem::Include(_, tree) => {
+                    stack.extend(tree.into_root().into_iter().map(Item::Block))
+                }
+                BlockItem::Attributed(_, item) => stack.push(Item::Block(*item)),
+                BlockItem::Table(table) => {
+                    for cell in table
+                        .head
+                        .into_iter()
+                        .chain(table.rows.into_iter().flatten())
+                    {
+                        if let TableCell::Cell { tree, .. } = cell {
+                            stack.extend(tree.into_root().into_iter().map(Item::Inline));
+                        }
+                    }
+                }
+                BlockItem::Comment(_) | BlockItem::Abbreviation(..) | BlockItem::CodeBlock(_) => {}
+            },
+            Item::Inline(item) => match item {
+                InlineItem::Italic(tree)
+                | InlineItem::Strong(tree)
+                | InlineItem::Highlight(tree)
+                | InlineItem::Span(tree)
+                | InlineItem::Footnote(tree)
+                | InlineItem::Link { tree, .. } => {
+                    stack.extend(tree.into_root().into_iter().map(Item::Inline))
+                }
+                InlineItem::Attributed(_, item) => stack.push(Item::Inline(*item)),
+                InlineItem::Text(_)
+                | InlineItem::Escaped(_)
+                | InlineItem::WikiLink { .. }
+                | InlineItem::Break
+                | InlineItem::Tag { .. }
+                | InlineItem::SoftBreak
+                | InlineItem::Comment(_) => {}
+            },
+        }
+    }
+}
+
+/// Push the names and the children of the list items to the stack of
+/// [`drop_items`].
+fn list_items<'a>(stack: &mut Vec<Item<'a>>, items: Vec<ListItem<'a>>) {
+    for item in items {
+        stack.extend(item.name.into_root().into_iter().map(Item::Inline));
+        stack.extend(item.children.into_iter().map(Item::Block));
+    }
+}
+
+impl Drop for BlockTree<'_> {
+    fn drop(&mut self) {
+        if self.root.iter().all(|item| {
+            matches!(
+                item,
+                BlockItem::Comment(_) | BlockItem::Abbreviation(..) | BlockItem::CodeBlock(_)
+            )
+        }) {
+            return;
+        }
+
+        drop_items(
+            std::mem::take(&mut self.root)
+                .into_iter()
+                .map(Item::Block)
+                .collect(),
+        );
+    }
+}
+
+impl Drop for ListTree<'_> {
+    fn drop(&mut self) {
+        if self.root.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![];
+
+        list_items(&mut stack, std::mem::take(&mut self.root));
+        drop_items(stack);
+    }
+}
+
+impl Drop for InlineTree<'_> {
+    fn drop(&mut self) {
+        if self.root.iter().all(|item| {
+            matches!(
+                item,
+                InlineItem::Text(_)
+                    | InlineItem::Escaped(_)
+                    | InlineItem::WikiLink { .. }
+                    | InlineItem::Break
+                    | InlineItem::Tag { .. }
+                    | InlineItem::SoftBreak
+                    | InlineItem::Comment(_)
+            )
+        }) {
+            return;
+        }
+
+        drop_items(
+            std::mem::take(&mut self.root)
+                .into_iter()
+                .map(Item::Inline)
+                .collect(),
+        );
+    }
+}
+
+impl MarkdownTree<'_> {
+    /// Convert into a tree which does not borrow the source.
+    pub fn into_owned(self) -> MarkdownTree<'static> {
+        MarkdownTree {
+            root: self.root.into_owned(),
+        }
+    }
+}
+
+impl<'a> BlockTree<'a> {
+    /// Take the items out of this tree.
+    pub fn into_root(mut self) -> Vec<BlockItem<'a>> {
+        std::mem::take(&mut self.root)
+    }
+
+    /// Convert into a tree which does not borrow the source.
+    pub fn into_owned(self) -> BlockTree<'static> {
+        BlockTree {
+            root: self
+                .into_root()
+                .into_iter()
+                .map(BlockItem::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl BlockItem<'_> {
+    /// Convert into an item which does not borrow the source.
+    pub fn into_owned(self) -> BlockItem<'static> {
+        match self {
+            BlockItem::Paragraph(tree) => BlockItem::Paragraph(tree.into_owned()),
+            BlockItem::Headline(level, tree) => BlockItem::Headline(level, tree.into_owned()),
+            BlockItem::BulletList(tree) => BlockItem::BulletList(tree.into_owned()),
+            BlockItem::OrderedList(start, marker, tree) => {
+                BlockItem::OrderedList(start, marker, tree.into_owned())
+            }
+            BlockItem::BlockQuote(tree) => BlockItem::BlockQuote(tree.into_owned()),
+            BlockItem::Container(names, tree) => BlockItem::Container(names, tree.into_owned()),
+            BlockItem::Attributed(attributes, item) => {
+                BlockItem::Attributed(attributes, Box::new(item.into_owned()))
+            }
+            BlockItem::Comment(text) => BlockItem::Comment(Cow::Owned(text.into_owned())),
+            BlockItem::Include(target, tree) => {
+                BlockItem::Include(Cow::Owned(target.into_owned()), tree.into_owned())
+            }
+            BlockItem::Table(table) => BlockItem::Table(table.into_owned()),
+            BlockItem::Abbreviation(abbreviation, expansion) => BlockItem::Abbreviation(
+                Cow::Owned(abbreviation.into_owned()),
+                Cow::Owned(expansion.into_owned()),
+            ),
+            BlockItem::CodeBlock(code) => BlockItem::CodeBlock(code.into_owned()),
+        }
+    }
+
+    /// Count the headlines in this item and its descendants.
+    pub(crate) fn headline_count(&self) -> usize {
+        let blocks = |items: &[BlockItem]| items.iter().map(BlockItem::headline_count).sum();
+
+        match self {
+            BlockItem::Headline(..) => 1,
+            BlockItem::Paragraph(_)
+            | BlockItem::Comment(_)
+            | BlockItem::Abbreviation(..)
+            | BlockItem::Table(_)
+            | BlockItem::CodeBlock(_) => 0,
+            BlockItem::BulletList(tree) | BlockItem::OrderedList(_, _, tree) => {
+                tree.root.iter().map(|item| blocks(&item.children)).sum()
+            }
+            BlockItem::BlockQuote(tree)
+            | BlockItem::Container(_, tree)
+            | BlockItem::Include(_, tree) => blocks(&tree.root),
+            BlockItem::Attributed(_, item) => item.headline_count(),
+        }
+    }
+}
+
+impl<'a> ListTree<'a> {
+    /// Take the items out of this tree.
+    pub fn into_root(mut self) -> Vec<ListItem<'a>> {
+        std::mem::take(&mut self.root)
+    }
+
+    /// Convert into a tree which does not borrow the source.
+    pub fn into_owned(self) -> ListTree<'static> {
+        ListTree {
+            root: self
+                .into_root()
+                .into_iter()
+                .map(|item| ListItem {
+                    name: item.name.into_owned(),
+                    children: item
+                        .children
+                        .into_iter()
+                        .map(BlockItem::into_owned)
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TableTree<'_> {
+    /// Convert into a tree which does not borrow the source.
+    pub fn into_owned(self) -> TableTree<'static> {
+        TableTree {
+            aligns: self.aligns,
+            head: self.head.into_iter().map(TableCell::into_owned).collect(),
+            rows: self
+                .rows
+                .into_iter()
+                .map(|row| row.into_iter().map(TableCell::into_owned).collect())
+                .collect(),
+        }
+    }
+}
+
+impl CodeBlock<'_> {
+    /// Convert into a code block which does not borrow the source.
+    pub fn into_owned(self) -> CodeBlock<'static> {
+        CodeBlock {
+            lang: self.lang.map(|lang| Cow::Owned(lang.into_owned())),
+            info: self.info,
+            text: Cow::Owned(self.text.into_owned()),
+        }
+    }
+}
+
+impl<'a> TableCell<'a> {
+    /// Create a cell which spans one column and one row.
+    pub fn new(tree: InlineTree<'a>) -> Self {
+        TableCell::Cell {
+            tree,
+            colspan: 1,
+            rowspan: 1,
+        }
+    }
+
+    /// Convert into a cell which does not borrow the source.
+    pub fn into_owned(self) -> TableCell<'static> {
+        match self {
+            TableCell::Cell {
+                tree,
+                colspan,
+                rowspan,
+            } => TableCell::Cell {
+                tree: tree.into_owned(),
+                colspan,
+                rowspan,
+            },
+            TableCell::MergedLeft => TableCell::MergedLeft,
+            TableCell::MergedUp => TableCell::MergedUp,
+        }
+    }
+}
+
+impl<'a> InlineTree<'a> {
+    /// Take the items out of this tree.
+    pub fn into_root(mut self) -> Vec<InlineItem<'a>> {
+        std::mem::take(&mut self.root)
+    }
+
+    /// Convert into a tree which does not borrow the source.
+    pub fn into_owned(self) -> InlineTree<'static> {
+        InlineTree {
+            root: self
+                .into_root()
+                .into_iter()
+                .map(InlineItem::into_owned)
+                .collect(),
+        }
+    }
+
+    /// Get the text without markup. Line breaks are replaced with spaces, and
+    /// comments are removed.
+    pub fn plain_text(&self) -> String {
+        let mut output = String::new();
+
+        for item in &self.root {
+            item.push_plain_text(&mut output);
+        }
+
+        output
+    }
+}
+
+impl InlineItem<'_> {
+    fn push_plain_text(&self, output: &mut String) {
+        match self {
+            InlineItem::Text(text) | InlineItem::Escaped(text) => output.push_str(text),
+            InlineItem::Italic(tree)
+            | InlineItem::Strong(tree)
+            | InlineItem::Highlight(tree)
+            | InlineItem::Span(tree)
+            | InlineItem::Link { tree, .. } => {
+                for item in &tree.root {
+                    item.push_plain_text(output);
+                }
+            }
+            InlineItem::Attributed(_, item) => item.push_plain_text(output),
+            InlineItem::WikiLink { target, label } => {
+                output.push_str(label.as_ref().unwrap_or(target))
+            }
+            InlineItem::Tag { kind, name } => {
+                output.push(kind.marker());
+                output.push_str(name);
+            }
+            InlineItem::Break | InlineItem::SoftBreak => output.push(' '),
+            // A footnote is not a part of the text around it.
+            InlineItem::Comment(_) | InlineItem::Footnote(_) => {}
+        }
+    }
+}
+
+impl InlineItem<'_> {
+    /// Convert into an item which does not borrow the source.
+    pub fn into_owned(self) -> InlineItem<'static> {
+        match self {
+            InlineItem::Text(text) => InlineItem::Text(Cow::Owned(text.into_owned())),
+            InlineItem::Escaped(text) => InlineItem::Escaped(Cow::Owned(text.into_owned())),
+            InlineItem::Italic(tree) => InlineItem::Italic(tree.into_owned()),
+            InlineItem::Strong(tree) => InlineItem::Strong(tree.into_owned()),
+            InlineItem::Highlight(tree) => InlineItem::Highlight(tree.into_owned()),
+            InlineItem::Footnote(tree) => InlineItem::Footnote(tree.into_owned()),
+            InlineItem::Link { url, title, tree } => InlineItem::Link {
+                url: Cow::Owned(url.into_owned()),
+                title: title.map(|title| Cow::Owned(title.into_owned())),
+                tree: tree.into_owned(),
+            },
+            InlineItem::Span(tree) => InlineItem::Span(tree.into_owned()),
+            InlineItem::Attributed(attributes, item) => {
+                InlineItem::Attributed(attributes, Box::new(item.into_owned()))
+            }
+            InlineItem::WikiLink { target, label } => InlineItem::WikiLink {
+                target: Cow::Owned(target.into_owned()),
+                label: label.map(|label| Cow::Owned(label.into_owned())),
+            },
+            InlineItem::Tag { kind, name } => InlineItem::Tag {
+                kind,
+                name: Cow::Owned(name.into_owned()),
+            },
+            InlineItem::Break => InlineItem::Break,
+            InlineItem::SoftBreak => InlineItem::SoftBreak,
+            InlineItem::Comment(text) => InlineItem::Comment(Cow::Owned(text.into_owned())),
+        }
+    }
 }
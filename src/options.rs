@@ -0,0 +1,827 @@
+//! Options of all layers in one struct.
+//!
+//! [`MarkdownOptions`] holds the settings of [`Parser`], [`Transformer`],
+//! [`Stringifier`] and [`TocMaker`] which can be written as data. With the
+//! `serde` feature, it can be loaded from a configuration file such as TOML
+//! or JSON. Enum values are written in their string forms, such as
+//! `common-mark` or `space:4`.
+//!
+//! Every option is optional, and a missing option keeps the default value of
+//! the layer, or of the [`Profile`] if one is set.
+//!
+//! ```toml
+//! profile = "notes"
+//!
+//! [parser]
+//! dialect = "common-mark"
+//! max_nesting = 32
+//!
+//! [stringifier]
+//! escape = true
+//! void_style = "xhtml"
+//! ```
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ParseConfigError,
+    layer::{
+        metadata::FrontMatter,
+        parser::{config::*, Parser},
+        stringifier::{config::*, Stringifier},
+        toc::{config::*, TocMaker},
+        transformer::{config::*, Transformer},
+    },
+};
+
+/// The options of all layers.
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{options::*, prelude::*};
+///
+/// let options = MarkdownOptions::new()
+///     .parser(ParserOptions {
+///         dialect: Some(Dialect::CommonMark),
+///         ..Default::default()
+///     })
+///     .stringifier(StringifierOptions {
+///         escape: Some(true),
+///         ..Default::default()
+///     });
+///
+/// let markdown = Markdown::from_options(&options);
+///
+/// assert_eq!(markdown.execute("<b>\nc"), "<p>&lt;b&gt;\nc</p>");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct MarkdownOptions {
+    /// The preset of all layers. Applied before the other options, so they
+    /// can override it.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub profile: Option<Profile>,
+    /// Whether front matter can override the options of its document, by
+    /// [`Markdown::front_matter_options`](crate::Markdown::front_matter_options).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub front_matter_options: Option<bool>,
+    /// The options of the parser.
+    pub parser: ParserOptions,
+    /// The options of the transformer.
+    pub transformer: TransformerOptions,
+    /// The options of the stringifier.
+    pub stringifier: StringifierOptions,
+    /// The options of the table of contents.
+    pub toc: TocOptions,
+}
+
+/// The function to change the options read from the front matter of a
+/// document, for
+/// [`Markdown::front_matter_hook`](crate::Markdown::front_matter_hook).
+///
+/// It can veto an option by setting it back to `None`, or read other keys.
+pub type FrontMatterHook = fn(&FrontMatter, &mut MarkdownOptions);
+
+/// A preset of all layers for a kind of content, for
+/// [`Markdown::profile`](crate::Markdown::profile).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Comments written by untrusted users, such as on a forum. The settings
+    /// of [`Markdown::untrusted`](crate::Markdown::untrusted) with mentions,
+    /// HTML comments stripped, and external links marked with
+    /// `rel="nofollow ugc noopener"`.
+    Comments,
+    /// Personal notes with front matter, wiki links, hashtags, highlights
+    /// and abbreviations. Headlines have ids, and quotes can end with a
+    /// citation.
+    Notes,
+    /// Documentation with front matter, fancy lists, highlights and
+    /// abbreviations, with HTML comments stripped. Headlines have anchors,
+    /// `[TOC]` is replaced with the table of contents, and punctuation is
+    /// made typographic.
+    Docs,
+}
+
+impl FromStr for Profile {
+    type Err = ParseConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "comments" => Ok(Self::Comments),
+            "notes" => Ok(Self::Notes),
+            "docs" => Ok(Self::Docs),
+            _ => Err(ParseConfigError::new("Profile", s)),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Comments => f.write_str("comments"),
+            Self::Notes => f.write_str("notes"),
+            Self::Docs => f.write_str("docs"),
+        }
+    }
+}
+
+/// The options of [`Parser`]. Each option is the setting of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ParserOptions {
+    /// Applied before the other options, so they can override it.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub dialect: Option<Dialect>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub paragraph_ending: Option<ParagraphEnding>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub headline_ending: Option<HeadlineEnding>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub line_break_style: Option<LineBreakStyle>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub list_indent_style: Option<IndentStyle>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub emphasis_rule: Option<EmphasisRule>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub list_indent_rule: Option<IndentRule>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_list_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub interrupters: Option<Vec<Interrupter>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub tab_stop: Option<u8>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub front_matter: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_nesting: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_block_nesting: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_inline_nesting: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_input_len: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_tokens: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub wiki_links: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hashtags: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mentions: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub highlight: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fancy_lists: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub comments: Option<Comments>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub abbreviations: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub inline_footnotes: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub tables: Option<Tables>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub table_spans: Option<bool>,
+}
+
+/// The options of [`Transformer`]. Each option is the setting of the same
+/// name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct TransformerOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub section: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub smart_punctuation: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub line_wrap: Option<LineWrap>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub list_start: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub external_rel: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub external_target: Option<String>,
+    /// Added to the internal hosts.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub internal_hosts: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub heading_ids: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub heading_anchor: Option<AnchorPosition>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub anchor_symbol: Option<String>,
+    /// Replace `[TOC]` paragraphs with the table of contents made with the
+    /// [`toc`](MarkdownOptions::toc) options.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub toc_placeholder: Option<bool>,
+    /// Replace shortcodes with the bundled emoji table.
+    #[cfg(feature = "emoji")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub emoji: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub quote_citation: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub quote_depth_class: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub code_block_wrapper: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub copy_button: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub text_direction: Option<TextDirection>,
+    #[cfg(feature = "nfc")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub nfc: Option<bool>,
+}
+
+/// The options of [`Stringifier`]. Each option is the setting of the same
+/// name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct StringifierOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub format: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub width: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub indent: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub preserve_inline: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub stable: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub escape: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub sanitize: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub url_schemes: Option<Vec<String>>,
+    /// Allow links of any scheme, even if the profile limits them. Applied
+    /// before [`url_schemes`](Self::url_schemes).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub any_url_scheme: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub void_style: Option<VoidStyle>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub variables: Option<BTreeMap<String, String>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub source_positions: Option<bool>,
+}
+
+/// The options of [`TocMaker`]. Each option is the setting of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct TocOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub min_level: Option<u8>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_level: Option<u8>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub list_type: Option<ListType>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub id_collision: Option<IdCollisionPolicy>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "string_form", skip_serializing_if = "Option::is_none")
+    )]
+    pub slug_style: Option<SlugStyle>,
+}
+
+impl MarkdownOptions {
+    /// Create new MarkdownOptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the preset of all layers.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Set the options of the parser.
+    pub fn parser(mut self, parser: ParserOptions) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Set the options of the transformer.
+    pub fn transformer(mut self, transformer: TransformerOptions) -> Self {
+        self.transformer = transformer;
+        self
+    }
+
+    /// Set the options of the stringifier.
+    pub fn stringifier(mut self, stringifier: StringifierOptions) -> Self {
+        self.stringifier = stringifier;
+        self
+    }
+
+    /// Set the options of the table of contents.
+    pub fn toc(mut self, toc: TocOptions) -> Self {
+        self.toc = toc;
+        self
+    }
+}
+
+impl MarkdownOptions {
+    /// Read the options of a document from its front matter.
+    ///
+    /// The keys and the options they set are:
+    ///
+    /// | Key | Option |
+    /// | --- | --- |
+    /// | `line_break_style`, `wiki_links`, `hashtags`, `mentions`, `highlight`, `fancy_lists`, `abbreviations`, `inline_footnotes`, `tables`, `table_spans` | the parser option of the same name |
+    /// | `section`, `smart_punctuation`, `line_wrap`, `heading_ids`, `heading_anchor`, `quote_citation`, `text_direction` | the transformer option of the same name |
+    /// | `toc` | [`TransformerOptions::toc_placeholder`] |
+    /// | `toc_min_level`, `toc_max_level` | [`TocOptions::min_level`], [`TocOptions::max_level`] |
+    ///
+    /// Values are written in their string forms. Other keys such as `title`
+    /// are ignored, and so are the options of the stringifier, so a document
+    /// cannot turn off escaping or sanitizing. Entries whose value cannot be
+    /// parsed are skipped and returned with their keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use note_mark::{options::*, prelude::*};
+    ///
+    /// let input = "---\ntitle: A\ntoc: true\nline_wrap: wrap\n---\n";
+    ///
+    /// let (front_matter, _) = FrontMatter::parse(input).unwrap();
+    ///
+    /// let (options, errors) = MarkdownOptions::from_front_matter(&front_matter);
+    ///
+    /// assert_eq!(options.transformer.toc_placeholder, Some(true));
+    /// assert_eq!(errors, vec![("line_wrap", ParseConfigError::new("LineWrap", "wrap"))]);
+    /// ```
+    pub fn from_front_matter<'a>(
+        front_matter: &FrontMatter<'a>,
+    ) -> (Self, Vec<(&'a str, ParseConfigError)>) {
+        let mut options = Self::default();
+        let mut errors = vec![];
+
+        let parser = &mut options.parser;
+        let transformer = &mut options.transformer;
+        let toc = &mut options.toc;
+
+        for &(key, value) in &front_matter.entries {
+            let result = match key {
+                "line_break_style" => set(&mut parser.line_break_style, "LineBreakStyle", value),
+                "wiki_links" => set(&mut parser.wiki_links, "bool", value),
+                "hashtags" => set(&mut parser.hashtags, "bool", value),
+                "mentions" => set(&mut parser.mentions, "bool", value),
+                "highlight" => set(&mut parser.highlight, "bool", value),
+                "fancy_lists" => set(&mut parser.fancy_lists, "bool", value),
+                "abbreviations" => set(&mut parser.abbreviations, "bool", value),
+                "inline_footnotes" => set(&mut parser.inline_footnotes, "bool", value),
+                "tables" => set(&mut parser.tables, "Tables", value),
+                "table_spans" => set(&mut parser.table_spans, "bool", value),
+                "section" => set(&mut transformer.section, "bool", value),
+                "smart_punctuation" => set(&mut transformer.smart_punctuation, "bool", value),
+                "line_wrap" => set(&mut transformer.line_wrap, "LineWrap", value),
+                "heading_ids" => set(&mut transformer.heading_ids, "bool", value),
+                "heading_anchor" => set(&mut transformer.heading_anchor, "AnchorPosition", value),
+                "quote_citation" => set(&mut transformer.quote_citation, "bool", value),
+                "text_direction" => set(&mut transformer.text_direction, "TextDirection", value),
+                "toc" => set(&mut transformer.toc_placeholder, "bool", value),
+                "toc_min_level" => set(&mut toc.min_level, "u8", value),
+                "toc_max_level" => set(&mut toc.max_level, "u8", value),
+                _ => Ok(()),
+            };
+
+            if let Err(error) = result {
+                errors.push((key, error));
+            }
+        }
+
+        (options, errors)
+    }
+}
+
+/// Parse the value into the option.
+fn set<T: FromStr>(
+    option: &mut Option<T>,
+    name: &'static str,
+    value: &str,
+) -> Result<(), ParseConfigError> {
+    *option = Some(
+        value
+            .parse()
+            .map_err(|_| ParseConfigError::new(name, value))?,
+    );
+
+    Ok(())
+}
+
+impl ParserOptions {
+    /// Apply the options which are set to the parser.
+    pub fn apply(&self, mut parser: Parser) -> Parser {
+        if let Some(dialect) = self.dialect {
+            parser = parser.dialect(dialect);
+        }
+        if let Some(ending) = self.paragraph_ending {
+            parser = parser.paragraph_ending(ending);
+        }
+        if let Some(ending) = self.headline_ending {
+            parser = parser.headline_ending(ending);
+        }
+        if let Some(style) = self.line_break_style {
+            parser = parser.line_break_style(style);
+        }
+        if let Some(style) = self.list_indent_style {
+            parser = parser.list_indent_style(style);
+        }
+        if let Some(rule) = self.emphasis_rule {
+            parser = parser.emphasis_rule(rule);
+        }
+        if let Some(interrupters) = &self.interrupters {
+            parser = parser.interrupters(interrupters);
+        }
+        if let Some(rule) = self.list_indent_rule {
+            parser = parser.list_indent_rule(rule);
+        }
+        if let Some(tab_stop) = self.tab_stop {
+            parser = parser.tab_stop(tab_stop);
+        }
+        if let Some(front_matter) = self.front_matter {
+            parser = parser.front_matter(front_matter);
+        }
+        if self.max_nesting.is_some() {
+            parser = parser.max_nesting(self.max_nesting);
+        }
+        if self.max_block_nesting.is_some() {
+            parser = parser.max_block_nesting(self.max_block_nesting);
+        }
+        if self.max_inline_nesting.is_some() {
+            parser = parser.max_inline_nesting(self.max_inline_nesting);
+        }
+        if self.max_input_len.is_some() {
+            parser = parser.max_input_len(self.max_input_len);
+        }
+        if self.max_tokens.is_some() {
+            parser = parser.max_tokens(self.max_tokens);
+        }
+        if let Some(wiki_links) = self.wiki_links {
+            parser = parser.wiki_links(wiki_links);
+        }
+        if let Some(hashtags) = self.hashtags {
+            parser = parser.hashtags(hashtags);
+        }
+        if let Some(mentions) = self.mentions {
+            parser = parser.mentions(mentions);
+        }
+        if let Some(highlight) = self.highlight {
+            parser = parser.highlight(highlight);
+        }
+        if let Some(fancy_lists) = self.fancy_lists {
+            parser = parser.fancy_lists(fancy_lists);
+        }
+        if let Some(comments) = self.comments {
+            parser = parser.comments(comments);
+        }
+        if let Some(abbreviations) = self.abbreviations {
+            parser = parser.abbreviations(abbreviations);
+        }
+        if let Some(inline_footnotes) = self.inline_footnotes {
+            parser = parser.inline_footnotes(inline_footnotes);
+        }
+        if let Some(tables) = self.tables {
+            parser = parser.tables(tables);
+        }
+        if let Some(table_spans) = self.table_spans {
+            parser = parser.table_spans(table_spans);
+        }
+
+        parser
+    }
+}
+
+impl TransformerOptions {
+    /// Apply the options which are set to the transformer.
+    pub fn apply(&self, mut transformer: Transformer) -> Transformer {
+        if let Some(section) = self.section {
+            transformer = transformer.section(section);
+        }
+        if let Some(smart_punctuation) = self.smart_punctuation {
+            transformer = transformer.smart_punctuation(smart_punctuation);
+        }
+        if let Some(line_wrap) = self.line_wrap {
+            transformer = transformer.line_wrap(line_wrap);
+        }
+        if let Some(list_start) = self.list_start {
+            transformer = transformer.list_start(list_start);
+        }
+        if let Some(rel) = &self.external_rel {
+            transformer = transformer.external_rel(rel);
+        }
+        if let Some(target) = &self.external_target {
+            transformer = transformer.external_target(target);
+        }
+        for host in &self.internal_hosts {
+            transformer = transformer.internal_host(host);
+        }
+        if let Some(heading_ids) = self.heading_ids {
+            transformer = transformer.heading_ids(heading_ids);
+        }
+        if let Some(position) = self.heading_anchor {
+            transformer = transformer.heading_anchor(position);
+        }
+        if let Some(symbol) = &self.anchor_symbol {
+            transformer = transformer.anchor_symbol(symbol);
+        }
+        if let Some(quote_citation) = self.quote_citation {
+            transformer = transformer.quote_citation(quote_citation);
+        }
+        if let Some(quote_depth_class) = self.quote_depth_class {
+            transformer = transformer.quote_depth_class(quote_depth_class);
+        }
+        if let Some(code_block_wrapper) = self.code_block_wrapper {
+            transformer = transformer.code_block_wrapper(code_block_wrapper);
+        }
+        if let Some(copy_button) = self.copy_button {
+            transformer = transformer.copy_button(copy_button);
+        }
+        if let Some(text_direction) = self.text_direction {
+            transformer = transformer.text_direction(text_direction);
+        }
+        #[cfg(feature = "nfc")]
+        if let Some(nfc) = self.nfc {
+            transformer = transformer.nfc(nfc);
+        }
+        #[cfg(feature = "emoji")]
+        match self.emoji {
+            Some(true) => transformer = transformer.emoji(crate::emoji::Shortcodes),
+            Some(false) => transformer.emoji = None,
+            None => {}
+        }
+        match self.toc_placeholder {
+            Some(true) => transformer = transformer.toc_placeholder(TocMaker::default()),
+            Some(false) => transformer.toc_placeholder = None,
+            None => {}
+        }
+
+        transformer
+    }
+}
+
+impl StringifierOptions {
+    /// Apply the options which are set to the stringifier.
+    pub fn apply(&self, mut stringifier: Stringifier) -> Stringifier {
+        if let Some(format) = self.format {
+            stringifier = stringifier.format(format);
+        }
+        if let Some(width) = self.width {
+            stringifier = stringifier.width(width);
+        }
+        if let Some(indent) = &self.indent {
+            stringifier = stringifier.indent(indent);
+        }
+        if let Some(preserve_inline) = self.preserve_inline {
+            stringifier = stringifier.preserve_inline(preserve_inline);
+        }
+        if let Some(stable) = self.stable {
+            stringifier = stringifier.stable(stable);
+        }
+        if let Some(escape) = self.escape {
+            stringifier = stringifier.escape(escape);
+        }
+        if let Some(sanitize) = self.sanitize {
+            stringifier = stringifier.sanitize(sanitize);
+        }
+        if self.any_url_scheme == Some(true) {
+            stringifier.url_schemes = None;
+        }
+        if let Some(schemes) = &self.url_schemes {
+            let schemes = schemes.iter().map(String::as_str).collect::<Vec<_>>();
+
+            stringifier = stringifier.url_schemes(&schemes);
+        }
+        if let Some(style) = self.void_style {
+            stringifier = stringifier.void_style(style);
+        }
+        if let Some(variables) = &self.variables {
+            for (key, value) in variables {
+                stringifier = stringifier.variable(key, value);
+            }
+        }
+        if let Some(source_positions) = self.source_positions {
+            stringifier = stringifier.source_positions(source_positions);
+        }
+
+        stringifier
+    }
+}
+
+impl TocOptions {
+    /// Apply the options which are set to the table of contents maker.
+    pub fn apply(&self, mut toc_maker: TocMaker) -> TocMaker {
+        if let Some(level) = self.min_level {
+            toc_maker = toc_maker.min_level(level);
+        }
+        if let Some(level) = self.max_level {
+            toc_maker = toc_maker.max_level(level);
+        }
+        if let Some(list_type) = self.list_type {
+            toc_maker = toc_maker.list_type(list_type);
+        }
+        if let Some(policy) = self.id_collision {
+            toc_maker = toc_maker.id_collision(policy);
+        }
+        if let Some(style) = self.slug_style {
+            toc_maker = toc_maker.slug_style(style);
+        }
+
+        toc_maker
+    }
+}
+
+/// Serialize options with their string forms.
+#[cfg(feature = "serde")]
+mod string_form {
+    use std::{fmt, str::FromStr};
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| value.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+/// Serialize lists of options with their string forms.
+#[cfg(feature = "serde")]
+mod string_list_form {
+    use std::{fmt, str::FromStr};
+
+    use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &Option<Vec<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        let Some(values) = value else {
+            return serializer.serialize_none();
+        };
+
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+
+        for value in values {
+            seq.serialize_element(&value.to_string())?;
+        }
+
+        seq.end()
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        Option::<Vec<String>>::deserialize(deserializer)?
+            .map(|values| {
+                values
+                    .iter()
+                    .map(|value| value.parse().map_err(D::Error::custom))
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_serde() {
+        let options = toml::from_str::<MarkdownOptions>(
+            r#"
+            [parser]
+            dialect = "common-mark"
+            list_indent_style = "space:4"
+            interrupters = ["headline", "bullet-list"]
+
+            [transformer]
+            section = true
+
+            [stringifier]
+            escape = true
+            void_style = "xhtml"
+
+            [toc]
+            list_type = "ordered"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            options.parser.list_indent_style,
+            Some(IndentStyle::Space(4))
+        );
+        assert_eq!(
+            options.parser.interrupters,
+            Some(vec![Interrupter::Headline, Interrupter::BulletList])
+        );
+        assert_eq!(options.toc.list_type, Some(ListType::Ordered));
+
+        let markdown = crate::Markdown::from_options(&options);
+
+        assert_eq!(
+            markdown.execute("# A\n<b>\nc  \nd"),
+            "<section><h1>A</h1><p>&lt;b&gt;\nc<br />d</p></section>"
+        );
+
+        let json = serde_json::to_string(&options).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<MarkdownOptions>(&json).unwrap(),
+            options
+        );
+
+        let error =
+            toml::from_str::<MarkdownOptions>("[stringifier]\nvoid_style = \"x\"").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("invalid value `x` for VoidStyle"));
+        assert!(toml::from_str::<MarkdownOptions>("[parser]\nunknown = 1").is_err());
+        assert!(toml::from_str::<MarkdownOptions>("[parser]\ninterrupters = [\"x\"]").is_err());
+    }
+}
@@ -1,11 +1,27 @@
 //! The prelude of the note-mark crate.
 
 pub use crate::{
+    batch::{BatchDocument, BatchOutput, LinkGraph, MarkdownBatch},
+    cache::RenderCache,
+    error::{ConfigWarning, Diagnostic, DiagnosticKind, ParseConfigError},
+    incremental::{content_hash, BlockHash, IncrementalDocument},
     layer::{
-        parser::{config::*, Parser},
-        stringifier::*,
-        toc::{config::*, TocMaker},
-        transformer::*,
+        figures::{Figure, FigureListMaker},
+        formatter::{config::*, MarkdownStringifier},
+        lexer::lex,
+        metadata::{config::*, FrontMatter, MetadataExtractor},
+        parser::{config::*, IncludeResolver, Inclusion, Parser},
+        stringifier::{config::*, *},
+        toc::{config::*, Heading, TocMaker},
+        transformer::{config::*, EmojiLookup, TagResolver, Transformer, WikiResolver},
+    },
+    model::{
+        counter::{Counter, CounterStyle, Numbering},
+        html::ElementTag,
+        messages::Messages,
+        stats::{RenderStats, TextStats},
+        token::{LineIndex, Position, Token, TokenKind},
+        tree::{ListDelimiter, ListMarker, TagKind},
     },
     Markdown,
 };
@@ -0,0 +1,56 @@
+//! Default stylesheet.
+//!
+//! This module is available with the `theme` feature. The stylesheet is scoped
+//! to the `note-mark` class, so wrap the output in an element with that class
+//! or use [page] to get a full HTML page.
+
+use crate::layer::stringifier::{config::DocumentOptions, Stringifier};
+
+/// The default stylesheet for the generated HTML.
+pub const DEFAULT_CSS: &str = include_str!("../assets/theme.css");
+
+/// Wrap the body in a full HTML page with the default stylesheet inlined.
+///
+/// The title is escaped, and the body is written as it is. To render
+/// markdown to such a page directly, inline the stylesheet with
+/// [`DocumentOptions::style`].
+///
+/// # Example
+///
+/// ```
+/// use note_mark::{prelude::*, theme};
+///
+/// let body = Markdown::default().execute("# Hello");
+///
+/// let html = theme::page("Hello", &body);
+///
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains("<style>"));
+/// assert!(html.ends_with("<body><div class=\"note-mark\"><h1>Hello</h1></div></body></html>"));
+/// ```
+pub fn page(title: &str, body: &str) -> String {
+    let options = DocumentOptions::new().title(title).style(DEFAULT_CSS);
+
+    let mut output = String::new();
+
+    Stringifier::new().write_document(&options, &mut output, |output| {
+        output.push_str("<div class=\"note-mark\">");
+        output.push_str(body);
+        output.push_str("</div>");
+    });
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_title_escaped() {
+        let html = page("</title><script>x</script>", "");
+
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<title>&lt;/title&gt;&lt;script&gt;x&lt;/script&gt;</title>"));
+    }
+}
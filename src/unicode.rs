@@ -0,0 +1,1051 @@
+//! Classes of Unicode characters which the standard library does not
+//! provide, the transliteration of letters into ASCII, and the normalization
+//! of text with the `nfc` feature.
+
+#[cfg(feature = "nfc")]
+use std::borrow::Cow;
+
+/// Check if the character is a combining mark, which is in the general
+/// category `M`, such as U+0301 COMBINING ACUTE ACCENT or the virama of
+/// Devanagari.
+///
+/// A mark belongs to the character before it, so it is a part of a word
+/// although it is neither alphabetic nor numeric.
+pub(crate) fn is_mark(c: char) -> bool {
+    // Marks are rare in text, and all of them are after U+0300.
+    if c < '\u{0300}' {
+        return false;
+    }
+
+    MARKS
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if c < start {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Normalize the text to NFC, which composes a letter and its combining
+/// marks into one character where Unicode has one, so that `e` followed by
+/// U+0301 becomes `é`.
+#[cfg(feature = "nfc")]
+pub(crate) fn nfc(text: Cow<str>) -> Cow<str> {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => text,
+        _ => Cow::Owned(text.nfc().collect()),
+    }
+}
+
+/// Transliterate the letters of lowercase text into ASCII.
+///
+/// Latin letters lose their diacritics, Greek and Cyrillic letters are
+/// romanized one by one, kana is romanized in Hepburn spelling, and Hangul is
+/// romanized syllable by syllable in the Revised Romanization. Combining
+/// marks after ASCII are dropped. Other characters, such as Han characters
+/// whose reading needs a dictionary, are kept.
+pub(crate) fn transliterate(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    // The romaji of the last kana, which a small kana after it can change.
+    let mut syllable: Option<String> = None;
+    // Whether a small `っ` doubles the consonant of the next kana.
+    let mut sokuon = false;
+    // Whether the last character is written in ASCII, so that marks after it
+    // are dropped.
+    let mut ascii = false;
+
+    for c in text.chars() {
+        // Katakana are read as the hiragana of the same sound.
+        let c = match c {
+            '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        };
+
+        if let Some(romaji) = &mut syllable {
+            match c {
+                // `きゃ` is `kya`, and `しゃ` is `sha`.
+                'ゃ' | 'ゅ' | 'ょ' if romaji.len() > 1 && romaji.ends_with('i') => {
+                    romaji.pop();
+
+                    let palatal =
+                        romaji.ends_with("sh") || romaji.ends_with("ch") || romaji.ends_with('j');
+
+                    if !palatal {
+                        romaji.push('y');
+                    }
+
+                    romaji.push_str(&KANA[c as usize - 0x3041][1..]);
+                    continue;
+                }
+                // `ふぁ` is `fa`, and `うぃ` is `wi`.
+                'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                    if romaji == "u" {
+                        *romaji = "w".to_string();
+                    } else if romaji.len() > 1 {
+                        romaji.pop();
+                    }
+
+                    romaji.push_str(KANA[c as usize - 0x3041]);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(romaji) = syllable.take() {
+            output.push_str(&romaji);
+        }
+
+        match c {
+            'っ' => {
+                sokuon = true;
+                ascii = true;
+                continue;
+            }
+            // The mark of a long vowel is dropped as a macron would be.
+            'ー' => {
+                ascii = true;
+                continue;
+            }
+            '\u{3041}'..='\u{3096}' => {
+                let romaji = KANA[c as usize - 0x3041];
+                let mut doubled = String::new();
+
+                if std::mem::take(&mut sokuon) {
+                    match romaji.chars().next() {
+                        Some('c') => doubled.push('t'),
+                        Some(c) if !matches!(c, 'a' | 'i' | 'u' | 'e' | 'o') => doubled.push(c),
+                        _ => {}
+                    }
+                }
+
+                doubled.push_str(romaji);
+                syllable = Some(doubled);
+                ascii = true;
+                continue;
+            }
+            _ => sokuon = false,
+        }
+
+        if let Some(romaji) = hangul(c) {
+            output.push_str(&romaji);
+        } else if let Ok(index) = LETTERS.binary_search_by_key(&c, |&(letter, _)| letter) {
+            output.push_str(LETTERS[index].1);
+        } else if is_mark(c) && ascii {
+            continue;
+        } else {
+            output.push(c);
+            ascii = c.is_ascii();
+            continue;
+        }
+
+        ascii = true;
+    }
+
+    if let Some(romaji) = syllable {
+        output.push_str(&romaji);
+    }
+
+    output
+}
+
+/// Romanize a Hangul syllable from its initial, medial and final jamo.
+fn hangul(c: char) -> Option<String> {
+    const INITIALS: [&str; 19] = [
+        "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t",
+        "p", "h",
+    ];
+    const MEDIALS: [&str; 21] = [
+        "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo",
+        "we", "wi", "yu", "eu", "ui", "i",
+    ];
+    const FINALS: [&str; 28] = [
+        "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p",
+        "p", "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+    ];
+
+    let index = (c as usize)
+        .checked_sub(0xAC00)
+        .filter(|&index| index < 11172)?;
+
+    Some(
+        [
+            INITIALS[index / 588],
+            MEDIALS[index % 588 / 28],
+            FINALS[index % 28],
+        ]
+        .concat(),
+    )
+}
+
+/// The romaji of the hiragana from U+3041 to U+3096. A small kana has the
+/// romaji of the large one.
+const KANA: [&str; 86] = [
+    "a", "a", "i", "i", "u", "u", "e", "e", "o", "o", "ka", "ga", "ki", "gi", "ku", "gu", "ke",
+    "ge", "ko", "go", "sa", "za", "shi", "ji", "su", "zu", "se", "ze", "so", "zo", "ta", "da",
+    "chi", "ji", "tsu", "tsu", "zu", "te", "de", "to", "do", "na", "ni", "nu", "ne", "no", "ha",
+    "ba", "pa", "hi", "bi", "pi", "fu", "bu", "pu", "he", "be", "pe", "ho", "bo", "po", "ma", "mi",
+    "mu", "me", "mo", "ya", "ya", "yu", "yu", "yo", "yo", "ra", "ri", "ru", "re", "ro", "wa", "wa",
+    "i", "e", "o", "n", "vu", "ka", "ke",
+];
+
+/// The lowercase Latin, Greek and Cyrillic letters other than ASCII and
+/// their transliterations, sorted.
+const LETTERS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('à', "a"),
+    ('á', "a"),
+    ('â', "a"),
+    ('ã', "a"),
+    ('ä', "a"),
+    ('å', "a"),
+    ('æ', "ae"),
+    ('ç', "c"),
+    ('è', "e"),
+    ('é', "e"),
+    ('ê', "e"),
+    ('ë', "e"),
+    ('ì', "i"),
+    ('í', "i"),
+    ('î', "i"),
+    ('ï', "i"),
+    ('ð', "d"),
+    ('ñ', "n"),
+    ('ò', "o"),
+    ('ó', "o"),
+    ('ô', "o"),
+    ('õ', "o"),
+    ('ö', "o"),
+    ('ø', "o"),
+    ('ù', "u"),
+    ('ú', "u"),
+    ('û', "u"),
+    ('ü', "u"),
+    ('ý', "y"),
+    ('þ', "th"),
+    ('ÿ', "y"),
+    ('ā', "a"),
+    ('ă', "a"),
+    ('ą', "a"),
+    ('ć', "c"),
+    ('ĉ', "c"),
+    ('ċ', "c"),
+    ('č', "c"),
+    ('ď', "d"),
+    ('đ', "d"),
+    ('ē', "e"),
+    ('ĕ', "e"),
+    ('ė', "e"),
+    ('ę', "e"),
+    ('ě', "e"),
+    ('ĝ', "g"),
+    ('ğ', "g"),
+    ('ġ', "g"),
+    ('ģ', "g"),
+    ('ĥ', "h"),
+    ('ħ', "h"),
+    ('ĩ', "i"),
+    ('ī', "i"),
+    ('ĭ', "i"),
+    ('į', "i"),
+    ('ı', "i"),
+    ('ĳ', "ij"),
+    ('ĵ', "j"),
+    ('ķ', "k"),
+    ('ĸ', "k"),
+    ('ĺ', "l"),
+    ('ļ', "l"),
+    ('ľ', "l"),
+    ('ŀ', "l"),
+    ('ł', "l"),
+    ('ń', "n"),
+    ('ņ', "n"),
+    ('ň', "n"),
+    ('ŋ', "ng"),
+    ('ō', "o"),
+    ('ŏ', "o"),
+    ('ő', "o"),
+    ('œ', "oe"),
+    ('ŕ', "r"),
+    ('ŗ', "r"),
+    ('ř', "r"),
+    ('ś', "s"),
+    ('ŝ', "s"),
+    ('ş', "s"),
+    ('š', "s"),
+    ('ţ', "t"),
+    ('ť', "t"),
+    ('ŧ', "t"),
+    ('ũ', "u"),
+    ('ū', "u"),
+    ('ŭ', "u"),
+    ('ů', "u"),
+    ('ű', "u"),
+    ('ų', "u"),
+    ('ŵ', "w"),
+    ('ŷ', "y"),
+    ('ź', "z"),
+    ('ż', "z"),
+    ('ž', "z"),
+    ('ſ', "s"),
+    ('ƀ', "b"),
+    ('ƈ', "c"),
+    ('ƌ', "d"),
+    ('ƒ', "f"),
+    ('ƙ', "k"),
+    ('ƚ', "l"),
+    ('ơ', "o"),
+    ('ƥ', "p"),
+    ('ƭ', "t"),
+    ('ư', "u"),
+    ('ƴ', "y"),
+    ('ƶ', "z"),
+    ('ǆ', "dz"),
+    ('ǉ', "lj"),
+    ('ǌ', "nj"),
+    ('ǎ', "a"),
+    ('ǐ', "i"),
+    ('ǒ', "o"),
+    ('ǔ', "u"),
+    ('ǖ', "u"),
+    ('ǘ', "u"),
+    ('ǚ', "u"),
+    ('ǜ', "u"),
+    ('ǟ', "a"),
+    ('ǡ', "a"),
+    ('ǣ', "ae"),
+    ('ǥ', "g"),
+    ('ǧ', "g"),
+    ('ǩ', "k"),
+    ('ǫ', "o"),
+    ('ǭ', "o"),
+    ('ǰ', "j"),
+    ('ǳ', "dz"),
+    ('ǵ', "g"),
+    ('ǹ', "n"),
+    ('ǻ', "a"),
+    ('ǽ', "ae"),
+    ('ǿ', "o"),
+    ('ȁ', "a"),
+    ('ȃ', "a"),
+    ('ȅ', "e"),
+    ('ȇ', "e"),
+    ('ȉ', "i"),
+    ('ȋ', "i"),
+    ('ȍ', "o"),
+    ('ȏ', "o"),
+    ('ȑ', "r"),
+    ('ȓ', "r"),
+    ('ȕ', "u"),
+    ('ȗ', "u"),
+    ('ș', "s"),
+    ('ț', "t"),
+    ('ȟ', "h"),
+    ('ȥ', "z"),
+    ('ȧ', "a"),
+    ('ȩ', "e"),
+    ('ȫ', "o"),
+    ('ȭ', "o"),
+    ('ȯ', "o"),
+    ('ȱ', "o"),
+    ('ȳ', "y"),
+    ('ȷ', "j"),
+    ('ɇ', "e"),
+    ('ɉ', "j"),
+    ('ɍ', "r"),
+    ('ɏ', "y"),
+    ('ɠ', "g"),
+    ('ɨ', "i"),
+    ('ʉ', "u"),
+    ('ΐ', "i"),
+    ('ά', "a"),
+    ('έ', "e"),
+    ('ή', "i"),
+    ('ί', "i"),
+    ('ΰ', "y"),
+    ('α', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('ς', "s"),
+    ('σ', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+    ('ϊ', "i"),
+    ('ϋ', "y"),
+    ('ό', "o"),
+    ('ύ', "y"),
+    ('ώ', "o"),
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', ""),
+    ('ы', "y"),
+    ('ь', ""),
+    ('э', "e"),
+    ('ю', "yu"),
+    ('я', "ya"),
+    ('ё', "e"),
+    ('ђ', "dj"),
+    ('ѓ', "gj"),
+    ('є', "ye"),
+    ('ѕ', "dz"),
+    ('і', "i"),
+    ('ї', "yi"),
+    ('ј', "j"),
+    ('љ', "lj"),
+    ('њ', "nj"),
+    ('ћ', "c"),
+    ('ќ', "kj"),
+    ('ў', "u"),
+    ('џ', "dz"),
+    ('ґ', "g"),
+    ('ḁ', "a"),
+    ('ḃ', "b"),
+    ('ḅ', "b"),
+    ('ḇ', "b"),
+    ('ḉ', "c"),
+    ('ḋ', "d"),
+    ('ḍ', "d"),
+    ('ḏ', "d"),
+    ('ḑ', "d"),
+    ('ḓ', "d"),
+    ('ḕ', "e"),
+    ('ḗ', "e"),
+    ('ḙ', "e"),
+    ('ḛ', "e"),
+    ('ḝ', "e"),
+    ('ḟ', "f"),
+    ('ḡ', "g"),
+    ('ḣ', "h"),
+    ('ḥ', "h"),
+    ('ḧ', "h"),
+    ('ḩ', "h"),
+    ('ḫ', "h"),
+    ('ḭ', "i"),
+    ('ḯ', "i"),
+    ('ḱ', "k"),
+    ('ḳ', "k"),
+    ('ḵ', "k"),
+    ('ḷ', "l"),
+    ('ḹ', "l"),
+    ('ḻ', "l"),
+    ('ḽ', "l"),
+    ('ḿ', "m"),
+    ('ṁ', "m"),
+    ('ṃ', "m"),
+    ('ṅ', "n"),
+    ('ṇ', "n"),
+    ('ṉ', "n"),
+    ('ṋ', "n"),
+    ('ṍ', "o"),
+    ('ṏ', "o"),
+    ('ṑ', "o"),
+    ('ṓ', "o"),
+    ('ṕ', "p"),
+    ('ṗ', "p"),
+    ('ṙ', "r"),
+    ('ṛ', "r"),
+    ('ṝ', "r"),
+    ('ṟ', "r"),
+    ('ṡ', "s"),
+    ('ṣ', "s"),
+    ('ṥ', "s"),
+    ('ṧ', "s"),
+    ('ṩ', "s"),
+    ('ṫ', "t"),
+    ('ṭ', "t"),
+    ('ṯ', "t"),
+    ('ṱ', "t"),
+    ('ṳ', "u"),
+    ('ṵ', "u"),
+    ('ṷ', "u"),
+    ('ṹ', "u"),
+    ('ṻ', "u"),
+    ('ṽ', "v"),
+    ('ṿ', "v"),
+    ('ẁ', "w"),
+    ('ẃ', "w"),
+    ('ẅ', "w"),
+    ('ẇ', "w"),
+    ('ẉ', "w"),
+    ('ẋ', "x"),
+    ('ẍ', "x"),
+    ('ẏ', "y"),
+    ('ẑ', "z"),
+    ('ẓ', "z"),
+    ('ẕ', "z"),
+    ('ẖ', "h"),
+    ('ẗ', "t"),
+    ('ẘ', "w"),
+    ('ẙ', "y"),
+    ('ạ', "a"),
+    ('ả', "a"),
+    ('ấ', "a"),
+    ('ầ', "a"),
+    ('ẩ', "a"),
+    ('ẫ', "a"),
+    ('ậ', "a"),
+    ('ắ', "a"),
+    ('ằ', "a"),
+    ('ẳ', "a"),
+    ('ẵ', "a"),
+    ('ặ', "a"),
+    ('ẹ', "e"),
+    ('ẻ', "e"),
+    ('ẽ', "e"),
+    ('ế', "e"),
+    ('ề', "e"),
+    ('ể', "e"),
+    ('ễ', "e"),
+    ('ệ', "e"),
+    ('ỉ', "i"),
+    ('ị', "i"),
+    ('ọ', "o"),
+    ('ỏ', "o"),
+    ('ố', "o"),
+    ('ồ', "o"),
+    ('ổ', "o"),
+    ('ỗ', "o"),
+    ('ộ', "o"),
+    ('ớ', "o"),
+    ('ờ', "o"),
+    ('ở', "o"),
+    ('ỡ', "o"),
+    ('ợ', "o"),
+    ('ụ', "u"),
+    ('ủ', "u"),
+    ('ứ', "u"),
+    ('ừ', "u"),
+    ('ử', "u"),
+    ('ữ', "u"),
+    ('ự', "u"),
+    ('ỳ', "y"),
+    ('ỵ', "y"),
+    ('ỷ', "y"),
+    ('ỹ', "y"),
+    ('ἀ', "a"),
+    ('ἁ', "a"),
+    ('ἂ', "a"),
+    ('ἃ', "a"),
+    ('ἄ', "a"),
+    ('ἅ', "a"),
+    ('ἆ', "a"),
+    ('ἇ', "a"),
+    ('ἐ', "e"),
+    ('ἑ', "e"),
+    ('ἒ', "e"),
+    ('ἓ', "e"),
+    ('ἔ', "e"),
+    ('ἕ', "e"),
+    ('ἠ', "i"),
+    ('ἡ', "i"),
+    ('ἢ', "i"),
+    ('ἣ', "i"),
+    ('ἤ', "i"),
+    ('ἥ', "i"),
+    ('ἦ', "i"),
+    ('ἧ', "i"),
+    ('ἰ', "i"),
+    ('ἱ', "i"),
+    ('ἲ', "i"),
+    ('ἳ', "i"),
+    ('ἴ', "i"),
+    ('ἵ', "i"),
+    ('ἶ', "i"),
+    ('ἷ', "i"),
+    ('ὀ', "o"),
+    ('ὁ', "o"),
+    ('ὂ', "o"),
+    ('ὃ', "o"),
+    ('ὄ', "o"),
+    ('ὅ', "o"),
+    ('ὐ', "y"),
+    ('ὑ', "y"),
+    ('ὒ', "y"),
+    ('ὓ', "y"),
+    ('ὔ', "y"),
+    ('ὕ', "y"),
+    ('ὖ', "y"),
+    ('ὗ', "y"),
+    ('ὠ', "o"),
+    ('ὡ', "o"),
+    ('ὢ', "o"),
+    ('ὣ', "o"),
+    ('ὤ', "o"),
+    ('ὥ', "o"),
+    ('ὦ', "o"),
+    ('ὧ', "o"),
+    ('ὰ', "a"),
+    ('ά', "a"),
+    ('ὲ', "e"),
+    ('έ', "e"),
+    ('ὴ', "i"),
+    ('ή', "i"),
+    ('ὶ', "i"),
+    ('ί', "i"),
+    ('ὸ', "o"),
+    ('ό', "o"),
+    ('ὺ', "y"),
+    ('ύ', "y"),
+    ('ὼ', "o"),
+    ('ώ', "o"),
+    ('ᾀ', "a"),
+    ('ᾁ', "a"),
+    ('ᾂ', "a"),
+    ('ᾃ', "a"),
+    ('ᾄ', "a"),
+    ('ᾅ', "a"),
+    ('ᾆ', "a"),
+    ('ᾇ', "a"),
+    ('ᾐ', "i"),
+    ('ᾑ', "i"),
+    ('ᾒ', "i"),
+    ('ᾓ', "i"),
+    ('ᾔ', "i"),
+    ('ᾕ', "i"),
+    ('ᾖ', "i"),
+    ('ᾗ', "i"),
+    ('ᾠ', "o"),
+    ('ᾡ', "o"),
+    ('ᾢ', "o"),
+    ('ᾣ', "o"),
+    ('ᾤ', "o"),
+    ('ᾥ', "o"),
+    ('ᾦ', "o"),
+    ('ᾧ', "o"),
+    ('ᾰ', "a"),
+    ('ᾱ', "a"),
+    ('ᾲ', "a"),
+    ('ᾳ', "a"),
+    ('ᾴ', "a"),
+    ('ᾶ', "a"),
+    ('ᾷ', "a"),
+    ('ι', "i"),
+    ('ῂ', "i"),
+    ('ῃ', "i"),
+    ('ῄ', "i"),
+    ('ῆ', "i"),
+    ('ῇ', "i"),
+    ('ῐ', "i"),
+    ('ῑ', "i"),
+    ('ῒ', "i"),
+    ('ΐ', "i"),
+    ('ῖ', "i"),
+    ('ῗ', "i"),
+    ('ῠ', "y"),
+    ('ῡ', "y"),
+    ('ῢ', "y"),
+    ('ΰ', "y"),
+    ('ῤ', "r"),
+    ('ῥ', "r"),
+    ('ῦ', "y"),
+    ('ῧ', "y"),
+    ('ῲ', "o"),
+    ('ῳ', "o"),
+    ('ῴ', "o"),
+    ('ῶ', "o"),
+    ('ῷ', "o"),
+];
+
+/// The ranges of the combining marks of Unicode 16.0, sorted.
+const MARKS: &[(char, char)] = &[
+    ('\u{0300}', '\u{036F}'),
+    ('\u{0483}', '\u{0489}'),
+    ('\u{0591}', '\u{05BD}'),
+    ('\u{05BF}', '\u{05BF}'),
+    ('\u{05C1}', '\u{05C2}'),
+    ('\u{05C4}', '\u{05C5}'),
+    ('\u{05C7}', '\u{05C7}'),
+    ('\u{0610}', '\u{061A}'),
+    ('\u{064B}', '\u{065F}'),
+    ('\u{0670}', '\u{0670}'),
+    ('\u{06D6}', '\u{06DC}'),
+    ('\u{06DF}', '\u{06E4}'),
+    ('\u{06E7}', '\u{06E8}'),
+    ('\u{06EA}', '\u{06ED}'),
+    ('\u{0711}', '\u{0711}'),
+    ('\u{0730}', '\u{074A}'),
+    ('\u{07A6}', '\u{07B0}'),
+    ('\u{07EB}', '\u{07F3}'),
+    ('\u{07FD}', '\u{07FD}'),
+    ('\u{0816}', '\u{0819}'),
+    ('\u{081B}', '\u{0823}'),
+    ('\u{0825}', '\u{0827}'),
+    ('\u{0829}', '\u{082D}'),
+    ('\u{0859}', '\u{085B}'),
+    ('\u{0897}', '\u{089F}'),
+    ('\u{08CA}', '\u{08E1}'),
+    ('\u{08E3}', '\u{0903}'),
+    ('\u{093A}', '\u{093C}'),
+    ('\u{093E}', '\u{094F}'),
+    ('\u{0951}', '\u{0957}'),
+    ('\u{0962}', '\u{0963}'),
+    ('\u{0981}', '\u{0983}'),
+    ('\u{09BC}', '\u{09BC}'),
+    ('\u{09BE}', '\u{09C4}'),
+    ('\u{09C7}', '\u{09C8}'),
+    ('\u{09CB}', '\u{09CD}'),
+    ('\u{09D7}', '\u{09D7}'),
+    ('\u{09E2}', '\u{09E3}'),
+    ('\u{09FE}', '\u{09FE}'),
+    ('\u{0A01}', '\u{0A03}'),
+    ('\u{0A3C}', '\u{0A3C}'),
+    ('\u{0A3E}', '\u{0A42}'),
+    ('\u{0A47}', '\u{0A48}'),
+    ('\u{0A4B}', '\u{0A4D}'),
+    ('\u{0A51}', '\u{0A51}'),
+    ('\u{0A70}', '\u{0A71}'),
+    ('\u{0A75}', '\u{0A75}'),
+    ('\u{0A81}', '\u{0A83}'),
+    ('\u{0ABC}', '\u{0ABC}'),
+    ('\u{0ABE}', '\u{0AC5}'),
+    ('\u{0AC7}', '\u{0AC9}'),
+    ('\u{0ACB}', '\u{0ACD}'),
+    ('\u{0AE2}', '\u{0AE3}'),
+    ('\u{0AFA}', '\u{0AFF}'),
+    ('\u{0B01}', '\u{0B03}'),
+    ('\u{0B3C}', '\u{0B3C}'),
+    ('\u{0B3E}', '\u{0B44}'),
+    ('\u{0B47}', '\u{0B48}'),
+    ('\u{0B4B}', '\u{0B4D}'),
+    ('\u{0B55}', '\u{0B57}'),
+    ('\u{0B62}', '\u{0B63}'),
+    ('\u{0B82}', '\u{0B82}'),
+    ('\u{0BBE}', '\u{0BC2}'),
+    ('\u{0BC6}', '\u{0BC8}'),
+    ('\u{0BCA}', '\u{0BCD}'),
+    ('\u{0BD7}', '\u{0BD7}'),
+    ('\u{0C00}', '\u{0C04}'),
+    ('\u{0C3C}', '\u{0C3C}'),
+    ('\u{0C3E}', '\u{0C44}'),
+    ('\u{0C46}', '\u{0C48}'),
+    ('\u{0C4A}', '\u{0C4D}'),
+    ('\u{0C55}', '\u{0C56}'),
+    ('\u{0C62}', '\u{0C63}'),
+    ('\u{0C81}', '\u{0C83}'),
+    ('\u{0CBC}', '\u{0CBC}'),
+    ('\u{0CBE}', '\u{0CC4}'),
+    ('\u{0CC6}', '\u{0CC8}'),
+    ('\u{0CCA}', '\u{0CCD}'),
+    ('\u{0CD5}', '\u{0CD6}'),
+    ('\u{0CE2}', '\u{0CE3}'),
+    ('\u{0CF3}', '\u{0CF3}'),
+    ('\u{0D00}', '\u{0D03}'),
+    ('\u{0D3B}', '\u{0D3C}'),
+    ('\u{0D3E}', '\u{0D44}'),
+    ('\u{0D46}', '\u{0D48}'),
+    ('\u{0D4A}', '\u{0D4D}'),
+    ('\u{0D57}', '\u{0D57}'),
+    ('\u{0D62}', '\u{0D63}'),
+    ('\u{0D81}', '\u{0D83}'),
+    ('\u{0DCA}', '\u{0DCA}'),
+    ('\u{0DCF}', '\u{0DD4}'),
+    ('\u{0DD6}', '\u{0DD6}'),
+    ('\u{0DD8}', '\u{0DDF}'),
+    ('\u{0DF2}', '\u{0DF3}'),
+    ('\u{0E31}', '\u{0E31}'),
+    ('\u{0E34}', '\u{0E3A}'),
+    ('\u{0E47}', '\u{0E4E}'),
+    ('\u{0EB1}', '\u{0EB1}'),
+    ('\u{0EB4}', '\u{0EBC}'),
+    ('\u{0EC8}', '\u{0ECE}'),
+    ('\u{0F18}', '\u{0F19}'),
+    ('\u{0F35}', '\u{0F35}'),
+    ('\u{0F37}', '\u{0F37}'),
+    ('\u{0F39}', '\u{0F39}'),
+    ('\u{0F3E}', '\u{0F3F}'),
+    ('\u{0F71}', '\u{0F84}'),
+    ('\u{0F86}', '\u{0F87}'),
+    ('\u{0F8D}', '\u{0F97}'),
+    ('\u{0F99}', '\u{0FBC}'),
+    ('\u{0FC6}', '\u{0FC6}'),
+    ('\u{102B}', '\u{103E}'),
+    ('\u{1056}', '\u{1059}'),
+    ('\u{105E}', '\u{1060}'),
+    ('\u{1062}', '\u{1064}'),
+    ('\u{1067}', '\u{106D}'),
+    ('\u{1071}', '\u{1074}'),
+    ('\u{1082}', '\u{108D}'),
+    ('\u{108F}', '\u{108F}'),
+    ('\u{109A}', '\u{109D}'),
+    ('\u{135D}', '\u{135F}'),
+    ('\u{1712}', '\u{1715}'),
+    ('\u{1732}', '\u{1734}'),
+    ('\u{1752}', '\u{1753}'),
+    ('\u{1772}', '\u{1773}'),
+    ('\u{17B4}', '\u{17D3}'),
+    ('\u{17DD}', '\u{17DD}'),
+    ('\u{180B}', '\u{180D}'),
+    ('\u{180F}', '\u{180F}'),
+    ('\u{1885}', '\u{1886}'),
+    ('\u{18A9}', '\u{18A9}'),
+    ('\u{1920}', '\u{192B}'),
+    ('\u{1930}', '\u{193B}'),
+    ('\u{1A17}', '\u{1A1B}'),
+    ('\u{1A55}', '\u{1A5E}'),
+    ('\u{1A60}', '\u{1A7C}'),
+    ('\u{1A7F}', '\u{1A7F}'),
+    ('\u{1AB0}', '\u{1ACE}'),
+    ('\u{1B00}', '\u{1B04}'),
+    ('\u{1B34}', '\u{1B44}'),
+    ('\u{1B6B}', '\u{1B73}'),
+    ('\u{1B80}', '\u{1B82}'),
+    ('\u{1BA1}', '\u{1BAD}'),
+    ('\u{1BE6}', '\u{1BF3}'),
+    ('\u{1C24}', '\u{1C37}'),
+    ('\u{1CD0}', '\u{1CD2}'),
+    ('\u{1CD4}', '\u{1CE8}'),
+    ('\u{1CED}', '\u{1CED}'),
+    ('\u{1CF4}', '\u{1CF4}'),
+    ('\u{1CF7}', '\u{1CF9}'),
+    ('\u{1DC0}', '\u{1DFF}'),
+    ('\u{20D0}', '\u{20F0}'),
+    ('\u{2CEF}', '\u{2CF1}'),
+    ('\u{2D7F}', '\u{2D7F}'),
+    ('\u{2DE0}', '\u{2DFF}'),
+    ('\u{302A}', '\u{302F}'),
+    ('\u{3099}', '\u{309A}'),
+    ('\u{A66F}', '\u{A672}'),
+    ('\u{A674}', '\u{A67D}'),
+    ('\u{A69E}', '\u{A69F}'),
+    ('\u{A6F0}', '\u{A6F1}'),
+    ('\u{A802}', '\u{A802}'),
+    ('\u{A806}', '\u{A806}'),
+    ('\u{A80B}', '\u{A80B}'),
+    ('\u{A823}', '\u{A827}'),
+    ('\u{A82C}', '\u{A82C}'),
+    ('\u{A880}', '\u{A881}'),
+    ('\u{A8B4}', '\u{A8C5}'),
+    ('\u{A8E0}', '\u{A8F1}'),
+    ('\u{A8FF}', '\u{A8FF}'),
+    ('\u{A926}', '\u{A92D}'),
+    ('\u{A947}', '\u{A953}'),
+    ('\u{A980}', '\u{A983}'),
+    ('\u{A9B3}', '\u{A9C0}'),
+    ('\u{A9E5}', '\u{A9E5}'),
+    ('\u{AA29}', '\u{AA36}'),
+    ('\u{AA43}', '\u{AA43}'),
+    ('\u{AA4C}', '\u{AA4D}'),
+    ('\u{AA7B}', '\u{AA7D}'),
+    ('\u{AAB0}', '\u{AAB0}'),
+    ('\u{AAB2}', '\u{AAB4}'),
+    ('\u{AAB7}', '\u{AAB8}'),
+    ('\u{AABE}', '\u{AABF}'),
+    ('\u{AAC1}', '\u{AAC1}'),
+    ('\u{AAEB}', '\u{AAEF}'),
+    ('\u{AAF5}', '\u{AAF6}'),
+    ('\u{ABE3}', '\u{ABEA}'),
+    ('\u{ABEC}', '\u{ABED}'),
+    ('\u{FB1E}', '\u{FB1E}'),
+    ('\u{FE00}', '\u{FE0F}'),
+    ('\u{FE20}', '\u{FE2F}'),
+    ('\u{101FD}', '\u{101FD}'),
+    ('\u{102E0}', '\u{102E0}'),
+    ('\u{10376}', '\u{1037A}'),
+    ('\u{10A01}', '\u{10A03}'),
+    ('\u{10A05}', '\u{10A06}'),
+    ('\u{10A0C}', '\u{10A0F}'),
+    ('\u{10A38}', '\u{10A3A}'),
+    ('\u{10A3F}', '\u{10A3F}'),
+    ('\u{10AE5}', '\u{10AE6}'),
+    ('\u{10D24}', '\u{10D27}'),
+    ('\u{10D69}', '\u{10D6D}'),
+    ('\u{10EAB}', '\u{10EAC}'),
+    ('\u{10EFC}', '\u{10EFF}'),
+    ('\u{10F46}', '\u{10F50}'),
+    ('\u{10F82}', '\u{10F85}'),
+    ('\u{11000}', '\u{11002}'),
+    ('\u{11038}', '\u{11046}'),
+    ('\u{11070}', '\u{11070}'),
+    ('\u{11073}', '\u{11074}'),
+    ('\u{1107F}', '\u{11082}'),
+    ('\u{110B0}', '\u{110BA}'),
+    ('\u{110C2}', '\u{110C2}'),
+    ('\u{11100}', '\u{11102}'),
+    ('\u{11127}', '\u{11134}'),
+    ('\u{11145}', '\u{11146}'),
+    ('\u{11173}', '\u{11173}'),
+    ('\u{11180}', '\u{11182}'),
+    ('\u{111B3}', '\u{111C0}'),
+    ('\u{111C9}', '\u{111CC}'),
+    ('\u{111CE}', '\u{111CF}'),
+    ('\u{1122C}', '\u{11237}'),
+    ('\u{1123E}', '\u{1123E}'),
+    ('\u{11241}', '\u{11241}'),
+    ('\u{112DF}', '\u{112EA}'),
+    ('\u{11300}', '\u{11303}'),
+    ('\u{1133B}', '\u{1133C}'),
+    ('\u{1133E}', '\u{11344}'),
+    ('\u{11347}', '\u{11348}'),
+    ('\u{1134B}', '\u{1134D}'),
+    ('\u{11357}', '\u{11357}'),
+    ('\u{11362}', '\u{11363}'),
+    ('\u{11366}', '\u{1136C}'),
+    ('\u{11370}', '\u{11374}'),
+    ('\u{113B8}', '\u{113C0}'),
+    ('\u{113C2}', '\u{113C2}'),
+    ('\u{113C5}', '\u{113C5}'),
+    ('\u{113C7}', '\u{113CA}'),
+    ('\u{113CC}', '\u{113D0}'),
+    ('\u{113D2}', '\u{113D2}'),
+    ('\u{113E1}', '\u{113E2}'),
+    ('\u{11435}', '\u{11446}'),
+    ('\u{1145E}', '\u{1145E}'),
+    ('\u{114B0}', '\u{114C3}'),
+    ('\u{115AF}', '\u{115B5}'),
+    ('\u{115B8}', '\u{115C0}'),
+    ('\u{115DC}', '\u{115DD}'),
+    ('\u{11630}', '\u{11640}'),
+    ('\u{116AB}', '\u{116B7}'),
+    ('\u{1171D}', '\u{1172B}'),
+    ('\u{1182C}', '\u{1183A}'),
+    ('\u{11930}', '\u{11935}'),
+    ('\u{11937}', '\u{11938}'),
+    ('\u{1193B}', '\u{1193E}'),
+    ('\u{11940}', '\u{11940}'),
+    ('\u{11942}', '\u{11943}'),
+    ('\u{119D1}', '\u{119D7}'),
+    ('\u{119DA}', '\u{119E0}'),
+    ('\u{119E4}', '\u{119E4}'),
+    ('\u{11A01}', '\u{11A0A}'),
+    ('\u{11A33}', '\u{11A39}'),
+    ('\u{11A3B}', '\u{11A3E}'),
+    ('\u{11A47}', '\u{11A47}'),
+    ('\u{11A51}', '\u{11A5B}'),
+    ('\u{11A8A}', '\u{11A99}'),
+    ('\u{11C2F}', '\u{11C36}'),
+    ('\u{11C38}', '\u{11C3F}'),
+    ('\u{11C92}', '\u{11CA7}'),
+    ('\u{11CA9}', '\u{11CB6}'),
+    ('\u{11D31}', '\u{11D36}'),
+    ('\u{11D3A}', '\u{11D3A}'),
+    ('\u{11D3C}', '\u{11D3D}'),
+    ('\u{11D3F}', '\u{11D45}'),
+    ('\u{11D47}', '\u{11D47}'),
+    ('\u{11D8A}', '\u{11D8E}'),
+    ('\u{11D90}', '\u{11D91}'),
+    ('\u{11D93}', '\u{11D97}'),
+    ('\u{11EF3}', '\u{11EF6}'),
+    ('\u{11F00}', '\u{11F01}'),
+    ('\u{11F03}', '\u{11F03}'),
+    ('\u{11F34}', '\u{11F3A}'),
+    ('\u{11F3E}', '\u{11F42}'),
+    ('\u{11F5A}', '\u{11F5A}'),
+    ('\u{13440}', '\u{13440}'),
+    ('\u{13447}', '\u{13455}'),
+    ('\u{1611E}', '\u{1612F}'),
+    ('\u{16AF0}', '\u{16AF4}'),
+    ('\u{16B30}', '\u{16B36}'),
+    ('\u{16F4F}', '\u{16F4F}'),
+    ('\u{16F51}', '\u{16F87}'),
+    ('\u{16F8F}', '\u{16F92}'),
+    ('\u{16FE4}', '\u{16FE4}'),
+    ('\u{16FF0}', '\u{16FF1}'),
+    ('\u{1BC9D}', '\u{1BC9E}'),
+    ('\u{1CF00}', '\u{1CF2D}'),
+    ('\u{1CF30}', '\u{1CF46}'),
+    ('\u{1D165}', '\u{1D169}'),
+    ('\u{1D16D}', '\u{1D172}'),
+    ('\u{1D17B}', '\u{1D182}'),
+    ('\u{1D185}', '\u{1D18B}'),
+    ('\u{1D1AA}', '\u{1D1AD}'),
+    ('\u{1D242}', '\u{1D244}'),
+    ('\u{1DA00}', '\u{1DA36}'),
+    ('\u{1DA3B}', '\u{1DA6C}'),
+    ('\u{1DA75}', '\u{1DA75}'),
+    ('\u{1DA84}', '\u{1DA84}'),
+    ('\u{1DA9B}', '\u{1DA9F}'),
+    ('\u{1DAA1}', '\u{1DAAF}'),
+    ('\u{1E000}', '\u{1E006}'),
+    ('\u{1E008}', '\u{1E018}'),
+    ('\u{1E01B}', '\u{1E021}'),
+    ('\u{1E023}', '\u{1E024}'),
+    ('\u{1E026}', '\u{1E02A}'),
+    ('\u{1E08F}', '\u{1E08F}'),
+    ('\u{1E130}', '\u{1E136}'),
+    ('\u{1E2AE}', '\u{1E2AE}'),
+    ('\u{1E2EC}', '\u{1E2EF}'),
+    ('\u{1E4EC}', '\u{1E4EF}'),
+    ('\u{1E5EE}', '\u{1E5EF}'),
+    ('\u{1E8D0}', '\u{1E8D6}'),
+    ('\u{1E944}', '\u{1E94A}'),
+    ('\u{E0100}', '\u{E01EF}'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mark() {
+        for c in ['\u{0301}', '\u{094D}', '\u{0E31}', '\u{FE0F}', '\u{E01EF}'] {
+            assert!(is_mark(c), "{c:?}");
+        }
+
+        for c in [
+            'a',
+            'é',
+            '日',
+            '\u{02FF}',
+            '\u{0370}',
+            '\u{200D}',
+            '\u{1F3FB}',
+            '😀',
+        ] {
+            assert!(!is_mark(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn test_marks_are_sorted() {
+        assert!(MARKS.iter().all(|(start, end)| start <= end));
+        assert!(MARKS.windows(2).all(|pair| pair[0].1 < pair[1].0));
+    }
+}
@@ -0,0 +1,90 @@
+//! Bindings for JavaScript.
+//!
+//! This module is available with the `wasm` feature. Options are passed as
+//! [`MarkdownOptions`] in JSON, with the same keys and string forms of enum
+//! values as in configuration files. For example:
+//!
+//! ```json
+//! {
+//!     "parser": { "dialect": "common-mark", "max_nesting": 32 },
+//!     "stringifier": { "escape": true, "void_style": "xhtml" },
+//!     "toc": { "max_level": 2 }
+//! }
+//! ```
+//!
+//! Missing keys keep the default values, and an empty string is the same as
+//! `{}`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{options::MarkdownOptions, Markdown};
+
+/// Parse options from JSON.
+fn options(options_json: &str) -> Result<MarkdownOptions, String> {
+    match options_json.trim() {
+        "" => Ok(MarkdownOptions::default()),
+        json => serde_json::from_str(json).map_err(|error| error.to_string()),
+    }
+}
+
+/// Make the Markdown instance from options in JSON.
+fn markdown(options_json: &str) -> Result<Markdown, JsError> {
+    let options = options(options_json).map_err(|error| JsError::new(&error))?;
+
+    Ok(Markdown::from_options(&options))
+}
+
+/// Render markdown to HTML.
+#[wasm_bindgen]
+pub fn render(input: &str, options_json: &str) -> Result<String, JsError> {
+    Ok(markdown(options_json)?.execute(input))
+}
+
+/// The HTML and the table of contents returned by [`render_with_toc`].
+#[wasm_bindgen(getter_with_clone)]
+pub struct RenderedWithToc {
+    pub html: String,
+    pub toc: String,
+}
+
+/// Render markdown to HTML with a table of contents, which is made with the
+/// `toc` options.
+#[wasm_bindgen(js_name = renderWithToc)]
+pub fn render_with_toc(input: &str, options_json: &str) -> Result<RenderedWithToc, JsError> {
+    let (html, toc) = markdown(options_json)?.execute_with_toc(input);
+
+    Ok(RenderedWithToc { html, toc })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options() {
+        let markdown = markdown(
+            r#"{
+                "parser": { "dialect": "common-mark", "list_indent_style": "space:4" },
+                "transformer": { "section": true, "heading_ids": true },
+                "stringifier": { "escape": true, "void_style": "xhtml" },
+                "toc": { "max_level": 1 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            markdown.execute("# A\n<b>\nc  \nd"),
+            "<section><h1 id=\"a\">A</h1><p>&lt;b&gt;\nc<br />d</p></section>"
+        );
+        assert_eq!(
+            markdown.execute_with_toc("# A\n\n## B").1,
+            "<ul><li><a href=\"#a\">A</a></li></ul>"
+        );
+
+        assert_eq!(options(""), Ok(MarkdownOptions::default()));
+        assert!(options(r#"{"parser": {"unknown": 1}}"#).is_err());
+        assert!(options(r#"{"stringifier": {"void_style": "x"}}"#)
+            .unwrap_err()
+            .contains("invalid value `x` for VoidStyle"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Run the `note-mark` binary, which is built with the `cli` feature.
+
+#![cfg(feature = "cli")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Run the binary with the input as the standard input.
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_note-mark"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(args: &[&str], input: &str) -> String {
+    let output = run(args, input);
+
+    assert!(output.status.success(), "{output:?}");
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn stderr(args: &[&str], input: &str, code: i32) -> String {
+    let output = run(args, input);
+
+    assert_eq!(output.status.code(), Some(code), "{output:?}");
+
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn cli() {
+    let input = "# A\n\n<b>x</b> [l](javascript:a)\n\n## B\n\nc";
+
+    assert_eq!(
+        stdout(&[], input),
+        "<h1>A</h1><p>&lt;b&gt;x&lt;/b&gt; <a>l</a></p><h2>B</h2><p>c</p>\n"
+    );
+    assert_eq!(
+        stdout(&["--unsafe-html"], input),
+        "<h1>A</h1><p><b>x</b> <a href=\"javascript:a\">l</a></p><h2>B</h2><p>c</p>\n"
+    );
+    assert_eq!(
+        stdout(&["--unsafe-html", "--profile", "comments"], input),
+        stdout(&["--unsafe-html"], input),
+        "the flag lets any link through, even if the profile limits them"
+    );
+    assert_eq!(
+        stdout(&["--toc", "-"], "# A\n\n## B"),
+        concat![
+            "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li></ul>\n",
+            "<h1 id=\"a\">A</h1><h2 id=\"b\">B</h2>\n",
+        ]
+    );
+    assert_eq!(
+        stdout(&["--format", "--section", "b"], input),
+        "<h2>B</h2>\n<p>c</p>\n"
+    );
+    assert_eq!(stdout(&["--section=b"], input), "<h2>B</h2><p>c</p>\n");
+    assert_eq!(
+        stdout(&["--profile", "notes"], "# A\n\n==b=="),
+        "<h1 id=\"a\">A</h1><p><mark>b</mark></p>\n"
+    );
+    assert!(stdout(&["--help"], "").starts_with("Usage: note-mark"));
+}
+
+#[test]
+fn cli_errors() {
+    assert_eq!(
+        stderr(&["--section", "z"], "# A", 1),
+        "note-mark: no headline has the id `z`\n"
+    );
+
+    // The path after `--` is a file even if it looks like an option.
+    let error = std::fs::File::open("--missing.md").unwrap_err();
+
+    assert_eq!(
+        stderr(&["--", "--missing.md"], "", 1),
+        format!("note-mark: --missing.md: cannot open the file: {error}\n")
+    );
+    assert!(stderr(&["--bogus"], "", 2).starts_with("note-mark: unknown option `--bogus`\n"));
+    assert!(stderr(&["--profile", "x"], "", 2)
+        .starts_with("note-mark: invalid value `x` for Profile\n"));
+    assert!(stderr(&["--toc", "--section", "a"], "", 2)
+        .starts_with("note-mark: `--toc` and `--section` cannot be used together\n"));
+    assert!(stderr(&["--section"], "", 2).starts_with("note-mark: `--section` needs a value\n"));
+}
@@ -0,0 +1,78 @@
+//! Run the examples of the CommonMark spec and report the pass rate.
+//!
+//! The examples of spec 0.31.2 are in `tests/commonmark/spec.json`. Another
+//! path can be given by the `COMMONMARK_SPEC` environment variable. The pass
+//! rates of each section are printed with:
+//!
+//! ```sh
+//! cargo test --test commonmark -- --nocapture
+//! ```
+//!
+//! The dialects are not fully compatible with CommonMark, so the test fails
+//! only if fewer examples pass than the minimum of a dialect. Raise the
+//! minimum when a change makes more examples pass.
+
+use std::{collections::BTreeMap, env, fs};
+
+use note_mark::prelude::*;
+use serde::Deserialize;
+
+/// An example of the spec. The other fields are ignored.
+#[derive(Deserialize)]
+struct Example {
+    markdown: String,
+    html: String,
+    section: String,
+}
+
+#[test]
+fn commonmark_spec() {
+    let path = env::var("COMMONMARK_SPEC").unwrap_or_else(|_| {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/commonmark/spec.json").to_string()
+    });
+
+    let spec = fs::read_to_string(&path).unwrap_or_else(|error| panic!("{path}: {error}"));
+
+    let examples = serde_json::from_str::<Vec<Example>>(&spec)
+        .unwrap_or_else(|error| panic!("{path}: {error}"));
+
+    // The least number of examples which must pass in each dialect.
+    for (dialect, minimum) in [(Dialect::NoteMark, 197), (Dialect::CommonMark, 224)] {
+        let markdown = Markdown::default()
+            .parser(Parser::default().dialect(dialect))
+            .stringifier(Stringifier::new().escape(true));
+
+        let mut sections = BTreeMap::<&str, (usize, usize)>::new();
+
+        for example in &examples {
+            let passed =
+                normalize(&markdown.execute(&example.markdown)) == normalize(&example.html);
+
+            let (pass, total) = sections.entry(&example.section).or_default();
+
+            *pass += passed as usize;
+            *total += 1;
+        }
+
+        let pass = sections.values().map(|(pass, _)| pass).sum::<usize>();
+
+        println!("{dialect}: {pass}/{} passed", examples.len());
+
+        for (section, (pass, total)) in sections {
+            println!("  {section}: {pass}/{total}");
+        }
+
+        assert!(
+            pass >= minimum,
+            "{dialect}: {pass} examples passed, fewer than {minimum}"
+        );
+    }
+}
+
+/// Remove line breaks between tags, since the output does not have them.
+fn normalize(html: &str) -> String {
+    html.replace(">\n", ">")
+        .replace("\n<", "<")
+        .trim_end()
+        .to_string()
+}
@@ -0,0 +1,71 @@
+//! Render the documents of `tests/corpus` and compare them with their
+//! expected HTML.
+//!
+//! Each `name.md` is rendered to formatted HTML and compared with
+//! `name.html` next to it. Front matter options are enabled, so a document
+//! turns on the syntax it uses in its front matter.
+//!
+//! To add a real-world document, such as meeting notes, a README or a wiki
+//! page, put it in the directory and write its HTML with:
+//!
+//! ```sh
+//! UPDATE_CORPUS=1 cargo test --test corpus
+//! ```
+//!
+//! The same command updates the HTML of all documents after an intended
+//! change. Review the diff of the HTML files before committing them.
+
+use std::{env, fs, path::Path};
+
+use note_mark::prelude::*;
+
+#[test]
+fn corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let update = env::var_os("UPDATE_CORPUS").is_some();
+
+    let markdown = Markdown::default()
+        .parser(Parser::default().front_matter(true))
+        .front_matter_options(true)
+        .stringifier(Stringifier::new().format(true));
+
+    let mut paths = fs::read_dir(&dir)
+        .expect("tests/corpus is not found")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "md"))
+        .collect::<Vec<_>>();
+
+    paths.sort();
+
+    assert!(!paths.is_empty(), "tests/corpus has no documents");
+
+    let mut failures = vec![];
+
+    for path in &paths {
+        let input = fs::read_to_string(path).unwrap();
+        let html = markdown.execute(&input) + "\n";
+        let expected_path = path.with_extension("html");
+
+        if update {
+            fs::write(&expected_path, &html).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == html => {}
+            Ok(expected) => {
+                failures.push(format!(
+                    "{}:\n--- expected\n{expected}--- actual\n{html}",
+                    path.display()
+                ));
+            }
+            Err(_) => failures.push(format!("{}: no expected HTML", path.display())),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nRun `UPDATE_CORPUS=1 cargo test --test corpus` to accept the output.",
+        failures.join("\n\n")
+    );
+}
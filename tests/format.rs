@@ -0,0 +1,184 @@
+//! Render generated documents compactly and formatted, which must render the
+//! same.
+//!
+//! Formatting may only add white spaces which browsers do not render, so the
+//! outputs are compared after collapsing white spaces like HTML does.
+
+use note_mark::prelude::*;
+use proptest::prelude::*;
+
+/// The elements whose tags end a line of text, so white spaces next to their
+/// tags are not rendered.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "ul",
+    "ol",
+    "li",
+    "h1",
+    "h2",
+    "h3",
+    "blockquote",
+    "div",
+    "section",
+    "nav",
+    "figure",
+    "figcaption",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+];
+
+/// Split HTML into tags and text, and drop the white spaces which are not
+/// rendered.
+fn rendered(html: &str) -> Vec<String> {
+    let mut nodes: Vec<String> = vec![];
+    // Whether the last rendered character is a space or the start of a line.
+    let mut after_space = true;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let len = match rest.find('<') {
+            Some(0) => rest.find('>').map_or(rest.len(), |end| end + 1),
+            Some(len) => len,
+            None => rest.len(),
+        };
+
+        let (node, next) = rest.split_at(len);
+
+        rest = next;
+
+        if node.starts_with('<') {
+            let name = node
+                .trim_start_matches(['<', '/'])
+                .split([' ', '>'])
+                .next()
+                .unwrap_or_default();
+
+            if BLOCK_TAGS.contains(&name) {
+                // A space before the end of a line is not rendered.
+                if let Some(last) = nodes.last_mut().filter(|last| !last.starts_with('<')) {
+                    last.truncate(last.trim_end().len());
+
+                    if last.is_empty() {
+                        nodes.pop();
+                    }
+                }
+
+                after_space = true;
+            }
+
+            nodes.push(node.to_string());
+            continue;
+        }
+
+        let mut text = String::new();
+
+        for c in node.chars() {
+            if c.is_ascii_whitespace() {
+                if !after_space {
+                    text.push(' ');
+                }
+
+                after_space = true;
+            } else {
+                text.push(c);
+                after_space = false;
+            }
+        }
+
+        if !text.is_empty() {
+            nodes.push(text);
+        }
+    }
+
+    nodes
+}
+
+/// Generate inline markup.
+fn inline() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        Just("a".to_string()),
+        Just("word".to_string()),
+        Just("two words".to_string()),
+        Just("a long run of words to pass the width".to_string()),
+    ];
+
+    leaf.prop_recursive(3, 12, 3, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|text| format!("*{text}*")),
+            inner.clone().prop_map(|text| format!("**{text}**")),
+            inner.clone().prop_map(|text| format!("[{text}](url)")),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("{a}{b}")),
+            (inner.clone(), inner).prop_map(|(a, b)| format!("{a} {b}")),
+        ]
+    })
+}
+
+/// Generate the lines of blocks.
+fn blocks() -> impl Strategy<Value = Vec<String>> {
+    let leaf = prop_oneof![
+        inline().prop_map(|text| vec![text]),
+        (inline(), inline()).prop_map(|(a, b)| vec![a, b]),
+        inline().prop_map(|text| vec![format!("# {text}")]),
+    ];
+
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            // A list item whose blocks are indented under the marker.
+            (inline(), prop::option::of(inner.clone())).prop_map(|(name, children)| {
+                let mut lines = vec![format!("- {name}")];
+
+                lines.extend(
+                    children
+                        .into_iter()
+                        .flatten()
+                        .map(|line| format!("  {line}")),
+                );
+                lines
+            }),
+            inner
+                .clone()
+                .prop_map(|lines| lines.iter().map(|line| format!("> {line}")).collect()),
+            (inner.clone(), inner).prop_map(|(mut a, b)| {
+                a.push(String::new());
+                a.extend(b);
+                a
+            }),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn formatted_renders_like_compact(lines in blocks(), width in 1u32..40) {
+        let input = lines.join("\n");
+
+        let compact = Markdown::default().execute(&input);
+
+        for preserve_inline in [false, true] {
+            let stringifier = Stringifier::new()
+                .format(true)
+                .width(width)
+                .preserve_inline(preserve_inline);
+
+            let formatted = Markdown::default().stringifier(stringifier).execute(&input);
+
+            prop_assert_eq!(rendered(&formatted), rendered(&compact), "{}", formatted);
+        }
+    }
+}
+
+#[test]
+fn test_rendered() {
+    assert_eq!(
+        rendered("<p>\n  a\n  <em>b</em> \n</p>\n<p>c</p>"),
+        vec!["<p>", "a ", "<em>", "b", "</em>", "</p>", "<p>", "c", "</p>"]
+    );
+    assert_ne!(
+        rendered("<p>a<em>b</em></p>"),
+        rendered("<p>a\n<em>b</em></p>")
+    );
+}
@@ -0,0 +1,147 @@
+//! Compare `IndentRule::Strict` and `IndentRule::Loose` on generated lists.
+//!
+//! The lists are indented by whole levels, and each marker line is then given
+//! extra indent of less than one level. Loose lists must be the same with and
+//! without the extra indent, and the same as strict lists without it.
+
+use note_mark::prelude::*;
+
+/// A xorshift generator, so the inputs are the same in every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+/// A line of a generated list.
+struct Line {
+    depth: usize,
+    text: &'static str,
+    blank_before: bool,
+}
+
+const TEXTS: &[&str] = &["- a", "- *b*", "1. c", "2. d", "e", "> f"];
+
+/// Generate a list. A line can be one level deeper than a list marker before
+/// it, so indented lines are always in a list item. A blank line follows a
+/// line which is not a list marker, since a paragraph takes the markers after
+/// it as text. Such a line without indent ends the list.
+fn lines(rng: &mut Rng) -> Vec<Line> {
+    let len = 1 + rng.next() % 8;
+    let mut max_depth = 0;
+    let mut paragraph = false;
+    let mut lines = vec![];
+
+    for index in 0..len {
+        let depth = rng.next() % (max_depth + 1);
+
+        // The first line starts the list.
+        let text = TEXTS[rng.next() % if index == 0 { 4 } else { TEXTS.len() }];
+
+        lines.push(Line {
+            depth,
+            text,
+            blank_before: paragraph || (index > 0 && rng.next() & 3 == 0),
+        });
+
+        paragraph = !is_marker(text);
+
+        max_depth = match (is_marker(text), depth) {
+            (true, _) => depth + 1,
+            (false, 0) => break,
+            (false, _) => depth,
+        };
+    }
+
+    lines
+}
+
+fn is_marker(text: &str) -> bool {
+    text.starts_with('-') || text.contains(". ")
+}
+
+/// Render the lines with the indent of each level and the extra indent of
+/// each marker line.
+fn render(lines: &[Line], level: &[&str], extra: impl Fn(usize) -> usize) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let blank = if line.blank_before { "\n" } else { "" };
+            let extra = match is_marker(line.text) {
+                true => extra(index),
+                false => 0,
+            };
+
+            format!(
+                "{blank}{}{}{}\n",
+                level[index % level.len()].repeat(line.depth),
+                " ".repeat(extra),
+                line.text
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn strict_and_loose() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    // The indent style, the indents of one level, and the width of a level.
+    let styles: [(IndentStyle, &[&str], usize); 3] = [
+        (IndentStyle::Space(2), &["  "], 2),
+        (IndentStyle::Space(4), &["    "], 4),
+        (IndentStyle::Both, &["\t", "    ", "  \t", " \t"], 4),
+    ];
+
+    for (style, level, width) in styles {
+        let strict = Markdown::default().parser(Parser::default().list_indent_style(style));
+        let loose = Markdown::default().parser(
+            Parser::default()
+                .list_indent_style(style)
+                .list_indent_rule(IndentRule::Loose),
+        );
+
+        for _ in 0..500 {
+            let lines = lines(&mut rng);
+            let seed = rng.next();
+
+            let aligned = render(&lines, level, |_| 0);
+            let shifted = render(&lines, level, |index| (seed >> index) % width);
+
+            let expected = strict.execute(&aligned);
+
+            assert_eq!(loose.execute(&aligned), expected, "{style}: {aligned:?}");
+            assert_eq!(loose.execute(&shifted), expected, "{style}: {shifted:?}");
+        }
+    }
+}
+
+#[test]
+fn extra_indent() {
+    let strict = Markdown::default();
+    let loose = Markdown::default().parser(Parser::default().list_indent_rule(IndentRule::Loose));
+
+    for (input, expected) in [
+        (" - a", "<ul><li>a</li></ul>"),
+        ("1. a\n 2. b", "<ol><li>a</li><li>b</li></ol>"),
+        ("- a\n\n - b", "<ul><li>a</li><li>b</li></ul>"),
+        (
+            "- a\n   - b\n  - c",
+            "<ul><li>a<ul><li>b</li><li>c</li></ul></li></ul>",
+        ),
+    ] {
+        assert_eq!(loose.execute(input), expected, "{input:?}");
+    }
+
+    assert_eq!(strict.execute(" - a"), "<p> - a</p>");
+    assert_eq!(
+        strict.execute("1. a\n 2. b"),
+        "<ol><li>a<br> 2. b</li></ol>"
+    );
+}
@@ -0,0 +1,194 @@
+//! Render generated input with several configurations, which must not
+//! panic.
+//!
+//! The input is made of pieces of markup, so it reaches more syntax than
+//! random bytes. The `fuzz` directory has a fuzz target for longer runs.
+
+use note_mark::prelude::*;
+
+const PIECES: &[&str] = &[
+    "*",
+    "**",
+    "[",
+    "]",
+    "(",
+    ")",
+    "#",
+    "# ",
+    "- ",
+    "1. ",
+    "b) ",
+    "iv. ",
+    "> ",
+    " ",
+    "  ",
+    "    ",
+    "\t",
+    "\n",
+    "\n\n",
+    "\r\n",
+    "a",
+    "b c",
+    "\\",
+    "{",
+    "}",
+    "{#x .y z=1}",
+    "*[a]: b c\n",
+    "^[",
+    ":",
+    "`",
+    "|",
+    "| :-- |\n",
+    "||",
+    "^^",
+    ".",
+    "\"",
+    "あ",
+    "\u{301}",
+    "---\n",
+    "!",
+    "~",
+    "=",
+    "==",
+    "_",
+    "<",
+    "<!--",
+    "-->",
+    "+",
+    "$",
+    "99999999999999999999999. ",
+    "![[a]]",
+    "![[b c|d]]\n",
+];
+
+/// A xorshift generator, so the inputs are the same in every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+fn markdowns() -> Vec<Markdown> {
+    vec![
+        Markdown::default(),
+        Markdown::default().parser(Parser::new().dialect(Dialect::CommonMark)),
+        Markdown::default().parser(
+            Parser::new()
+                .list_indent_style(IndentStyle::Tab)
+                .emphasis_rule(EmphasisRule::Nearest)
+                .comments(Comments::Preserve)
+                .tables(Tables::Piped)
+                .max_nesting(Some(2)),
+        ),
+        Markdown::default()
+            .parser(
+                Parser::new()
+                    .list_indent_style(IndentStyle::Both)
+                    .front_matter(true)
+                    .max_block_nesting(Some(1))
+                    .max_inline_nesting(Some(1))
+                    .include_resolver(|target: &str| match target {
+                        "a" => Some(Inclusion::Markdown("# a\n\n![[a]]\n\n> ![[b c]]".into())),
+                        "b c" => Some(Inclusion::Markdown("- ![[a]]\n\n## b".into())),
+                        _ => None,
+                    }),
+            )
+            .stringifier(
+                Stringifier::new()
+                    .format(true)
+                    .escape(true)
+                    .source_positions(true),
+            ),
+        Markdown::default()
+            .parser(
+                Parser::new()
+                    .list_indent_style(IndentStyle::Space(0))
+                    .line_break_style(LineBreakStyle::SoftBreak)
+                    .wiki_links(true)
+                    .hashtags(true)
+                    .mentions(true)
+                    .highlight(true)
+                    .fancy_lists(true)
+                    .comments(Comments::Strip)
+                    .abbreviations(true)
+                    .inline_footnotes(true)
+                    .tables(Tables::Optional)
+                    .table_spans(true),
+            )
+            .transformer(Transformer::new().section(true).line_wrap(LineWrap::Join)),
+        Markdown::untrusted().parser(
+            Parser::new()
+                .list_indent_style(IndentStyle::Space(3))
+                .list_indent_rule(IndentRule::Loose)
+                .max_input_len(Some(7))
+                .max_tokens(Some(5)),
+        ),
+    ]
+}
+
+fn render(markdown: &Markdown, input: &str) {
+    markdown.execute(input);
+    markdown.execute_text(input);
+    markdown.execute_with_toc(input);
+    markdown.execute_with_diagnostics(input);
+    markdown.reformat(input);
+    markdown.validate_input(input);
+    markdown.outline(input);
+}
+
+#[test]
+fn generated_input() {
+    let markdowns = markdowns();
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..2000 {
+        let len = rng.next() % 32;
+
+        let input = (0..len)
+            .map(|_| PIECES[rng.next() % PIECES.len()])
+            .collect::<String>();
+
+        for markdown in &markdowns {
+            render(markdown, &input);
+        }
+    }
+}
+
+#[test]
+fn regressions() {
+    let markdowns = markdowns();
+
+    // Mixed indents, and an ordered list with a width of 0.
+    for input in [
+        "-  \t",
+        "- a\n \t- b",
+        "9999999999999999999999. a",
+        "1. a\n   - b",
+    ] {
+        for markdown in &markdowns {
+            render(markdown, input);
+        }
+    }
+}
+
+#[test]
+fn deep_nesting() {
+    let markdowns = markdowns();
+
+    // Input like this overflows the stack of a test thread without a
+    // nesting limit.
+    for input in [
+        ">".repeat(20_000) + " a",
+        "[".repeat(5_000) + "a" + &"](b)".repeat(5_000),
+        "*a ".repeat(20_000),
+    ] {
+        for markdown in &markdowns {
+            render(markdown, &input);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Reformat generated documents, which must parse to the same tree.
+//!
+//! Editors reformat documents on save, so [`Markdown::reformat`] must never
+//! change what a document means, and reformatting its output again must not
+//! change it.
+//!
+//! The formatter drops white spaces at the start of lines and escapes text
+//! which would start a block, which do not change the rendered document. The
+//! trees are compared after [`Normalizer`] undoes those differences. The
+//! rendered text is compared without white spaces, since smart punctuation
+//! reads escaped characters differently. Headline ids are not compared, which
+//! change with white spaces.
+//!
+//! Emphasis directly in emphasis of the same kind is not checked, since `*`
+//! around punctuation, such as `*{*=*[*`, cannot always be written so that it
+//! pairs the same way.
+
+use note_mark::{model::tree::*, options::Profile, prelude::*};
+use proptest::prelude::*;
+
+/// The configurations to test, whose parser and markdown stringifier agree,
+/// and their parsers.
+fn configs() -> Vec<(Markdown, Parser)> {
+    let extended = Parser::default()
+        .wiki_links(true)
+        .hashtags(true)
+        .mentions(true)
+        .highlight(true)
+        .fancy_lists(true)
+        .abbreviations(true)
+        .inline_footnotes(true)
+        .tables(Tables::Optional)
+        .table_spans(true);
+
+    let soft_break = Parser::default()
+        .line_break_style(LineBreakStyle::SoftBreak)
+        .list_indent_style(IndentStyle::Tab);
+
+    let docs = Parser::default()
+        .front_matter(true)
+        .fancy_lists(true)
+        .highlight(true)
+        .abbreviations(true)
+        .comments(Comments::Strip);
+
+    vec![
+        (Markdown::default(), Parser::default()),
+        (Markdown::profile(Profile::Docs), docs),
+        (
+            Markdown::default()
+                .parser(extended.clone())
+                .markdown_stringifier(MarkdownStringifier::new().tables(Tables::Optional)),
+            extended,
+        ),
+        (
+            Markdown::default()
+                .parser(soft_break.clone())
+                .markdown_stringifier(
+                    MarkdownStringifier::new()
+                        .line_break_style(LineBreakStyle::SoftBreak)
+                        .list_indent_style(IndentStyle::Tab),
+                ),
+            soft_break,
+        ),
+    ]
+}
+
+/// The normalizer of the parts of a tree which the formatter may write
+/// differently: escaped characters are text, white spaces at the start and
+/// the end of lines are removed, and paragraphs of only white spaces are
+/// dropped.
+#[derive(Default)]
+struct Normalizer {
+    /// Whether emphasis is directly in emphasis of the same kind, such as
+    /// `*a*` in `*{*a*}*`. `*` around punctuation may pair differently when
+    /// it is written back, so such trees are not checked.
+    nested_emphasis: bool,
+}
+
+impl Normalizer {
+    fn tree(&mut self, tree: MarkdownTree<'_>) -> MarkdownTree<'static> {
+        let mut tree = tree.into_owned();
+
+        self.blocks(&mut tree.root.root);
+
+        tree
+    }
+
+    fn blocks(&mut self, items: &mut Vec<BlockItem<'_>>) {
+        for item in items.iter_mut() {
+            self.block(item);
+        }
+
+        items.retain(|item| !matches!(item, BlockItem::Paragraph(tree) if tree.root.is_empty()));
+    }
+
+    fn block(&mut self, item: &mut BlockItem<'_>) {
+        match item {
+            BlockItem::Paragraph(tree) | BlockItem::Headline(_, tree) => self.inline(tree),
+            BlockItem::BulletList(tree) | BlockItem::OrderedList(_, _, tree) => {
+                for item in &mut tree.root {
+                    self.inline(&mut item.name);
+                    self.blocks(&mut item.children);
+                }
+            }
+            BlockItem::BlockQuote(tree) | BlockItem::Container(_, tree) => {
+                self.blocks(&mut tree.root)
+            }
+            BlockItem::Attributed(_, item) => self.block(item),
+            BlockItem::Table(table) => {
+                for cell in table.head.iter_mut().chain(table.rows.iter_mut().flatten()) {
+                    if let TableCell::Cell { tree, .. } = cell {
+                        self.inline(tree);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn inline(&mut self, tree: &mut InlineTree<'_>) {
+        let mut root: Vec<InlineItem<'_>> = vec![];
+        let mut line_start = true;
+
+        for mut item in tree.root.drain(..) {
+            if let InlineItem::Escaped(text) = item {
+                item = InlineItem::Text(text);
+            }
+
+            match &mut item {
+                InlineItem::Text(text) => {
+                    let text = if line_start { text.trim_start() } else { text };
+
+                    if text.is_empty() {
+                        continue;
+                    }
+
+                    line_start = false;
+
+                    if let Some(InlineItem::Text(last)) = root.last_mut() {
+                        last.to_mut().push_str(text);
+                    } else {
+                        root.push(InlineItem::Text(text.to_string().into()));
+                    }
+
+                    continue;
+                }
+                InlineItem::Break | InlineItem::SoftBreak => line_start = true,
+                _ => line_start = false,
+            }
+
+            self.inline_item(&mut item);
+            root.push(item);
+        }
+
+        for index in 0..root.len() {
+            let line_end = matches!(
+                root.get(index + 1),
+                None | Some(InlineItem::Break | InlineItem::SoftBreak)
+            );
+
+            if let (true, InlineItem::Text(text)) = (line_end, &mut root[index]) {
+                *text = text.trim_end().to_string().into();
+            }
+        }
+
+        root.retain(|item| !matches!(item, InlineItem::Text(text) if text.is_empty()));
+
+        tree.root = root;
+    }
+
+    fn inline_item(&mut self, item: &mut InlineItem<'_>) {
+        let kind = std::mem::discriminant(&*item);
+
+        match item {
+            InlineItem::Italic(tree) | InlineItem::Strong(tree) => {
+                self.nested_emphasis |= tree
+                    .root
+                    .iter()
+                    .any(|child| std::mem::discriminant(child) == kind);
+
+                self.inline(tree);
+            }
+            InlineItem::Highlight(tree)
+            | InlineItem::Footnote(tree)
+            | InlineItem::Span(tree)
+            | InlineItem::Link { tree, .. } => self.inline(tree),
+            InlineItem::Attributed(_, item) => self.inline_item(item),
+            _ => {}
+        }
+    }
+}
+
+/// Reformat the input, and check that the output means the same and is
+/// stable.
+fn check((markdown, parser): &(Markdown, Parser), input: &str) -> Result<(), TestCaseError> {
+    let mut normalizer = Normalizer::default();
+
+    let tree = normalizer.tree(parser.parse(input, lex(input)));
+
+    prop_assume!(!normalizer.nested_emphasis);
+
+    let output = markdown.reformat(input);
+
+    prop_assert_eq!(
+        Normalizer::default().tree(parser.parse(&output, lex(&output))),
+        tree,
+        "output: {:?}",
+        output
+    );
+    prop_assert_eq!(markdown.reformat(&output), output.clone());
+
+    let render = |input: &str| {
+        let mut text = markdown.execute_text(input);
+
+        text.retain(|c| !c.is_whitespace());
+        text
+    };
+
+    prop_assert_eq!(render(&output), render(input), "output: {:?}", output);
+
+    Ok(())
+}
+
+/// Generate inline markup.
+fn inline() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        Just("a".to_string()),
+        Just("two words".to_string()),
+        Just("snake_case".to_string()),
+        Just("1.".to_string()),
+        Just("#tag".to_string()),
+        Just("@name".to_string()),
+        Just("\\*".to_string()),
+        Just("a\\".to_string()),
+        Just("|".to_string()),
+    ];
+
+    leaf.prop_recursive(3, 12, 3, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|text| format!("*{text}*")),
+            inner.clone().prop_map(|text| format!("**{text}**")),
+            inner.clone().prop_map(|text| format!("=={text}==")),
+            inner.clone().prop_map(|text| format!("^[{text}]")),
+            inner.clone().prop_map(|text| format!("[{text}](/url)")),
+            inner
+                .clone()
+                .prop_map(|text| format!("[{text}](/a_(b) \"t \\\" t\")")),
+            inner
+                .clone()
+                .prop_map(|text| format!("[{text}]{{.c #i k=\"v w\"}}")),
+            inner.clone().prop_map(|text| format!("[[{text}]]")),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("[[{a}|{b}]]")),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("{a}{b}")),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("{a}  \n{b}")),
+            (inner.clone(), inner).prop_map(|(a, b)| format!("{a} {b}")),
+        ]
+    })
+}
+
+/// Generate the lines of blocks.
+fn blocks() -> impl Strategy<Value = Vec<String>> {
+    let leaf = prop_oneof![
+        inline().prop_map(|text| text.lines().map(str::to_string).collect()),
+        (1usize..4, inline()).prop_map(|(level, text)| vec![format!(
+            "{} {}",
+            "#".repeat(level),
+            text.replace('\n', " ")
+        )]),
+        inline().prop_map(|text| vec![
+            format!("| {} | b |", text.replace('\n', " ")),
+            "|:-|--:|".to_string(),
+            "| c || ".to_string()
+        ]),
+        Just(vec!["*[HTML]: Hyper Text".to_string()]),
+        Just(vec!["<!-- note -->".to_string()]),
+    ];
+
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        let marker = prop_oneof![Just("-"), Just("1."), Just("3)"), Just("b."), Just("iv."),];
+
+        prop_oneof![
+            // A list item whose blocks are indented under the marker.
+            (marker, inline(), prop::option::of(inner.clone())).prop_map(
+                |(marker, name, children)| {
+                    let mut lines = vec![format!("{marker} {}", name.replace('\n', " "))];
+
+                    lines.extend(
+                        children
+                            .into_iter()
+                            .flatten()
+                            .map(|line| format!("  {line}")),
+                    );
+                    lines
+                }
+            ),
+            inner
+                .clone()
+                .prop_map(|lines| lines.iter().map(|line| format!("> {line}")).collect()),
+            inner.clone().prop_map(|mut lines| {
+                lines.push("{.c}".to_string());
+                lines
+            }),
+            (inner.clone(), inner).prop_map(|(mut a, b)| {
+                a.push(String::new());
+                a.extend(b);
+                a
+            }),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn reformat_keeps_structured_documents(lines in blocks()) {
+        let input = lines.join("\n");
+
+        for config in configs() {
+            check(&config, &input)?;
+        }
+    }
+
+    #[test]
+    fn reformat_keeps_any_documents(input in "[a1 *_#>|:=^@!{}()\\[\\]\\-.\\\\\n\t]{0,48}") {
+        for config in configs() {
+            check(&config, &input)?;
+        }
+    }
+}
+
+#[test]
+fn reformat_keeps_front_matter() {
+    let markdown = Markdown::default().parser(Parser::default().front_matter(true));
+
+    for input in [
+        "---\ntitle: x\ntoc: true\n---\n# A\n",
+        "---\ntitle: x\n...\n",
+        "---\ntitle: x\n---",
+    ] {
+        assert_eq!(markdown.reformat(input), input);
+    }
+
+    assert_eq!(
+        markdown.reformat("---\ntitle: x\n---\n\n#  A\n"),
+        "---\ntitle: x\n---\n# A\n"
+    );
+    assert_eq!(
+        Markdown::default().reformat("---\ntitle: x\n---\n"),
+        "---\ntitle: x\n---\n",
+        "without the option, the lines are a paragraph"
+    );
+}
+
+#[test]
+fn reformat_keeps_text_after_stripped_comments() {
+    let comments = Parser::default().mentions(true).comments(Comments::Strip);
+
+    let config = (Markdown::profile(Profile::Comments), comments);
+
+    for input in [
+        "<!-- c --># a",
+        "<!-- c -->- a",
+        "<!-- c -->1. a",
+        "<!-- c -->> a",
+        "<!-- more -->b. c",
+    ] {
+        for config in configs().iter().chain([&config]) {
+            check(config, input).unwrap();
+        }
+
+        assert_eq!(
+            Markdown::profile(Profile::Docs).reformat(input),
+            format!("{input}\n")
+        );
+    }
+}
+
+#[test]
+fn reformat_keeps_smart_punctuation() {
+    for input in ["a\n---\nb", "a\n--\n-", "a|b\n---\nc", "a\n:--\n'b'"] {
+        for config in configs() {
+            check(&config, input).unwrap();
+        }
+    }
+}
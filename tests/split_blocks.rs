@@ -0,0 +1,150 @@
+//! Render documents made of random lines through every path which parses
+//! the chunks of [`Parser::split_blocks`] one by one, and compare them with
+//! parsing the whole document.
+//!
+//! A chunk boundary must never be put before a line which continues or
+//! changes the block before it, such as a list item after a blank line or an
+//! attribute block which gives attributes to the previous block.
+
+use note_mark::prelude::*;
+use proptest::prelude::*;
+
+/// The lines documents are made of.
+const LINES: &[&str] = &[
+    "",
+    "",
+    "para",
+    "- a",
+    "  b",
+    "    c",
+    "1. x",
+    "b. y",
+    "> q",
+    ">",
+    "# h",
+    "{.cls}",
+    "{#id key=v}",
+    "{.x} y",
+    "| a | b |",
+    "|---|---|",
+    "---",
+    "[x]: /u",
+    "[x] and [^1]",
+    "[^1]: note",
+    "a^[x]",
+    "b^[y *z*]",
+    "*[HTML]: Hyper",
+    "HTML",
+    "<!-- c -->",
+    "```",
+    "~~~~",
+    "```rust,linenos,hl_lines=\"2-3\"",
+    "  ```",
+    "> ```",
+];
+
+fn parser(soft: bool) -> Parser {
+    let ending = match soft {
+        true => ParagraphEnding::AllowSoftBreak,
+        false => ParagraphEnding::HardBreak,
+    };
+
+    Parser::default()
+        .fancy_lists(true)
+        .abbreviations(true)
+        .tables(Tables::Optional)
+        .inline_footnotes(true)
+        .paragraph_ending(ending)
+}
+
+proptest! {
+    #[test]
+    fn chunks_parse_like_the_whole_document(
+        lines in prop::collection::vec(prop::sample::select(LINES), 0..16),
+        soft in any::<bool>(),
+        positions in any::<bool>(),
+        edit in (any::<prop::sample::Index>(), 0..2usize, prop::sample::select(LINES)),
+    ) {
+        let input = lines.join("\n");
+        let parser = parser(soft);
+        let markdown = Markdown::default()
+            .parser(parser.clone())
+            .stringifier(Stringifier::new().source_positions(positions));
+
+        let whole = parser.parse(&input, Markdown::lex(&input));
+
+        let chunked = parser
+            .split_blocks(&input)
+            .into_iter()
+            .flat_map(|range| {
+                let chunk = &input[range];
+
+                parser.parse(chunk, Markdown::lex(chunk)).root.into_root()
+            })
+            .collect::<Vec<_>>();
+
+        prop_assert_eq!(&whole.root.root, &chunked);
+
+        prop_assert_eq!(
+            markdown.events(&input).collect::<Vec<_>>(),
+            whole.into_events()
+        );
+
+        let html = markdown.execute(&input);
+
+        let mut document = markdown.incremental(&input);
+
+        prop_assert_eq!(&document.render(), &html);
+
+        #[cfg(feature = "parallel")]
+        prop_assert_eq!(&markdown.execute_parallel(&input), &html);
+
+        // Replace some lines with a line, which can join, split or open
+        // blocks after it.
+        let (at, len, line) = edit;
+        let starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+            .collect::<Vec<_>>();
+        let first = at.index(starts.len());
+        let end = starts.get(first + len).map_or(input.len(), |&end| end);
+
+        document.replace_range(starts[first]..end, &format!("{line}\n"));
+
+        prop_assert_eq!(document.render(), markdown.execute(document.source()));
+    }
+}
+
+#[test]
+fn inline_footnotes_across_chunks() {
+    let markdown = Markdown::default().parser(Parser::default().inline_footnotes(true));
+    let input = "a^[x]\n\nb^[y]\n";
+
+    let html = markdown.execute(input);
+
+    assert_eq!(html.matches("<section class=\"footnotes\">").count(), 1);
+    assert_eq!(markdown.incremental(input).render(), html);
+
+    #[cfg(feature = "parallel")]
+    assert_eq!(markdown.execute_parallel(input), html);
+}
+
+#[test]
+fn attribute_line_after_blank_line() {
+    let markdown = Markdown::default();
+    let input = "- t\n\n{.cls}\n";
+
+    let html = markdown.execute(input);
+
+    assert_eq!(html, "<ul class=\"cls\"><li>t</li></ul>");
+    assert_eq!(markdown.incremental(input).render(), html);
+
+    #[cfg(feature = "parallel")]
+    assert_eq!(markdown.execute_parallel(input), html);
+
+    let whole = Parser::default().parse(input, Markdown::lex(input));
+
+    assert_eq!(
+        markdown.events(input).collect::<Vec<_>>(),
+        whole.into_events()
+    );
+}
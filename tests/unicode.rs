@@ -0,0 +1,93 @@
+//! Render text with multibyte characters, such as CJK, emoji and combining
+//! characters, mixed with markup.
+//!
+//! The ranges of tokens, diagnostics, links and headlines are byte ranges of
+//! the input, so they must start and end on character boundaries, and no
+//! method may panic on slicing the input.
+
+use note_mark::prelude::*;
+use proptest::prelude::*;
+
+/// The configurations to render with, which enable most of the syntax and
+/// the transforms on text.
+fn configs() -> Vec<Markdown> {
+    let parser = Parser::default()
+        .wiki_links(true)
+        .hashtags(true)
+        .mentions(true)
+        .highlight(true)
+        .fancy_lists(true)
+        .abbreviations(true)
+        .inline_footnotes(true)
+        .tables(Tables::Optional)
+        .table_spans(true);
+
+    let transformer = Transformer::new()
+        .section(true)
+        .smart_punctuation(true)
+        .line_wrap(LineWrap::Join)
+        .heading_ids(true)
+        .heading_anchor(AnchorPosition::After)
+        .quote_citation(true)
+        .text_direction(TextDirection::Detect)
+        .toc_placeholder(TocMaker::default());
+
+    vec![
+        Markdown::default(),
+        Markdown::default()
+            .parser(parser.clone())
+            .transformer(transformer)
+            .stringifier(Stringifier::new().source_positions(true).escape(true)),
+        Markdown::default()
+            .parser(parser.max_input_len(Some(7)))
+            .stringifier(Stringifier::new().format(true).width(4)),
+    ]
+}
+
+/// Check the ranges and render the input with every method.
+fn check(markdown: &Markdown, input: &str) -> Result<(), TestCaseError> {
+    let boundary = |range: &std::ops::Range<usize>| {
+        input.is_char_boundary(range.start) && input.is_char_boundary(range.end)
+    };
+
+    for token in Markdown::lex(input) {
+        prop_assert!(boundary(&token.range()), "token {:?}", token);
+    }
+
+    let (_, diagnostics) = markdown.execute_with_diagnostics(input);
+
+    for diagnostic in diagnostics {
+        prop_assert!(boundary(&diagnostic.span), "diagnostic {:?}", diagnostic);
+    }
+
+    for link in markdown.extract_links(input) {
+        prop_assert!(boundary(&link.span), "link {:?}", link);
+    }
+
+    for heading in markdown.outline(input) {
+        prop_assert!(boundary(&heading.span), "headline {:?}", heading);
+    }
+
+    for range in Parser::default().split_blocks(input) {
+        prop_assert!(boundary(&range), "chunk {:?}", range);
+    }
+
+    markdown.execute_text(input);
+    markdown.execute_with_toc(input);
+    markdown.execute_with_figures(input);
+    markdown.reformat(input);
+    markdown.events(input).for_each(drop);
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn multibyte_text_has_aligned_ranges(
+        input in "[a *_#>|:=^@!{}()\\[\\]\\-.\\\\\n\té日本語😀👍🏽\u{301}\u{200d}\u{fe0f}שלום]{0,48}"
+    ) {
+        for markdown in configs() {
+            check(&markdown, &input)?;
+        }
+    }
+}